@@ -0,0 +1,219 @@
+//! Micro-benchmarks for the hot paths of the containers
+//!
+//! Run with `cargo bench`. Pass a filter to run a subset, e.g. `cargo bench -- deque`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use heapless::{
+    binary_heap::{BinaryHeap, Max, SmallPriorityQueue},
+    spsc::Queue,
+    Deque, FnvIndexMap, Vec,
+};
+
+fn vec(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vec");
+    group.throughput(Throughput::Elements(256));
+
+    group.bench_function("push", |b| {
+        b.iter(|| {
+            let mut v: Vec<u32, 256> = Vec::new();
+            for i in 0..256 {
+                v.push(black_box(i)).unwrap();
+            }
+            v
+        })
+    });
+
+    let src = [0xa5u8; 256];
+    group.bench_function("extend_from_slice", |b| {
+        b.iter(|| {
+            let mut v: Vec<u8, 256> = Vec::new();
+            v.extend_from_slice(black_box(&src)).unwrap();
+            v
+        })
+    });
+
+    group.bench_function("extend_iter", |b| {
+        b.iter(|| {
+            let mut v: Vec<u8, 256> = Vec::new();
+            v.extend(black_box(&src).iter().copied());
+            v
+        })
+    });
+
+    group.finish();
+}
+
+/// A full deque whose contents wrap around the end of its storage.
+fn wrapped_deque() -> Deque<u8, 256> {
+    let mut d = Deque::new();
+    for i in 0..192 {
+        d.push_back(i as u8).unwrap();
+    }
+    for _ in 0..192 {
+        d.pop_front();
+    }
+    for i in 0..256 {
+        d.push_back(i as u8).unwrap();
+    }
+    d
+}
+
+fn deque(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deque");
+    group.throughput(Throughput::Elements(256));
+
+    group.bench_function("push_pop_wrapping", |b| {
+        let mut d: Deque<u32, 64> = Deque::new();
+        for i in 0..48 {
+            d.push_back(i).unwrap();
+        }
+        b.iter(|| {
+            for i in 0..256 {
+                d.push_back(black_box(i)).unwrap();
+                black_box(d.pop_front());
+            }
+        })
+    });
+
+    group.bench_function("copy_out_wrapped", |b| {
+        let d = wrapped_deque();
+        b.iter(|| {
+            let mut out = [0u8; 256];
+            let (front, back) = black_box(&d).as_slices();
+            out[..front.len()].copy_from_slice(front);
+            out[front.len()..].copy_from_slice(back);
+            out
+        })
+    });
+
+    group.bench_function("clone_wrapped", |b| {
+        let d = wrapped_deque();
+        b.iter(|| black_box(&d).clone())
+    });
+
+    group.finish();
+}
+
+fn indexmap(c: &mut Criterion) {
+    let mut group = c.benchmark_group("indexmap");
+    group.throughput(Throughput::Elements(48));
+
+    // 75% load factor, so that probe sequences have some length
+    let mut map: FnvIndexMap<u32, u32, 64> = FnvIndexMap::new();
+    for i in 0..48 {
+        map.insert(i * 7, i).unwrap();
+    }
+
+    group.bench_function("get_hit", |b| {
+        b.iter(|| {
+            for i in 0..48 {
+                black_box(map.get(&black_box(i * 7)));
+            }
+        })
+    });
+
+    group.bench_function("get_miss", |b| {
+        b.iter(|| {
+            for i in 0..48 {
+                black_box(map.get(&black_box(i * 7 + 1)));
+            }
+        })
+    });
+
+    group.bench_function("insert", |b| {
+        b.iter_batched_ref(
+            FnvIndexMap::<u32, u32, 64>::new,
+            |map| {
+                for i in 0..48 {
+                    map.insert(black_box(i * 7), i).unwrap();
+                }
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+/// Fills a priority queue of capacity `$n` with pseudo-random items, then empties it.
+macro_rules! fill_drain {
+    ($group:expr, $name:literal, $queue:ty, $n:literal) => {
+        $group.bench_function(concat!($name, "_", $n), |b| {
+            b.iter(|| {
+                let mut queue: $queue = <$queue>::new();
+                let mut x = 1u32;
+                for _ in 0..$n {
+                    x = x.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    queue.push(black_box(x >> 16)).unwrap();
+                }
+                while let Some(x) = queue.pop() {
+                    black_box(x);
+                }
+            })
+        });
+    };
+}
+
+// where `SmallPriorityQueue` stops being faster than `BinaryHeap`
+fn priority_queue(c: &mut Criterion) {
+    let mut group = c.benchmark_group("priority_queue");
+
+    fill_drain!(group, "binary_heap", BinaryHeap<u32, Max, 8>, 8);
+    fill_drain!(group, "sorted_ring", SmallPriorityQueue<u32, Max, 8>, 8);
+    fill_drain!(group, "binary_heap", BinaryHeap<u32, Max, 32>, 32);
+    fill_drain!(group, "sorted_ring", SmallPriorityQueue<u32, Max, 32>, 32);
+    fill_drain!(group, "binary_heap", BinaryHeap<u32, Max, 128>, 128);
+    fill_drain!(group, "sorted_ring", SmallPriorityQueue<u32, Max, 128>, 128);
+
+    group.finish();
+}
+
+fn spsc(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spsc");
+    group.throughput(Throughput::Elements(1024));
+
+    group.bench_function("enqueue_dequeue", |b| {
+        let mut queue: Queue<u32, 64> = Queue::new();
+        let (mut p, mut c) = queue.split();
+        b.iter(|| {
+            for i in 0..1024 {
+                p.enqueue(black_box(i)).unwrap();
+                black_box(c.dequeue());
+            }
+        })
+    });
+
+    group.bench_function("threaded", |b| {
+        b.iter_batched_ref(
+            Queue::<u32, 64>::new,
+            |queue| {
+                let (mut p, mut c) = queue.split();
+                std::thread::scope(|s| {
+                    s.spawn(move || {
+                        for i in 0..1024 {
+                            while p.enqueue(i).is_err() {
+                                std::thread::yield_now();
+                            }
+                        }
+                    });
+                    let mut received = 0;
+                    while received < 1024 {
+                        match c.dequeue() {
+                            Some(x) => {
+                                black_box(x);
+                                received += 1;
+                            }
+                            None => std::thread::yield_now(),
+                        }
+                    }
+                });
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, vec, deque, indexmap, priority_queue, spsc);
+criterion_main!(benches);
@@ -11,6 +11,11 @@ use std::{
 fn main() -> Result<(), Box<dyn Error>> {
     let target = env::var("TARGET")?;
 
+    println!("cargo:rustc-check-cfg=cfg(has_atomic_load_store)");
+    println!("cargo:rustc-check-cfg=cfg(arm_llsc)");
+    println!("cargo:rustc-check-cfg=cfg(capacity_stats)");
+    println!("cargo:rustc-check-cfg=cfg(loom)");
+
     // Manually list targets that have atomic load/store, but no CAS.
     // Remove when `cfg(target_has_atomic_load_store)` is stable.
     // last updated nightly-2023-10-28
@@ -26,6 +31,17 @@ fn main() -> Result<(), Box<dyn Error>> {
         _ => {}
     };
 
+    // The high-water marks need atomic read-modify-write operations, the same as the `init`
+    // module. `capacity-stats` adds fields to the containers in several modules, which all use
+    // this cfg instead of repeating the condition.
+    let has_cas = env::var("CARGO_CFG_TARGET_HAS_ATOMIC")
+        .is_ok_and(|widths| widths.split(',').any(|width| width == "ptr"));
+    if env::var_os("CARGO_FEATURE_CAPACITY_STATS").is_some()
+        && (has_cas || env::var_os("CARGO_FEATURE_PORTABLE_ATOMIC").is_some())
+    {
+        println!("cargo:rustc-cfg=capacity_stats");
+    }
+
     // AArch64 instruction set contains `clrex` but not `ldrex` or `strex`; the
     // probe will succeed when we already know to deny this target from LLSC.
     if !target.starts_with("aarch64") {
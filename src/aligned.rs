@@ -0,0 +1,138 @@
+//! Element wrapper that pads container slots to an alignment.
+//!
+//! Storing [`Aligned<A, T>`] instead of `T` in a [`Vec`](crate::Vec), [`Deque`](crate::Deque) or
+//! any other container aligns every slot, and thereby the stride between slots, to the alignment
+//! of the marker type `A`. This lets the containers keep the bookkeeping for arrays of e.g. DMA
+//! descriptors that the hardware requires to be cache line aligned.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{
+//!     aligned::{Aligned, A32},
+//!     Deque,
+//! };
+//!
+//! #[derive(Clone, Copy, Default)]
+//! struct Descriptor {
+//!     addr: u32,
+//!     len: u16,
+//!     flags: u16,
+//! }
+//!
+//! let mut ring: Deque<Aligned<A32, Descriptor>, 4> = Deque::new();
+//! for i in 0..4 {
+//!     ring.push_back(Aligned::new(Descriptor { addr: i * 0x100, len: 0x100, flags: 0 }))
+//!         .ok()
+//!         .unwrap();
+//! }
+//!
+//! assert_eq!(core::mem::size_of::<Aligned<A32, Descriptor>>(), 32);
+//! for desc in &ring {
+//!     assert_eq!(desc as *const _ as usize % 32, 0);
+//! }
+//! ```
+
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+macro_rules! alignment {
+    ($($name:ident = $align:literal,)+) => {
+        $(
+            #[doc = concat!("Marker type with an alignment of ", stringify!($align), " bytes.")]
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+            #[repr(align($align))]
+            pub struct $name;
+        )+
+    };
+}
+
+alignment! {
+    A2 = 2,
+    A4 = 4,
+    A8 = 8,
+    A16 = 16,
+    A32 = 32,
+    A64 = 64,
+    A128 = 128,
+}
+
+/// A `T` aligned to at least the alignment of `A`, and padded to a multiple of it.
+///
+/// The value is at offset 0, so a pointer to the wrapper is an aligned pointer to the value.
+#[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(C)]
+pub struct Aligned<A, T> {
+    _alignment: [A; 0],
+    value: T,
+}
+
+impl<A, T> Aligned<A, T> {
+    /// Wraps `value`.
+    pub const fn new(value: T) -> Self {
+        Self {
+            _alignment: [],
+            value,
+        }
+    }
+
+    /// Unwraps the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<A, T> Deref for Aligned<A, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<A, T> DerefMut for Aligned<A, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<A, T> From<T> for Aligned<A, T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<A, T> fmt::Debug for Aligned<A, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(&self.value, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::{align_of, size_of};
+
+    use super::{Aligned, A16, A64};
+    use crate::Vec;
+
+    #[test]
+    fn layout() {
+        assert_eq!(align_of::<Aligned<A16, u8>>(), 16);
+        assert_eq!(size_of::<Aligned<A16, u8>>(), 16);
+        assert_eq!(size_of::<Aligned<A16, [u8; 17]>>(), 32);
+        // never less aligned than the value itself
+        assert_eq!(align_of::<Aligned<A16, u128>>(), align_of::<u128>().max(16));
+
+        let v: Vec<Aligned<A64, u32>, 3> = (0..3).map(Aligned::new).collect();
+        let base = v.as_ptr() as usize;
+        assert_eq!(base % 64, 0);
+        for (i, x) in v.iter().enumerate() {
+            assert_eq!(&**x as *const u32 as usize, base + i * 64);
+        }
+    }
+}
@@ -0,0 +1,292 @@
+//! A ring buffer that one context pushes to and another pops from, through a shared reference.
+//!
+//! [`AtomicDeque`] is the single producer single consumer subset of [`Deque`](crate::Deque):
+//! [`push_back`](AtomicDeque::push_back) and [`pop_front`](AtomicDeque::pop_front) take `&self`,
+//! so a `static AtomicDeque` can replace a `Deque` that is shared behind a mutex today, without
+//! restructuring the code around the [`Producer`](crate::spsc::Producer) and
+//! [`Consumer`](crate::spsc::Consumer) handles of [`spsc::Queue`](crate::spsc::Queue).
+//!
+//! Instead of being enforced by the handle types, the single producer single consumer rule is
+//! checked at runtime: each side holds a flag while it's in `push_back` or `pop_front`, and the
+//! call panics if another context is in the same method at the same time. This turns a second
+//! producer or consumer into an immediate failure instead of a data race.
+//!
+//! NOTE: This module requires atomic CAS operations. On targets without them, they are emulated by
+//! the [`portable-atomic`](https://crates.io/crates/portable-atomic) crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::atomic_deque::AtomicDeque;
+//!
+//! static EVENTS: AtomicDeque<u8, 4> = AtomicDeque::new();
+//!
+//! // in the interrupt handler, the producer
+//! EVENTS.push_back(1).unwrap();
+//! EVENTS.push_back(2).unwrap();
+//!
+//! // in `main`, the consumer
+//! assert_eq!(EVENTS.pop_front(), Some(1));
+//! assert_eq!(EVENTS.pop_front(), Some(2));
+//! assert_eq!(EVENTS.pop_front(), None);
+//! ```
+
+use core::{cell::UnsafeCell, fmt, mem::MaybeUninit};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// A fixed capacity ring buffer with a producer and a consumer that share it by reference, see the
+/// [module-level documentation](self).
+pub struct AtomicDeque<T, const N: usize> {
+    buffer: [UnsafeCell<MaybeUninit<T>>; N],
+    /// The position to pop from, in `0..2 * N`; only the consumer changes it.
+    head: AtomicUsize,
+    /// The position to push to, in `0..2 * N`; only the producer changes it.
+    tail: AtomicUsize,
+    pushing: AtomicBool,
+    popping: AtomicBool,
+}
+
+impl<T, const N: usize> AtomicDeque<T, N> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: UnsafeCell<MaybeUninit<T>> = UnsafeCell::new(MaybeUninit::uninit());
+
+    /// Creates an empty deque with a fixed capacity of `N`.
+    pub const fn new() -> Self {
+        // Const assert N > 0
+        crate::sealed::greater_than_0::<N>();
+
+        Self {
+            buffer: [Self::INIT; N],
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            pushing: AtomicBool::new(false),
+            popping: AtomicBool::new(false),
+        }
+    }
+
+    // positions run through `0..2 * N`, so that a full deque can be told apart from an empty one
+    // without giving up a slot
+    fn increment(position: usize) -> usize {
+        if position + 1 == 2 * N {
+            0
+        } else {
+            position + 1
+        }
+    }
+
+    fn distance(head: usize, tail: usize) -> usize {
+        if tail >= head {
+            tail - head
+        } else {
+            tail + 2 * N - head
+        }
+    }
+
+    /// Returns the maximum number of elements the deque can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of elements in the deque.
+    ///
+    /// If the producer or the consumer is active in another context, the length may already have
+    /// changed when this returns.
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Relaxed);
+        Self::distance(head, tail)
+    }
+
+    /// Returns whether the deque is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the deque is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == N
+    }
+
+    /// Appends an `item` to the back of the deque.
+    ///
+    /// Returns back the `item` if the deque is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another context is in `push_back` on this deque at the same time.
+    pub fn push_back(&self, item: T) -> Result<(), T> {
+        let _guard = Active::enter(&self.pushing, "push_back");
+
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::Acquire);
+        if Self::distance(head, tail) == N {
+            return Err(item);
+        }
+        // NOTE(unsafe) the slot is outside of `head..tail`, so the consumer doesn't read it, and
+        // the guard excludes other producers
+        unsafe { (*self.buffer[tail % N].get()).write(item) };
+        self.tail.store(Self::increment(tail), Ordering::Release);
+        Ok(())
+    }
+
+    /// Removes the item from the front of the deque and returns it, or `None` if it's empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another context is in `pop_front` on this deque at the same time.
+    pub fn pop_front(&self) -> Option<T> {
+        let _guard = Active::enter(&self.popping, "pop_front");
+
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        // NOTE(unsafe) the slot is inside of `head..tail`, so it's initialized and the producer
+        // doesn't write to it, and the guard excludes other consumers
+        let item = unsafe { (*self.buffer[head % N].get()).assume_init_read() };
+        self.head.store(Self::increment(head), Ordering::Release);
+        Some(item)
+    }
+
+    /// Removes all elements.
+    ///
+    /// This needs no checks since the exclusive borrow statically guarantees that neither side
+    /// is active.
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+}
+
+impl<T, const N: usize> Default for AtomicDeque<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for AtomicDeque<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The elements can't be printed without becoming the consumer.
+        f.debug_struct("AtomicDeque")
+            .field("len", &self.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T, const N: usize> Drop for AtomicDeque<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+unsafe impl<T, const N: usize> Sync for AtomicDeque<T, N> where T: Send {}
+
+/// Marks one side of an [`AtomicDeque`] as active until dropped.
+struct Active<'a> {
+    flag: &'a AtomicBool,
+}
+
+impl<'a> Active<'a> {
+    fn enter(flag: &'a AtomicBool, method: &str) -> Self {
+        if flag.swap(true, Ordering::Acquire) {
+            panic!("AtomicDeque: `{}` called from two contexts at once", method);
+        }
+        Self { flag }
+    }
+}
+
+impl Drop for Active<'_> {
+    fn drop(&mut self) {
+        self.flag.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        panic::{catch_unwind, AssertUnwindSafe},
+        sync::atomic::Ordering,
+        thread,
+    };
+
+    use super::AtomicDeque;
+
+    #[test]
+    fn push_pop() {
+        droppable!();
+
+        let q: AtomicDeque<Droppable, 3> = AtomicDeque::new();
+        for _ in 0..3 {
+            q.push_back(Droppable::new()).ok().unwrap();
+        }
+        assert!(q.is_full());
+        assert!(q.push_back(Droppable::new()).is_err());
+        assert_eq!(Droppable::count(), 3);
+
+        // wraps around the end of the buffer several times
+        for _ in 0..10 {
+            q.pop_front().unwrap();
+            q.push_back(Droppable::new()).ok().unwrap();
+            assert_eq!(q.len(), 3);
+        }
+        q.pop_front().unwrap();
+        assert_eq!(q.len(), 2);
+        drop(q);
+        assert_eq!(Droppable::count(), 0);
+
+        let mut q: AtomicDeque<u8, 1> = AtomicDeque::new();
+        q.push_back(0).unwrap();
+        assert_eq!(q.push_back(1), Err(1));
+        q.clear();
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn concurrent_use_is_detected() {
+        let q: AtomicDeque<u8, 2> = AtomicDeque::new();
+
+        // as if another producer were in `push_back`
+        q.pushing.store(true, Ordering::Relaxed);
+        let result = catch_unwind(AssertUnwindSafe(|| q.push_back(0)));
+        assert!(result.is_err());
+        q.pushing.store(false, Ordering::Relaxed);
+        q.push_back(0).unwrap();
+
+        q.popping.store(true, Ordering::Relaxed);
+        let result = catch_unwind(AssertUnwindSafe(|| q.pop_front()));
+        assert!(result.is_err());
+        q.popping.store(false, Ordering::Relaxed);
+        assert_eq!(q.pop_front(), Some(0));
+    }
+
+    #[test]
+    fn threaded() {
+        static Q: AtomicDeque<u32, 8> = AtomicDeque::new();
+        const COUNT: u32 = 10_000;
+
+        let producer = thread::spawn(|| {
+            for i in 0..COUNT {
+                while Q.push_back(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < COUNT {
+            if let Some(i) = Q.pop_front() {
+                assert_eq!(i, expected);
+                expected += 1;
+            } else {
+                thread::yield_now();
+            }
+        }
+        producer.join().unwrap();
+        assert!(Q.is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+//! Queue fill level reporting for flow control.
+
+use crate::Deque;
+
+/// A queue that reports how full it is.
+///
+/// Flow-control code written against this trait works the same whether a build uses a [`Deque`],
+/// an [`spsc`](crate::spsc) queue or an [`mpmc`](crate::mpmc) queue.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{Backpressure, Deque};
+///
+/// fn should_throttle(queue: &impl Backpressure) -> bool {
+///     queue.pressure() >= 128
+/// }
+///
+/// let mut queue: Deque<u8, 4> = Deque::new();
+/// queue.push_back(0).unwrap();
+/// queue.push_back(1).unwrap();
+/// assert!(!should_throttle(&queue));
+/// queue.push_back(2).unwrap();
+/// assert!(should_throttle(&queue));
+/// ```
+pub trait Backpressure {
+    /// Returns the fill level of the queue, scaled from `0` (empty) to `255` (full).
+    ///
+    /// For queues shared between contexts this is a snapshot that may be outdated by the time it
+    /// is returned.
+    fn pressure(&self) -> u8;
+}
+
+/// Scales `len` to the `0..=255` range, rounding down; a full queue always reports `255`.
+pub(crate) fn fill_ratio(len: usize, capacity: usize) -> u8 {
+    if len >= capacity {
+        return u8::MAX;
+    }
+    (len as u64 * u64::from(u8::MAX) / capacity as u64) as u8
+}
+
+impl<T, const N: usize> Backpressure for Deque<T, N> {
+    fn pressure(&self) -> u8 {
+        fill_ratio(self.len(), self.capacity())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fill_ratio;
+    use crate::{Backpressure, Deque};
+
+    #[test]
+    fn scaling() {
+        assert_eq!(fill_ratio(0, 4), 0);
+        assert_eq!(fill_ratio(1, 4), 63);
+        assert_eq!(fill_ratio(3, 4), 191);
+        assert_eq!(fill_ratio(4, 4), 255);
+        assert_eq!(fill_ratio(1000, 1001), 254);
+        assert_eq!(fill_ratio(0, 0), 255);
+    }
+
+    #[test]
+    fn deque() {
+        let mut d: Deque<u8, 2> = Deque::new();
+        assert_eq!(d.pressure(), 0);
+        d.push_back(0).unwrap();
+        assert_eq!(d.pressure(), 127);
+        d.push_back(1).unwrap();
+        assert_eq!(d.pressure(), 255);
+    }
+}
@@ -17,7 +17,10 @@ use core::{
     ptr, slice,
 };
 
-use crate::vec::Vec;
+use crate::{
+    deque::{self, Deque},
+    vec::Vec,
+};
 
 /// Min-heap
 pub enum Min {}
@@ -211,11 +214,51 @@ where
     /// Returns a mutable iterator visiting all values in the underlying vector, in arbitrary order.
     ///
     /// **WARNING** Mutating the items in the binary heap can leave the heap in an inconsistent
-    /// state.
+    /// state. Use [`bulk_mut`](BinaryHeap::bulk_mut), or call [`repair`](BinaryHeap::repair)
+    /// afterwards, to keep it consistent.
     pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
         self.data.as_mut_slice().iter_mut()
     }
 
+    /// Returns a guard that gives mutable access to all items, in arbitrary order, and restores
+    /// the heap property when dropped.
+    ///
+    /// Adjusting every item this way takes *O*(n) time, instead of the *O*(n \* log(n)) of
+    /// popping and pushing each one back.
+    ///
+    /// Note: If the `BulkMut` value is leaked, the heap may be in an inconsistent state.
+    ///
+    /// ```
+    /// use heapless::binary_heap::{BinaryHeap, Min};
+    ///
+    /// // deadlines in ticks of a 16-bit clock that is about to roll over
+    /// let mut heap: BinaryHeap<u16, Min, 8> = BinaryHeap::new();
+    /// heap.push(65_000).unwrap();
+    /// heap.push(200).unwrap();
+    /// heap.push(65_500).unwrap();
+    ///
+    /// for deadline in heap.bulk_mut().iter_mut() {
+    ///     *deadline = deadline.wrapping_sub(60_000);
+    /// }
+    ///
+    /// assert_eq!(heap.pop(), Some(5_000));
+    /// assert_eq!(heap.pop(), Some(5_500));
+    /// assert_eq!(heap.pop(), Some(5_736));
+    /// ```
+    pub fn bulk_mut(&mut self) -> BulkMut<'_, T, K, N> {
+        BulkMut { heap: self }
+    }
+
+    /// Restores the heap property after the items were mutated through
+    /// [`iter_mut`](BinaryHeap::iter_mut).
+    ///
+    /// Computes in *O*(n) time.
+    pub fn repair(&mut self) {
+        for pos in (0..self.len() / 2).rev() {
+            self.sift_down_to_bottom(pos);
+        }
+    }
+
     /// Returns the *top* (greatest if max-heap, smallest if min-heap) item in the binary heap, or
     /// None if it is empty.
     ///
@@ -234,6 +277,67 @@ where
         self.data.as_slice().first()
     }
 
+    /// Returns the `M` *top* items of the binary heap, in the order they would be popped, or all
+    /// of them if there are fewer.
+    ///
+    /// Unlike popping and re-pushing them, this leaves the heap untouched. Only the items that
+    /// can be among the `M` top ones are looked at, which takes *O*(M²) time, independent of the
+    /// length of the heap.
+    ///
+    /// ```
+    /// use heapless::binary_heap::{BinaryHeap, Min};
+    ///
+    /// // deadlines of scheduled events
+    /// let mut events: BinaryHeap<_, Min, 8> = BinaryHeap::new();
+    /// for deadline in [40, 10, 70, 30, 20] {
+    ///     events.push(deadline).unwrap();
+    /// }
+    ///
+    /// assert_eq!(events.peek_many::<3>(), [&10, &20, &30]);
+    /// assert_eq!(events.len(), 5);
+    /// ```
+    pub fn peek_many<const M: usize>(&self) -> Vec<&T, M> {
+        let mut top = Vec::new();
+        // the items that may come next: the children of the items taken so far. Each item taken
+        // adds at most one net candidate, so there are never more than `M`
+        let mut candidates: Vec<usize, M> = Vec::new();
+        if M > 0 && !self.is_empty() {
+            // NOTE(unsafe) `M > 0`
+            unsafe { candidates.push_unchecked(0) };
+        }
+
+        while !top.is_full() {
+            let best = candidates
+                .iter()
+                .enumerate()
+                .reduce(|best, candidate| {
+                    if self.data[*candidate.1].cmp(&self.data[*best.1]) == K::ordering() {
+                        candidate
+                    } else {
+                        best
+                    }
+                })
+                .map(|(i, _)| i);
+            let Some(best) = best else {
+                break;
+            };
+            let index = candidates.swap_remove(best);
+            // NOTE(unsafe) `top` isn't full
+            unsafe { top.push_unchecked(&self.data[index]) };
+
+            if !top.is_full() {
+                for child in [2 * index + 1, 2 * index + 2] {
+                    if child < self.len() {
+                        // NOTE(unsafe) with `t < M` items taken, there are at most `t + 1`
+                        // candidates after this
+                        unsafe { candidates.push_unchecked(child) };
+                    }
+                }
+            }
+        }
+        top
+    }
+
     /// Returns a mutable reference to the greatest item in the binary heap, or
     /// `None` if it is empty.
     ///
@@ -320,6 +424,11 @@ where
     /// assert_eq!(heap.peek(), Some(&5));
     /// ```
     pub fn push(&mut self, item: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.data.take_injected_failure() {
+            return Err(item);
+        }
+
         if self.data.is_full() {
             return Err(item);
         }
@@ -342,6 +451,32 @@ where
     }
 
     /* Private API */
+    /// Returns the index of the item that would be popped last.
+    fn worst(&self) -> Option<usize> {
+        // the worst item is a leaf
+        let leaves = self.len() / 2;
+        self.data[leaves..]
+            .iter()
+            .enumerate()
+            .reduce(|worst, item| {
+                if item.1.cmp(worst.1) == K::ordering().reverse() {
+                    item
+                } else {
+                    worst
+                }
+            })
+            .map(|(i, _)| leaves + i)
+    }
+
+    /// Removes the item at `index`.
+    fn remove(&mut self, index: usize) -> T {
+        let item = self.data.swap_remove(index);
+        if index < self.len() && self.sift_up(0, index) == index {
+            self.sift_down_to_bottom(index);
+        }
+        item
+    }
+
     fn sift_down_to_bottom(&mut self, mut pos: usize) {
         let end = self.len();
         let start = pos;
@@ -505,6 +640,50 @@ where
     }
 }
 
+/// Structure giving mutable access to all items of a `BinaryHeap`, which restores the heap
+/// property when dropped.
+///
+/// This `struct` is created by [`BinaryHeap::bulk_mut`].
+/// See its documentation for more.
+pub struct BulkMut<'a, T, K, const N: usize>
+where
+    T: Ord,
+    K: Kind,
+{
+    heap: &'a mut BinaryHeap<T, K, N>,
+}
+
+impl<T, K, const N: usize> Drop for BulkMut<'_, T, K, N>
+where
+    T: Ord,
+    K: Kind,
+{
+    fn drop(&mut self) {
+        self.heap.repair();
+    }
+}
+
+impl<T, K, const N: usize> Deref for BulkMut<'_, T, K, N>
+where
+    T: Ord,
+    K: Kind,
+{
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        self.heap.data.as_slice()
+    }
+}
+
+impl<T, K, const N: usize> DerefMut for BulkMut<'_, T, K, N>
+where
+    T: Ord,
+    K: Kind,
+{
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.heap.data.as_mut_slice()
+    }
+}
+
 impl<'a, T> Drop for Hole<'a, T> {
     #[inline]
     fn drop(&mut self) {
@@ -562,11 +741,537 @@ where
     }
 }
 
+/// A priority queue like [`BinaryHeap`] that pops equal items in insertion (FIFO) order.
+///
+/// Every item is stored together with a 32-bit insertion sequence number that breaks ties between
+/// items that compare equal. Sequence numbers are compared with wrap-around, so the FIFO
+/// guarantee holds as long as an item is popped before 2<sup>31</sup> further items have been
+/// pushed.
+///
+/// ```
+/// use heapless::binary_heap::{Max, StableBinaryHeap};
+///
+/// let mut heap: StableBinaryHeap<_, Max, 8> = StableBinaryHeap::new();
+///
+/// // Tasks are ordered by priority only
+/// #[derive(Debug)]
+/// struct Task(u8, &'static str);
+///
+/// impl PartialEq for Task {
+///     fn eq(&self, other: &Self) -> bool {
+///         self.0 == other.0
+///     }
+/// }
+/// impl Eq for Task {}
+/// impl PartialOrd for Task {
+///     fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+///         Some(self.cmp(other))
+///     }
+/// }
+/// impl Ord for Task {
+///     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+///         self.0.cmp(&other.0)
+///     }
+/// }
+///
+/// heap.push(Task(1, "idle")).unwrap();
+/// heap.push(Task(5, "first")).unwrap();
+/// heap.push(Task(5, "second")).unwrap();
+/// heap.push(Task(5, "third")).unwrap();
+///
+/// assert_eq!(heap.pop().unwrap().1, "first");
+/// assert_eq!(heap.pop().unwrap().1, "second");
+/// assert_eq!(heap.pop().unwrap().1, "third");
+/// assert_eq!(heap.pop().unwrap().1, "idle");
+/// ```
+pub struct StableBinaryHeap<T, K, const N: usize> {
+    heap: BinaryHeap<Sequenced<T, K>, K, N>,
+    next_seq: u32,
+}
+
+/// An item of a [`StableBinaryHeap`] and its insertion sequence number.
+struct Sequenced<T, K> {
+    item: T,
+    seq: u32,
+    _kind: PhantomData<K>,
+}
+
+impl<T, K> PartialEq for Sequenced<T, K>
+where
+    T: Ord,
+    K: Kind,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T, K> Eq for Sequenced<T, K>
+where
+    T: Ord,
+    K: Kind,
+{
+}
+
+impl<T, K> PartialOrd for Sequenced<T, K>
+where
+    T: Ord,
+    K: Kind,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, K> Ord for Sequenced<T, K>
+where
+    T: Ord,
+    K: Kind,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.item.cmp(&other.item).then_with(|| {
+            // The older item has to end up closer to the top of the heap, i.e. compare as smaller
+            // in a min-heap and as larger in a max-heap.
+            let age = (other.seq.wrapping_sub(self.seq) as i32).cmp(&0);
+            if K::ordering() == Ordering::Less {
+                age.reverse()
+            } else {
+                age
+            }
+        })
+    }
+}
+
+impl<T, K, const N: usize> StableBinaryHeap<T, K, N> {
+    /// Creates an empty `StableBinaryHeap`.
+    pub const fn new() -> Self {
+        Self {
+            heap: BinaryHeap::new(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl<T, K, const N: usize> StableBinaryHeap<T, K, N>
+where
+    T: Ord,
+    K: Kind,
+{
+    /// Returns the capacity of the heap.
+    pub fn capacity(&self) -> usize {
+        self.heap.capacity()
+    }
+
+    /// Drops all items from the heap.
+    pub fn clear(&mut self) {
+        self.heap.clear()
+    }
+
+    /// Returns the length of the heap.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks if the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns an iterator visiting all values in the underlying vector, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.heap.iter().map(|s| &s.item)
+    }
+
+    /// Returns the *top* item in the heap, or `None` if it is empty.
+    ///
+    /// Of several equal items, this is the one that was pushed first.
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek().map(|s| &s.item)
+    }
+
+    /// Removes the *top* item from the heap and returns it, or `None` if it is empty.
+    ///
+    /// Of several equal items, this is the one that was pushed first.
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop().map(|s| s.item)
+    }
+
+    /// Pushes an item onto the heap.
+    ///
+    /// Returns back the `item` if the heap is full.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        let seq = self.next_seq;
+        self.heap
+            .push(Sequenced {
+                item,
+                seq,
+                _kind: PhantomData,
+            })
+            .map_err(|s| s.item)?;
+        self.next_seq = seq.wrapping_add(1);
+        Ok(())
+    }
+}
+
+impl<T, K, const N: usize> Default for StableBinaryHeap<T, K, N>
+where
+    T: Ord,
+    K: Kind,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, K, const N: usize> Clone for StableBinaryHeap<T, K, N>
+where
+    K: Kind,
+    T: Ord + Clone,
+{
+    fn clone(&self) -> Self {
+        let mut data = Vec::new();
+        for s in self.heap.data.iter() {
+            // Can't fail, the capacities are the same.
+            let _ = data.push(Sequenced {
+                item: s.item.clone(),
+                seq: s.seq,
+                _kind: PhantomData,
+            });
+        }
+
+        Self {
+            heap: BinaryHeap {
+                _kind: PhantomData,
+                data,
+            },
+            next_seq: self.next_seq,
+        }
+    }
+}
+
+impl<T, K, const N: usize> fmt::Debug for StableBinaryHeap<T, K, N>
+where
+    K: Kind,
+    T: Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// What a [`SoftLimitedHeap`] does with an item that's pushed when the soft limit is reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Reject the new item.
+    Reject,
+    /// Make room by removing the item that would be popped last, unless that's the new item.
+    EvictWorst,
+}
+
+/// A [`BinaryHeap`] that calls a policy when it holds more than a soft limit of items.
+///
+/// The policy decides whether the item that's pushed beyond the limit is rejected or makes room
+/// by evicting the worst item, which keeps memory pressure handling in one place instead of at
+/// every call site. The heap never holds more than `N` items; a full heap consults the policy
+/// too.
+///
+/// The soft limit can be changed at runtime, e.g. to shed load when the system is busy.
+///
+/// ```
+/// use heapless::binary_heap::{Max, Overflow, SoftLimitedHeap};
+///
+/// // keep the highest priority requests; under pressure, drop low priority ones
+/// let mut heap: SoftLimitedHeap<u8, Max, _, 8> = SoftLimitedHeap::new(3, |_, item| {
+///     if *item >= 5 {
+///         Overflow::EvictWorst
+///     } else {
+///         Overflow::Reject
+///     }
+/// });
+///
+/// heap.push(4).unwrap();
+/// heap.push(7).unwrap();
+/// heap.push(2).unwrap();
+/// assert_eq!(heap.push(3), Err(3));
+/// assert_eq!(heap.push(9), Ok(Some(2)));
+/// assert_eq!(heap.len(), 3);
+/// assert_eq!(heap.pop(), Some(9));
+/// ```
+pub struct SoftLimitedHeap<T, K, P, const N: usize> {
+    heap: BinaryHeap<T, K, N>,
+    soft_limit: usize,
+    policy: P,
+}
+
+impl<T, K, P, const N: usize> SoftLimitedHeap<T, K, P, N> {
+    /// Creates an empty heap with the given soft limit, which calls `policy` when it's reached.
+    ///
+    /// `policy` gets the heap and the item that's being pushed.
+    pub const fn new(soft_limit: usize, policy: P) -> Self
+    where
+        P: FnMut(&BinaryHeap<T, K, N>, &T) -> Overflow,
+    {
+        Self {
+            heap: BinaryHeap::new(),
+            soft_limit,
+            policy,
+        }
+    }
+}
+
+impl<T, K, P, const N: usize> SoftLimitedHeap<T, K, P, N>
+where
+    T: Ord,
+    K: Kind,
+    P: FnMut(&BinaryHeap<T, K, N>, &T) -> Overflow,
+{
+    /// Pushes an item onto the heap.
+    ///
+    /// Returns the evicted item if the policy chose to [evict](Overflow::EvictWorst) one, and
+    /// returns back the `item` if it was [rejected](Overflow::Reject), or if it would be the
+    /// evicted one.
+    pub fn push(&mut self, item: T) -> Result<Option<T>, T> {
+        if self.heap.len() < self.soft_limit.min(N) {
+            return self.heap.push(item).map(|()| None);
+        }
+
+        match (self.policy)(&self.heap, &item) {
+            Overflow::Reject => Err(item),
+            Overflow::EvictWorst => match self.heap.worst() {
+                Some(worst) if item.cmp(&self.heap.data[worst]) == K::ordering() => {
+                    let evicted = self.heap.remove(worst);
+                    // NOTE(unsafe) an item was just removed
+                    unsafe { self.heap.push_unchecked(item) };
+                    Ok(Some(evicted))
+                }
+                _ => Err(item),
+            },
+        }
+    }
+
+    /// Removes the *top* (greatest if max-heap, smallest if min-heap) item from the heap and
+    /// returns it, or `None` if it is empty.
+    pub fn pop(&mut self) -> Option<T> {
+        self.heap.pop()
+    }
+
+    /// Returns the *top* (greatest if max-heap, smallest if min-heap) item in the heap, or `None`
+    /// if it is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.heap.peek()
+    }
+
+    /// Returns the length of the heap.
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    /// Checks if the heap is empty.
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    /// Returns the soft limit.
+    pub fn soft_limit(&self) -> usize {
+        self.soft_limit
+    }
+
+    /// Changes the soft limit.
+    ///
+    /// Lowering the limit below the current length doesn't remove any items; the policy is called
+    /// on the next push.
+    pub fn set_soft_limit(&mut self, soft_limit: usize) {
+        self.soft_limit = soft_limit;
+    }
+
+    /// Returns the underlying heap.
+    pub fn as_heap(&self) -> &BinaryHeap<T, K, N> {
+        &self.heap
+    }
+
+    /// Returns the underlying heap, dropping the policy.
+    pub fn into_heap(self) -> BinaryHeap<T, K, N> {
+        self.heap
+    }
+}
+
+impl<T, K, P, const N: usize> fmt::Debug for SoftLimitedHeap<T, K, P, N>
+where
+    K: Kind,
+    T: Ord + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SoftLimitedHeap")
+            .field("heap", &self.heap)
+            .field("soft_limit", &self.soft_limit)
+            .finish()
+    }
+}
+
+/// A priority queue like [`BinaryHeap`] that keeps its items sorted in a ring buffer.
+///
+/// [`push`](SmallPriorityQueue::push) finds the position of the new item with a binary search and
+/// moves the items on the shorter side of that position by one slot, which takes *O*(n) time.
+/// [`pop`](SmallPriorityQueue::pop) takes the item at the front in *O*(1) time. A
+/// [`BinaryHeap`] needs *O*(log n) time for both, but sifting an item through the heap takes a
+/// data dependent branch and an item move per level.
+///
+/// Which of the two is faster depends on the core more than on `N`. On small in-order cores
+/// without a branch predictor, like the Cortex-M0+, moving a few items by one slot tends to be
+/// cheaper than sifting, up to a few dozen items. On cores with branch prediction and caches, the
+/// heap wins even for 8 items; that's what `cargo bench -- priority_queue` shows on an x86-64
+/// host. The size of `T` and the cost of comparing items move the crossover too, so measure both
+/// on the target before choosing this queue for speed.
+///
+/// Equal items are popped in the order they were pushed, like with [`StableBinaryHeap`], and
+/// [`iter`](SmallPriorityQueue::iter) visits the items in the order they'll be popped.
+///
+/// ```
+/// use heapless::binary_heap::{Min, SmallPriorityQueue};
+///
+/// // (deadline, task)
+/// let mut timers: SmallPriorityQueue<(u32, char), Min, 8> = SmallPriorityQueue::new();
+/// timers.push((30, 'c')).unwrap();
+/// timers.push((10, 'a')).unwrap();
+/// timers.push((20, 'b')).unwrap();
+///
+/// assert_eq!(timers.peek(), Some(&(10, 'a')));
+/// assert!(timers.iter().map(|(_, task)| *task).eq(['a', 'b', 'c']));
+/// assert_eq!(timers.pop(), Some((10, 'a')));
+/// assert_eq!(timers.len(), 2);
+/// ```
+pub struct SmallPriorityQueue<T, K, const N: usize> {
+    /// Sorted so that the item that's popped next is at the front.
+    items: Deque<T, N>,
+    _kind: PhantomData<K>,
+}
+
+impl<T, K, const N: usize> SmallPriorityQueue<T, K, N> {
+    /// Creates an empty `SmallPriorityQueue`.
+    ///
+    /// ```
+    /// use heapless::binary_heap::{Max, SmallPriorityQueue};
+    ///
+    /// static mut QUEUE: SmallPriorityQueue<u8, Max, 16> = SmallPriorityQueue::new();
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            items: Deque::new(),
+            _kind: PhantomData,
+        }
+    }
+
+    /// Returns the capacity of the queue.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of items in the queue.
+    pub const fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Checks if the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Checks if the queue is full.
+    pub fn is_full(&self) -> bool {
+        self.items.is_full()
+    }
+
+    /// Drops all items from the queue.
+    pub fn clear(&mut self) {
+        self.items.clear()
+    }
+
+    /// Returns an iterator over the items, in the order they will be popped.
+    pub fn iter(&self) -> deque::Iter<'_, T, N> {
+        self.items.iter()
+    }
+
+    /// Returns the *top* item, the one that will be popped next, or `None` if the queue is empty.
+    pub fn peek(&self) -> Option<&T> {
+        self.items.front()
+    }
+
+    /// Removes the *top* item from the queue and returns it, or `None` if the queue is empty.
+    ///
+    /// Of several equal items, this is the one that was pushed first.
+    pub fn pop(&mut self) -> Option<T> {
+        self.items.pop_front()
+    }
+}
+
+impl<T, K, const N: usize> SmallPriorityQueue<T, K, N>
+where
+    T: Ord,
+    K: Kind,
+{
+    /// Pushes an item into the queue.
+    ///
+    /// Returns back the `item` if the queue is full.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if self.items.is_full() {
+            return Err(item);
+        }
+
+        // after the items that are popped before it, including the equal ones
+        let after = K::ordering().reverse();
+        let goes_before = |other: &T| other.cmp(&item) != after;
+        let (front, back) = self.items.as_slices();
+        let index = match front.partition_point(goes_before) {
+            i if i == front.len() => i + back.partition_point(goes_before),
+            i => i,
+        };
+        self.items.insert(index, item)
+    }
+}
+
+impl<T, K, const N: usize> Default for SmallPriorityQueue<T, K, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, K, const N: usize> Clone for SmallPriorityQueue<T, K, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            _kind: PhantomData,
+        }
+    }
+}
+
+impl<T, K, const N: usize> fmt::Debug for SmallPriorityQueue<T, K, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, K, const N: usize> IntoIterator for &'a SmallPriorityQueue<T, K, N> {
+    type Item = &'a T;
+    type IntoIter = deque::Iter<'a, T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::vec::Vec;
 
-    use crate::binary_heap::{BinaryHeap, Max, Min};
+    use crate::binary_heap::{
+        BinaryHeap, Max, Min, Overflow, SmallPriorityQueue, SoftLimitedHeap, StableBinaryHeap,
+    };
 
     #[test]
     fn static_new() {
@@ -735,4 +1440,171 @@ mod tests {
         assert_eq!(heap.pop(), Some(1));
         assert_eq!(heap.pop(), None);
     }
+
+    // Pairs that only compare by their first field
+    #[derive(Debug, Clone, Copy)]
+    struct P(u8, u8);
+    impl PartialEq for P {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Eq for P {}
+    impl PartialOrd for P {
+        fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for P {
+        fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    #[test]
+    fn stable_fifo() {
+        let mut heap: StableBinaryHeap<P, Min, 8> = StableBinaryHeap::new();
+        // Sequence numbers wrap around while the items are in the heap
+        heap.next_seq = u32::MAX - 2;
+        for (i, p) in [2, 1, 2, 1, 2, 1, 2, 1].into_iter().enumerate() {
+            heap.push(P(p, i as u8)).unwrap();
+        }
+        assert!(heap.push(P(0, 0)).is_err());
+
+        let order: Vec<u8> = core::iter::from_fn(|| heap.pop()).map(|p| p.1).collect();
+        assert_eq!(order, [1, 3, 5, 7, 0, 2, 4, 6]);
+
+        let mut heap: StableBinaryHeap<P, Max, 8> = StableBinaryHeap::new();
+        for (i, p) in [2, 1, 2, 1].into_iter().enumerate() {
+            heap.push(P(p, i as u8)).unwrap();
+        }
+        let clone = heap.clone();
+        assert_eq!(heap.peek().map(|p| p.1), Some(0));
+
+        let order: Vec<u8> = core::iter::from_fn(|| heap.pop()).map(|p| p.1).collect();
+        assert_eq!(order, [0, 2, 1, 3]);
+        assert_eq!(clone.len(), 4);
+    }
+
+    #[test]
+    fn soft_limit() {
+        let mut heap: SoftLimitedHeap<u8, Min, _, 4> =
+            SoftLimitedHeap::new(8, |_, _| Overflow::EvictWorst);
+        for i in [5, 3, 9, 7] {
+            heap.push(i).unwrap();
+        }
+        // capped by the hard capacity; 9 is the worst item in a min-heap
+        assert_eq!(heap.push(4), Ok(Some(9)));
+        assert_eq!(heap.push(10), Err(10));
+        assert_eq!(heap.push(7), Err(7));
+
+        heap.set_soft_limit(2);
+        assert_eq!(heap.push(1), Ok(Some(7)));
+        assert_eq!(heap.len(), 4);
+        assert_eq!(heap.as_heap().iter().count(), 4);
+
+        let mut sorted = Vec::new();
+        while let Some(x) = heap.pop() {
+            sorted.push(x);
+        }
+        assert_eq!(sorted, [1, 3, 4, 5]);
+
+        // eviction from the middle of larger heaps keeps the heap property
+        for len in 1..32 {
+            let mut heap: SoftLimitedHeap<u32, Max, _, 32> =
+                SoftLimitedHeap::new(len, |_, _| Overflow::EvictWorst);
+            let mut x = 7u32;
+            for _ in 0..100 {
+                x = x.wrapping_mul(1_103_515_245).wrapping_add(12345) % 1000;
+                let _ = heap.push(x);
+            }
+            let mut popped = Vec::new();
+            while let Some(x) = heap.pop() {
+                popped.push(x);
+            }
+            assert_eq!(popped.len(), len);
+            assert!(popped.windows(2).all(|w| w[0] >= w[1]));
+        }
+    }
+
+    #[test]
+    fn bulk_mut() {
+        let mut heap: BinaryHeap<u32, Max, 32> = BinaryHeap::new();
+        let mut x = 7u32;
+        for _ in 0..32 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345) % 1000;
+            heap.push(x).unwrap();
+        }
+
+        for item in heap.bulk_mut().iter_mut() {
+            *item = (*item * 7) % 1000;
+        }
+        let mut popped = Vec::new();
+        while let Some(x) = heap.pop() {
+            popped.push(x);
+        }
+        assert_eq!(popped.len(), 32);
+        assert!(popped.windows(2).all(|w| w[0] >= w[1]));
+    }
+
+    #[test]
+    fn peek_many() {
+        let mut heap: BinaryHeap<u32, Max, 32> = BinaryHeap::new();
+        let mut x = 7u32;
+        for _ in 0..32 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345) % 100;
+            heap.push(x).unwrap();
+        }
+
+        let mut sorted: Vec<u32> = heap.iter().copied().collect();
+        sorted.sort_unstable_by(|a, b| b.cmp(a));
+        assert!(heap.peek_many::<5>().into_iter().eq(&sorted[..5]));
+        assert!(heap.peek_many::<32>().into_iter().eq(&sorted));
+        assert!(heap.peek_many::<40>().into_iter().eq(&sorted));
+        assert!(heap.peek_many::<0>().is_empty());
+        assert_eq!(heap.len(), 32);
+
+        let mut heap: BinaryHeap<u32, Min, 4> = BinaryHeap::new();
+        assert!(heap.peek_many::<2>().is_empty());
+        heap.push(3).unwrap();
+        heap.push(1).unwrap();
+        assert_eq!(heap.peek_many::<3>(), [&1, &3]);
+    }
+
+    #[test]
+    fn small_priority_queue() {
+        fn check<K: super::Kind>() {
+            // same order as a `StableBinaryHeap`, across wrap-arounds of the ring
+            let mut queue: SmallPriorityQueue<P, K, 6> = SmallPriorityQueue::new();
+            let mut heap: StableBinaryHeap<P, K, 6> = StableBinaryHeap::new();
+            let mut seed = 1u32;
+            for i in 0..500 {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                if seed >> 30 == 0 {
+                    assert_eq!(queue.pop().map(|p| p.1), heap.pop().map(|p| p.1));
+                } else {
+                    let p = P((seed >> 16) as u8 % 4, i as u8);
+                    assert_eq!(queue.push(p).is_ok(), heap.push(p).is_ok());
+                }
+                assert_eq!(queue.peek().map(|p| p.1), heap.peek().map(|p| p.1));
+                assert_eq!(queue.len(), heap.len());
+            }
+            let order: Vec<u8> = queue.iter().map(|p| p.1).collect();
+            let popped: Vec<u8> = core::iter::from_fn(|| heap.pop()).map(|p| p.1).collect();
+            assert_eq!(order, popped);
+        }
+        check::<Min>();
+        check::<Max>();
+
+        droppable!();
+        let mut queue: SmallPriorityQueue<Droppable, Max, 4> = SmallPriorityQueue::new();
+        for _ in 0..4 {
+            queue.push(Droppable::new()).ok().unwrap();
+        }
+        assert!(queue.push(Droppable::new()).is_err());
+        queue.pop().unwrap();
+        assert_eq!(Droppable::count(), 3);
+        core::mem::drop(queue);
+        assert_eq!(Droppable::count(), 0);
+    }
 }
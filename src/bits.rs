@@ -0,0 +1,176 @@
+//! Bit-level access to byte vectors
+//!
+//! Bits are numbered LSB first: bit `i` is bit `i % 8` of byte `i / 8`. Multi-bit fields are read
+//! and written least significant bit first, so a field never needs to be byte aligned.
+
+use core::ops::Range;
+
+use crate::Vec;
+
+impl<const N: usize> Vec<u8, N> {
+    /// Returns the bit at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, i.e. not less than `self.len() * 8`.
+    pub fn get_bit(&self, index: usize) -> bool {
+        self[index / 8] & (1 << (index % 8)) != 0
+    }
+
+    /// Sets the bit at `index` to `1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, i.e. not less than `self.len() * 8`.
+    pub fn set_bit(&mut self, index: usize) {
+        self[index / 8] |= 1 << (index % 8);
+    }
+
+    /// Clears the bit at `index` to `0`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, i.e. not less than `self.len() * 8`.
+    pub fn clear_bit(&mut self, index: usize) {
+        self[index / 8] &= !(1 << (index % 8));
+    }
+
+    /// Sets the bit at `index` to `value`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, i.e. not less than `self.len() * 8`.
+    pub fn write_bit(&mut self, index: usize, value: bool) {
+        if value {
+            self.set_bit(index)
+        } else {
+            self.clear_bit(index)
+        }
+    }
+
+    /// Reads the bits in `range` as an integer, with `range.start` as its least significant bit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is wider than 32 bits or out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let v: Vec<u8, 4> = Vec::from_slice(&[0b1010_0000, 0b0000_0011]).unwrap();
+    /// // a 6 bit field straddling the byte boundary
+    /// assert_eq!(v.get_bits(5..11), 0b011_101);
+    /// ```
+    pub fn get_bits(&self, range: Range<usize>) -> u32 {
+        let width = self.check_bit_range(&range);
+
+        let mut value = 0;
+        let mut done = 0;
+        while done < width {
+            let bit = range.start + done;
+            let shift = bit % 8;
+            let take = (8 - shift).min(width - done);
+            let mask = (1u32 << take) - 1;
+            value |= ((u32::from(self[bit / 8]) >> shift) & mask) << done;
+            done += take;
+        }
+        value
+    }
+
+    /// Writes the low bits of `value` to the bits in `range`, with `range.start` receiving its
+    /// least significant bit. The remaining bits of `value` are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is wider than 32 bits or out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// // assemble a 2 byte header: 3 bit version, 1 bit flag, 12 bit length
+    /// let mut header: Vec<u8, 2> = Vec::from_slice(&[0; 2]).unwrap();
+    /// header.write_bits(0..3, 5);
+    /// header.set_bit(3);
+    /// header.write_bits(4..16, 0x123);
+    /// assert_eq!(header, [0x3d, 0x12]);
+    /// ```
+    pub fn write_bits(&mut self, range: Range<usize>, value: u32) {
+        let width = self.check_bit_range(&range);
+
+        let mut done = 0;
+        while done < width {
+            let bit = range.start + done;
+            let shift = bit % 8;
+            let take = (8 - shift).min(width - done);
+            let mask = (((1u32 << take) - 1) << shift) as u8;
+            let bits = ((value >> done) << shift) as u8;
+            let byte = &mut self[bit / 8];
+            *byte = (*byte & !mask) | (bits & mask);
+            done += take;
+        }
+    }
+
+    /// Returns the width of `range`, after checking that it's a valid field.
+    fn check_bit_range(&self, range: &Range<usize>) -> usize {
+        assert!(
+            range.start <= range.end && range.end <= self.len() * 8,
+            "bit range out of bounds"
+        );
+        let width = range.end - range.start;
+        assert!(width <= 32, "bit range wider than 32 bits");
+        width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Vec;
+
+    #[test]
+    fn single_bits() {
+        let mut v: Vec<u8, 2> = Vec::from_slice(&[0; 2]).unwrap();
+        v.set_bit(0);
+        v.set_bit(9);
+        v.write_bit(15, true);
+        assert_eq!(v, [0x01, 0x82]);
+        assert!(v.get_bit(9));
+        assert!(!v.get_bit(8));
+
+        v.clear_bit(9);
+        v.write_bit(0, false);
+        assert_eq!(v, [0x00, 0x80]);
+    }
+
+    #[test]
+    fn fields() {
+        let mut v: Vec<u8, 6> = Vec::from_slice(&[0xff; 6]).unwrap();
+
+        v.write_bits(4..36, 0x1234_5678);
+        assert_eq!(v, [0x8f, 0x67, 0x45, 0x23, 0xf1, 0xff]);
+        assert_eq!(v.get_bits(4..36), 0x1234_5678);
+        assert_eq!(v.get_bits(0..4), 0xf);
+        assert_eq!(v.get_bits(8..8), 0);
+
+        // excess bits of the value are ignored
+        v.write_bits(44..48, 0xf0);
+        assert_eq!(v.get_bits(40..48), 0x0f);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of bounds")]
+    fn out_of_bounds() {
+        let v: Vec<u8, 4> = Vec::from_slice(&[0; 2]).unwrap();
+        v.get_bits(8..17);
+    }
+
+    #[test]
+    #[should_panic(expected = "wider than 32 bits")]
+    fn too_wide() {
+        let v: Vec<u8, 8> = Vec::from_slice(&[0; 8]).unwrap();
+        v.get_bits(0..33);
+    }
+}
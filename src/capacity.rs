@@ -0,0 +1,131 @@
+//! Compile-time capacity planning.
+//!
+//! Stable Rust can't compute a capacity such as `N + 16` from a generic `N` in a type, so code
+//! generic over capacities usually checks the relationship between them with a runtime assert.
+//! The types in this module check it at compile time instead: each of the `assert_*` functions
+//! fails to compile, when instantiated, with capacities that violate the relationship.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{capacity::CapacityAdd, Vec};
+//!
+//! const HEADER: usize = 16;
+//!
+//! /// Prepends a header to `payload`.
+//! fn frame<const IN: usize, const OUT: usize>(payload: &Vec<u8, IN>) -> Vec<u8, OUT> {
+//!     // every payload fits, so the `unwrap`s below can't fail
+//!     CapacityAdd::<IN, HEADER>::assert_fits_in::<OUT>();
+//!
+//!     let mut out = Vec::new();
+//!     out.extend_from_slice(&[0xff; HEADER]).unwrap();
+//!     out.extend_from_slice(payload).unwrap();
+//!     out
+//! }
+//!
+//! let payload: Vec<u8, 32> = Vec::from_slice(b"hello").unwrap();
+//! let out: Vec<u8, 48> = frame(&payload);
+//! assert_eq!(out.len(), 21);
+//!
+//! // in non-generic code the sum can also be used as a capacity directly
+//! let out: Vec<u8, { CapacityAdd::<32, HEADER>::SUM }> = frame(&payload);
+//! assert_eq!(out.capacity(), 48);
+//! ```
+//!
+//! An output buffer that's too small is rejected by the compiler:
+//!
+//! ```compile_fail
+//! # use heapless::{capacity::CapacityAdd, Vec};
+//! fn frame<const IN: usize, const OUT: usize>(payload: &Vec<u8, IN>) -> Vec<u8, OUT> {
+//!     CapacityAdd::<IN, 16>::assert_fits_in::<OUT>();
+//!     Vec::new()
+//! }
+//!
+//! let payload: Vec<u8, 32> = Vec::new();
+//! let out: Vec<u8, 47> = frame(&payload);
+//! ```
+
+/// A capacity of `N` elements.
+///
+/// Not to be confused with the [`Capacity`](crate::traits::Capacity) trait, which reports the
+/// capacity of a container at runtime.
+pub struct ConstCapacity<const N: usize>;
+
+impl<const N: usize> ConstCapacity<N> {
+    /// The capacity, `N`.
+    pub const VALUE: usize = N;
+
+    /// Fails to compile unless `N >= MIN`.
+    pub const fn assert_at_least<const MIN: usize>() {
+        crate::sealed::greater_than_eq::<N, MIN>();
+    }
+
+    /// Fails to compile unless `N <= MAX`.
+    pub const fn assert_at_most<const MAX: usize>() {
+        crate::sealed::greater_than_eq::<MAX, N>();
+    }
+}
+
+/// The sum of the capacities `A` and `B`.
+pub struct CapacityAdd<const A: usize, const B: usize>;
+
+impl<const A: usize, const B: usize> CapacityAdd<A, B> {
+    /// `A + B`. Fails to compile if the sum overflows.
+    pub const SUM: usize = match A.checked_add(B) {
+        Some(sum) => sum,
+        None => panic!("capacity overflow"),
+    };
+
+    /// Fails to compile unless `A + B` elements fit in a capacity of `N`.
+    pub const fn assert_fits_in<const N: usize>() {
+        crate::sealed::sum_at_most::<A, B, N>();
+    }
+}
+
+/// The product of the capacities `A` and `B`.
+pub struct CapacityMul<const A: usize, const B: usize>;
+
+impl<const A: usize, const B: usize> CapacityMul<A, B> {
+    /// `A * B`. Fails to compile if the product overflows.
+    pub const PRODUCT: usize = match A.checked_mul(B) {
+        Some(product) => product,
+        None => panic!("capacity overflow"),
+    };
+
+    /// Fails to compile unless `A * B` elements fit in a capacity of `N`.
+    pub const fn assert_fits_in<const N: usize>() {
+        crate::sealed::product_at_most::<A, B, N>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CapacityAdd, CapacityMul, ConstCapacity};
+    use crate::Vec;
+
+    fn concat<const A: usize, const B: usize, const N: usize>(
+        a: &Vec<u8, A>,
+        b: &Vec<u8, B>,
+    ) -> Vec<u8, N> {
+        CapacityAdd::<A, B>::assert_fits_in::<N>();
+        let mut out = Vec::new();
+        out.extend_from_slice(a).unwrap();
+        out.extend_from_slice(b).unwrap();
+        out
+    }
+
+    #[test]
+    fn planning() {
+        assert_eq!(ConstCapacity::<8>::VALUE, 8);
+        ConstCapacity::<8>::assert_at_least::<8>();
+        ConstCapacity::<8>::assert_at_most::<9>();
+        assert_eq!(CapacityAdd::<3, 4>::SUM, 7);
+        assert_eq!(CapacityMul::<3, 4>::PRODUCT, 12);
+        CapacityMul::<3, 4>::assert_fits_in::<12>();
+
+        let a: Vec<u8, 2> = Vec::from_slice(&[1, 2]).unwrap();
+        let b: Vec<u8, 3> = Vec::from_slice(&[3, 4, 5]).unwrap();
+        let out: Vec<u8, 5> = concat(&a, &b);
+        assert_eq!(out, [1, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,313 @@
+//! Records the largest length that containers reach, to size their capacities from data.
+//!
+//! The capacity of each container is usually a guess, made when the code was written. With the
+//! `capacity-stats` feature, a container can be labelled with a [`HighWaterMark`], a `static`
+//! that records the largest length that the container reaches from then on. After a soak test,
+//! [`report`] lists all marks that were used, with the capacity of their containers, which shows
+//! what each capacity can be shrunk to, or which ones were barely large enough.
+//!
+//! Several containers can share a mark, e.g. the buffers of all connections, and the mark records
+//! the largest length of any of them.
+//!
+//! NOTE: This module is only available with the `capacity-stats` feature. It adds a field to
+//! [`Vec`] and [`Deque`], and so to the containers built on them, so it's not meant to be enabled
+//! in production builds. It requires atomic CAS operations. On targets without them, they are
+//! emulated by the [`portable-atomic`](https://crates.io/crates/portable-atomic) crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{
+//!     capacity_stats::{self, CapacityMonitor, HighWaterMark},
+//!     Vec,
+//! };
+//!
+//! const RX_LABEL: &str = "uart rx";
+//! static RX: HighWaterMark = HighWaterMark::new(RX_LABEL);
+//!
+//! let mut rx: Vec<u8, 64> = Vec::new();
+//! rx.monitor(&RX);
+//! rx.extend_from_slice(b"hello").unwrap();
+//! rx.clear();
+//! rx.push(b'!').unwrap();
+//!
+//! // at the end of the soak test
+//! for mark in capacity_stats::report() {
+//!     println!("{}", mark);
+//! }
+//! assert_eq!(RX.to_string(), "uart rx: 5 of 64");
+//! ```
+
+use core::{fmt, ptr};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use crate::{binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, LinearMap, Vec};
+
+/// The marks that have been attached to a container, most recent first.
+static MARKS: AtomicPtr<HighWaterMark> = AtomicPtr::new(ptr::null_mut());
+
+/// The largest length reached by the containers with a given label.
+///
+/// See the [module-level documentation](self) for an example.
+pub struct HighWaterMark {
+    label: &'static str,
+    capacity: AtomicUsize,
+    max_len: AtomicUsize,
+    registered: AtomicBool,
+    next: AtomicPtr<HighWaterMark>,
+}
+
+impl HighWaterMark {
+    /// Creates a mark that no container reported to yet.
+    pub const fn new(label: &'static str) -> Self {
+        Self {
+            label,
+            capacity: AtomicUsize::new(0),
+            max_len: AtomicUsize::new(0),
+            registered: AtomicBool::new(false),
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+
+    /// Returns the label of the mark.
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    /// Returns the largest length that any of the containers reached.
+    pub fn max_len(&self) -> usize {
+        self.max_len.load(Ordering::Relaxed)
+    }
+
+    /// Returns the largest capacity of the containers.
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Forgets the lengths reached so far, e.g. at the end of the start-up phase.
+    ///
+    /// The containers report their length again the next time it grows.
+    pub fn reset(&self) {
+        self.max_len.store(0, Ordering::Relaxed);
+    }
+
+    fn attach(&'static self, capacity: usize, len: usize) {
+        self.capacity.fetch_max(capacity, Ordering::Relaxed);
+        self.record(len);
+        if self.registered.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        let mut head = MARKS.load(Ordering::Relaxed);
+        loop {
+            self.next.store(head, Ordering::Relaxed);
+            match MARKS.compare_exchange_weak(
+                head,
+                self as *const Self as *mut Self,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(current) => head = current,
+            }
+        }
+    }
+
+    fn record(&self, len: usize) {
+        // the common case, a length below the mark, needs no read-modify-write
+        if len > self.max_len.load(Ordering::Relaxed) {
+            self.max_len.fetch_max(len, Ordering::Relaxed);
+        }
+    }
+}
+
+impl fmt::Debug for HighWaterMark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HighWaterMark")
+            .field("label", &self.label)
+            .field("max_len", &self.max_len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// Formats the mark as `label: max_len of capacity`.
+impl fmt::Display for HighWaterMark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} of {}",
+            self.label,
+            self.max_len(),
+            self.capacity()
+        )
+    }
+}
+
+/// Returns the marks that have been attached to a container, most recently attached first.
+pub fn report() -> Report {
+    Report {
+        next: MARKS.load(Ordering::Acquire),
+    }
+}
+
+/// An iterator over the [`HighWaterMark`]s that have been attached to a container.
+///
+/// This struct is created by [`report`].
+#[derive(Clone)]
+pub struct Report {
+    next: *const HighWaterMark,
+}
+
+impl Iterator for Report {
+    type Item = &'static HighWaterMark;
+
+    fn next(&mut self) -> Option<&'static HighWaterMark> {
+        // NOTE(unsafe) the list only links `'static` marks, and a mark's `next` doesn't change
+        // after it was published
+        let mark = unsafe { self.next.as_ref() }?;
+        self.next = mark.next.load(Ordering::Relaxed);
+        Some(mark)
+    }
+}
+
+impl fmt::Debug for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A container that can report its largest length to a [`HighWaterMark`].
+pub trait CapacityMonitor {
+    /// Makes the container report its length to `mark` from now on, whenever it grows.
+    ///
+    /// This replaces the mark of a previous call. Clones of the container don't report to the
+    /// mark.
+    fn monitor(&mut self, mark: &'static HighWaterMark);
+}
+
+/// The mark a container reports to, if any.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Monitor(Option<&'static HighWaterMark>);
+
+impl Monitor {
+    pub(crate) const fn new() -> Self {
+        Self(None)
+    }
+
+    pub(crate) fn attach(&mut self, mark: &'static HighWaterMark, capacity: usize, len: usize) {
+        mark.attach(capacity, len);
+        self.0 = Some(mark);
+    }
+
+    /// Records the length of the container after it grew.
+    #[inline]
+    pub(crate) fn record(&self, len: usize) {
+        if let Some(mark) = self.0 {
+            mark.record(len);
+        }
+    }
+}
+
+/// Includes changes made through a [`VecCapacityView`](crate::VecCapacityView).
+impl<T, const N: usize> CapacityMonitor for Vec<T, N> {
+    fn monitor(&mut self, mark: &'static HighWaterMark) {
+        let len = self.len();
+        self.monitor.attach(mark, N, len);
+    }
+}
+
+/// Includes changes made through a [`DequeCapacityView`](crate::DequeCapacityView).
+impl<T, const N: usize> CapacityMonitor for Deque<T, N> {
+    fn monitor(&mut self, mark: &'static HighWaterMark) {
+        let len = self.len();
+        self.monitor.attach(mark, N, len);
+    }
+}
+
+impl<K, V, const N: usize> CapacityMonitor for LinearMap<K, V, N> {
+    fn monitor(&mut self, mark: &'static HighWaterMark) {
+        self.buffer.monitor(mark);
+    }
+}
+
+impl<T, K, const N: usize> CapacityMonitor for BinaryHeap<T, K, N>
+where
+    K: BinaryHeapKind,
+{
+    fn monitor(&mut self, mark: &'static HighWaterMark) {
+        self.data.monitor(mark);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{report, CapacityMonitor, HighWaterMark};
+    use crate::{binary_heap::Max, BinaryHeap, Deque, FnvIndexMap, String, Vec};
+
+    #[test]
+    fn high_water_mark() {
+        static VECS: HighWaterMark = HighWaterMark::new("vecs");
+        let mut a: Vec<u8, 8> = Vec::from_slice(&[1, 2]).unwrap();
+        let mut b: Vec<u8, 4> = Vec::new();
+        a.monitor(&VECS);
+        b.monitor(&VECS);
+        assert_eq!((VECS.max_len(), VECS.capacity()), (2, 8));
+
+        a.clear();
+        b.extend_from_slice(&[1, 2, 3]).unwrap();
+        assert_eq!(VECS.max_len(), 3);
+        b.truncate(0);
+        b.shrink_capacity_view(4).push(0).unwrap();
+        assert_eq!(VECS.max_len(), 3);
+        a.shrink_capacity_view(8)
+            .extend_from_slice(&[0; 6])
+            .unwrap();
+        assert_eq!(VECS.max_len(), 6);
+        a.insert(0, 0).unwrap();
+        assert_eq!(VECS.max_len(), 7);
+        // clones start out unmonitored
+        let mut clone = a.clone();
+        clone.push(0).unwrap();
+        assert_eq!(VECS.max_len(), 7);
+
+        VECS.reset();
+        a.pop();
+        a.push(0).unwrap();
+        assert_eq!(VECS.to_string(), "vecs: 7 of 8");
+
+        static QUEUES: HighWaterMark = HighWaterMark::new("queues");
+        let mut d: Deque<u8, 4> = Deque::new();
+        d.monitor(&QUEUES);
+        d.push_back(0).unwrap();
+        d.push_front(0).unwrap();
+        d.pop_back();
+        d.shrink_capacity_view(4).push_front(0).unwrap();
+        d.shrink_capacity_view(4).push_back(0).unwrap();
+        assert_eq!(QUEUES.max_len(), 3);
+
+        static OTHERS: HighWaterMark = HighWaterMark::new("others");
+        let mut s: String<16> = String::new();
+        let mut h: BinaryHeap<u8, Max, 4> = BinaryHeap::new();
+        let mut m: FnvIndexMap<u8, u8, 4> = FnvIndexMap::new();
+        s.monitor(&OTHERS);
+        h.monitor(&OTHERS);
+        m.monitor(&OTHERS);
+        s.push_str("ñandú").unwrap();
+        h.push(0).unwrap();
+        m.insert(0, 0).unwrap();
+        assert_eq!((OTHERS.max_len(), OTHERS.capacity()), (7, 16));
+
+        // other tests may have attached marks too
+        let marks = [&VECS, &QUEUES, &OTHERS];
+        for mark in marks {
+            assert_eq!(report().filter(|m| core::ptr::eq(*m, mark)).count(), 1);
+        }
+    }
+}
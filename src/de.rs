@@ -1,6 +1,26 @@
+//! Helpers for deserializing containers with serde.
+//!
+//! The `Deserialize` impls of the containers fail if the input has more elements than fit. For
+//! fields where losing elements is acceptable, e.g. an optional list in a config file that may
+//! have been written for a device with more memory, the [`truncate`] and [`keep_last`] modules
+//! can be used with `#[serde(with = "...")]` instead:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "heapless::de::truncate")]
+//!     peers: Vec<u32, 8>,
+//!     #[serde(with = "heapless::de::keep_last")]
+//!     recent_errors: HistoryBuffer<u16, 4>,
+//! }
+//! ```
+//!
+//! They work with the [`LossySeq`] containers: [`Vec`], [`Deque`] and
+//! [`HistoryBuffer`].
+
 use crate::{
-    binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, IndexMap, IndexSet, LinearMap, String,
-    Vec,
+    binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, HistoryBuffer, IndexMap, IndexSet,
+    LinearMap, String, Vec,
 };
 use core::{
     fmt,
@@ -8,7 +28,26 @@ use core::{
     marker::PhantomData,
 };
 use hash32::BuildHasherDefault;
-use serde::de::{self, Deserialize, Deserializer, Error, MapAccess, SeqAccess};
+use serde::de::{self, Deserialize, Deserializer, Error, IgnoredAny, MapAccess, SeqAccess};
+
+/// Fails if the input declares more elements than fit in `capacity`.
+///
+/// Without this, malformed input with a huge length is only rejected after `capacity` elements
+/// were deserialized. Only for containers that keep every element: input for a map or a set may
+/// repeat keys, so it can declare more entries than the container ends up with.
+fn check_size_hint<E>(
+    size_hint: Option<usize>,
+    capacity: usize,
+    exp: &dyn de::Expected,
+) -> Result<(), E>
+where
+    E: Error,
+{
+    match size_hint {
+        Some(len) if len > capacity => Err(E::invalid_length(len, exp)),
+        _ => Ok(()),
+    }
+}
 
 // Sequential containers
 
@@ -39,6 +78,7 @@ where
             where
                 A: SeqAccess<'de>,
             {
+                check_size_hint(seq.size_hint(), N, &self)?;
                 let mut values = BinaryHeap::new();
 
                 while let Some(value) = seq.next_element()? {
@@ -119,6 +159,7 @@ where
             where
                 A: SeqAccess<'de>,
             {
+                check_size_hint(seq.size_hint(), N, &self)?;
                 let mut values = Vec::new();
 
                 while let Some(value) = seq.next_element()? {
@@ -158,6 +199,7 @@ where
             where
                 A: SeqAccess<'de>,
             {
+                check_size_hint(seq.size_hint(), N, &self)?;
                 let mut values = Deque::new();
 
                 while let Some(value) = seq.next_element()? {
@@ -304,3 +346,295 @@ impl<'de, const N: usize> Deserialize<'de> for String<N> {
         deserializer.deserialize_str(ValueVisitor::<'de, N>(PhantomData))
     }
 }
+
+// Lossy sequences
+
+/// A sequential container that [`truncate`] and [`keep_last`] can deserialize into.
+pub trait LossySeq: Default {
+    /// The type of the elements.
+    type Item;
+
+    /// Appends `item`, or returns it back if the container is full.
+    fn push_first(&mut self, item: Self::Item) -> Result<(), Self::Item>;
+
+    /// Appends `item`, removing the oldest element first if the container is full.
+    fn push_last(&mut self, item: Self::Item);
+}
+
+/// Removing the oldest element shifts all other elements, so [`keep_last`] takes *O*(n) time per
+/// element past the capacity.
+impl<T, const N: usize> LossySeq for Vec<T, N> {
+    type Item = T;
+
+    fn push_first(&mut self, item: T) -> Result<(), T> {
+        self.push(item)
+    }
+
+    fn push_last(&mut self, item: T) {
+        if N == 0 {
+            return;
+        }
+        if self.is_full() {
+            self.remove(0);
+        }
+        let _ = self.push(item);
+    }
+}
+
+impl<T, const N: usize> LossySeq for Deque<T, N> {
+    type Item = T;
+
+    fn push_first(&mut self, item: T) -> Result<(), T> {
+        self.push_back(item)
+    }
+
+    fn push_last(&mut self, item: T) {
+        if self.is_full() {
+            self.pop_front();
+        }
+        let _ = self.push_back(item);
+    }
+}
+
+impl<T, const N: usize> LossySeq for HistoryBuffer<T, N> {
+    type Item = T;
+
+    fn push_first(&mut self, item: T) -> Result<(), T> {
+        if self.len() == N {
+            return Err(item);
+        }
+        self.write(item);
+        Ok(())
+    }
+
+    fn push_last(&mut self, item: T) {
+        self.write(item);
+    }
+}
+
+struct LossyVisitor<'de, C, const LAST: bool>(PhantomData<(&'de (), C)>);
+
+impl<'de, C, const LAST: bool> de::Visitor<'de> for LossyVisitor<'de, C, LAST>
+where
+    C: LossySeq,
+    C::Item: Deserialize<'de>,
+{
+    type Value = C;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = C::default();
+
+        while let Some(value) = seq.next_element()? {
+            if LAST {
+                values.push_last(value);
+            } else if values.push_first(value).is_err() {
+                // the rest of the sequence still has to be consumed
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                break;
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// Deserializes a sequence into a [`LossySeq`], keeping the first elements that fit and dropping
+/// the rest, for use with `#[serde(with = "heapless::de::truncate")]`.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{de, Deque};
+/// use serde::de::value::{Error, SeqDeserializer};
+///
+/// let input = SeqDeserializer::<_, Error>::new([1, 2, 3, 4, 5].into_iter());
+/// let first: Deque<u8, 3> = de::truncate::deserialize(input)?;
+/// assert!(first.iter().eq(&[1, 2, 3]));
+/// # Ok::<(), Error>(())
+/// ```
+pub mod truncate {
+    use core::marker::PhantomData;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{LossySeq, LossyVisitor};
+
+    /// Deserializes a sequence, keeping the first elements that fit.
+    pub fn deserialize<'de, D, C>(deserializer: D) -> Result<C, D::Error>
+    where
+        D: Deserializer<'de>,
+        C: LossySeq,
+        C::Item: Deserialize<'de>,
+    {
+        deserializer.deserialize_seq(LossyVisitor::<C, false>(PhantomData))
+    }
+
+    /// Serializes the container as usual.
+    pub fn serialize<S, C>(value: &C, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        C: Serialize,
+    {
+        value.serialize(serializer)
+    }
+}
+
+/// Deserializes a sequence into a [`LossySeq`], keeping the last elements that fit and dropping
+/// the ones before them, for use with `#[serde(with = "heapless::de::keep_last")]`.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{de, HistoryBuffer};
+/// use serde::de::value::{Error, SeqDeserializer};
+///
+/// let input = SeqDeserializer::<_, Error>::new([1, 2, 3, 4, 5].into_iter());
+/// let last: HistoryBuffer<u8, 3> = de::keep_last::deserialize(input)?;
+/// assert!(last.oldest_ordered().eq(&[3, 4, 5]));
+/// # Ok::<(), Error>(())
+/// ```
+pub mod keep_last {
+    use core::marker::PhantomData;
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{LossySeq, LossyVisitor};
+
+    /// Deserializes a sequence, keeping the last elements that fit.
+    pub fn deserialize<'de, D, C>(deserializer: D) -> Result<C, D::Error>
+    where
+        D: Deserializer<'de>,
+        C: LossySeq,
+        C::Item: Deserialize<'de>,
+    {
+        deserializer.deserialize_seq(LossyVisitor::<C, true>(PhantomData))
+    }
+
+    /// Serializes the container as usual.
+    pub fn serialize<S, C>(value: &C, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        C: Serialize,
+    {
+        value.serialize(serializer)
+    }
+}
+
+#[cfg(all(test, feature = "postcard"))]
+mod tests {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    use serde::{Deserialize, Deserializer};
+
+    use super::{keep_last, truncate};
+    use crate::{Deque, FnvIndexMap, HistoryBuffer, Vec};
+
+    static DESERIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct Counted(u8);
+
+    impl<'de> Deserialize<'de> for Counted {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            DESERIALIZED.fetch_add(1, Ordering::Relaxed);
+            u8::deserialize(deserializer).map(Counted)
+        }
+    }
+
+    #[test]
+    fn declared_length_over_capacity() {
+        let mut input = [1; 101];
+        input[0] = 100;
+        assert_eq!(
+            postcard::from_bytes::<Vec<Counted, 4>>(&input),
+            Err(postcard::Error::SerdeDeCustom)
+        );
+        assert_eq!(
+            postcard::from_bytes::<Deque<Counted, 4>>(&input).err(),
+            Some(postcard::Error::SerdeDeCustom)
+        );
+        assert_eq!(DESERIALIZED.load(Ordering::Relaxed), 0);
+
+        assert_eq!(
+            postcard::from_bytes::<Vec<Counted, 4>>(&[2, 1, 2]).unwrap(),
+            [Counted(1), Counted(2)]
+        );
+        assert_eq!(DESERIALIZED.load(Ordering::Relaxed), 2);
+
+        // maps may repeat keys, the later value wins
+        let map =
+            postcard::from_bytes::<FnvIndexMap<u8, Counted, 4>>(&[5, 1, 1, 1, 2, 1, 3, 1, 4, 1, 5])
+                .unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map[&1], Counted(5));
+    }
+
+    #[test]
+    fn lossy() {
+        #[derive(Debug, PartialEq)]
+        struct Config {
+            peers: Vec<u8, 2>,
+            errors: HistoryBuffer<u8, 2>,
+            version: u8,
+        }
+
+        impl<'de> Deserialize<'de> for Config {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                // what `#[derive(Deserialize)]` generates for the `with` attributes, in short
+                struct Peers(Vec<u8, 2>);
+                impl<'de> Deserialize<'de> for Peers {
+                    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                        truncate::deserialize(d).map(Peers)
+                    }
+                }
+                struct Errors(HistoryBuffer<u8, 2>);
+                impl<'de> Deserialize<'de> for Errors {
+                    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+                        keep_last::deserialize(d).map(Errors)
+                    }
+                }
+
+                let (Peers(peers), Errors(errors), version) =
+                    <(Peers, Errors, u8)>::deserialize(deserializer)?;
+                Ok(Config {
+                    peers,
+                    errors,
+                    version,
+                })
+            }
+        }
+
+        // the elements past the capacity are consumed, so the next field is read correctly
+        let config: Config = postcard::from_bytes(&[3, 1, 2, 3, 3, 4, 5, 6, 9]).unwrap();
+        assert_eq!(config.peers, [1, 2]);
+        assert!(config.errors.oldest_ordered().eq(&[5, 6]));
+        assert_eq!(config.version, 9);
+
+        let mut bytes = [0; 8];
+        let errors = postcard::to_slice(&config.errors, &mut bytes).unwrap();
+        assert_eq!(errors, [2, 5, 6]);
+
+        let deque: Deque<u8, 2> =
+            keep_last::deserialize(&mut postcard::Deserializer::from_bytes(&[3, 1, 2, 3])).unwrap();
+        assert!(deque.iter().eq(&[2, 3]));
+        let vec: Vec<u8, 2> =
+            keep_last::deserialize(&mut postcard::Deserializer::from_bytes(&[3, 1, 2, 3])).unwrap();
+        assert_eq!(vec, [2, 3]);
+        let empty: Vec<u8, 0> =
+            keep_last::deserialize(&mut postcard::Deserializer::from_bytes(&[1, 1])).unwrap();
+        assert!(empty.is_empty());
+    }
+}
@@ -2,8 +2,11 @@ use core::fmt;
 use core::iter::FusedIterator;
 use core::marker::PhantomData;
 use core::mem::MaybeUninit;
+use core::ops::{Range, RangeBounds};
 use core::{ptr, slice};
 
+use crate::{display::DisplayJoined, Vec};
+
 /// A fixed capacity double-ended queue.
 ///
 /// # Examples
@@ -49,6 +52,12 @@ pub struct Deque<T, const N: usize> {
     /// Used to distinguish "empty" and "full" cases when `front == back`.
     /// May only be `true` if `front == back`, always `false` otherwise.
     full: bool,
+
+    #[cfg(feature = "test-util")]
+    pub(crate) fail_next: crate::test_util::FailNext,
+
+    #[cfg(capacity_stats)]
+    pub(crate) monitor: crate::capacity_stats::Monitor,
 }
 
 impl<T, const N: usize> Deque<T, N> {
@@ -76,23 +85,21 @@ impl<T, const N: usize> Deque<T, N> {
             front: 0,
             back: 0,
             full: false,
+            #[cfg(feature = "test-util")]
+            fail_next: crate::test_util::FailNext::new(),
+            #[cfg(capacity_stats)]
+            monitor: crate::capacity_stats::Monitor::new(),
         }
     }
 
+    #[inline]
     fn increment(i: usize) -> usize {
-        if i + 1 == N {
-            0
-        } else {
-            i + 1
-        }
+        ring::increment(i, N)
     }
 
+    #[inline]
     fn decrement(i: usize) -> usize {
-        if i == 0 {
-            N - 1
-        } else {
-            i - 1
-        }
+        ring::decrement(i, N)
     }
 
     /// Returns the maximum number of elements the deque can hold.
@@ -102,13 +109,7 @@ impl<T, const N: usize> Deque<T, N> {
 
     /// Returns the number of elements currently in the deque.
     pub const fn len(&self) -> usize {
-        if self.full {
-            N
-        } else if self.back < self.front {
-            self.back + N - self.front
-        } else {
-            self.back - self.front
-        }
+        ring::len(self.front, self.back, self.full, N)
     }
 
     /// Clears the deque, removing all values.
@@ -191,6 +192,144 @@ impl<T, const N: usize> Deque<T, N> {
         }
     }
 
+    /// Splits the contents of the `Deque` into the elements before the logical index `mid` and the
+    /// elements from `mid` on.
+    ///
+    /// Each half is returned as a pair of slices which contain, in order, its elements, like
+    /// [`as_mut_slices`](Deque::as_mut_slices). The halves can be handed to different parts of a
+    /// program at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mid > len`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(2).unwrap();
+    /// deque.push_back(3).unwrap();
+    /// deque.push_front(1).unwrap();
+    /// deque.push_front(0).unwrap();
+    ///
+    /// let ((a, b), (c, d)) = deque.split_at_mut(1);
+    /// assert_eq!((&a[..], &b[..]), (&[0][..], &[][..]));
+    /// assert_eq!((&c[..], &d[..]), (&[1][..], &[2, 3][..]));
+    /// ```
+    #[allow(clippy::type_complexity)]
+    pub fn split_at_mut(&mut self, mid: usize) -> ((&mut [T], &mut [T]), (&mut [T], &mut [T])) {
+        let len = self.len();
+        assert!(mid <= len, "mid (is {}) should be <= len (is {})", mid, len);
+
+        let (front, back) = self.as_mut_slices();
+        if mid <= front.len() {
+            let (left, right) = front.split_at_mut(mid);
+            ((left, &mut []), (right, back))
+        } else {
+            let (left, right) = back.split_at_mut(mid - front.len());
+            ((front, left), (right, &mut []))
+        }
+    }
+
+    /// Copies the elements in the logical index range `src` so that they start at logical index
+    /// `dest`, like [`slice::copy_within`].
+    ///
+    /// The copy is done in at most three `memmove`s on the underlying buffer, wherever the deque
+    /// wraps around.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `src` is out of bounds, or if `dest` is greater than `self.len()` minus the
+    /// length of `src`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(2).unwrap();
+    /// deque.push_back(3).unwrap();
+    /// deque.push_front(1).unwrap();
+    /// deque.push_front(0).unwrap();
+    ///
+    /// deque.copy_within(1..3, 2);
+    /// assert!(deque.iter().eq(&[0, 1, 1, 2]));
+    /// ```
+    pub fn copy_within<R>(&mut self, src: R, dest: usize)
+    where
+        R: RangeBounds<usize>,
+        T: Copy,
+    {
+        let len = self.len();
+        let Range { start, end } = crate::vec::slice_range(src, len);
+        let count = end - start;
+        assert!(
+            dest <= len - count,
+            "destination index (is {}) should be <= len - range length (is {})",
+            dest,
+            len - count
+        );
+
+        let front = self.front;
+        let physical = |i: usize| (front + i) % N;
+        if dest <= start {
+            let mut done = 0;
+            while done < count {
+                let (from, to) = (physical(start + done), physical(dest + done));
+                let n = (count - done).min(N - from).min(N - to);
+                self.buffer.copy_within(from..from + n, to);
+                done += n;
+            }
+        } else {
+            let mut left = count;
+            while left > 0 {
+                let from_end = physical(start + left - 1) + 1;
+                let to_end = physical(dest + left - 1) + 1;
+                let n = left.min(from_end).min(to_end);
+                self.buffer.copy_within(from_end - n..from_end, to_end - n);
+                left -= n;
+            }
+        }
+    }
+
+    /// Swaps all elements of the deque with those in `other`, in logical order, like
+    /// [`slice::swap_with_slice`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` has a different length than the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(1).unwrap();
+    /// deque.push_front(0).unwrap();
+    ///
+    /// let mut scratch = [5, 6];
+    /// deque.swap_with_slice(&mut scratch);
+    /// assert!(deque.iter().eq(&[5, 6]));
+    /// assert_eq!(scratch, [0, 1]);
+    /// ```
+    pub fn swap_with_slice(&mut self, other: &mut [T]) {
+        assert!(
+            other.len() == self.len(),
+            "slice length (is {}) should be == len (is {})",
+            other.len(),
+            self.len()
+        );
+
+        let (front, back) = self.as_mut_slices();
+        let (other_front, other_back) = other.split_at_mut(front.len());
+        front.swap_with_slice(other_front);
+        back.swap_with_slice(other_back);
+    }
+
     /// Provides a reference to the front element, or None if the `Deque` is empty.
     pub fn front(&self) -> Option<&T> {
         if self.is_empty() {
@@ -247,10 +386,48 @@ impl<T, const N: usize> Deque<T, N> {
         }
     }
 
+    /// Removes at most `n` items from the front of the deque, passing each to `f`, and returns the
+    /// number of items removed.
+    ///
+    /// This bounds the work done per call, e.g. by a cooperative task that shares the CPU with
+    /// others.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 8> = Deque::new();
+    /// for i in 0..5 {
+    ///     deque.push_back(i).unwrap();
+    /// }
+    ///
+    /// let mut sum = 0;
+    /// assert_eq!(deque.drain_up_to(3, |x| sum += x), 3);
+    /// assert_eq!(sum, 0 + 1 + 2);
+    /// assert_eq!(deque.drain_up_to(3, |x| sum += x), 2);
+    /// assert!(deque.is_empty());
+    /// ```
+    pub fn drain_up_to<F>(&mut self, n: usize, mut f: F) -> usize
+    where
+        F: FnMut(T),
+    {
+        let drained = n.min(self.len());
+        for _ in 0..drained {
+            f(unsafe { self.pop_front_unchecked() });
+        }
+        drained
+    }
+
     /// Appends an `item` to the front of the deque
     ///
     /// Returns back the `item` if the deque is full
     pub fn push_front(&mut self, item: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(item);
+        }
+
         if self.is_full() {
             Err(item)
         } else {
@@ -263,6 +440,11 @@ impl<T, const N: usize> Deque<T, N> {
     ///
     /// Returns back the `item` if the deque is full
     pub fn push_back(&mut self, item: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(item);
+        }
+
         if self.is_full() {
             Err(item)
         } else {
@@ -271,6 +453,153 @@ impl<T, const N: usize> Deque<T, N> {
         }
     }
 
+    /// Appends an `item` to the back of the deque, removing the front element first if the deque
+    /// is full.
+    ///
+    /// Returns the removed element, if any. This makes the deque a ring that keeps the most
+    /// recent `N` elements, e.g. for a trace buffer that prefers dropping old entries to dropping
+    /// new ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut trace: Deque<u8, 2> = Deque::new();
+    /// assert_eq!(trace.push_back_overwrite(1), None);
+    /// assert_eq!(trace.push_back_overwrite(2), None);
+    /// assert_eq!(trace.push_back_overwrite(3), Some(1));
+    /// assert!(trace.iter().eq(&[2, 3]));
+    /// ```
+    pub fn push_back_overwrite(&mut self, item: T) -> Option<T> {
+        let oldest = self.pop_front_if_full();
+        // NOTE(unsafe) there's room for `item` now
+        unsafe { self.push_back_unchecked(item) }
+        oldest
+    }
+
+    fn pop_front_if_full(&mut self) -> Option<T> {
+        if self.is_full() {
+            Some(unsafe { self.pop_front_unchecked() })
+        } else {
+            None
+        }
+    }
+
+    /// Lends out the deque with room for at most `capacity` elements, counting the ones already in
+    /// it.
+    ///
+    /// Pushes at either end of the view fail once the deque holds `capacity` elements, and pops
+    /// make room again, so e.g. a low-priority producer can be kept from filling more than its
+    /// share of a queue. A `capacity` below the current length is raised to it, one above `N` is
+    /// lowered to `N`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{Deque, DequeCapacityView};
+    ///
+    /// fn produce(queue: &mut DequeCapacityView<'_, u8>) {
+    ///     while queue.push_back(0xaa).is_ok() {}
+    /// }
+    ///
+    /// let mut queue: Deque<u8, 64> = Deque::new();
+    /// queue.push_back(1).unwrap();
+    /// produce(&mut queue.shrink_capacity_view(4));
+    /// assert!(queue.iter().eq(&[1, 0xaa, 0xaa, 0xaa]));
+    /// ```
+    pub fn shrink_capacity_view(&mut self, capacity: usize) -> CapacityView<'_, T> {
+        let capacity = capacity.clamp(self.len(), N);
+        CapacityView {
+            buffer: &mut self.buffer,
+            front: &mut self.front,
+            back: &mut self.back,
+            full: &mut self.full,
+            capacity,
+            #[cfg(feature = "test-util")]
+            fail_next: &mut self.fail_next,
+            #[cfg(capacity_stats)]
+            monitor: self.monitor,
+        }
+    }
+
+    /// Pushes items from `iter` to the front of the deque until it's full, and returns how many
+    /// were pushed.
+    ///
+    /// Like calling [`push_front`](Deque::push_front) for each item, so the items end up in
+    /// reverse order. Items are only taken from the iterator while there's room, so the rest
+    /// stays in an iterator passed by [`by_ref`](Iterator::by_ref).
+    ///
+    /// If a push fails anyway, e.g. because a failure was injected with the `test-util` feature,
+    /// returns the item that wasn't pushed and the rest of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(0).unwrap();
+    ///
+    /// let mut samples = 1..;
+    /// assert_eq!(deque.fill_from_iter_front(samples.by_ref()), Ok(3));
+    /// assert!(deque.iter().eq(&[3, 2, 1, 0]));
+    /// assert_eq!(samples.next(), Some(4));
+    /// ```
+    pub fn fill_from_iter_front<I>(&mut self, iter: I) -> Result<usize, (T, I::IntoIter)>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let mut count = 0;
+        while !self.is_full() {
+            let Some(item) = iter.next() else { break };
+            if let Err(item) = self.push_front(item) {
+                return Err((item, iter));
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Pushes items from `iter` to the back of the deque until it's full, and returns how many
+    /// were pushed.
+    ///
+    /// Items are only taken from the iterator while there's room, so the rest stays in an
+    /// iterator passed by [`by_ref`](Iterator::by_ref).
+    ///
+    /// If a push fails anyway, e.g. because a failure was injected with the `test-util` feature,
+    /// returns the item that wasn't pushed and the rest of the iterator.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(0).unwrap();
+    ///
+    /// let mut samples = 1..;
+    /// assert_eq!(deque.fill_from_iter_back(samples.by_ref()), Ok(3));
+    /// assert!(deque.iter().eq(&[0, 1, 2, 3]));
+    /// assert_eq!(samples.next(), Some(4));
+    /// ```
+    pub fn fill_from_iter_back<I>(&mut self, iter: I) -> Result<usize, (T, I::IntoIter)>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut iter = iter.into_iter();
+        let mut count = 0;
+        while !self.is_full() {
+            let Some(item) = iter.next() else { break };
+            if let Err(item) = self.push_back(item) {
+                return Err((item, iter));
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
     /// Removes an item from the front of the deque and returns it, without checking that the deque
     /// is not empty
     ///
@@ -280,10 +609,7 @@ impl<T, const N: usize> Deque<T, N> {
     pub unsafe fn pop_front_unchecked(&mut self) -> T {
         debug_assert!(!self.is_empty());
 
-        let index = self.front;
-        self.full = false;
-        self.front = Self::increment(self.front);
-        self.buffer.get_unchecked_mut(index).as_ptr().read()
+        ring::pop_front(&self.buffer, &mut self.front, &mut self.full)
     }
 
     /// Removes an item from the back of the deque and returns it, without checking that the deque
@@ -295,121 +621,909 @@ impl<T, const N: usize> Deque<T, N> {
     pub unsafe fn pop_back_unchecked(&mut self) -> T {
         debug_assert!(!self.is_empty());
 
+        ring::pop_back(&self.buffer, &mut self.back, &mut self.full)
+    }
+
+    /// Appends an `item` to the front of the deque
+    ///
+    /// # Safety
+    ///
+    /// This assumes the deque is not full.
+    pub unsafe fn push_front_unchecked(&mut self, item: T) {
+        debug_assert!(!self.is_full());
+
+        ring::push_front(
+            &mut self.buffer,
+            &mut self.front,
+            self.back,
+            &mut self.full,
+            item,
+        );
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len());
+    }
+
+    /// Appends an `item` to the back of the deque
+    ///
+    /// # Safety
+    ///
+    /// This assumes the deque is not full.
+    pub unsafe fn push_back_unchecked(&mut self, item: T) {
+        debug_assert!(!self.is_full());
+
+        ring::push_back(
+            &mut self.buffer,
+            self.front,
+            &mut self.back,
+            &mut self.full,
+            item,
+        );
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len());
+    }
+
+    /// Returns the free slots after the back of the deque that are contiguous in memory. They're
+    /// added to the deque with [`commit_back`](Deque::commit_back) once they're initialized.
+    pub(crate) fn spare_back_mut(&mut self) -> &mut [MaybeUninit<T>] {
+        let end = if self.full {
+            self.back
+        } else if self.back < self.front {
+            self.front
+        } else {
+            N
+        };
+        &mut self.buffer[self.back..end]
+    }
+
+    /// Appends the first `n` slots returned by [`spare_back_mut`](Deque::spare_back_mut).
+    ///
+    /// # Safety
+    ///
+    /// Those slots must be initialized.
+    pub(crate) unsafe fn commit_back(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        debug_assert!(n <= self.capacity() - self.len());
+
+        self.back = (self.back + n) % N;
+        self.full = self.front == self.back;
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len());
+    }
+
+    /// Inserts an `item` at `index`, moving the elements on the shorter side of `index` by one.
+    ///
+    /// Returns back the `item` if the deque is full.
+    pub(crate) fn insert(&mut self, index: usize, item: T) -> Result<(), T> {
+        let len = self.len();
+        debug_assert!(index <= len);
+        if self.is_full() {
+            return Err(item);
+        }
+
+        let buffer = self.buffer.as_mut_ptr();
+        let slot = if index < len - index {
+            // move the elements before `index` towards the front
+            self.front = Self::decrement(self.front);
+            let mut slot = self.front;
+            for _ in 0..index {
+                let next = Self::increment(slot);
+                // NOTE(unsafe) moves an element into the free slot before it
+                unsafe { ptr::copy_nonoverlapping(buffer.add(next), buffer.add(slot), 1) };
+                slot = next;
+            }
+            slot
+        } else {
+            // move the elements from `index` on towards the back
+            let mut slot = self.back;
+            self.back = Self::increment(self.back);
+            for _ in index..len {
+                let prev = Self::decrement(slot);
+                // NOTE(unsafe) moves an element into the free slot after it
+                unsafe { ptr::copy_nonoverlapping(buffer.add(prev), buffer.add(slot), 1) };
+                slot = prev;
+            }
+            slot
+        };
+        self.buffer[slot] = MaybeUninit::new(item);
+        if self.front == self.back {
+            self.full = true;
+        }
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len());
+        Ok(())
+    }
+
+    /// Returns an iterator that removes `K` items at a time from the front of the deque and
+    /// yields them as arrays.
+    ///
+    /// Each chunk is moved out with at most two bulk copies. Items that are not yielded, including
+    /// the last `len % K` items, stay in the deque.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 8> = Deque::new();
+    /// for i in 0..7 {
+    ///     deque.push_back(i).unwrap();
+    /// }
+    ///
+    /// let mut chunks = deque.drain_chunks::<3>();
+    /// assert_eq!(chunks.next(), Some([0, 1, 2]));
+    /// assert_eq!(chunks.next(), Some([3, 4, 5]));
+    /// assert_eq!(chunks.next(), None);
+    ///
+    /// assert_eq!(deque.pop_front(), Some(6));
+    /// ```
+    pub fn drain_chunks<const K: usize>(&mut self) -> DrainChunks<'_, T, N, K> {
+        // Const assert K > 0
+        crate::sealed::greater_than_0::<K>();
+
+        DrainChunks { deque: self }
+    }
+
+    /// Clones the deque into a new deque with a capacity of `M`, front to back.
+    ///
+    /// Returns `Err(())` without cloning any element if the elements don't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 16> = Deque::new();
+    /// deque.push_back(2).unwrap();
+    /// deque.push_front(1).unwrap();
+    /// let small: Deque<u8, 2> = deque.clone_into_capacity().unwrap();
+    /// assert!(small.iter().eq(&[1, 2]));
+    /// assert!(deque.clone_into_capacity::<1>().is_err());
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn clone_into_capacity<const M: usize>(&self) -> Result<Deque<T, M>, ()>
+    where
+        T: Clone,
+    {
+        if self.len() > M {
+            return Err(());
+        }
+        let mut new = Deque::new();
+        for elem in self.iter() {
+            // NOTE(unsafe) there are at most `M` elements
+            unsafe { new.push_back_unchecked(elem.clone()) };
+        }
+        Ok(new)
+    }
+
+    /// Returns an iterator over the deque.
+    pub fn iter(&self) -> Iter<'_, T, N> {
+        let done = self.is_empty();
+        Iter {
+            _phantom: PhantomData,
+            buffer: &self.buffer as *const MaybeUninit<T>,
+            front: self.front,
+            back: self.back,
+            done,
+        }
+    }
+
+    /// Returns an adapter that displays the elements from front to back separated by
+    /// `separator`, without building a [`String`](crate::String).
+    ///
+    /// See the [`display`](crate::display) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(2).unwrap();
+    /// deque.push_front(1).unwrap();
+    /// assert_eq!(format!("{}", deque.display_joined("->")), "1->2");
+    /// ```
+    pub fn display_joined<'a>(&'a self, separator: &'a str) -> DisplayJoined<'a, Iter<'a, T, N>> {
+        DisplayJoined::new(self.iter(), separator)
+    }
+
+    /// Returns an iterator that allows modifying each value.
+    pub fn iter_mut(&mut self) -> IterMut<'_, T, N> {
+        let done = self.is_empty();
+        IterMut {
+            _phantom: PhantomData,
+            buffer: &mut self.buffer as *mut _ as *mut MaybeUninit<T>,
+            front: self.front,
+            back: self.back,
+            done,
+        }
+    }
+
+    /// Starts rearranging the storage so that all elements are in the first slice returned by
+    /// [`as_slices`](Deque::as_slices), a bit at a time.
+    ///
+    /// If the elements wrap around the end of the storage, it's rotated in place, which takes
+    /// `N + gcd(N, f)` moves, where `f` is the storage index of the front element, so up to
+    /// `N + N / 2`. That can take too long for e.g. a time slice of a cooperative scheduler. The
+    /// returned [`MakeContiguous`] does the moves in steps of a chosen size instead. The deque is
+    /// borrowed until it's dropped, which does the remaining moves.
+    ///
+    /// Leaking the `MakeContiguous` leaks the elements and leaves the deque empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(1).unwrap();
+    /// deque.push_back(2).unwrap();
+    /// deque.push_front(0).unwrap();
+    /// assert_eq!(deque.as_slices(), (&[0][..], &[1, 2][..]));
+    ///
+    /// let mut op = deque.make_contiguous_incremental();
+    /// while !op.step(2) {
+    ///     // yield to more urgent work
+    /// }
+    /// drop(op);
+    /// assert_eq!(deque.as_slices(), (&[0, 1, 2][..], &[][..]));
+    /// ```
+    pub fn make_contiguous_incremental(&mut self) -> MakeContiguous<'_, T, N> {
+        let len = self.len();
+        let front = self.front;
+        // rotate the storage so that the front element ends up at index 0
+        let shift = if front + len <= N { 0 } else { front };
+        let mut cycles = N;
+        let mut rest = shift;
+        while rest != 0 {
+            (cycles, rest) = (rest, cycles % rest);
+        }
+
+        // the elements are owned by the `MakeContiguous` until it's done
+        self.front = 0;
+        self.back = 0;
+        self.full = false;
+
+        MakeContiguous {
+            deque: self,
+            len,
+            front,
+            shift,
+            cycles: if shift == 0 { 0 } else { cycles },
+            cycle: 0,
+            hole: None,
+            held: MaybeUninit::uninit(),
+            done: false,
+        }
+    }
+
+    /// Converts the deque into a [`Vec`] with the same capacity, front element first.
+    ///
+    /// The elements are moved with at most two bulk copies, regardless of where they are in the
+    /// deque's storage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// deque.push_back(1).unwrap();
+    /// deque.push_back(2).unwrap();
+    /// deque.push_front(0).unwrap();
+    ///
+    /// assert_eq!(deque.into_vec(), [0, 1, 2]);
+    /// ```
+    pub fn into_vec(mut self) -> Vec<T, N> {
+        let mut vec = Vec::new();
+        let (a, b) = self.as_slices();
+        let len = a.len() + b.len();
+
+        unsafe {
+            let dst = vec.as_mut_ptr();
+            ptr::copy_nonoverlapping(a.as_ptr(), dst, a.len());
+            ptr::copy_nonoverlapping(b.as_ptr(), dst.add(a.len()), b.len());
+            vec.set_len(len);
+        }
+
+        // The elements are owned by `vec` now.
+        self.front = 0;
+        self.back = 0;
         self.full = false;
-        self.back = Self::decrement(self.back);
-        self.buffer.get_unchecked_mut(self.back).as_ptr().read()
+        vec
+    }
+}
+
+impl<const N: usize> Deque<u8, N> {
+    /// Returns a cursor for parsing the bytes in the deque without consuming them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Deque;
+    ///
+    /// // frames are a 2-byte little endian length followed by the payload
+    /// fn parse(rx: &mut Deque<u8, 16>) -> Option<u16> {
+    ///     let mut reader = rx.reader();
+    ///     let len = reader.peek_u16_le()?;
+    ///     reader.advance(2).ok()?;
+    ///     let (a, b) = reader.peek_bytes(usize::from(len))?;
+    ///     let sum = a.iter().chain(b).map(|&x| u16::from(x)).sum();
+    ///     reader.advance(usize::from(len)).ok()?;
+    ///     reader.commit();
+    ///     Some(sum)
+    /// }
+    ///
+    /// let mut rx: Deque<u8, 16> = Deque::new();
+    /// for byte in [3, 0, 1, 2] {
+    ///     rx.push_back(byte).unwrap();
+    /// }
+    /// // the frame is incomplete, so it stays in the deque
+    /// assert_eq!(parse(&mut rx), None);
+    /// assert_eq!(rx.len(), 4);
+    ///
+    /// rx.push_back(3).unwrap();
+    /// assert_eq!(parse(&mut rx), Some(6));
+    /// assert!(rx.is_empty());
+    /// ```
+    pub fn reader(&mut self) -> ByteReader<'_, N> {
+        ByteReader {
+            deque: self,
+            position: 0,
+        }
+    }
+}
+
+// Trait implementations
+
+impl<T, const N: usize> Default for Deque<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Deque<T, N> {
+    fn drop(&mut self) {
+        // safety: `self` is left in an inconsistent state but it doesn't matter since
+        // it's getting dropped. Nothing should be able to observe `self` after drop.
+        unsafe { self.drop_contents() }
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for Deque<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self).finish()
+    }
+}
+
+/// An iterator that moves out of a [`Deque`].
+///
+/// This struct is created by calling the `into_iter` method.
+#[derive(Clone)]
+pub struct IntoIter<T, const N: usize> {
+    deque: Deque<T, N>,
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.deque.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.deque.len();
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.deque.pop_back()
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> IntoIterator for Deque<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { deque: self }
+    }
+}
+
+/// An iterator that moves chunks of `K` items out of the front of a [`Deque`].
+///
+/// This struct is created by calling the `drain_chunks` method.
+pub struct DrainChunks<'a, T, const N: usize, const K: usize> {
+    deque: &'a mut Deque<T, N>,
+}
+
+impl<T, const N: usize, const K: usize> Iterator for DrainChunks<'_, T, N, K> {
+    type Item = [T; K];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let deque = &mut *self.deque;
+        if deque.len() < K {
+            return None;
+        }
+
+        let mut chunk = MaybeUninit::<[T; K]>::uninit();
+        let front = deque.front;
+        // The chunk wraps around the end of the buffer if `first < K`.
+        let first = K.min(N - front);
+
+        unsafe {
+            let src = deque.buffer.as_ptr() as *const T;
+            let dst = chunk.as_mut_ptr() as *mut T;
+            ptr::copy_nonoverlapping(src.add(front), dst, first);
+            ptr::copy_nonoverlapping(src, dst.add(first), K - first);
+        }
+
+        deque.front = (front + K) % N;
+        deque.full = false;
+
+        Some(unsafe { chunk.assume_init() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.deque.len() / K;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize, const K: usize> ExactSizeIterator for DrainChunks<'_, T, N, K> {}
+impl<T, const N: usize, const K: usize> FusedIterator for DrainChunks<'_, T, N, K> {}
+
+// Index arithmetic and unchecked pushes and pops on a ring of `n` slots, shared by `Deque`, whose
+// `n` is the const `N`, and `CapacityView`, which only has a slice.
+mod ring {
+    use core::mem::MaybeUninit;
+
+    // NOTE for a `Deque`, `n.is_power_of_two()` is evaluated at compile time, so only one of the
+    // branches is compiled in. Masking avoids a compare-and-branch in the hot paths of
+    // power-of-two deques.
+    #[inline]
+    pub(super) fn increment(i: usize, n: usize) -> usize {
+        if n.is_power_of_two() {
+            (i + 1) & (n - 1)
+        } else if i + 1 == n {
+            0
+        } else {
+            i + 1
+        }
+    }
+
+    #[inline]
+    pub(super) fn decrement(i: usize, n: usize) -> usize {
+        if n.is_power_of_two() {
+            i.wrapping_sub(1) & (n - 1)
+        } else if i == 0 {
+            n - 1
+        } else {
+            i - 1
+        }
+    }
+
+    #[inline]
+    pub(super) const fn len(front: usize, back: usize, full: bool, n: usize) -> usize {
+        if full {
+            n
+        } else if back < front {
+            back + n - front
+        } else {
+            back - front
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The ring must not be full.
+    #[inline]
+    pub(super) unsafe fn push_front<T>(
+        buffer: &mut [MaybeUninit<T>],
+        front: &mut usize,
+        back: usize,
+        full: &mut bool,
+        item: T,
+    ) {
+        let index = decrement(*front, buffer.len());
+        // NOTE: the memory slot that we are about to write to is uninitialized. We assign
+        // a `MaybeUninit` to avoid running `T`'s destructor on the uninitialized memory
+        *buffer.get_unchecked_mut(index) = MaybeUninit::new(item);
+        *front = index;
+        *full = *front == back;
+    }
+
+    /// # Safety
+    ///
+    /// The ring must not be full.
+    #[inline]
+    pub(super) unsafe fn push_back<T>(
+        buffer: &mut [MaybeUninit<T>],
+        front: usize,
+        back: &mut usize,
+        full: &mut bool,
+        item: T,
+    ) {
+        // NOTE: see `push_front`
+        *buffer.get_unchecked_mut(*back) = MaybeUninit::new(item);
+        *back = increment(*back, buffer.len());
+        *full = front == *back;
+    }
+
+    /// # Safety
+    ///
+    /// The ring must not be empty.
+    #[inline]
+    pub(super) unsafe fn pop_front<T>(
+        buffer: &[MaybeUninit<T>],
+        front: &mut usize,
+        full: &mut bool,
+    ) -> T {
+        let index = *front;
+        *full = false;
+        *front = increment(index, buffer.len());
+        buffer.get_unchecked(index).as_ptr().read()
+    }
+
+    /// # Safety
+    ///
+    /// The ring must not be empty.
+    #[inline]
+    pub(super) unsafe fn pop_back<T>(
+        buffer: &[MaybeUninit<T>],
+        back: &mut usize,
+        full: &mut bool,
+    ) -> T {
+        *full = false;
+        *back = decrement(*back, buffer.len());
+        buffer.get_unchecked(*back).as_ptr().read()
+    }
+}
+
+/// A borrowed [`Deque`] whose pushes fail beyond a quota of elements, which may be below the
+/// capacity of its storage.
+///
+/// This `struct` is created by the [`shrink_capacity_view`](Deque::shrink_capacity_view) method
+/// on [`Deque`]. See its documentation for more.
+pub struct CapacityView<'a, T> {
+    buffer: &'a mut [MaybeUninit<T>],
+    front: &'a mut usize,
+    back: &'a mut usize,
+    full: &'a mut bool,
+    capacity: usize,
+    /// The deque's injected failures, which also apply to the view.
+    #[cfg(feature = "test-util")]
+    fail_next: &'a mut crate::test_util::FailNext,
+    #[cfg(capacity_stats)]
+    monitor: crate::capacity_stats::Monitor,
+}
+
+impl<T> CapacityView<'_, T> {
+    /// Returns the quota, the most elements the deque can hold while it's lent out as this view.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of elements in the deque, including those pushed before the view was
+    /// created.
+    pub fn len(&self) -> usize {
+        ring::len(*self.front, *self.back, *self.full, self.buffer.len())
+    }
+
+    /// Returns whether the deque is empty.
+    pub fn is_empty(&self) -> bool {
+        *self.front == *self.back && !*self.full
+    }
+
+    /// Returns whether the deque holds as many elements as the quota allows.
+    pub fn is_full(&self) -> bool {
+        self.len() >= self.capacity
+    }
+
+    /// Provides a reference to the front element, or None if the deque is empty.
+    pub fn front(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(unsafe { &*self.buffer[*self.front].as_ptr() })
+        }
+    }
+
+    /// Provides a reference to the back element, or None if the deque is empty.
+    pub fn back(&self) -> Option<&T> {
+        if self.is_empty() {
+            None
+        } else {
+            let index = ring::decrement(*self.back, self.buffer.len());
+            Some(unsafe { &*self.buffer[index].as_ptr() })
+        }
     }
 
-    /// Appends an `item` to the front of the deque
+    /// Pushes an `item` to the front of the deque
     ///
-    /// # Safety
-    ///
-    /// This assumes the deque is not full.
-    pub unsafe fn push_front_unchecked(&mut self, item: T) {
-        debug_assert!(!self.is_full());
+    /// Returns back the `item` if the quota is used up
+    pub fn push_front(&mut self, item: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(item);
+        }
 
-        let index = Self::decrement(self.front);
-        // NOTE: the memory slot that we are about to write to is uninitialized. We assign
-        // a `MaybeUninit` to avoid running `T`'s destructor on the uninitialized memory
-        *self.buffer.get_unchecked_mut(index) = MaybeUninit::new(item);
-        self.front = index;
-        if self.front == self.back {
-            self.full = true;
+        if self.is_full() {
+            return Err(item);
         }
+        // NOTE(unsafe) the quota is at most the size of the storage
+        unsafe { ring::push_front(self.buffer, self.front, *self.back, self.full, item) };
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len());
+        Ok(())
     }
 
-    /// Appends an `item` to the back of the deque
-    ///
-    /// # Safety
+    /// Pushes an `item` to the back of the deque
     ///
-    /// This assumes the deque is not full.
-    pub unsafe fn push_back_unchecked(&mut self, item: T) {
-        debug_assert!(!self.is_full());
+    /// Returns back the `item` if the quota is used up
+    pub fn push_back(&mut self, item: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(item);
+        }
 
-        // NOTE: the memory slot that we are about to write to is uninitialized. We assign
-        // a `MaybeUninit` to avoid running `T`'s destructor on the uninitialized memory
-        *self.buffer.get_unchecked_mut(self.back) = MaybeUninit::new(item);
-        self.back = Self::increment(self.back);
-        if self.front == self.back {
-            self.full = true;
+        if self.is_full() {
+            return Err(item);
         }
+        // NOTE(unsafe) the quota is at most the size of the storage
+        unsafe { ring::push_back(self.buffer, *self.front, self.back, self.full, item) };
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len());
+        Ok(())
     }
 
-    /// Returns an iterator over the deque.
-    pub fn iter(&self) -> Iter<'_, T, N> {
-        let done = self.is_empty();
-        Iter {
-            _phantom: PhantomData,
-            buffer: &self.buffer as *const MaybeUninit<T>,
-            front: self.front,
-            back: self.back,
-            done,
+    /// Removes the item from the front of the deque and returns it, or `None` if it's empty
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
         }
+        Some(unsafe { ring::pop_front(self.buffer, self.front, self.full) })
     }
 
-    /// Returns an iterator that allows modifying each value.
-    pub fn iter_mut(&mut self) -> IterMut<'_, T, N> {
-        let done = self.is_empty();
-        IterMut {
-            _phantom: PhantomData,
-            buffer: &mut self.buffer as *mut _ as *mut MaybeUninit<T>,
-            front: self.front,
-            back: self.back,
-            done,
+    /// Removes the item from the back of the deque and returns it, or `None` if it's empty
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
         }
+        Some(unsafe { ring::pop_back(self.buffer, self.back, self.full) })
     }
-}
-
-// Trait implementations
 
-impl<T, const N: usize> Default for Deque<T, N> {
-    fn default() -> Self {
-        Self::new()
+    /// Clears the deque, removing all values.
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
     }
-}
 
-impl<T, const N: usize> Drop for Deque<T, N> {
-    fn drop(&mut self) {
-        // safety: `self` is left in an inconsistent state but it doesn't matter since
-        // it's getting dropped. Nothing should be able to observe `self` after drop.
-        unsafe { self.drop_contents() }
+    /// Returns an iterator over the deque, front to back.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        let len = self.buffer.len();
+        (0..self.len()).map(move |i| {
+            // NOTE(unsafe) the first `len` slots after `front` are initialized
+            unsafe { &*self.buffer[(*self.front + i) % len].as_ptr() }
+        })
     }
 }
 
-impl<T: fmt::Debug, const N: usize> fmt::Debug for Deque<T, N> {
+impl<T> fmt::Debug for CapacityView<'_, T>
+where
+    T: fmt::Debug,
+{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_list().entries(self).finish()
+        f.debug_list().entries(self.iter()).finish()
     }
 }
 
-/// An iterator that moves out of a [`Deque`].
+/// An in-progress rearrangement of a [`Deque`]'s storage into a single slice.
 ///
-/// This struct is created by calling the `into_iter` method.
-#[derive(Clone)]
-pub struct IntoIter<T, const N: usize> {
-    deque: Deque<T, N>,
+/// This `struct` is created by the
+/// [`make_contiguous_incremental`](Deque::make_contiguous_incremental) method on [`Deque`]. See
+/// its documentation for more.
+pub struct MakeContiguous<'a, T, const N: usize> {
+    deque: &'a mut Deque<T, N>,
+    len: usize,
+    front: usize,
+    /// The storage is rotated left by `shift` slots, one cycle of slots at a time.
+    shift: usize,
+    cycles: usize,
+    cycle: usize,
+    /// The slot whose value was moved out, if a cycle is in progress.
+    hole: Option<usize>,
+    /// The value of the first slot of the cycle in progress.
+    held: MaybeUninit<T>,
+    done: bool,
 }
 
-impl<T, const N: usize> Iterator for IntoIter<T, N> {
-    type Item = T;
-    fn next(&mut self) -> Option<Self::Item> {
-        self.deque.pop_front()
+impl<T, const N: usize> MakeContiguous<'_, T, N> {
+    /// Does at most `budget` element moves, and returns `true` if the deque is contiguous.
+    pub fn step(&mut self, mut budget: usize) -> bool {
+        if self.done {
+            return true;
+        }
+
+        let buffer = self.deque.buffer.as_mut_ptr();
+        while self.cycle < self.cycles {
+            if budget == 0 {
+                return false;
+            }
+            budget -= 1;
+
+            // NOTE(unsafe) the slots are only moved around, and each value has exactly one slot
+            // (or `held`) at all times
+            unsafe {
+                match self.hole {
+                    None => {
+                        self.held = ptr::read(buffer.add(self.cycle));
+                        self.hole = Some(self.cycle);
+                    }
+                    Some(hole) => {
+                        let next = (hole + self.shift) % N;
+                        if next == self.cycle {
+                            ptr::write(buffer.add(hole), ptr::read(&self.held));
+                            self.hole = None;
+                            self.cycle += 1;
+                        } else {
+                            ptr::copy_nonoverlapping(buffer.add(next), buffer.add(hole), 1);
+                            self.hole = Some(next);
+                        }
+                    }
+                }
+            }
+        }
+
+        let front = self.front - self.shift;
+        self.deque.front = front;
+        self.deque.back = (front + self.len) % N;
+        self.deque.full = self.len == N;
+        self.done = true;
+        true
+    }
+
+    /// Returns `true` if the deque is contiguous.
+    pub fn is_done(&self) -> bool {
+        self.done
     }
 }
 
-impl<T, const N: usize> IntoIterator for Deque<T, N> {
-    type Item = T;
-    type IntoIter = IntoIter<T, N>;
+impl<T, const N: usize> Drop for MakeContiguous<'_, T, N> {
+    fn drop(&mut self) {
+        self.step(usize::MAX);
+    }
+}
 
-    fn into_iter(self) -> Self::IntoIter {
-        IntoIter { deque: self }
+/// A cursor over the bytes of a [`Deque`], which only removes bytes when asked to.
+///
+/// The `peek` methods read at the cursor position without moving it, and
+/// [`advance`](ByteReader::advance) moves it. Nothing is removed from the deque until
+/// [`consume`](ByteReader::consume) or [`commit`](ByteReader::commit) are called, so a parser
+/// that finds an incomplete frame can simply drop the reader and retry once more bytes arrived.
+///
+/// This `struct` is created by the [`reader`](Deque::reader) method on [`Deque`]. See its
+/// documentation for more.
+pub struct ByteReader<'a, const N: usize> {
+    deque: &'a mut Deque<u8, N>,
+    position: usize,
+}
+
+impl<const N: usize> ByteReader<'_, N> {
+    /// Returns the position of the cursor, relative to the front of the deque.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of bytes after the cursor.
+    pub fn remaining(&self) -> usize {
+        self.deque.len() - self.position
+    }
+
+    /// Moves the cursor `n` bytes forward.
+    ///
+    /// Returns `Err` without moving the cursor if there are fewer than `n` bytes after it.
+    #[allow(clippy::result_unit_err)]
+    pub fn advance(&mut self, n: usize) -> Result<(), ()> {
+        if n > self.remaining() {
+            return Err(());
+        }
+        self.position += n;
+        Ok(())
+    }
+
+    /// Moves the cursor back to the front of the deque.
+    pub fn rewind(&mut self) {
+        self.position = 0;
+    }
+
+    /// Returns the `len` bytes after the cursor as a pair of slices, or `None` if there are fewer.
+    pub fn peek_bytes(&self, len: usize) -> Option<(&[u8], &[u8])> {
+        if len > self.remaining() {
+            return None;
+        }
+
+        let (a, b) = self.deque.as_slices();
+        let start = self.position;
+        let end = start + len;
+        Some(if end <= a.len() {
+            (&a[start..end], &[])
+        } else if start >= a.len() {
+            (&b[start - a.len()..end - a.len()], &[])
+        } else {
+            (&a[start..], &b[..end - a.len()])
+        })
+    }
+
+    /// Returns the `K` bytes after the cursor, or `None` if there are fewer.
+    pub fn peek_array<const K: usize>(&self) -> Option<[u8; K]> {
+        let (a, b) = self.peek_bytes(K)?;
+        let mut array = [0; K];
+        array[..a.len()].copy_from_slice(a);
+        array[a.len()..].copy_from_slice(b);
+        Some(array)
+    }
+
+    /// Returns the byte after the cursor, or `None` if there is none.
+    pub fn peek_u8(&self) -> Option<u8> {
+        self.peek_array().map(|[byte]| byte)
+    }
+
+    /// Returns the little endian `u16` after the cursor, or `None` if there are fewer than 2
+    /// bytes.
+    pub fn peek_u16_le(&self) -> Option<u16> {
+        self.peek_array().map(u16::from_le_bytes)
+    }
+
+    /// Returns the big endian `u16` after the cursor, or `None` if there are fewer than 2 bytes.
+    pub fn peek_u16_be(&self) -> Option<u16> {
+        self.peek_array().map(u16::from_be_bytes)
+    }
+
+    /// Returns the little endian `u32` after the cursor, or `None` if there are fewer than 4
+    /// bytes.
+    pub fn peek_u32_le(&self) -> Option<u32> {
+        self.peek_array().map(u32::from_le_bytes)
+    }
+
+    /// Returns the big endian `u32` after the cursor, or `None` if there are fewer than 4 bytes.
+    pub fn peek_u32_be(&self) -> Option<u32> {
+        self.peek_array().map(u32::from_be_bytes)
+    }
+
+    /// Removes `n` bytes from the front of the deque.
+    ///
+    /// The cursor keeps pointing at the same byte, or at the new front if that byte was removed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the deque holds fewer than `n` bytes.
+    pub fn consume(&mut self, n: usize) {
+        let len = self.deque.len();
+        assert!(n <= len, "n (is {}) should be <= len (is {})", n, len);
+
+        if n != 0 {
+            // bytes don't need to be dropped
+            self.deque.front = (self.deque.front + n) % N;
+            self.deque.full = false;
+        }
+        self.position = self.position.saturating_sub(n);
+    }
+
+    /// Removes the bytes before the cursor from the deque.
+    pub fn commit(&mut self) {
+        self.consume(self.position);
     }
 }
 
 /// An iterator over the elements of a [`Deque`].
 ///
 /// This struct is created by calling the `iter` method.
-#[derive(Clone)]
 pub struct Iter<'a, T, const N: usize> {
     buffer: *const MaybeUninit<T>,
     _phantom: PhantomData<&'a T>,
@@ -418,6 +1532,19 @@ pub struct Iter<'a, T, const N: usize> {
     done: bool,
 }
 
+// NOTE(manual impl) the derive would needlessly require `T: Clone`
+impl<'a, T, const N: usize> Clone for Iter<'a, T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            _phantom: PhantomData,
+            front: self.front,
+            back: self.back,
+            done: self.done,
+        }
+    }
+}
+
 impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
     type Item = &'a T;
     fn next(&mut self) -> Option<Self::Item> {
@@ -552,9 +1679,35 @@ where
     }
 }
 
+impl<T, const N: usize> From<Vec<T, N>> for Deque<T, N> {
+    /// Converts a [`Vec`] into a deque with the same capacity, first element at the front.
+    ///
+    /// The elements are moved with a single bulk copy.
+    fn from(mut vec: Vec<T, N>) -> Self {
+        let mut deque = Deque::new();
+        let len = vec.len();
+
+        unsafe {
+            ptr::copy_nonoverlapping(vec.as_ptr(), deque.buffer.as_mut_ptr() as *mut T, len);
+            // The elements are owned by `deque` now.
+            vec.set_len(0);
+        }
+
+        deque.full = len == N;
+        deque.back = if deque.full { 0 } else { len };
+        deque
+    }
+}
+
+impl<T, const N: usize> From<Deque<T, N>> for Vec<T, N> {
+    fn from(deque: Deque<T, N>) -> Self {
+        deque.into_vec()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::Deque;
+    use crate::{Deque, Vec};
 
     #[test]
     fn static_new() {
@@ -566,6 +1719,20 @@ mod tests {
         let mut _v: Deque<i32, 4> = Deque::new();
     }
 
+    #[test]
+    fn ring_index() {
+        assert_eq!(Deque::<u8, 4>::increment(3), 0);
+        assert_eq!(Deque::<u8, 4>::decrement(0), 3);
+        assert_eq!(Deque::<u8, 3>::increment(2), 0);
+        assert_eq!(Deque::<u8, 3>::decrement(0), 2);
+        assert_eq!(Deque::<u8, 1>::increment(0), 0);
+        assert_eq!(Deque::<u8, 1>::decrement(0), 0);
+        for i in 0..7 {
+            assert_eq!(Deque::<u8, 8>::increment(i), i + 1);
+            assert_eq!(Deque::<u8, 8>::decrement(i + 1), i);
+        }
+    }
+
     #[test]
     fn drop() {
         droppable!();
@@ -718,6 +1885,27 @@ mod tests {
         assert_eq!(items.next(), None);
     }
 
+    #[test]
+    fn iter_move_double_ended() {
+        let mut v: Deque<i32, 4> = Deque::new();
+        v.push_back(2).unwrap();
+        v.push_back(3).unwrap();
+        v.push_front(1).unwrap();
+        v.push_front(0).unwrap();
+
+        let mut items = v.clone().into_iter();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items.next_back(), Some(3));
+        assert_eq!(items.next(), Some(0));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items.next_back(), Some(2));
+        assert_eq!(items.next_back(), Some(1));
+        assert_eq!(items.next_back(), None);
+        assert_eq!(items.next(), None);
+
+        assert!(v.into_iter().rev().eq([3, 2, 1, 0]));
+    }
+
     #[test]
     fn iter_move_drop() {
         droppable!();
@@ -810,6 +1998,199 @@ mod tests {
         assert_eq!(q.as_slices(), (&[1, 2, 3][..], &[4][..]));
     }
 
+    #[test]
+    fn split_at_mut() {
+        let mut q: Deque<i32, 4> = Deque::new();
+        assert_eq!(
+            q.split_at_mut(0),
+            ((&mut [][..], &mut [][..]), (&mut [][..], &mut [][..]))
+        );
+
+        for i in 0..4 {
+            q.push_back(i).unwrap();
+        }
+        q.pop_front().unwrap();
+        q.pop_front().unwrap();
+        q.push_back(4).unwrap();
+        q.push_back(5).unwrap();
+
+        for mid in 0..=4 {
+            let ((a, b), (c, d)) = q.split_at_mut(mid);
+            assert_eq!(a.len() + b.len(), mid);
+            assert_eq!(c.len() + d.len(), 4 - mid);
+            for x in a.iter_mut().chain(b).chain(c).chain(d) {
+                *x += 1;
+            }
+        }
+        assert!(q.iter().eq(&[7, 8, 9, 10]));
+    }
+
+    #[test]
+    fn fill_from_iter() {
+        let mut q: Deque<u8, 4> = Deque::new();
+        assert_eq!(q.fill_from_iter_back([]).ok(), Some(0));
+        assert_eq!(q.fill_from_iter_back([1, 2]).ok(), Some(2));
+        assert_eq!(q.fill_from_iter_front([0]).ok(), Some(1));
+        assert_eq!(q.fill_from_iter_back([3, 4, 5]).ok(), Some(1));
+        assert_eq!(q.fill_from_iter_front([9]).ok(), Some(0));
+        assert!(q.iter().eq(&[0, 1, 2, 3]));
+
+        q.pop_front();
+        q.pop_back();
+        assert_eq!(q.fill_from_iter_front([7, 8, 9]).ok(), Some(2));
+        assert!(q.iter().eq(&[8, 7, 1, 2]));
+    }
+
+    #[test]
+    fn copy_within() {
+        for rotation in 0..5 {
+            for start in 0..=4 {
+                for end in start..=4 {
+                    for dest in 0..=4 - (end - start) {
+                        let mut q: Deque<u8, 5> = Deque::new();
+                        for _ in 0..rotation {
+                            q.push_back(0).unwrap();
+                            q.pop_front().unwrap();
+                        }
+                        for i in 0..4 {
+                            q.push_back(i).unwrap();
+                        }
+
+                        let mut expected = [0, 1, 2, 3];
+                        expected.copy_within(start..end, dest);
+                        q.copy_within(start..end, dest);
+                        assert!(q.iter().eq(&expected));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swap_with_slice() {
+        let mut q: Deque<u8, 4> = Deque::new();
+        q.push_back(2).unwrap();
+        q.push_front(1).unwrap();
+        q.push_front(0).unwrap();
+        let mut other = [7, 8, 9];
+        q.swap_with_slice(&mut other);
+        assert!(q.iter().eq(&[7, 8, 9]));
+        assert_eq!(other, [0, 1, 2]);
+    }
+
+    #[test]
+    fn make_contiguous_incremental() {
+        for len in 0..=6 {
+            for start in 0..6 {
+                let mut q: Deque<usize, 6> = Deque::new();
+                for _ in 0..start {
+                    q.push_back(0).unwrap();
+                    q.pop_front().unwrap();
+                }
+                for i in 0..len {
+                    q.push_back(i).unwrap();
+                }
+
+                // only wrapped storage is rotated, with `N + gcd(N, front)` moves
+                let moves = if start + len > 6 {
+                    let (mut a, mut b) = (6, start);
+                    while b != 0 {
+                        (a, b) = (b, a % b);
+                    }
+                    6 + a
+                } else {
+                    0
+                };
+
+                let mut op = q.make_contiguous_incremental();
+                let mut steps = 1;
+                while !op.step(1) {
+                    steps += 1;
+                }
+                assert!(op.is_done());
+                // the step that does the last move also returns `true`
+                assert_eq!(steps, moves.max(1));
+                core::mem::drop(op);
+
+                assert_eq!(q.len(), len);
+                assert!(q.as_slices().0.iter().copied().eq(0..len));
+                q.push_back(len).ok();
+                q.push_front(len).ok();
+                assert!(q.len() <= 6);
+            }
+        }
+    }
+
+    #[test]
+    fn make_contiguous_incremental_drop() {
+        droppable!();
+
+        let mut q: Deque<Droppable, 4> = Deque::new();
+        for _ in 0..3 {
+            q.push_front(Droppable::new()).ok().unwrap();
+        }
+        let mut op = q.make_contiguous_incremental();
+        op.step(2);
+        core::mem::drop(op);
+        assert_eq!(q.as_slices().1.len(), 0);
+        assert_eq!(Droppable::count(), 3);
+
+        for _ in 0..2 {
+            q.pop_back().unwrap();
+            q.push_front(Droppable::new()).ok().unwrap();
+        }
+        assert_eq!(q.as_slices().1.len(), 2);
+        let mut op = q.make_contiguous_incremental();
+        op.step(1);
+        core::mem::forget(op);
+        assert!(q.is_empty());
+        core::mem::drop(q);
+        assert_eq!(Droppable::count(), 3);
+    }
+
+    #[test]
+    fn reader() {
+        let mut q: Deque<u8, 4> = Deque::new();
+        q.push_back(0).unwrap();
+        q.pop_front().unwrap();
+        for byte in [1, 2, 3, 4] {
+            q.push_back(byte).unwrap();
+        }
+        assert_eq!(q.as_slices(), (&[1, 2, 3][..], &[4][..]));
+
+        let mut reader = q.reader();
+        assert_eq!(reader.peek_u32_le(), Some(0x0403_0201));
+        assert_eq!(reader.peek_u32_be(), Some(0x0102_0304));
+        assert_eq!(reader.peek_array::<5>(), None);
+        reader.advance(2).unwrap();
+        assert_eq!(reader.peek_u16_le(), Some(0x0403));
+        assert_eq!(reader.peek_u16_be(), Some(0x0304));
+        assert_eq!(reader.peek_bytes(2), Some((&[3][..], &[4][..])));
+        assert_eq!(reader.advance(3), Err(()));
+        reader.advance(1).unwrap();
+        assert_eq!(reader.peek_bytes(1), Some((&[4][..], &[][..])));
+        assert_eq!(reader.peek_bytes(0), Some((&[][..], &[][..])));
+
+        reader.consume(1);
+        assert_eq!(reader.position(), 2);
+        assert_eq!(reader.peek_u8(), Some(4));
+        reader.commit();
+        assert_eq!(reader.position(), 0);
+        assert_eq!(reader.remaining(), 1);
+        reader.rewind();
+        assert_eq!(q.len(), 1);
+        q.push_back(5).unwrap();
+        assert_eq!(q.reader().peek_u16_le(), Some(0x0504));
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_at_mut_out_of_bounds() {
+        let mut q: Deque<i32, 4> = Deque::new();
+        q.push_back(0).unwrap();
+        q.split_at_mut(2);
+    }
+
     #[test]
     fn clear() {
         let mut q: Deque<i32, 4> = Deque::new();
@@ -827,4 +2208,143 @@ mod tests {
         q.push_back(0).unwrap();
         assert_eq!(q.len(), 1);
     }
+
+    #[test]
+    fn drain_chunks() {
+        let mut q: Deque<u8, 5> = Deque::new();
+
+        // Move the front to the end of the buffer so that chunks wrap around
+        for i in 0..3 {
+            q.push_back(i).unwrap();
+            q.pop_front().unwrap();
+        }
+        for i in 0..5 {
+            q.push_back(i).unwrap();
+        }
+        assert!(q.is_full());
+
+        let mut chunks = q.drain_chunks::<2>();
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks.next(), Some([0, 1]));
+        assert_eq!(chunks.next(), Some([2, 3]));
+        assert_eq!(chunks.next(), None);
+
+        assert_eq!(q.len(), 1);
+        q.push_back(5).unwrap();
+        assert!(q.iter().eq([4, 5].iter()));
+
+        assert_eq!(q.drain_chunks::<2>().next(), Some([4, 5]));
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn push_back_overwrite() {
+        droppable!();
+
+        let mut q: Deque<Droppable, 3> = Deque::new();
+        for _ in 0..3 {
+            assert!(q.push_back_overwrite(Droppable::new()).is_none());
+        }
+        assert_eq!(Droppable::count(), 3);
+        let oldest = q.push_back_overwrite(Droppable::new());
+        assert!(oldest.is_some());
+        assert_eq!(Droppable::count(), 4);
+        core::mem::drop(oldest);
+        assert_eq!(q.len(), 3);
+        assert_eq!(Droppable::count(), 3);
+        core::mem::drop(q);
+        assert_eq!(Droppable::count(), 0);
+
+        let mut q: Deque<u8, 3> = Deque::new();
+        for i in 0..7 {
+            q.push_back_overwrite(i);
+        }
+        assert!(q.iter().eq(&[4, 5, 6]));
+        assert_eq!(q.pop_back(), Some(6));
+        assert_eq!(q.push_back_overwrite(7), None);
+        assert_eq!(q.push_back_overwrite(8), Some(4));
+        assert!(q.iter().eq(&[5, 7, 8]));
+    }
+
+    #[test]
+    fn drain_up_to() {
+        let mut q: Deque<u8, 4> = Deque::new();
+        for i in 0..4 {
+            q.push_back(i).unwrap();
+        }
+
+        let mut drained = Vec::<u8, 4>::new();
+        assert_eq!(q.drain_up_to(0, |x| drained.push(x).unwrap()), 0);
+        assert_eq!(q.drain_up_to(3, |x| drained.push(x).unwrap()), 3);
+        assert_eq!(q.drain_up_to(3, |x| drained.push(x).unwrap()), 1);
+        assert_eq!(drained, [0, 1, 2, 3]);
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn vec_conversions() {
+        droppable!();
+
+        let mut v: Vec<Droppable, 3> = Vec::new();
+        v.push(Droppable::new()).ok().unwrap();
+        v.push(Droppable::new()).ok().unwrap();
+        let mut d = Deque::from(v);
+        assert_eq!(d.len(), 2);
+        d.push_back(Droppable::new()).ok().unwrap();
+        assert!(d.is_full());
+        assert_eq!(Droppable::count(), 3);
+
+        // wrapped around the end of the storage
+        d.pop_front().unwrap();
+        d.push_back(Droppable::new()).ok().unwrap();
+        d.pop_front().unwrap();
+        let v = Vec::from(d);
+        assert_eq!(v.len(), 2);
+        assert_eq!(Droppable::count(), 2);
+        core::mem::drop(v);
+        assert_eq!(Droppable::count(), 0);
+
+        let mut d: Deque<u8, 4> = Deque::from(Vec::from_slice(&[1, 2, 3, 4]).unwrap());
+        assert_eq!(d.pop_back(), Some(4));
+        d.push_front(0).unwrap();
+        assert_eq!(d.into_vec(), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn capacity_view() {
+        let mut d: Deque<u8, 4> = Deque::new();
+        d.push_back(0).unwrap();
+        d.push_back(1).unwrap();
+        d.pop_front().unwrap();
+
+        // the quota wraps around the end of the storage
+        let mut view = d.shrink_capacity_view(3);
+        assert_eq!((view.len(), view.capacity()), (1, 3));
+        view.push_back(2).unwrap();
+        view.push_front(0).unwrap();
+        assert!(view.is_full());
+        assert_eq!(view.push_back(3), Err(3));
+        assert_eq!(view.push_front(3), Err(3));
+        assert_eq!((view.front(), view.back()), (Some(&0), Some(&2)));
+        assert_eq!(format!("{view:?}"), "[0, 1, 2]");
+        assert_eq!(view.pop_back(), Some(2));
+        view.push_back(3).unwrap();
+        assert!(d.iter().eq(&[0, 1, 3]));
+
+        // clamped to the length and to `N`
+        assert_eq!(d.shrink_capacity_view(1).capacity(), 3);
+        let mut view = d.shrink_capacity_view(10);
+        assert_eq!(view.capacity(), 4);
+        view.push_back(4).unwrap();
+        assert_eq!(view.push_back(5), Err(5));
+        assert!(d.is_full());
+        assert!(d.iter().eq(&[0, 1, 3, 4]));
+
+        let mut view = d.shrink_capacity_view(0);
+        assert_eq!(view.pop_front(), Some(0));
+        view.clear();
+        assert!(view.is_empty());
+        assert_eq!(view.pop_back(), None);
+        assert!(d.is_empty());
+    }
 }
@@ -0,0 +1,126 @@
+//! Formatting of container contents without an intermediate [`String`](crate::String).
+//!
+//! [`DisplayJoined`] implements [`Display`](fmt::Display) by writing the items of an iterator
+//! straight to the formatter, separated by a string. It comes from the `display_joined` methods of
+//! [`Vec`](crate::Vec::display_joined), [`Deque`](crate::Deque::display_joined) and
+//! [`IndexSet`](crate::IndexSet::display_joined), or from [`DisplayJoined::new`] for any other
+//! iterator. With the `ufmt` feature, it implements `ufmt::uDisplay` too.
+//!
+//! # Examples
+//!
+//! ```
+//! use core::fmt::Write;
+//! use heapless::{String, Vec};
+//!
+//! let readings: Vec<u16, 4> = Vec::from_slice(&[7, 42, 130]).unwrap();
+//!
+//! let mut line: String<32> = String::new();
+//! write!(line, "[{}]", readings.display_joined(", ")).unwrap();
+//! assert_eq!(line, "[7, 42, 130]");
+//!
+//! // width, fill and alignment apply to each item
+//! line.clear();
+//! write!(line, "{:>4}", readings.display_joined("|")).unwrap();
+//! assert_eq!(line, "   7|  42| 130");
+//! ```
+
+use core::fmt;
+
+/// Displays the items of an iterator separated by a string, see the
+/// [module-level documentation](self).
+///
+/// The formatting options, e.g. the width and alignment, are passed on to each item, not applied
+/// to the output as a whole.
+#[derive(Clone)]
+pub struct DisplayJoined<'a, I> {
+    pub(crate) items: I,
+    pub(crate) separator: &'a str,
+}
+
+impl<'a, I> DisplayJoined<'a, I>
+where
+    I: Iterator + Clone,
+{
+    /// Creates an adapter that displays the items of `items` separated by `separator`.
+    ///
+    /// `items` is cloned every time the adapter is formatted, so it's usually an iterator over
+    /// references.
+    pub fn new<T>(items: T, separator: &'a str) -> Self
+    where
+        T: IntoIterator<IntoIter = I>,
+    {
+        Self {
+            items: items.into_iter(),
+            separator,
+        }
+    }
+}
+
+impl<I> fmt::Display for DisplayJoined<'_, I>
+where
+    I: Iterator + Clone,
+    I::Item: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, item) in self.items.clone().enumerate() {
+            if i != 0 {
+                f.write_str(self.separator)?;
+            }
+            item.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl<I> fmt::Debug for DisplayJoined<'_, I>
+where
+    I: Iterator + Clone,
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.items.clone()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::DisplayJoined;
+    use crate::{Deque, FnvIndexSet, String, Vec};
+
+    #[test]
+    fn display_joined() {
+        let mut s: String<32> = String::new();
+
+        let v: Vec<u8, 4> = Vec::from_slice(&[1, 2, 3]).unwrap();
+        write!(s, "{}", v.display_joined(", ")).unwrap();
+        assert_eq!(s, "1, 2, 3");
+
+        s.clear();
+        let mut d: Deque<&str, 4> = Deque::new();
+        d.push_back("b").unwrap();
+        d.push_front("a").unwrap();
+        write!(s, "{:-<3}", d.display_joined("/")).unwrap();
+        assert_eq!(s, "a--/b--");
+
+        s.clear();
+        let mut set: FnvIndexSet<u8, 4> = FnvIndexSet::new();
+        set.insert(5).unwrap();
+        write!(s, "{}", set.display_joined(",")).unwrap();
+        assert_eq!(s, "5");
+
+        s.clear();
+        let empty: Vec<u8, 4> = Vec::new();
+        write!(s, "{}", empty.display_joined(",")).unwrap();
+        assert_eq!(s, "");
+
+        s.clear();
+        write!(s, "{}", DisplayJoined::new(0..3, "")).unwrap();
+        assert_eq!(s, "012");
+
+        // doesn't fit
+        let mut s: String<4> = String::new();
+        assert!(write!(s, "{}", v.display_joined(", ")).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+//! A ping-pong buffer shared between one writer and one reader.
+//!
+//! [`DoubleBuffer`] holds two values: the front one, which the reader sees, and the back one,
+//! which the writer fills. [`swap`](Writer::swap) atomically publishes the back value as the new
+//! front one, and hands the old front value to the writer to fill next. This is the usual DMA
+//! double-buffering pattern: e.g. an interrupt handler fills one block of samples while the main
+//! loop processes the previous one, without copying the blocks through a queue.
+//!
+//! Neither side ever waits for the other: a swap fails instead of blocking while the reader is
+//! reading the front value.
+//!
+//! NOTE: This module requires atomic CAS operations. On targets without them, they are emulated by
+//! the [`portable-atomic`](https://crates.io/crates/portable-atomic) crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::double_buffer::DoubleBuffer;
+//!
+//! let mut buffer: DoubleBuffer<[u16; 4]> = DoubleBuffer::new([0; 4], [0; 4]);
+//! let (mut writer, mut reader) = buffer.split();
+//!
+//! // interrupt handler: fill the back block and publish it
+//! writer.fill(|block| block.copy_from_slice(&[1, 2, 3, 4]));
+//! writer.swap().unwrap();
+//!
+//! // main loop: process each block once
+//! let block = reader.read_fresh().unwrap();
+//! assert_eq!(block.iter().sum::<u16>(), 10);
+//!
+//! // the writer can't take the block back while it's being read
+//! assert!(writer.swap().is_err());
+//! drop(block);
+//! assert!(reader.read_fresh().is_none());
+//! ```
+
+use core::{cell::UnsafeCell, fmt, ops::Deref};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicUsize, Ordering};
+
+/// The index of the front slot.
+const FRONT: usize = 1;
+/// The reader holds the front slot.
+const READING: usize = 1 << 1;
+/// The front slot was published after the reader last acquired it.
+const FRESH: usize = 1 << 2;
+
+/// A two-slot buffer for handing values from a writer to a reader, see the
+/// [module-level documentation](self).
+pub struct DoubleBuffer<T> {
+    slots: [UnsafeCell<T>; 2],
+    state: AtomicUsize,
+}
+
+impl<T> DoubleBuffer<T> {
+    /// Creates a buffer whose front value is `front` and whose back value is `back`.
+    pub const fn new(front: T, back: T) -> Self {
+        Self {
+            slots: [UnsafeCell::new(front), UnsafeCell::new(back)],
+            state: AtomicUsize::new(0),
+        }
+    }
+
+    /// Splits the buffer into its writer and reader endpoints.
+    pub fn split(&mut self) -> (Writer<'_, T>, Reader<'_, T>) {
+        (Writer { buffer: self }, Reader { buffer: self })
+    }
+
+    /// Returns a mutable reference to the front value.
+    pub fn front_mut(&mut self) -> &mut T {
+        let front = *self.state.get_mut() & FRONT;
+        self.slots[front].get_mut()
+    }
+
+    /// Returns a mutable reference to the back value.
+    pub fn back_mut(&mut self) -> &mut T {
+        let back = 1 - (*self.state.get_mut() & FRONT);
+        self.slots[back].get_mut()
+    }
+}
+
+impl<T> fmt::Debug for DoubleBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.load(Ordering::Relaxed);
+        f.debug_struct("DoubleBuffer")
+            .field("front", &(state & FRONT))
+            .field("reading", &(state & READING != 0))
+            .field("fresh", &(state & FRESH != 0))
+            .finish()
+    }
+}
+
+unsafe impl<T> Sync for DoubleBuffer<T> where T: Send {}
+
+/// The writer endpoint of a [`DoubleBuffer`], which owns the back value.
+pub struct Writer<'a, T> {
+    buffer: &'a DoubleBuffer<T>,
+}
+
+unsafe impl<T> Send for Writer<'_, T> where T: Send {}
+
+impl<T> Writer<'_, T> {
+    /// Returns a mutable reference to the back value.
+    pub fn back(&mut self) -> &mut T {
+        // NOTE(unsafe) only the writer changes the front index, and the reader only accesses the
+        // front slot
+        let back = 1 - (self.buffer.state.load(Ordering::Relaxed) & FRONT);
+        unsafe { &mut *self.buffer.slots[back].get() }
+    }
+
+    /// Fills the back value with `f`.
+    pub fn fill<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        f(self.back())
+    }
+
+    /// Publishes the back value as the new front value.
+    ///
+    /// The old front value becomes the back value. Returns `Err` if the reader is reading the
+    /// front value.
+    #[allow(clippy::result_unit_err)]
+    pub fn swap(&mut self) -> Result<(), ()> {
+        let state = &self.buffer.state;
+        let mut current = state.load(Ordering::Relaxed);
+        loop {
+            if current & READING != 0 {
+                return Err(());
+            }
+
+            let new = ((current & FRONT) ^ FRONT) | FRESH;
+            match state.compare_exchange_weak(current, new, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => return Ok(()),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Returns `true` if the reader hasn't acquired the front value since it was published.
+    pub fn is_fresh(&self) -> bool {
+        self.buffer.state.load(Ordering::Relaxed) & FRESH != 0
+    }
+}
+
+/// The reader endpoint of a [`DoubleBuffer`], which reads the front value.
+pub struct Reader<'a, T> {
+    buffer: &'a DoubleBuffer<T>,
+}
+
+unsafe impl<T> Send for Reader<'_, T> where T: Send {}
+
+impl<T> Reader<'_, T> {
+    /// Returns the front value.
+    ///
+    /// The writer can't swap while the returned guard is alive.
+    pub fn read(&mut self) -> ReadGuard<'_, T> {
+        let state = self.buffer.state.fetch_or(READING, Ordering::Acquire);
+        self.buffer.state.fetch_and(!FRESH, Ordering::Relaxed);
+        ReadGuard {
+            buffer: self.buffer,
+            slot: state & FRONT,
+        }
+    }
+
+    /// Returns the front value if it was published after it was last read, or `None` otherwise.
+    ///
+    /// The writer can't swap while the returned guard is alive.
+    pub fn read_fresh(&mut self) -> Option<ReadGuard<'_, T>> {
+        let state = self.buffer.state.fetch_or(READING, Ordering::Acquire);
+        if state & FRESH == 0 {
+            self.buffer.state.fetch_and(!READING, Ordering::Release);
+            return None;
+        }
+
+        self.buffer.state.fetch_and(!FRESH, Ordering::Relaxed);
+        Some(ReadGuard {
+            buffer: self.buffer,
+            slot: state & FRONT,
+        })
+    }
+
+    /// Returns `true` if the front value was published after it was last read.
+    pub fn is_fresh(&self) -> bool {
+        self.buffer.state.load(Ordering::Relaxed) & FRESH != 0
+    }
+}
+
+/// The front value of a [`DoubleBuffer`], which the writer can't swap while this guard is alive.
+///
+/// Comes from [`Reader::read`] and [`Reader::read_fresh`].
+pub struct ReadGuard<'a, T> {
+    buffer: &'a DoubleBuffer<T>,
+    slot: usize,
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // NOTE(unsafe) the writer doesn't access the front slot, and can't swap while the reading
+        // flag is set
+        unsafe { &*self.buffer.slots[self.slot].get() }
+    }
+}
+
+impl<T> fmt::Debug for ReadGuard<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.buffer.state.fetch_and(!READING, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DoubleBuffer;
+
+    #[test]
+    fn ping_pong() {
+        let mut buffer: DoubleBuffer<u8> = DoubleBuffer::new(0, 0);
+        {
+            let (mut writer, mut reader) = buffer.split();
+            assert!(reader.read_fresh().is_none());
+            assert_eq!(*reader.read(), 0);
+
+            for i in 1..=3 {
+                // unread values are replaced
+                assert_eq!(writer.is_fresh(), i > 1);
+                writer.fill(|x| *x = i);
+                writer.swap().unwrap();
+                assert!(reader.is_fresh());
+            }
+            assert_eq!(*writer.back(), 2);
+
+            let front = reader.read();
+            assert!(!writer.is_fresh());
+            *writer.back() = 4;
+            assert_eq!(writer.swap(), Err(()));
+            assert_eq!(*front, 3);
+            drop(front);
+
+            writer.swap().unwrap();
+            assert_eq!(reader.read_fresh().as_deref(), Some(&4));
+            assert_eq!(reader.read_fresh().as_deref(), None);
+            writer.swap().unwrap();
+        }
+        assert_eq!(*buffer.front_mut(), 3);
+        assert_eq!(*buffer.back_mut(), 4);
+    }
+
+    #[test]
+    fn threaded() {
+        let mut buffer: DoubleBuffer<[u32; 16]> = DoubleBuffer::new([0; 16], [0; 16]);
+        let (mut writer, mut reader) = buffer.split();
+
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                let mut i = 1;
+                while i <= 1000 {
+                    writer.fill(|block| block.fill(i));
+                    if writer.swap().is_ok() {
+                        i += 1;
+                    }
+                }
+            });
+
+            let mut last = 0;
+            while last < 1000 {
+                if let Some(block) = reader.read_fresh() {
+                    // never torn, never older than the previous one
+                    assert!(block.iter().all(|x| *x == block[0]));
+                    assert!(block[0] > last);
+                    last = block[0];
+                }
+            }
+        });
+    }
+}
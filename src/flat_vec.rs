@@ -0,0 +1,335 @@
+use core::{fmt, iter::FusedIterator, ops};
+
+use crate::Vec;
+
+/// A list of variable-length segments stored back to back in one pool of `NTOTAL` elements.
+///
+/// Holding e.g. up to 8 packets of up to 256 bytes as `Vec<Vec<u8, 256>, 8>` reserves 2 KiB, even
+/// when the packets are mostly small. A `FlatVec<u8, 512, 8>` stores the same packets in a shared
+/// pool instead, and only limits their total size. Each segment costs one `usize` end offset.
+///
+/// Segments can only be added at the end, but any of them can be removed.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::FlatVec;
+///
+/// let mut packets: FlatVec<u8, 16, 4> = FlatVec::new();
+/// packets.push(b"ping").unwrap();
+/// packets.push(&[0xff; 10]).unwrap();
+/// assert_eq!(packets.push(b"pong"), Err(()));
+///
+/// assert_eq!(packets.len(), 2);
+/// assert_eq!(&packets[0], b"ping");
+/// assert_eq!(packets.remove(0), 4);
+/// packets.push(b"pong").unwrap();
+/// assert!(packets.iter().eq([&[0xff; 10][..], b"pong"]));
+/// ```
+pub struct FlatVec<T, const NTOTAL: usize, const NSEGS: usize> {
+    data: Vec<T, NTOTAL>,
+    /// The offset in `data` after the end of each segment.
+    ends: Vec<usize, NSEGS>,
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> FlatVec<T, NTOTAL, NSEGS> {
+    /// Constructs a new, empty `FlatVec`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FlatVec;
+    ///
+    /// static mut PACKETS: FlatVec<u8, 1024, 16> = FlatVec::new();
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            ends: Vec::new(),
+        }
+    }
+
+    /// Returns the number of segments.
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    /// Returns `true` if there are no segments.
+    pub fn is_empty(&self) -> bool {
+        self.ends.is_empty()
+    }
+
+    /// Returns the total number of elements in all segments.
+    pub fn total_len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the number of elements that can still be added to the pool.
+    pub fn remaining(&self) -> usize {
+        NTOTAL - self.data.len()
+    }
+
+    /// Returns the segment at `index`, or `None` if it's out of bounds.
+    pub fn get(&self, index: usize) -> Option<&[T]> {
+        let range = self.range(index)?;
+        Some(&self.data[range])
+    }
+
+    /// Returns the segment at `index` mutably, or `None` if it's out of bounds.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut [T]> {
+        let range = self.range(index)?;
+        Some(&mut self.data[range])
+    }
+
+    /// Returns the last segment, or `None` if there are none.
+    pub fn last(&self) -> Option<&[T]> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Returns an iterator over the segments.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            data: &self.data,
+            ends: &self.ends,
+            start: 0,
+        }
+    }
+
+    /// Appends `segment`.
+    ///
+    /// Returns `Err` without modifying anything if there are already `NSEGS` segments or the pool
+    /// doesn't have room for `segment`.
+    #[allow(clippy::result_unit_err)]
+    pub fn push(&mut self, segment: &[T]) -> Result<(), ()>
+    where
+        T: Clone,
+    {
+        if self.ends.is_full() {
+            return Err(());
+        }
+        self.data.extend_from_slice(segment)?;
+        // NOTE(unsafe) checked above
+        unsafe { self.ends.push_unchecked(self.data.len()) };
+        Ok(())
+    }
+
+    /// Appends `elements` to the last segment.
+    ///
+    /// This builds a segment piece by piece, e.g. from the chunks of a received packet. Returns
+    /// `Err` without modifying anything if there are no segments or the pool doesn't have room
+    /// for `elements`.
+    #[allow(clippy::result_unit_err)]
+    pub fn extend_last(&mut self, elements: &[T]) -> Result<(), ()>
+    where
+        T: Clone,
+    {
+        let end = self.ends.last_mut().ok_or(())?;
+        self.data.extend_from_slice(elements)?;
+        *end = self.data.len();
+        Ok(())
+    }
+
+    /// Removes the segment at `index`, shifting all segments after it to the left, and returns
+    /// its length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> usize {
+        let range = match self.range(index) {
+            Some(range) => range,
+            None => panic!(
+                "removal index (is {}) should be < len (is {})",
+                index,
+                self.len()
+            ),
+        };
+        let removed = range.len();
+
+        self.data[range.start..].rotate_left(removed);
+        self.data.truncate(self.data.len() - removed);
+        self.ends.remove(index);
+        for end in &mut self.ends[index..] {
+            *end -= removed;
+        }
+        removed
+    }
+
+    /// Shortens the list to its first `len` segments.
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            let end = if len == 0 { 0 } else { self.ends[len - 1] };
+            self.data.truncate(end);
+            self.ends.truncate(len);
+        }
+    }
+
+    /// Removes all segments.
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.ends.clear();
+    }
+
+    fn range(&self, index: usize) -> Option<ops::Range<usize>> {
+        let end = *self.ends.get(index)?;
+        let start = if index == 0 { 0 } else { self.ends[index - 1] };
+        Some(start..end)
+    }
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> ops::Index<usize> for FlatVec<T, NTOTAL, NSEGS> {
+    type Output = [T];
+
+    fn index(&self, index: usize) -> &[T] {
+        self.get(index).expect("segment index out of bounds")
+    }
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> ops::IndexMut<usize>
+    for FlatVec<T, NTOTAL, NSEGS>
+{
+    fn index_mut(&mut self, index: usize) -> &mut [T] {
+        self.get_mut(index).expect("segment index out of bounds")
+    }
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> Default for FlatVec<T, NTOTAL, NSEGS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> Clone for FlatVec<T, NTOTAL, NSEGS>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            data: self.data.clone(),
+            ends: self.ends.clone(),
+        }
+    }
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> fmt::Debug for FlatVec<T, NTOTAL, NSEGS>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> PartialEq for FlatVec<T, NTOTAL, NSEGS>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ends == other.ends && self.data == other.data
+    }
+}
+
+impl<T, const NTOTAL: usize, const NSEGS: usize> Eq for FlatVec<T, NTOTAL, NSEGS> where T: Eq {}
+
+impl<'a, T, const NTOTAL: usize, const NSEGS: usize> IntoIterator
+    for &'a FlatVec<T, NTOTAL, NSEGS>
+{
+    type Item = &'a [T];
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the segments of a [`FlatVec`].
+///
+/// This `struct` is created by the [`iter`](FlatVec::iter) method on [`FlatVec`].
+#[derive(Clone)]
+pub struct Iter<'a, T> {
+    data: &'a [T],
+    ends: &'a [usize],
+    start: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<&'a [T]> {
+        let (&end, ends) = self.ends.split_first()?;
+        let segment = &self.data[self.start..end];
+        self.ends = ends;
+        self.start = end;
+        Some(segment)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.ends.len(), Some(self.ends.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (&end, ends) = self.ends.split_last()?;
+        let start = ends.last().copied().unwrap_or(self.start);
+        self.ends = ends;
+        Some(&self.data[start..end])
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::FlatVec;
+
+    #[test]
+    fn segments() {
+        let mut v: FlatVec<u8, 8, 4> = FlatVec::new();
+        assert_eq!(v.last(), None);
+        v.push(&[1, 2]).unwrap();
+        v.push(&[]).unwrap();
+        v.push(&[3, 4, 5]).unwrap();
+        v.extend_last(&[6]).unwrap();
+        assert_eq!(v.extend_last(&[7, 8, 9]), Err(()));
+        assert_eq!(v.total_len(), 6);
+        assert_eq!(v.remaining(), 2);
+        assert!(v.iter().eq([&[1, 2][..], &[], &[3, 4, 5, 6]]));
+        assert!(v.iter().rev().eq([&[3, 4, 5, 6][..], &[], &[1, 2]]));
+        assert_eq!(v.iter().len(), 3);
+
+        v[2][0] = 0;
+        assert_eq!(v.get(2), Some(&[0, 4, 5, 6][..]));
+        assert_eq!(v.get(3), None);
+
+        assert_eq!(v.remove(0), 2);
+        assert!(v.iter().eq([&[][..], &[0, 4, 5, 6]]));
+        v.push(&[7, 8]).unwrap();
+        v.push(&[9, 10]).unwrap();
+        assert_eq!(v.push(&[]), Err(()));
+        assert_eq!(v.last(), Some(&[9, 10][..]));
+
+        v.truncate(2);
+        assert_eq!(v.total_len(), 4);
+        let mut w = v.clone();
+        assert_eq!(v, w);
+        w.truncate(0);
+        assert!(w.is_empty());
+        assert_eq!(w.total_len(), 0);
+    }
+
+    #[test]
+    fn iter_both_ends() {
+        let mut v: FlatVec<u8, 8, 4> = FlatVec::new();
+        for s in [&[1][..], &[2, 3], &[4]] {
+            v.push(s).unwrap();
+        }
+        let mut iter = v.iter();
+        assert_eq!(iter.next(), Some(&[1][..]));
+        assert_eq!(iter.next_back(), Some(&[4][..]));
+        assert_eq!(iter.next_back(), Some(&[2, 3][..]));
+        assert_eq!(iter.next(), None);
+    }
+}
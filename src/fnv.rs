@@ -0,0 +1,139 @@
+//! The 32-bit Fowler-Noll-Vo hasher used by [`FnvIndexMap`](crate::FnvIndexMap) and
+//! [`FnvIndexSet`](crate::FnvIndexSet).
+//!
+//! [`FnvHasher`] produces the same hashes as the `hash32` hasher behind those maps, but can also
+//! be constructed and fed in `const` contexts. This lets hashes of keys be computed at compile
+//! time, e.g. to build lookup tables that agree with the maps.
+//!
+//! # Examples
+//!
+//! ```
+//! use core::hash::BuildHasher;
+//! use heapless::fnv::{self, FnvBuildHasher};
+//!
+//! const KEY_HASH: u32 = fnv::hash_str("temperature");
+//!
+//! let hash = FnvBuildHasher::new().hash_one("temperature");
+//! assert_eq!(hash, u64::from(KEY_HASH));
+//! ```
+
+use core::hash::Hasher;
+
+const BASIS: u32 = 0x811c_9dc5;
+const PRIME: u32 = 0x0100_0193;
+
+/// A [`BuildHasher`](core::hash::BuildHasher) that creates [`FnvHasher`]s.
+pub type FnvBuildHasher = hash32::BuildHasherDefault<FnvHasher>;
+
+/// The 32-bit Fowler-Noll-Vo (FNV-1a) hasher.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FnvHasher {
+    state: u32,
+}
+
+impl FnvHasher {
+    /// Creates a hasher in its initial state.
+    pub const fn new() -> Self {
+        Self { state: BASIS }
+    }
+
+    /// Feeds `bytes` into the hasher.
+    ///
+    /// This is the `const` equivalent of [`Hasher::write`].
+    pub const fn with_bytes(mut self, bytes: &[u8]) -> Self {
+        let mut i = 0;
+        while i < bytes.len() {
+            self.state ^= bytes[i] as u32;
+            self.state = self.state.wrapping_mul(PRIME);
+            i += 1;
+        }
+        self
+    }
+
+    /// Returns the hash of the bytes fed so far.
+    pub const fn finish32(&self) -> u32 {
+        self.state
+    }
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        *self = self.with_bytes(bytes);
+    }
+
+    fn finish(&self) -> u64 {
+        self.state.into()
+    }
+}
+
+impl hash32::Hasher for FnvHasher {
+    fn finish32(&self) -> u32 {
+        self.state
+    }
+}
+
+/// Returns the hash of `s` as a key, i.e. what hashing a `str`, `&str` or
+/// [`String`](crate::String) with an [`FnvHasher`] produces.
+pub const fn hash_str(s: &str) -> u32 {
+    // `str` hashes its bytes followed by a `0xff` terminator
+    FnvHasher::new()
+        .with_bytes(s.as_bytes())
+        .with_bytes(&[0xff])
+        .finish32()
+}
+
+/// Returns the hash of `bytes` as a key, i.e. what hashing a `[u8]`, `&[u8]` or
+/// [`Vec<u8, N>`](crate::Vec) with an [`FnvHasher`] produces.
+pub const fn hash_bytes(bytes: &[u8]) -> u32 {
+    // slices hash their length as a native-endian `usize` followed by their elements
+    FnvHasher::new()
+        .with_bytes(&bytes.len().to_ne_bytes())
+        .with_bytes(bytes)
+        .finish32()
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::{BuildHasher, Hash, Hasher};
+
+    use super::{hash_bytes, hash_str, FnvBuildHasher, FnvHasher};
+    use crate::{String, Vec};
+
+    fn hash32<T: Hash + ?Sized>(value: &T) -> u64 {
+        hash32::BuildHasherDefault::<hash32::FnvHasher>::new().hash_one(value)
+    }
+
+    #[test]
+    fn matches_hash32() {
+        let mut hasher = FnvHasher::new();
+        hasher.write(b"abc");
+        let mut other = hash32::FnvHasher::default();
+        other.write(b"abc");
+        assert_eq!(hasher.finish(), other.finish());
+
+        let build = FnvBuildHasher::new();
+        for s in ["", "a", "temperature", "héllo"] {
+            assert_eq!(build.hash_one(s), hash32(s));
+            assert_eq!(u64::from(hash_str(s)), hash32(s));
+            assert_eq!(u64::from(hash_bytes(s.as_bytes())), hash32(s.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn owned_types() {
+        const HASH: u32 = hash_str("key");
+        let s: String<8> = String::try_from("key").unwrap();
+        assert_eq!(hash32(&s), u64::from(HASH));
+
+        let v: Vec<u8, 8> = Vec::from_slice(b"key").unwrap();
+        let w: Vec<u8, 16> = Vec::from_slice(b"key").unwrap();
+        assert_eq!(hash32(&v), u64::from(hash_bytes(b"key")));
+        assert_eq!(hash32(&v), hash32(&w));
+    }
+}
@@ -149,11 +149,16 @@ impl<T, const N: usize> HistoryBuffer<T, N> {
         }
         self.data[self.write_at] = MaybeUninit::new(t);
 
-        self.write_at += 1;
-        if self.write_at == self.capacity() {
-            self.write_at = 0;
-            self.filled = true;
-        }
+        // NOTE `N.is_power_of_two()` is evaluated at compile time, so power-of-two buffers wrap
+        // with a mask rather than a compare-and-branch
+        self.write_at = if N.is_power_of_two() {
+            (self.write_at + 1) & (N - 1)
+        } else if self.write_at + 1 == N {
+            0
+        } else {
+            self.write_at + 1
+        };
+        self.filled |= self.write_at == 0;
     }
 
     /// Clones and writes all elements in a slice to the buffer.
@@ -253,6 +258,54 @@ impl<T, const N: usize> HistoryBuffer<T, N> {
     }
 }
 
+#[cfg(feature = "interpolate")]
+impl<T, const N: usize> HistoryBuffer<T, N>
+where
+    T: Copy + Into<f32>,
+{
+    /// Linearly interpolates between the elements around a fractional position in the buffer.
+    ///
+    /// Positions count from the oldest element, at `0.0`, to the most recent one, at
+    /// `self.len() - 1`. Returns `None` if `index` is outside that range (or NaN), which is always
+    /// the case for an empty buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::HistoryBuffer;
+    ///
+    /// let mut buf: HistoryBuffer<i16, 3> = HistoryBuffer::new();
+    /// buf.extend([0, 10, 20, -20]);
+    ///
+    /// assert_eq!(buf.interpolate_at(0.0), Some(10.0));
+    /// assert_eq!(buf.interpolate_at(0.5), Some(15.0));
+    /// assert_eq!(buf.interpolate_at(1.25), Some(10.0));
+    /// assert_eq!(buf.interpolate_at(2.5), None);
+    /// ```
+    pub fn interpolate_at(&self, index: f32) -> Option<f32> {
+        // written so that NaN is rejected as well
+        if !(index >= 0.0 && index <= (self.len() as f32 - 1.0)) {
+            return None;
+        }
+
+        let (older, newer) = self.as_slices();
+        let at = |i: usize| -> f32 {
+            match older.get(i) {
+                Some(x) => (*x).into(),
+                None => newer[i - older.len()].into(),
+            }
+        };
+
+        let i = index as usize;
+        let fraction = index - i as f32;
+        let a = at(i);
+        if fraction == 0.0 {
+            return Some(a);
+        }
+        Some(a + (at(i + 1) - a) * fraction)
+    }
+}
+
 impl<T, const N: usize> Extend<T> for HistoryBuffer<T, N> {
     fn extend<I>(&mut self, iter: I)
     where
@@ -341,6 +394,114 @@ where
     }
 }
 
+/// A [`HistoryBuffer`] that calls `on_write` with every value written to it.
+///
+/// This lets e.g. a display task be woken when new samples arrive instead of polling the buffer:
+/// the callback can capture a [`Waker`](core::task::Waker) and call `wake_by_ref` on it. Reading
+/// goes through `Deref` to the [`HistoryBuffer`].
+///
+/// # Examples
+///
+/// ```
+/// use heapless::ObservedHistoryBuffer;
+///
+/// let mut new_samples = 0;
+/// let mut buf = ObservedHistoryBuffer::<u16, _, 4>::new(|_: &u16| new_samples += 1);
+///
+/// buf.write(3);
+/// buf.extend([5, 8]);
+/// assert_eq!(buf.recent(), Some(&8));
+///
+/// drop(buf);
+/// assert_eq!(new_samples, 3);
+/// ```
+pub struct ObservedHistoryBuffer<T, F, const N: usize>
+where
+    F: FnMut(&T),
+{
+    buffer: HistoryBuffer<T, N>,
+    on_write: F,
+}
+
+impl<T, F, const N: usize> ObservedHistoryBuffer<T, F, N>
+where
+    F: FnMut(&T),
+{
+    /// Constructs a new history buffer that calls `on_write` on each write.
+    ///
+    /// The construction works in `const` contexts if `F` is a function pointer.
+    pub const fn new(on_write: F) -> Self {
+        Self {
+            buffer: HistoryBuffer::new(),
+            on_write,
+        }
+    }
+
+    /// Writes an element to the buffer, overwriting the oldest value, and calls `on_write` with
+    /// it.
+    pub fn write(&mut self, t: T) {
+        self.buffer.write(t);
+        if let Some(recent) = self.buffer.recent() {
+            (self.on_write)(recent);
+        }
+    }
+
+    /// Clones and writes all elements in a slice to the buffer, calling `on_write` for each.
+    pub fn extend_from_slice(&mut self, other: &[T])
+    where
+        T: Clone,
+    {
+        for item in other {
+            self.write(item.clone());
+        }
+    }
+
+    /// Clears the buffer, without calling `on_write`.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+
+    /// Returns the buffer, dropping the callback.
+    pub fn into_inner(self) -> HistoryBuffer<T, N> {
+        self.buffer
+    }
+}
+
+impl<T, F, const N: usize> Deref for ObservedHistoryBuffer<T, F, N>
+where
+    F: FnMut(&T),
+{
+    type Target = HistoryBuffer<T, N>;
+
+    fn deref(&self) -> &HistoryBuffer<T, N> {
+        &self.buffer
+    }
+}
+
+impl<T, F, const N: usize> Extend<T> for ObservedHistoryBuffer<T, F, N>
+where
+    F: FnMut(&T),
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.write(item);
+        }
+    }
+}
+
+impl<T, F, const N: usize> fmt::Debug for ObservedHistoryBuffer<T, F, N>
+where
+    T: fmt::Debug,
+    F: FnMut(&T),
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.buffer.fmt(f)
+    }
+}
+
 /// An iterator on the underlying buffer ordered from oldest data to newest
 #[derive(Clone)]
 pub struct OldestOrdered<'a, T, const N: usize> {
@@ -371,7 +532,7 @@ impl<'a, T, const N: usize> Iterator for OldestOrdered<'a, T, N> {
 
 #[cfg(test)]
 mod tests {
-    use crate::HistoryBuffer;
+    use crate::{HistoryBuffer, ObservedHistoryBuffer};
     use core::fmt::Debug;
     use core::sync::atomic::{AtomicUsize, Ordering};
 
@@ -400,6 +561,32 @@ mod tests {
 
         x.extend([11, 12].iter());
         assert_eq!(x.as_slice(), [10, 11, 12, 6]);
+
+        // not a power of two
+        let mut x: HistoryBuffer<u8, 3> = HistoryBuffer::new();
+        x.extend([1, 2, 3, 4].iter());
+        assert_eq!(x.as_slice(), [4, 2, 3]);
+        assert!(x.oldest_ordered().eq(&[2, 3, 4]));
+    }
+
+    #[test]
+    fn observed() {
+        static SUM: AtomicUsize = AtomicUsize::new(0);
+        fn add(x: &usize) {
+            SUM.fetch_add(*x, Ordering::Relaxed);
+        }
+
+        let mut x: ObservedHistoryBuffer<usize, fn(&usize), 2> = ObservedHistoryBuffer::new(add);
+        x.write(1);
+        x.extend_from_slice(&[2, 3]);
+        x.extend([4]);
+        assert_eq!(SUM.load(Ordering::Relaxed), 10);
+        assert_eq!(x.as_slice(), [3, 4]);
+
+        x.clear();
+        assert!(x.is_empty());
+        assert_eq!(SUM.load(Ordering::Relaxed), 10);
+        assert_eq!(x.into_inner().len(), 0);
     }
 
     #[test]
@@ -591,4 +778,23 @@ mod tests {
             );
         }
     }
+
+    #[cfg(feature = "interpolate")]
+    #[test]
+    fn interpolate_at() {
+        let mut x: HistoryBuffer<f32, 4> = HistoryBuffer::new();
+        assert_eq!(x.interpolate_at(0.0), None);
+
+        x.write(1.0);
+        assert_eq!(x.interpolate_at(0.0), Some(1.0));
+        assert_eq!(x.interpolate_at(0.5), None);
+
+        // across the wrap-around point of the storage
+        x.extend([2.0, 3.0, 4.0, 8.0]);
+        assert_eq!(x.as_slices(), (&[2.0, 3.0, 4.0][..], &[8.0][..]));
+        assert_eq!(x.interpolate_at(2.75), Some(7.0));
+        assert_eq!(x.interpolate_at(3.0), Some(8.0));
+        assert_eq!(x.interpolate_at(-0.5), None);
+        assert_eq!(x.interpolate_at(f32::NAN), None);
+    }
 }
@@ -0,0 +1,429 @@
+use core::{fmt, hash, ops};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec as AllocVec;
+
+use crate::{vec::capacity_overflow, Vec};
+
+/// A vector that stores up to `N` elements inline, and moves them to the heap when it grows
+/// beyond that if the `alloc` feature is enabled.
+///
+/// Without the `alloc` feature a `HybridVec` behaves like a [`Vec`]: operations that would exceed
+/// the capacity fail. This lets code that is shared between firmware and a host build use one
+/// type, where only the host build may allocate.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::HybridVec;
+///
+/// let mut v: HybridVec<u8, 2> = HybridVec::new();
+/// v.push(1).unwrap();
+/// v.push(2).unwrap();
+/// assert!(!v.is_spilled());
+///
+/// // a third element only fits with the `alloc` feature
+/// match v.push(3) {
+///     Ok(()) => assert!(v.is_spilled()),
+///     Err(x) => assert_eq!(x, 3),
+/// }
+/// assert_eq!(v[..2], [1, 2]);
+/// ```
+pub struct HybridVec<T, const N: usize> {
+    inner: Inner<T, N>,
+}
+
+enum Inner<T, const N: usize> {
+    Inline(Vec<T, N>),
+    #[cfg(feature = "alloc")]
+    Heap(AllocVec<T>),
+}
+
+impl<T, const N: usize> HybridVec<T, N> {
+    /// Constructs a new, empty vector that stores its elements inline.
+    pub const fn new() -> Self {
+        Self {
+            inner: Inner::Inline(Vec::new()),
+        }
+    }
+
+    /// Returns `true` if the elements were moved to the heap.
+    pub fn is_spilled(&self) -> bool {
+        !matches!(self.inner, Inner::Inline(_))
+    }
+
+    /// Returns the number of elements the vector can hold without spilling or reallocating.
+    pub fn capacity(&self) -> usize {
+        match &self.inner {
+            Inner::Inline(v) => v.capacity(),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v.capacity(),
+        }
+    }
+
+    /// Extracts a slice containing the entire vector.
+    pub fn as_slice(&self) -> &[T] {
+        match &self.inner {
+            Inner::Inline(v) => v,
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v,
+        }
+    }
+
+    /// Extracts a mutable slice containing the entire vector.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        match &mut self.inner {
+            Inner::Inline(v) => v,
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v,
+        }
+    }
+
+    /// Clears the vector, removing all values.
+    ///
+    /// A spilled vector keeps its heap allocation.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Shortens the vector, keeping the first `len` elements and dropping the rest.
+    pub fn truncate(&mut self, len: usize) {
+        match &mut self.inner {
+            Inner::Inline(v) => v.truncate(len),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v.truncate(len),
+        }
+    }
+
+    /// Appends an `item` to the back of the collection.
+    ///
+    /// Returns back the `item` if the vector is full and can't spill.
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        if !self.make_room(1) {
+            return Err(item);
+        }
+        match &mut self.inner {
+            Inner::Inline(v) => v.push(item),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => {
+                v.push(item);
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes the last element from a vector and returns it, or `None` if it's empty.
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.inner {
+            Inner::Inline(v) => v.pop(),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v.pop(),
+        }
+    }
+
+    /// Inserts an element at position `index` within the vector, shifting all elements after it
+    /// to the right.
+    ///
+    /// Returns back the `element` if the vector is full and can't spill.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        let len = self.len();
+        assert!(
+            index <= len,
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            len
+        );
+        if !self.make_room(1) {
+            return Err(element);
+        }
+        match &mut self.inner {
+            Inner::Inline(v) => v.insert(index, element),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => {
+                v.insert(index, element);
+                Ok(())
+            }
+        }
+    }
+
+    /// Removes and returns the element at position `index` within the vector, shifting all
+    /// elements after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        match &mut self.inner {
+            Inner::Inline(v) => v.remove(index),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v.remove(index),
+        }
+    }
+
+    /// Removes an element from the vector and returns it, replacing it with the last element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        match &mut self.inner {
+            Inner::Inline(v) => v.swap_remove(index),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v.swap_remove(index),
+        }
+    }
+
+    /// Retains only the elements specified by the predicate.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        match &mut self.inner {
+            Inner::Inline(v) => v.retain(f),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => v.retain(f),
+        }
+    }
+
+    /// Clones and appends all elements in a slice to the vector.
+    ///
+    /// Returns `Err(())` and leaves the vector unchanged if the elements don't fit and it can't
+    /// spill.
+    #[allow(clippy::result_unit_err)]
+    pub fn extend_from_slice(&mut self, other: &[T]) -> Result<(), ()>
+    where
+        T: Clone,
+    {
+        if !self.make_room(other.len()) {
+            return Err(());
+        }
+        match &mut self.inner {
+            Inner::Inline(v) => v.extend_from_slice(other),
+            #[cfg(feature = "alloc")]
+            Inner::Heap(v) => {
+                v.extend_from_slice(other);
+                Ok(())
+            }
+        }
+    }
+
+    /// Makes sure that `additional` more elements fit, spilling if needed and possible.
+    fn make_room(&mut self, additional: usize) -> bool {
+        match &self.inner {
+            Inner::Inline(v) if v.len() + additional > N => self.spill(additional),
+            _ => true,
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    fn spill(&mut self, additional: usize) -> bool {
+        if let Inner::Inline(v) = &mut self.inner {
+            let inline = core::mem::take(v);
+            let mut heap = AllocVec::with_capacity((2 * N).max(inline.len() + additional));
+            heap.extend(inline);
+            self.inner = Inner::Heap(heap);
+        }
+        true
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    fn spill(&mut self, _additional: usize) -> bool {
+        false
+    }
+}
+
+impl<T, const N: usize> Default for HybridVec<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Clone for HybridVec<T, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: match &self.inner {
+                Inner::Inline(v) => Inner::Inline(v.clone()),
+                #[cfg(feature = "alloc")]
+                Inner::Heap(v) => Inner::Heap(v.clone()),
+            },
+        }
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for HybridVec<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <[T] as fmt::Debug>::fmt(self, f)
+    }
+}
+
+impl<T, const N: usize> ops::Deref for HybridVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> ops::DerefMut for HybridVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for HybridVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> AsMut<[T]> for HybridVec<T, N> {
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<A, B, const N1: usize, const N2: usize> PartialEq<HybridVec<B, N2>> for HybridVec<A, N1>
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &HybridVec<B, N2>) -> bool {
+        <[A]>::eq(self, &**other)
+    }
+}
+
+impl<A, B, const N: usize> PartialEq<[B]> for HybridVec<A, N>
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &[B]) -> bool {
+        <[A]>::eq(self, other)
+    }
+}
+
+impl<A, B, const N: usize, const M: usize> PartialEq<[B; M]> for HybridVec<A, N>
+where
+    A: PartialEq<B>,
+{
+    fn eq(&self, other: &[B; M]) -> bool {
+        <[A]>::eq(self, other)
+    }
+}
+
+impl<T, const N: usize> Eq for HybridVec<T, N> where T: Eq {}
+
+/// Hashes like the slice `[T]`, whether or not the vector spilled.
+impl<T, const N: usize> hash::Hash for HybridVec<T, N>
+where
+    T: hash::Hash,
+{
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        <[T] as hash::Hash>::hash(self, state)
+    }
+}
+
+impl<T, const N: usize> Extend<T> for HybridVec<T, N> {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for elem in iter {
+            if self.push(elem).is_err() {
+                capacity_overflow("HybridVec::extend overflow");
+                return;
+            }
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for HybridVec<T, N> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut vec = HybridVec::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a HybridVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut HybridVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HybridVec;
+
+    #[test]
+    fn inline() {
+        let mut v: HybridVec<u8, 4> = (0..3).collect();
+        v.insert(0, 9).unwrap();
+        assert_eq!(v, [9, 0, 1, 2]);
+        assert!(!v.is_spilled());
+        assert_eq!(v.remove(1), 0);
+        assert_eq!(v.swap_remove(0), 9);
+        assert_eq!(v, [2, 1]);
+        v.retain(|x| *x > 1);
+        assert_eq!(v.pop(), Some(2));
+        assert_eq!(v.pop(), None);
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn full() {
+        let mut v: HybridVec<u8, 2> = HybridVec::new();
+        v.extend_from_slice(&[1, 2]).unwrap();
+        assert_eq!(v.push(3), Err(3));
+        assert_eq!(v.insert(0, 3), Err(3));
+        assert_eq!(v.extend_from_slice(&[3]), Err(()));
+        assert_eq!(v, [1, 2]);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn spill() {
+        droppable!();
+
+        let mut v: HybridVec<Droppable, 2> = HybridVec::new();
+        for _ in 0..5 {
+            v.push(Droppable::new()).ok().unwrap();
+        }
+        assert!(v.is_spilled());
+        assert!(v.capacity() >= 5);
+        assert_eq!(Droppable::count(), 5);
+        v.truncate(1);
+        assert_eq!(Droppable::count(), 1);
+        core::mem::drop(v);
+        assert_eq!(Droppable::count(), 0);
+
+        let mut v: HybridVec<u8, 2> = HybridVec::new();
+        v.extend_from_slice(&[1, 2, 3]).unwrap();
+        v.insert(0, 0).unwrap();
+        assert_eq!(v, [0, 1, 2, 3]);
+        assert_eq!(v.clone(), v);
+    }
+}
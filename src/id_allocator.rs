@@ -0,0 +1,327 @@
+/// An allocator of integer IDs in `0..N`, backed by a bitset of `W` 32-bit words.
+///
+/// `W` must be `N` divided by 32, rounded up, which is checked at compile time; it's a separate
+/// parameter only because array lengths can't be computed from `N` yet. The default of one word
+/// covers up to 32 IDs.
+///
+/// [`alloc`](IdAllocator::alloc) always hands out the lowest free ID. See
+/// [`VersionedIdAllocator`] for IDs that can be told apart from earlier uses of the same number.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::IdAllocator;
+///
+/// // 40 socket slots, in 2 words
+/// let mut slots: IdAllocator<40, 2> = IdAllocator::new();
+/// assert_eq!(slots.capacity(), 40);
+///
+/// let a = slots.alloc().unwrap();
+/// let b = slots.alloc().unwrap();
+/// assert_eq!((a, b), (0, 1));
+///
+/// assert!(slots.free(a));
+/// assert!(!slots.is_allocated(a));
+/// assert_eq!(slots.alloc(), Some(0));
+/// ```
+///
+/// A word count that doesn't match `N` fails the build:
+///
+/// ```compile_fail
+/// use heapless::IdAllocator;
+///
+/// let slots: IdAllocator<40> = IdAllocator::new();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdAllocator<const N: usize, const W: usize = 1> {
+    // the bits past `N` in the last word are always set, so that they're never handed out
+    words: [u32; W],
+    len: usize,
+}
+
+impl<const N: usize, const W: usize> IdAllocator<N, W> {
+    /// Creates an allocator with all IDs free.
+    pub const fn new() -> Self {
+        crate::sealed::words_for_bits::<N, W>();
+
+        let mut words = [0; W];
+        if !N.is_multiple_of(32) {
+            words[W - 1] = u32::MAX << (N % 32);
+        }
+        Self { words, len: 0 }
+    }
+
+    /// Returns the number of IDs, `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of allocated IDs.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no ID is allocated.
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if all IDs are allocated.
+    pub const fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Allocates the lowest free ID, or returns `None` if all IDs are allocated.
+    pub fn alloc(&mut self) -> Option<usize> {
+        let (word, bits) = self
+            .words
+            .iter_mut()
+            .enumerate()
+            .find(|(_, bits)| **bits != u32::MAX)?;
+        let bit = bits.trailing_ones() as usize;
+        *bits |= 1 << bit;
+        self.len += 1;
+        Some(32 * word + bit)
+    }
+
+    /// Allocates `id` if it's free, e.g. to reserve fixed IDs before handing out the others.
+    ///
+    /// Returns `false` if `id` is already allocated or out of range.
+    pub fn alloc_id(&mut self, id: usize) -> bool {
+        match self.words.get_mut(id / 32) {
+            Some(bits) if id < N && *bits & (1 << (id % 32)) == 0 => {
+                *bits |= 1 << (id % 32);
+                self.len += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Frees `id` so that it can be allocated again.
+    ///
+    /// Returns `false` if `id` wasn't allocated.
+    pub fn free(&mut self, id: usize) -> bool {
+        match self.words.get_mut(id / 32) {
+            Some(bits) if id < N && *bits & (1 << (id % 32)) != 0 => {
+                *bits &= !(1 << (id % 32));
+                self.len -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if `id` is allocated.
+    pub fn is_allocated(&self, id: usize) -> bool {
+        id < N && self.words[id / 32] & (1 << (id % 32)) != 0
+    }
+
+    /// Frees all IDs.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns an iterator over the allocated IDs, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(word, &bits)| {
+            (0..32)
+                .filter(move |bit| bits & (1 << bit) != 0)
+                .map(move |bit| 32 * word + bit)
+                .filter(|&id| id < N)
+        })
+    }
+}
+
+impl<const N: usize, const W: usize> Default for IdAllocator<N, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An ID handed out by a [`VersionedIdAllocator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VersionedId {
+    index: usize,
+    version: u8,
+}
+
+impl VersionedId {
+    /// Returns the ID number.
+    pub const fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns how many times the ID number was freed before this ID was handed out, modulo 256.
+    pub const fn version(&self) -> u8 {
+        self.version
+    }
+}
+
+/// An [`IdAllocator`] whose IDs carry a version, so that a handle that was kept after its ID was
+/// freed isn't mistaken for a later allocation of the same number.
+///
+/// Each ID number has an 8-bit version that's incremented when it's freed, so a stale handle is
+/// only accepted again after its number was reused 256 times. `N` and `W` are as for
+/// [`IdAllocator`].
+///
+/// # Examples
+///
+/// ```
+/// use heapless::VersionedIdAllocator;
+///
+/// let mut endpoints: VersionedIdAllocator<16> = VersionedIdAllocator::new();
+///
+/// let stale = endpoints.alloc().unwrap();
+/// assert!(endpoints.free(stale));
+///
+/// let current = endpoints.alloc().unwrap();
+/// assert_eq!(current.index(), stale.index());
+/// assert!(endpoints.is_allocated(current));
+/// assert!(!endpoints.is_allocated(stale));
+/// assert!(!endpoints.free(stale));
+/// ```
+#[derive(Clone, Debug)]
+pub struct VersionedIdAllocator<const N: usize, const W: usize = 1> {
+    ids: IdAllocator<N, W>,
+    versions: [u8; N],
+}
+
+impl<const N: usize, const W: usize> VersionedIdAllocator<N, W> {
+    /// Creates an allocator with all IDs free.
+    pub const fn new() -> Self {
+        Self {
+            ids: IdAllocator::new(),
+            versions: [0; N],
+        }
+    }
+
+    /// Returns the number of ID numbers, `N`.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of allocated IDs.
+    pub const fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Returns `true` if no ID is allocated.
+    pub const fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Allocates the lowest free ID number, or returns `None` if all are allocated.
+    pub fn alloc(&mut self) -> Option<VersionedId> {
+        let index = self.ids.alloc()?;
+        Some(VersionedId {
+            index,
+            version: self.versions[index],
+        })
+    }
+
+    /// Frees `id` so that its number can be allocated again.
+    ///
+    /// Returns `false` if `id` isn't allocated, e.g. because it was already freed.
+    pub fn free(&mut self, id: VersionedId) -> bool {
+        if !self.is_allocated(id) {
+            return false;
+        }
+        self.ids.free(id.index);
+        let version = &mut self.versions[id.index];
+        *version = version.wrapping_add(1);
+        true
+    }
+
+    /// Returns `true` if `id` is allocated and wasn't freed since it was handed out.
+    pub fn is_allocated(&self, id: VersionedId) -> bool {
+        self.ids.is_allocated(id.index) && self.versions[id.index] == id.version
+    }
+}
+
+impl<const N: usize, const W: usize> Default for VersionedIdAllocator<N, W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IdAllocator, VersionedIdAllocator};
+
+    #[test]
+    fn alloc_free() {
+        let mut ids: IdAllocator<64, 2> = IdAllocator::new();
+        assert_eq!(ids.capacity(), 64);
+        assert!(ids.alloc_id(1));
+        assert!(!ids.alloc_id(1));
+        assert!(!ids.alloc_id(64));
+        for expected in (0..64).filter(|&id| id != 1) {
+            assert_eq!(ids.alloc(), Some(expected));
+        }
+        assert!(ids.is_full());
+        assert_eq!(ids.alloc(), None);
+
+        assert!(ids.free(40));
+        assert!(!ids.free(40));
+        assert!(!ids.free(100));
+        assert!(!ids.is_allocated(100));
+        assert_eq!(ids.len(), 63);
+        assert_eq!(ids.iter().count(), 63);
+        assert!(ids.iter().all(|id| id != 40));
+        assert_eq!(ids.alloc(), Some(40));
+
+        ids.clear();
+        assert!(ids.is_empty());
+        assert_eq!(ids.iter().next(), None);
+    }
+
+    #[test]
+    fn partial_word() {
+        let mut ids: IdAllocator<40, 2> = IdAllocator::new();
+        assert_eq!(ids.capacity(), 40);
+        assert!(!ids.alloc_id(40));
+        assert!(!ids.is_allocated(63));
+        assert!(!ids.free(63));
+        for expected in 0..40 {
+            assert_eq!(ids.alloc(), Some(expected));
+        }
+        assert!(ids.is_full());
+        assert_eq!(ids.alloc(), None);
+        assert!(ids.iter().eq(0..40));
+
+        ids.clear();
+        assert!(ids.is_empty());
+        assert_eq!(ids.alloc(), Some(0));
+
+        let mut small: IdAllocator<3> = IdAllocator::new();
+        assert_eq!(small.capacity(), 3);
+        assert_eq!(
+            (small.alloc(), small.alloc(), small.alloc(), small.alloc()),
+            (Some(0), Some(1), Some(2), None)
+        );
+
+        let mut none: IdAllocator<0, 0> = IdAllocator::new();
+        assert!(none.is_full());
+        assert_eq!(none.alloc(), None);
+        assert!(!none.is_allocated(0));
+    }
+
+    #[test]
+    fn versions() {
+        let mut ids: VersionedIdAllocator<1> = VersionedIdAllocator::new();
+        assert_eq!(ids.capacity(), 1);
+        let a = ids.alloc().unwrap();
+        assert!(ids.free(a));
+        for version in 1..=255 {
+            let b = ids.alloc().unwrap();
+            assert_eq!((b.index(), b.version()), (0, version));
+            assert!(!ids.is_allocated(a));
+            assert!(!ids.free(a));
+            assert!(ids.free(b));
+        }
+        // the version wrapped around
+        assert_eq!(ids.alloc(), Some(a));
+        assert!(ids.is_allocated(a));
+    }
+}
@@ -5,12 +5,12 @@ use core::{
     iter::FromIterator,
     mem,
     num::NonZeroU32,
-    ops, slice,
+    ops, ptr, slice,
 };
 
 use hash32::{BuildHasherDefault, FnvHasher};
 
-use crate::Vec;
+use crate::{vec::capacity_overflow, Vec};
 
 /// An [`IndexMap`] using the default FNV hasher.
 ///
@@ -138,6 +138,10 @@ macro_rules! probe_loop {
     }
 }
 
+/// The entries in insertion order and the robin hood hash index into them.
+///
+/// NOTE: Probing must only read `indices` and compare the hashes stored there; an entry is only
+/// read once its hash matches, see "Memory layout" in the docs of `IndexMap`.
 struct CoreMap<K, V, const N: usize> {
     entries: Vec<Bucket<K, V>, N>,
     indices: [Option<Pos>; N],
@@ -302,44 +306,97 @@ where
     where
         F: FnMut(&mut K, &mut V) -> bool,
     {
-        const INIT: Option<Pos> = None;
-
         self.entries
             .retain_mut(|entry| keep(&mut entry.key, &mut entry.value));
 
         if self.entries.len() < self.indices.len() {
-            for index in self.indices.iter_mut() {
-                *index = INIT;
-            }
+            self.rebuild_indices();
+        }
+    }
 
-            for (index, entry) in self.entries.iter().enumerate() {
-                let mut probe = entry.hash.desired_pos(Self::mask());
-                let mut dist = 0;
-
-                probe_loop!(probe < self.indices.len(), {
-                    let pos = &mut self.indices[probe];
-
-                    if let Some(pos) = *pos {
-                        let entry_hash = pos.hash();
-
-                        // robin hood: steal the spot if it's better for us
-                        let their_dist = entry_hash.probe_distance(Self::mask(), probe);
-                        if their_dist < dist {
-                            Self::insert_phase_2(
-                                &mut self.indices,
-                                probe,
-                                Pos::new(index, entry.hash),
-                            );
-                            break;
-                        }
-                    } else {
-                        *pos = Some(Pos::new(index, entry.hash));
-                        break;
-                    }
-                    dist += 1;
-                });
+    /// Appends an entry whose key is known not to be in the map yet.
+    fn insert_unique(&mut self, hash: HashValue, key: K, value: V) -> Result<(), (K, V)> {
+        if self.entries.is_full() {
+            return Err((key, value));
+        }
+        let index = self.entries.len();
+        unsafe { self.entries.push_unchecked(Bucket { hash, key, value }) };
+        Self::insert_index(&mut self.indices, index, hash);
+        Ok(())
+    }
+
+    /// Recomputes `indices` from the stored hashes after entries were moved around.
+    fn rebuild_indices(&mut self) {
+        const INIT: Option<Pos> = None;
+
+        for index in self.indices.iter_mut() {
+            *index = INIT;
+        }
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            Self::insert_index(&mut self.indices, index, entry.hash);
+        }
+    }
+
+    /// Inserts the index of an entry whose key is known not to be in `indices` yet.
+    fn insert_index(indices: &mut [Option<Pos>; N], index: usize, hash: HashValue) {
+        let mut probe = hash.desired_pos(Self::mask());
+        let mut dist = 0;
+
+        probe_loop!(probe < indices.len(), {
+            let pos = &mut indices[probe];
+
+            if let Some(pos) = *pos {
+                let entry_hash = pos.hash();
+
+                // robin hood: steal the spot if it's better for us
+                let their_dist = entry_hash.probe_distance(Self::mask(), probe);
+                if their_dist < dist {
+                    Self::insert_phase_2(indices, probe, Pos::new(index, hash));
+                    break;
+                }
+            } else {
+                *pos = Some(Pos::new(index, hash));
+                break;
             }
+            dist += 1;
+        });
+    }
+
+    /// Moves all entries into a map with capacity `M >= len`, reusing the stored hashes.
+    fn migrate_into<const M: usize>(self) -> CoreMap<K, V, M> {
+        let mut core = CoreMap::<K, V, M>::new();
+
+        for entry in self.entries {
+            let index = core.entries.len();
+            CoreMap::<K, V, M>::insert_index(&mut core.indices, index, entry.hash);
+            // NOTE(unsafe) the caller guarantees that `M >= len`
+            unsafe { core.entries.push_unchecked(entry) };
+        }
+
+        core
+    }
+
+    fn partition_into<const M: usize, F>(self, mut pred: F) -> (CoreMap<K, V, M>, CoreMap<K, V, M>)
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let mut left = CoreMap::<K, V, M>::new();
+        let mut right = CoreMap::<K, V, M>::new();
+
+        for entry in self.entries {
+            let core = if pred(&entry.key, &entry.value) {
+                &mut left
+            } else {
+                &mut right
+            };
+            let index = core.entries.len();
+            CoreMap::<K, V, M>::insert_index(&mut core.indices, index, entry.hash);
+            // NOTE(unsafe) the caller guarantees that `M >= len`
+            unsafe { core.entries.push_unchecked(entry) };
         }
+
+        (left, right)
     }
 
     fn backward_shift_after_removal(&mut self, probe_at_remove: usize) {
@@ -387,6 +444,45 @@ pub enum Entry<'a, K, V, const N: usize> {
     Vacant(VacantEntry<'a, K, V, N>),
 }
 
+impl<'a, K, V, const N: usize> Entry<'a, K, V, N>
+where
+    K: Eq + Hash,
+{
+    /// If the entry is occupied, replaces its value with the result of `f`, or removes it if `f`
+    /// returns `None`. See [`OccupiedEntry::replace_entry_with`].
+    ///
+    /// This updates or removes an entry with a single lookup, e.g. to drop expired values when
+    /// they're accessed.
+    ///
+    /// ```
+    /// use heapless::{Entry, FnvIndexMap};
+    ///
+    /// // peer ID -> (address, expiry time)
+    /// let mut peers = FnvIndexMap::<u32, (u8, u32), 8>::new();
+    /// peers.insert(7, (0x10, 100)).unwrap();
+    ///
+    /// let now = 150;
+    /// let address = match peers
+    ///     .entry(7)
+    ///     .and_replace_entry_with(|_, peer| (peer.1 > now).then_some(peer))
+    /// {
+    ///     Entry::Occupied(entry) => Some(entry.get().0),
+    ///     Entry::Vacant(_) => None,
+    /// };
+    /// assert_eq!(address, None);
+    /// assert!(peers.is_empty());
+    /// ```
+    pub fn and_replace_entry_with<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.replace_entry_with(f),
+            Entry::Vacant(_) => self,
+        }
+    }
+}
+
 /// An occupied entry which can be manipulated
 pub struct OccupiedEntry<'a, K, V, const N: usize> {
     key: K,
@@ -446,6 +542,85 @@ where
     pub fn remove(self) -> V {
         self.remove_entry().1
     }
+
+    /// Replaces the value of this entry with the result of `f`, or removes the entry if `f`
+    /// returns `None`.
+    ///
+    /// `f` is called with the key the entry was looked up with and the current value. Returns the
+    /// entry in its new state.
+    pub fn replace_entry_with<F>(self, f: F) -> Entry<'a, K, V, N>
+    where
+        F: FnOnce(&K, V) -> Option<V>,
+    {
+        /// Removes the entry, whose value has been moved out, if `f` panics.
+        struct Guard<'b, K, V, const N: usize>
+        where
+            K: Eq + Hash,
+        {
+            core: &'b mut CoreMap<K, V, N>,
+            probe: usize,
+            pos: usize,
+        }
+
+        impl<'b, K, V, const N: usize> Guard<'b, K, V, N>
+        where
+            K: Eq + Hash,
+        {
+            fn disarm(self) -> &'b mut CoreMap<K, V, N> {
+                let this = mem::ManuallyDrop::new(self);
+                // SAFETY: `this` is never used or dropped again
+                unsafe { ptr::read(&this.core) }
+            }
+
+            fn remove(self) -> &'b mut CoreMap<K, V, N> {
+                let (probe, pos) = (self.probe, self.pos);
+                let core = self.disarm();
+                mem::forget(core.remove_found(probe, pos).1);
+                core
+            }
+        }
+
+        impl<K, V, const N: usize> Drop for Guard<'_, K, V, N>
+        where
+            K: Eq + Hash,
+        {
+            fn drop(&mut self) {
+                mem::forget(self.core.remove_found(self.probe, self.pos).1);
+            }
+        }
+
+        let OccupiedEntry {
+            key,
+            probe,
+            pos,
+            core,
+        } = self;
+        // SAFETY: Already checked existence at instantiation and the only mutable reference
+        // to the map is internally held.
+        let bucket = unsafe { core.entries.get_unchecked_mut(pos) };
+        let hash_val = bucket.hash;
+        let slot: *mut V = &mut bucket.value;
+        let guard = Guard { core, probe, pos };
+
+        // SAFETY: the value is either written back or the entry removed without dropping it, even
+        // if `f` panics
+        match f(&key, unsafe { ptr::read(slot) }) {
+            Some(value) => {
+                unsafe { ptr::write(slot, value) };
+                Entry::Occupied(OccupiedEntry {
+                    key,
+                    probe,
+                    pos,
+                    core: guard.disarm(),
+                })
+            }
+            None => Entry::Vacant(VacantEntry {
+                key,
+                hash_val,
+                core: guard.remove(),
+            }),
+        }
+    }
 }
 
 /// A view into an empty slot in the underlying map
@@ -496,6 +671,15 @@ where
 ///
 /// Note that the capacity of the `IndexMap` must be a power of 2.
 ///
+/// # Memory layout
+///
+/// The map keeps its hash index apart from its entries. The index is a dense array of `N` 4-byte
+/// slots, each holding the 16-bit hash of a key and the 16-bit position of its entry. The entries
+/// hold the keys and values in insertion order, together with the hashes. A lookup probes the
+/// index only, and reads an entry only once the 16-bit hashes match, so the size of the values
+/// doesn't affect the cost of probing: a successful lookup touches a single entry and a failed one
+/// usually none.
+///
 /// # Examples
 ///
 /// Since `IndexMap` cannot be used directly, we're using its `FnvIndexMap` instantiation
@@ -566,6 +750,21 @@ impl<K, V, S, const N: usize> IndexMap<K, V, BuildHasherDefault<S>, N> {
 }
 
 impl<K, V, S, const N: usize> IndexMap<K, V, S, N> {
+    /// Creates an empty `IndexMap` that hashes keys with `build_hasher`.
+    ///
+    /// Use this with e.g. a keyed [`SipBuildHasher`](crate::sip::SipBuildHasher) for maps whose
+    /// keys are chosen by an untrusted party.
+    pub const fn with_hasher(build_hasher: S) -> Self {
+        // Const assert
+        crate::sealed::greater_than_1::<N>();
+        crate::sealed::power_of_two::<N>();
+
+        IndexMap {
+            build_hasher,
+            core: CoreMap::new(),
+        }
+    }
+
     /// Returns the number of elements the map can hold
     pub fn capacity(&self) -> usize {
         N
@@ -719,6 +918,49 @@ impl<K, V, S, const N: usize> IndexMap<K, V, S, N> {
             .map(|bucket| (&bucket.key, &mut bucket.value))
     }
 
+    /// Collect references to the values into `out`, sorted by the key `f` returns for each value
+    ///
+    /// `out` is cleared first. The sort is stable, so values with equal keys stay in insertion
+    /// order. Returns `Err(())` and leaves `out` empty if it can't hold all values.
+    ///
+    /// Computes in *O*(n²) time
+    ///
+    /// ```
+    /// use heapless::{FnvIndexMap, Vec};
+    ///
+    /// let mut map = FnvIndexMap::<_, _, 8>::new();
+    /// map.insert("uart", 3).unwrap();
+    /// map.insert("spi", 1).unwrap();
+    /// map.insert("i2c", 2).unwrap();
+    ///
+    /// let mut report: Vec<&u32, 8> = Vec::new();
+    /// map.values_vec_sorted_by(&mut report, |errors| *errors).unwrap();
+    /// assert_eq!(report, [&1, &2, &3]);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn values_vec_sorted_by<'a, B, F, const M: usize>(
+        &'a self,
+        out: &mut Vec<&'a V, M>,
+        mut f: F,
+    ) -> Result<(), ()>
+    where
+        B: Ord,
+        F: FnMut(&V) -> B,
+    {
+        out.clear();
+        if self.len() > M {
+            return Err(());
+        }
+
+        for value in self.values() {
+            let key = f(value);
+            let index = out.partition_point(|other| f(other) <= key);
+            // capacity was checked above
+            let _ = out.insert(index, value);
+        }
+        Ok(())
+    }
+
     /// Return the number of key-value pairs in the map.
     ///
     /// Computes in *O*(1) time.
@@ -890,6 +1132,49 @@ where
         }
     }
 
+    /// Returns mutable references to the values corresponding to several keys at once.
+    ///
+    /// Returns `None` if any of the keys is missing or if two of the keys are equal, since that
+    /// would hand out two mutable references to the same value.
+    ///
+    /// Computes in *O*(M²) time (average).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexMap;
+    ///
+    /// let mut balances = FnvIndexMap::<_, _, 8>::new();
+    /// balances.insert("alice", 100).unwrap();
+    /// balances.insert("bob", 20).unwrap();
+    ///
+    /// let [from, to] = balances.get_many_mut([&"alice", &"bob"]).unwrap();
+    /// *from -= 30;
+    /// *to += 30;
+    /// assert_eq!(balances[&"alice"], 70);
+    /// assert_eq!(balances[&"bob"], 50);
+    ///
+    /// assert!(balances.get_many_mut([&"alice", &"alice"]).is_none());
+    /// assert!(balances.get_many_mut([&"alice", &"carol"]).is_none());
+    /// ```
+    pub fn get_many_mut<Q, const M: usize>(&mut self, keys: [&Q; M]) -> Option<[&mut V; M]>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let mut indices = [0; M];
+        for (index, key) in indices.iter_mut().zip(keys) {
+            *index = self.find(key)?.1;
+        }
+        if (1..M).any(|i| indices[..i].contains(&indices[i])) {
+            return None;
+        }
+
+        let entries = self.core.entries.as_mut_ptr();
+        // SAFETY: the indices are in bounds and pairwise distinct, so the references don't alias
+        Some(indices.map(|i| unsafe { &mut (*entries.add(i)).value }))
+    }
+
     /// Inserts a key-value pair into the map.
     ///
     /// If an equivalent key already exists in the map: the key remains and retains in its place in
@@ -918,6 +1203,11 @@ where
     /// assert_eq!(map[&37], "c");
     /// ```
     pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        #[cfg(feature = "test-util")]
+        if self.core.entries.take_injected_failure() {
+            return Err((key, value));
+        }
+
         let hash = hash_with(&key, &self.build_hasher);
         match self.core.insert(hash, key, value) {
             Insert::Success(inserted) => Ok(inserted.old_value),
@@ -925,6 +1215,137 @@ where
         }
     }
 
+    /// Inserts a key-value pair whose key the caller guarantees is not in the map yet, skipping
+    /// the comparisons with the keys already in it.
+    ///
+    /// This speeds up building a map from pairs that are known to be unique, e.g. a deduplicated
+    /// table in flash at boot. If the key is in the map already, the map ends up with two entries
+    /// for it; which one lookups find is unspecified, but this is not undefined behavior.
+    ///
+    /// Returns back the pair if the map is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexMap;
+    ///
+    /// const ROUTES: [(u16, u8); 3] = [(0x0a01, 1), (0x0a02, 2), (0x0b00, 1)];
+    ///
+    /// let mut routes = FnvIndexMap::<_, _, 4>::new();
+    /// for (addr, port) in ROUTES {
+    ///     routes.insert_unique_assume_absent(addr, port).unwrap();
+    /// }
+    /// assert_eq!(routes[&0x0a02], 2);
+    /// ```
+    pub fn insert_unique_assume_absent(&mut self, key: K, value: V) -> Result<(), (K, V)> {
+        #[cfg(feature = "test-util")]
+        if self.core.entries.take_injected_failure() {
+            return Err((key, value));
+        }
+
+        let hash = hash_with(&key, &self.build_hasher);
+        self.core.insert_unique(hash, key, value)
+    }
+
+    /// Inserts the pairs of `iter`, whose keys the caller guarantees are unique and not in the map
+    /// yet, until the map is full.
+    ///
+    /// See [`insert_unique_assume_absent`](IndexMap::insert_unique_assume_absent). Returns the
+    /// first pair that didn't fit, if any. The pairs after it are not consumed.
+    pub fn extend_unique_assume_absent<I>(&mut self, iter: I) -> Result<(), (K, V)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        for (key, value) in iter {
+            self.insert_unique_assume_absent(key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Inserts the pairs of `iter` with their keys' hashes, whose keys the caller guarantees are
+    /// unique and not in the map yet, until the map is full.
+    ///
+    /// Each hash must be what [`BuildHasher::hash_one`] returns for the key with this map's
+    /// hasher, e.g. computed by a build script next to a table in flash, so that building the map
+    /// at boot hashes nothing. Like with
+    /// [`insert_unique_assume_absent`](IndexMap::insert_unique_assume_absent), a wrong hash or a
+    /// duplicate key makes lookups miss or find either entry, but is not undefined behavior.
+    ///
+    /// Returns the first triple that didn't fit, if any. The triples after it are not consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::hash::BuildHasher;
+    ///
+    /// use hash32::{BuildHasherDefault, FnvHasher};
+    /// use heapless::FnvIndexMap;
+    ///
+    /// // usually generated ahead of time
+    /// let hasher = BuildHasherDefault::<FnvHasher>::default();
+    /// let routes = [(0x0a01_u16, 1_u8), (0x0a02, 2), (0x0b00, 1)]
+    ///     .map(|(addr, port)| (hasher.hash_one(addr), addr, port));
+    ///
+    /// let mut map = FnvIndexMap::<_, _, 4>::new();
+    /// map.extend_prehashed_assume_absent(routes).unwrap();
+    /// assert_eq!(map[&0x0a02], 2);
+    /// ```
+    pub fn extend_prehashed_assume_absent<I>(&mut self, iter: I) -> Result<(), (u64, K, V)>
+    where
+        I: IntoIterator<Item = (u64, K, V)>,
+    {
+        for (hash, key, value) in iter {
+            #[cfg(feature = "test-util")]
+            if self.core.entries.take_injected_failure() {
+                return Err((hash, key, value));
+            }
+
+            // NOTE the map only keeps the low bits, see `hash_with`
+            self.core
+                .insert_unique(HashValue(hash as u16), key, value)
+                .map_err(|(key, value)| (hash, key, value))?;
+        }
+        Ok(())
+    }
+
+    /// Clones the map into a new map with a capacity of `M`, in the same order.
+    ///
+    /// The keys aren't hashed again. Returns `Err(())` without cloning any pair if the pairs don't
+    /// fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexMap;
+    ///
+    /// let mut map = FnvIndexMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("c", 3).unwrap();
+    /// let small: FnvIndexMap<_, _, 4> = map.clone_into_capacity().unwrap();
+    /// assert_eq!(small, map);
+    /// assert!(map.clone_into_capacity::<2>().is_err());
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn clone_into_capacity<const M: usize>(&self) -> Result<IndexMap<K, V, S, M>, ()>
+    where
+        K: Clone,
+        V: Clone,
+        S: Clone,
+    {
+        if self.len() > M {
+            return Err(());
+        }
+        let mut new = IndexMap::with_hasher(self.build_hasher.clone());
+        for bucket in self.core.entries.iter() {
+            // the keys are distinct and there are at most `M` of them, so this can't fail
+            let _ = new
+                .core
+                .insert_unique(bucket.hash, bucket.key.clone(), bucket.value.clone());
+        }
+        Ok(new)
+    }
+
     /// Same as [`swap_remove`](Self::swap_remove)
     ///
     /// Computes in *O*(1) time (average).
@@ -964,6 +1385,136 @@ where
             .map(|(probe, found)| self.core.remove_found(probe, found).1)
     }
 
+    /// Moves all key-value pairs into a map with a capacity of `M`, keeping their order.
+    ///
+    /// The keys are not hashed again; their hashes stored in this map are reused to build the
+    /// new one. `M` must be at least `N`, which is checked at compile time.
+    ///
+    /// ```
+    /// use heapless::FnvIndexMap;
+    ///
+    /// let mut small = FnvIndexMap::<_, _, 4>::new();
+    /// small.insert("a", 1).unwrap();
+    /// small.insert("b", 2).unwrap();
+    ///
+    /// let mut large: FnvIndexMap<_, _, 16> = small.migrate_into();
+    /// large.insert("c", 3).unwrap();
+    ///
+    /// assert_eq!(large.capacity(), 16);
+    /// assert_eq!(large["a"], 1);
+    /// assert!(large.keys().eq(["a", "b", "c"].iter()));
+    /// ```
+    pub fn migrate_into<const M: usize>(self) -> IndexMap<K, V, S, M> {
+        // Const assert
+        crate::sealed::greater_than_1::<M>();
+        crate::sealed::power_of_two::<M>();
+        crate::sealed::greater_than_eq::<M, N>();
+
+        IndexMap {
+            core: self.core.migrate_into(),
+            build_hasher: self.build_hasher,
+        }
+    }
+
+    /// Splits the map into the pairs `(k, v)` for which `f(&k, &v)` returns `true` and those for
+    /// which it returns `false`, in a single pass.
+    ///
+    /// Both maps keep the order of the pairs. As with [`migrate_into`](IndexMap::migrate_into), the
+    /// keys are not hashed again, and `M` must be at least `N`, which is checked at compile time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexMap;
+    ///
+    /// let mut sessions = FnvIndexMap::<_, _, 8>::new();
+    /// sessions.insert(1, 0).unwrap();
+    /// sessions.insert(2, 120).unwrap();
+    /// sessions.insert(3, 5).unwrap();
+    ///
+    /// let (active, idle): (FnvIndexMap<_, _, 8>, _) =
+    ///     sessions.partition_into(|_, idle| *idle < 60);
+    /// assert!(active.keys().eq(&[1, 3]));
+    /// assert!(idle.keys().eq(&[2]));
+    /// assert_eq!(active[&3], 5);
+    /// ```
+    pub fn partition_into<const M: usize, F>(
+        self,
+        f: F,
+    ) -> (IndexMap<K, V, S, M>, IndexMap<K, V, S, M>)
+    where
+        F: FnMut(&K, &V) -> bool,
+        S: Clone,
+    {
+        // Const assert
+        crate::sealed::greater_than_1::<M>();
+        crate::sealed::power_of_two::<M>();
+        crate::sealed::greater_than_eq::<M, N>();
+
+        let (left, right) = self.core.partition_into(f);
+        (
+            IndexMap {
+                core: left,
+                build_hasher: self.build_hasher.clone(),
+            },
+            IndexMap {
+                core: right,
+                build_hasher: self.build_hasher,
+            },
+        )
+    }
+
+    /// Moves each pair `(k, v)` into the map `groups[f(&k, &v)]`, in a single pass.
+    ///
+    /// Pairs whose group is full stay in this map, in their order. A pair whose key is already in
+    /// its group replaces the value there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` returns an index that's out of bounds for `groups`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexMap;
+    ///
+    /// let mut sessions: FnvIndexMap<u8, u32, 8> =
+    ///     (0..6).map(|id| (id, u32::from(id) * 30)).collect();
+    ///
+    /// // fresh, stale and expired sessions
+    /// let mut groups: [FnvIndexMap<u8, u32, 2>; 3] = Default::default();
+    /// sessions.group_by_key_into(&mut groups, |_, idle| (*idle / 60) as usize);
+    ///
+    /// assert!(groups[0].keys().eq(&[0, 1]));
+    /// assert!(groups[1].keys().eq(&[2, 3]));
+    /// assert!(groups[2].keys().eq(&[4, 5]));
+    /// assert!(sessions.is_empty());
+    ///
+    /// sessions.insert(6, 0).unwrap();
+    /// sessions.group_by_key_into(&mut groups, |_, idle| (*idle / 60) as usize);
+    /// assert!(sessions.keys().eq(&[6]));
+    /// ```
+    pub fn group_by_key_into<S2, F, const M: usize>(
+        &mut self,
+        groups: &mut [IndexMap<K, V, S2, M>],
+        mut f: F,
+    ) where
+        S2: BuildHasher,
+        F: FnMut(&K, &V) -> usize,
+    {
+        let mut extractor = Extractor::new(&mut self.core);
+        let mut group = 0;
+
+        while let Some((key, value)) = extractor.next(|key, value| {
+            group = f(key, value);
+            let target = &groups[group];
+            target.len() < M || target.contains_key(key)
+        }) {
+            // NOTE(unwrap) there's room for the key, as checked above
+            groups[group].insert(key, value).ok().unwrap();
+        }
+    }
+
     /// Retains only the elements specified by the predicate.
     ///
     /// In other words, remove all pairs `(k, v)` for which `f(&k, &mut v)` returns `false`.
@@ -974,6 +1525,47 @@ where
         self.core.retain_in_order(move |k, v| f(k, v));
     }
 
+    /// Returns an iterator that removes and yields the pairs `(k, v)` for which `f(&k, &mut v)`
+    /// returns `true`.
+    ///
+    /// The remaining pairs keep their order. The map is traversed in a single pass, and its hash
+    /// index is rebuilt only once, when the iterator is dropped. If the iterator is dropped before
+    /// it's exhausted, the pairs that haven't been visited yet are kept.
+    ///
+    /// If the iterator is leaked (e.g. with [`mem::forget`](core::mem::forget)), the map is left
+    /// empty and its pairs are leaked.
+    ///
+    /// Computes in *O*(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexMap;
+    ///
+    /// let mut map: FnvIndexMap<_, _, 8> = (0..6).map(|i| (i, i * 10)).collect();
+    ///
+    /// let mut odd = map.extract_if(|k, _| k % 2 == 1);
+    /// assert_eq!(odd.next(), Some((1, 10)));
+    /// assert_eq!(odd.next(), Some((3, 30)));
+    /// drop(odd);
+    ///
+    /// assert!(map.keys().eq(&[0, 2, 4, 5]));
+    /// assert_eq!(map.get(&5), Some(&50));
+    /// ```
+    pub fn extract_if<F>(&mut self, f: F) -> ExtractIf<'_, K, V, F, N>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        ExtractIf {
+            extractor: Extractor::new(&mut self.core),
+            pred: f,
+        }
+    }
+
+    pub(crate) fn extractor(&mut self) -> Extractor<'_, K, V, N> {
+        Extractor::new(&mut self.core)
+    }
+
     /* Private API */
     /// Return probe (indices) and position (entries)
     fn find<Q>(&self, key: &Q) -> Option<(usize, usize)>
@@ -989,7 +1581,7 @@ where
     }
 }
 
-impl<'a, K, Q, V, S, const N: usize> ops::Index<&'a Q> for IndexMap<K, V, S, N>
+impl<K, Q, V, S, const N: usize> ops::Index<&Q> for IndexMap<K, V, S, N>
 where
     K: Eq + Hash + Borrow<Q>,
     Q: ?Sized + Eq + Hash,
@@ -1002,7 +1594,7 @@ where
     }
 }
 
-impl<'a, K, Q, V, S, const N: usize> ops::IndexMut<&'a Q> for IndexMap<K, V, S, N>
+impl<K, Q, V, S, const N: usize> ops::IndexMut<&Q> for IndexMap<K, V, S, N>
 where
     K: Eq + Hash + Borrow<Q>,
     Q: ?Sized + Eq + Hash,
@@ -1053,6 +1645,21 @@ where
     }
 }
 
+/// Affects [`insert`](IndexMap::insert), also when it would replace a value.
+#[cfg(feature = "test-util")]
+impl<K, V, S, const N: usize> crate::test_util::FailureInjection for IndexMap<K, V, S, N> {
+    fn fail_next(&mut self, k: usize) {
+        self.core.entries.fail_next(k);
+    }
+}
+
+#[cfg(capacity_stats)]
+impl<K, V, S, const N: usize> crate::capacity_stats::CapacityMonitor for IndexMap<K, V, S, N> {
+    fn monitor(&mut self, mark: &'static crate::capacity_stats::HighWaterMark) {
+        self.core.entries.monitor(mark);
+    }
+}
+
 impl<K, V, S, S2, const N: usize, const N2: usize> PartialEq<IndexMap<K, V, S2, N2>>
     for IndexMap<K, V, S, N>
 where
@@ -1065,7 +1672,7 @@ where
         self.len() == other.len()
             && self
                 .iter()
-                .all(|(key, value)| other.get(key).map_or(false, |v| *value == *v))
+                .all(|(key, value)| other.get(key).is_some_and(|v| *value == *v))
     }
 }
 
@@ -1087,7 +1694,10 @@ where
         I: IntoIterator<Item = (K, V)>,
     {
         for (k, v) in iterable {
-            self.insert(k, v).ok().unwrap();
+            if self.insert(k, v).is_err() {
+                capacity_overflow("IndexMap::extend overflow");
+                return;
+            }
         }
     }
 }
@@ -1261,15 +1871,137 @@ where
     HashValue(build_hasher.hash_one(key) as u16)
 }
 
+/// Removes entries from a [`CoreMap`] in a single pass, compacting the remaining ones as it goes.
+pub(crate) struct Extractor<'a, K, V, const N: usize>
+where
+    K: Eq + Hash,
+{
+    core: &'a mut CoreMap<K, V, N>,
+    /// The next entry to visit.
+    next: usize,
+    removed: usize,
+    len: usize,
+}
+
+impl<'a, K, V, const N: usize> Extractor<'a, K, V, N>
+where
+    K: Eq + Hash,
+{
+    fn new(core: &'a mut CoreMap<K, V, N>) -> Self {
+        let len = core.entries.len();
+        // Leak amplification: the entries are owned by the extractor until it's dropped.
+        unsafe { core.entries.set_len(0) };
+
+        Self {
+            core,
+            next: 0,
+            removed: 0,
+            len,
+        }
+    }
+
+    pub(crate) fn next<F>(&mut self, mut pred: F) -> Option<(K, V)>
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let entries = self.core.entries.as_mut_ptr();
+
+        while self.next < self.len {
+            unsafe {
+                let entry = entries.add(self.next);
+                let extract = pred(&(*entry).key, &mut (*entry).value);
+                self.next += 1;
+
+                if extract {
+                    self.removed += 1;
+                    let entry = ptr::read(entry);
+                    return Some((entry.key, entry.value));
+                } else if self.removed > 0 {
+                    let hole = entries.add(self.next - 1 - self.removed);
+                    ptr::copy_nonoverlapping(entry, hole, 1);
+                }
+            }
+        }
+        None
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.len - self.next
+    }
+}
+
+impl<K, V, const N: usize> Drop for Extractor<'_, K, V, N>
+where
+    K: Eq + Hash,
+{
+    fn drop(&mut self) {
+        unsafe {
+            let entries = self.core.entries.as_mut_ptr();
+            if self.removed > 0 {
+                ptr::copy(
+                    entries.add(self.next),
+                    entries.add(self.next - self.removed),
+                    self.len - self.next,
+                );
+            }
+            self.core.entries.set_len(self.len - self.removed);
+        }
+
+        if self.removed > 0 {
+            self.core.rebuild_indices();
+        }
+    }
+}
+
+/// An iterator that removes the pairs matching a predicate from an [`IndexMap`].
+///
+/// This `struct` is created by the [`extract_if`](IndexMap::extract_if) method on [`IndexMap`].
+/// See its documentation for more.
+pub struct ExtractIf<'a, K, V, F, const N: usize>
+where
+    K: Eq + Hash,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    extractor: Extractor<'a, K, V, N>,
+    pred: F,
+}
+
+impl<K, V, F, const N: usize> Iterator for ExtractIf<'_, K, V, F, N>
+where
+    K: Eq + Hash,
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.extractor.next(&mut self.pred)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.extractor.remaining()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{indexmap::Entry, FnvIndexMap};
 
-    use core::mem;
-
     #[test]
     fn size() {
+        use core::mem;
+
         const CAP: usize = 4;
+        // the testing features add `usize`-aligned fields to the entries `Vec`
+        #[allow(unused_mut)]
+        let mut features = 0;
+        #[cfg(feature = "test-util")]
+        {
+            features += mem::size_of::<crate::test_util::FailNext>();
+        }
+        #[cfg(capacity_stats)]
+        {
+            features += mem::size_of::<crate::capacity_stats::Monitor>();
+        }
         assert_eq!(
             mem::size_of::<FnvIndexMap<i16, u16, CAP>>(),
             CAP * mem::size_of::<u32>() + // indices
@@ -1277,10 +2009,57 @@ mod tests {
                      mem::size_of::<u16>() + // value
                      mem::size_of::<u16>() // hash
                 ) + // buckets
-                mem::size_of::<usize>() // entries.length
+                mem::size_of::<usize>() + // entries.length
+                features
         )
     }
 
+    #[test]
+    fn probing_reads_only_the_index() {
+        use core::{
+            hash::{Hash, Hasher},
+            sync::atomic::{AtomicUsize, Ordering},
+        };
+
+        static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+
+        #[derive(Debug)]
+        struct Key(u32);
+        impl PartialEq for Key {
+            fn eq(&self, other: &Self) -> bool {
+                COMPARISONS.fetch_add(1, Ordering::Relaxed);
+                self.0 == other.0
+            }
+        }
+        impl Eq for Key {}
+        impl Hash for Key {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.hash(state)
+            }
+        }
+
+        assert_eq!(core::mem::size_of::<Option<super::Pos>>(), 4);
+
+        let mut map = FnvIndexMap::<Key, [u8; 64], 16>::new();
+        for i in 0..12 {
+            map.insert_unique_assume_absent(Key(i), [i as u8; 64])
+                .unwrap();
+        }
+
+        COMPARISONS.store(0, Ordering::Relaxed);
+        for i in 0..12 {
+            assert_eq!(map.get(&Key(i)).map(|v| v[0]), Some(i as u8));
+        }
+        // one comparison per hit: no other entry was read
+        assert_eq!(COMPARISONS.load(Ordering::Relaxed), 12);
+
+        COMPARISONS.store(0, Ordering::Relaxed);
+        for i in 12..24 {
+            assert!(map.get(&Key(i)).is_none());
+        }
+        assert_eq!(COMPARISONS.load(Ordering::Relaxed), 0);
+    }
+
     #[test]
     fn partial_eq() {
         {
@@ -1323,6 +2102,108 @@ mod tests {
         }
     }
 
+    #[test]
+    fn insert_unique_assume_absent() {
+        let mut src = FnvIndexMap::<_, _, 8>::new();
+        for i in 0..8u32 {
+            src.insert(i * 16, i).unwrap();
+        }
+
+        let mut map = FnvIndexMap::<_, _, 8>::new();
+        map.insert_unique_assume_absent(0, 0).unwrap();
+        assert_eq!(
+            map.extend_unique_assume_absent((1..8).map(|i| (i * 16, i))),
+            Ok(())
+        );
+        assert_eq!(map, src);
+        for i in 0..8 {
+            assert_eq!(map.get(&(i * 16)), Some(&i));
+        }
+        assert_eq!(map.insert_unique_assume_absent(1, 1), Err((1, 1)));
+
+        // removing entries keeps the index consistent
+        map.remove(&32).unwrap();
+        assert_eq!(map.get(&32), None);
+        assert_eq!(map.get(&(7 * 16)), Some(&7));
+        assert_eq!(
+            map.extend_unique_assume_absent([(1, 1), (2, 2)]),
+            Err((2, 2))
+        );
+    }
+
+    #[test]
+    fn extend_prehashed_assume_absent() {
+        use core::hash::BuildHasher;
+
+        use hash32::{BuildHasherDefault, FnvHasher};
+
+        let hasher = BuildHasherDefault::<FnvHasher>::default();
+        let mut map = FnvIndexMap::<_, _, 8>::new();
+        map.insert(0, 0).unwrap();
+        assert_eq!(
+            map.extend_prehashed_assume_absent((1..7u32).map(|i| (hasher.hash_one(i), i, i * 10))),
+            Ok(())
+        );
+        for i in 0..7 {
+            assert_eq!(map.get(&i), Some(&(i * 10)));
+        }
+        assert!(map.keys().copied().eq(0..7));
+
+        let extra = [7, 8, 9].map(|i| (hasher.hash_one(i), i, i * 10));
+        assert_eq!(
+            map.extend_prehashed_assume_absent(extra),
+            Err((hasher.hash_one(8), 8, 80))
+        );
+        assert_eq!(map.get(&7), Some(&70));
+        assert_eq!(map.get(&8), None);
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut map = FnvIndexMap::<_, _, 8>::new();
+        for (i, key) in ["a", "b", "c", "d"].into_iter().enumerate() {
+            map.insert(key, i).unwrap();
+        }
+
+        // every reference points to its own value
+        let [a, c, b] = map.get_many_mut(["a", "c", "b"]).unwrap();
+        assert!(!core::ptr::eq(a, b) && !core::ptr::eq(a, c) && !core::ptr::eq(b, c));
+        core::mem::swap(a, c);
+        *b += 10;
+        assert!(map.values().copied().eq([2, 11, 0, 3]));
+
+        // equal keys are rejected even when they're different references
+        let (x, y) = (crate::String::<4>::try_from("d").unwrap(), "d");
+        assert!(map.get_many_mut(["a", x.as_str(), y]).is_none());
+        assert!(map.get_many_mut(["b", "b"]).is_none());
+
+        // a missing key fails the whole lookup
+        assert!(map.get_many_mut(["a", "e"]).is_none());
+        map.swap_remove("a").unwrap();
+        assert!(map.get_many_mut(["a"]).is_none());
+        assert_eq!(map.get_many_mut(["d"]), Some([&mut 3]));
+        assert!(map.get_many_mut::<str, 0>([]).is_some());
+        assert!(map.values().copied().eq([3, 11, 0]));
+    }
+
+    #[test]
+    fn clone_into_capacity() {
+        let mut map = FnvIndexMap::<u32, u32, 32>::new();
+        for i in 0..8 {
+            map.insert(i * 7, i).unwrap();
+        }
+        map.remove(&14).unwrap();
+
+        let small: FnvIndexMap<u32, u32, 8> = map.clone_into_capacity().unwrap();
+        assert!(small.iter().eq(map.iter()));
+        for i in 0..8 {
+            assert_eq!(small.get(&(i * 7)), map.get(&(i * 7)));
+        }
+        let large: FnvIndexMap<u32, u32, 64> = small.clone_into_capacity().unwrap();
+        assert_eq!(large, map);
+        assert!(map.clone_into_capacity::<4>().is_err());
+    }
+
     #[test]
     fn insert_replaces_on_full_map() {
         let mut a: FnvIndexMap<_, _, 2> = FnvIndexMap::new();
@@ -1570,4 +2451,150 @@ mod tests {
             assert_eq!(value, i + 1);
         }
     }
+
+    #[test]
+    fn migrate_into() {
+        let mut small = FnvIndexMap::<_, _, 16>::new();
+        for i in 0..16 {
+            small.insert(i, i * 10).unwrap();
+        }
+        small.remove(&3);
+
+        let mut large: FnvIndexMap<_, _, 64> = small.clone().migrate_into();
+        assert_eq!(large, small);
+        assert!(large.keys().eq(small.keys()));
+
+        for i in 16..64 {
+            large.insert(i, i * 10).unwrap();
+        }
+        assert_eq!(large.get(&3), None);
+        for i in (0..64).filter(|&i| i != 3) {
+            assert_eq!(large.get(&i), Some(&(i * 10)));
+        }
+        assert_eq!(large.remove(&15), Some(150));
+        assert_eq!(large.get(&14), Some(&140));
+
+        // Same capacity is allowed as well
+        let same: FnvIndexMap<_, _, 16> = small.clone().migrate_into();
+        assert_eq!(same, small);
+    }
+
+    #[test]
+    fn replace_entry_with() {
+        let mut map: FnvIndexMap<u8, u8, 4> = (0..4).map(|i| (i, i)).collect();
+
+        // replacing keeps the position
+        match map.entry(1).and_replace_entry_with(|k, v| Some(k + v + 10)) {
+            Entry::Occupied(o) => assert_eq!(*o.get(), 12),
+            Entry::Vacant(_) => panic!("entry was removed"),
+        }
+        assert!(map.iter().eq([(&0, &0), (&1, &12), (&2, &2), (&3, &3)]));
+
+        // removing leaves a usable vacant entry
+        match map.entry(0).and_replace_entry_with(|_, _| None) {
+            Entry::Occupied(_) => panic!("entry wasn't removed"),
+            Entry::Vacant(v) => assert_eq!(*v.insert(20).unwrap(), 20),
+        }
+        assert_eq!(map.len(), 4);
+        assert_eq!(map.get(&0), Some(&20));
+        for i in 1..4 {
+            assert!(map.contains_key(&i));
+        }
+
+        // vacant entries are left alone
+        map.remove(&2);
+        assert!(matches!(
+            map.entry(2).and_replace_entry_with(|_, _| unreachable!()),
+            Entry::Vacant(_)
+        ));
+
+        // a panic removes the entry without dropping the value twice
+        droppable!();
+        let mut map: FnvIndexMap<u8, Droppable, 4> = FnvIndexMap::new();
+        map.insert(0, Droppable::new()).ok().unwrap();
+        map.insert(1, Droppable::new()).ok().unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            if let Entry::Occupied(o) = map.entry(0) {
+                o.replace_entry_with(|_, _| panic!("oops"));
+            }
+        }));
+        assert!(result.is_err());
+        assert_eq!(Droppable::count(), 1);
+        assert!(map.keys().eq(&[1]));
+        core::mem::drop(map);
+        assert_eq!(Droppable::count(), 0);
+    }
+
+    #[test]
+    fn extract_if() {
+        droppable!();
+
+        let mut map: FnvIndexMap<u8, Droppable, 8> = FnvIndexMap::new();
+        for i in 0..8 {
+            map.insert(i, Droppable::new()).ok().unwrap();
+        }
+
+        // stop early: the unvisited entries are kept
+        let mut extracted = map.extract_if(|k, _| k % 3 == 0);
+        assert_eq!(extracted.next().map(|(k, _)| k), Some(0));
+        assert_eq!(extracted.next().map(|(k, _)| k), Some(3));
+        core::mem::drop(extracted);
+        assert_eq!(Droppable::count(), 6);
+        assert!(map.keys().eq(&[1, 2, 4, 5, 6, 7]));
+
+        assert_eq!(map.extract_if(|_, _| true).count(), 6);
+        assert!(map.is_empty());
+        assert_eq!(Droppable::count(), 0);
+
+        // the hash index is intact after extraction
+        let mut map: FnvIndexMap<u8, u8, 16> = (0..16).map(|i| (i, i)).collect();
+        map.extract_if(|k, _| k % 2 == 0).for_each(drop);
+        for i in 0..16 {
+            assert_eq!(map.get(&i).is_some(), i % 2 == 1);
+        }
+        map.insert(0, 0).unwrap();
+        assert_eq!(map.len(), 9);
+    }
+
+    #[test]
+    fn partition_and_group() {
+        let map: FnvIndexMap<u8, u8, 8> = (0..8).map(|i| (i, i)).collect();
+        let (even, odd): (FnvIndexMap<_, _, 16>, _) = map.partition_into(|k, _| k % 2 == 0);
+        assert!(even.keys().eq(&[0, 2, 4, 6]));
+        assert!(odd.keys().eq(&[1, 3, 5, 7]));
+        for i in 0..8 {
+            assert_eq!(even.contains_key(&i), i % 2 == 0);
+            assert_eq!(odd.contains_key(&i), i % 2 == 1);
+        }
+
+        let mut map: FnvIndexMap<u8, u8, 8> = (0..8).map(|i| (i, i)).collect();
+        let mut groups: [FnvIndexMap<u8, u8, 2>; 3] = Default::default();
+        groups[0].insert(3, 0).unwrap();
+        groups[0].insert(7, 0).unwrap();
+        map.group_by_key_into(&mut groups, |k, _| usize::from(k % 3));
+
+        // key 3 replaced the value in the full group 0, the other pairs of group 0 stayed
+        assert!(groups[0].iter().eq([(&3, &3), (&7, &0)]));
+        assert!(groups[1].keys().eq(&[1, 4]));
+        assert!(groups[2].keys().eq(&[2, 5]));
+        assert!(map.keys().eq(&[0, 6, 7]));
+        assert_eq!(map.get(&7), Some(&7));
+    }
+
+    #[test]
+    fn values_vec_sorted_by() {
+        let mut map: FnvIndexMap<u8, (u8, char), 8> = FnvIndexMap::new();
+        for (k, v) in [(0, (2, 'a')), (1, (1, 'b')), (2, (2, 'c')), (3, (0, 'd'))] {
+            map.insert(k, v).unwrap();
+        }
+
+        let mut out: crate::Vec<_, 4> = crate::Vec::new();
+        map.values_vec_sorted_by(&mut out, |v| v.0).unwrap();
+        assert!(out.iter().map(|v| v.1).eq(['d', 'b', 'a', 'c']));
+
+        let mut small: crate::Vec<_, 3> = crate::Vec::new();
+        small.push(&(9, 'z')).unwrap();
+        assert_eq!(map.values_vec_sorted_by(&mut small, |v| v.0), Err(()));
+        assert!(small.is_empty());
+    }
 }
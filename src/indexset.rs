@@ -1,4 +1,7 @@
-use crate::indexmap::{self, IndexMap};
+use crate::{
+    display::DisplayJoined,
+    indexmap::{self, IndexMap},
+};
 use core::{
     borrow::Borrow,
     fmt,
@@ -97,6 +100,15 @@ impl<T, S, const N: usize> IndexSet<T, BuildHasherDefault<S>, N> {
 }
 
 impl<T, S, const N: usize> IndexSet<T, S, N> {
+    /// Creates an empty `IndexSet` that hashes values with `build_hasher`.
+    ///
+    /// See [`IndexMap::with_hasher`].
+    pub const fn with_hasher(build_hasher: S) -> Self {
+        IndexSet {
+            map: IndexMap::with_hasher(build_hasher),
+        }
+    }
+
     /// Returns the number of elements the set can hold
     ///
     /// # Examples
@@ -133,6 +145,25 @@ impl<T, S, const N: usize> IndexSet<T, S, N> {
         }
     }
 
+    /// Returns an adapter that displays the values in insertion order separated by `separator`,
+    /// without building a [`String`](crate::String).
+    ///
+    /// See the [`display`](crate::display) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexSet;
+    ///
+    /// let mut set = FnvIndexSet::<_, 16>::new();
+    /// set.insert("a").unwrap();
+    /// set.insert("b").unwrap();
+    /// assert_eq!(format!("{}", set.display_joined(" ")), "a b");
+    /// ```
+    pub fn display_joined<'a>(&'a self, separator: &'a str) -> DisplayJoined<'a, Iter<'a, T>> {
+        DisplayJoined::new(self.iter(), separator)
+    }
+
     /// Get the first value
     ///
     /// Computes in *O*(1) time
@@ -452,6 +483,84 @@ where
             .map_err(|(k, _)| k)
     }
 
+    /// Adds a value that the caller guarantees is not in the set yet, skipping the comparisons
+    /// with the values already in it.
+    ///
+    /// See [`IndexMap::insert_unique_assume_absent`]. Returns back the value if the set is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexSet;
+    ///
+    /// const ALLOWED: [u32; 3] = [0x1001, 0x1002, 0x2001];
+    ///
+    /// let mut allowed = FnvIndexSet::<_, 4>::new();
+    /// allowed.extend_unique_assume_absent(ALLOWED).unwrap();
+    /// allowed.insert_unique_assume_absent(0x3001).unwrap();
+    /// assert!(allowed.contains(&0x1002));
+    /// assert_eq!(allowed.insert_unique_assume_absent(0x3002), Err(0x3002));
+    /// ```
+    pub fn insert_unique_assume_absent(&mut self, value: T) -> Result<(), T> {
+        self.map
+            .insert_unique_assume_absent(value, ())
+            .map_err(|(k, _)| k)
+    }
+
+    /// Adds the values of `iter`, which the caller guarantees are unique and not in the set yet,
+    /// until the set is full.
+    ///
+    /// See [`insert_unique_assume_absent`](IndexSet::insert_unique_assume_absent). Returns the
+    /// first value that didn't fit, if any. The values after it are not consumed.
+    pub fn extend_unique_assume_absent<I>(&mut self, iter: I) -> Result<(), T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for value in iter {
+            self.insert_unique_assume_absent(value)?;
+        }
+        Ok(())
+    }
+
+    /// Adds the values of `iter` with their hashes, which the caller guarantees are unique and not
+    /// in the set yet, until the set is full.
+    ///
+    /// See [`IndexMap::extend_prehashed_assume_absent`]. Returns the first pair that didn't fit,
+    /// if any. The pairs after it are not consumed.
+    pub fn extend_prehashed_assume_absent<I>(&mut self, iter: I) -> Result<(), (u64, T)>
+    where
+        I: IntoIterator<Item = (u64, T)>,
+    {
+        self.map
+            .extend_prehashed_assume_absent(iter.into_iter().map(|(hash, value)| (hash, value, ())))
+            .map_err(|(hash, value, ())| (hash, value))
+    }
+
+    /// Clones the set into a new set with a capacity of `M`, in the same order.
+    ///
+    /// Returns `Err(())` without cloning any value if the values don't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::FnvIndexSet;
+    ///
+    /// let mut set = FnvIndexSet::<_, 16>::new();
+    /// set.insert(3).unwrap();
+    /// let small: FnvIndexSet<_, 2> = set.clone_into_capacity().unwrap();
+    /// assert_eq!(small, set);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn clone_into_capacity<const M: usize>(&self) -> Result<IndexSet<T, S, M>, ()>
+    where
+        T: Clone,
+        S: Clone,
+    {
+        Ok(IndexSet {
+            map: self.map.clone_into_capacity()?,
+        })
+    }
+
     /// Removes a value from the set. Returns `true` if the value was present in the set.
     ///
     /// The value may be any borrowed form of the set's value type, but `Hash` and `Eq` on the
@@ -485,6 +594,38 @@ where
     {
         self.map.retain(move |k, _| f(k));
     }
+
+    /// Returns an iterator that removes and yields the elements for which `f` returns `true`.
+    ///
+    /// The remaining elements keep their order. The set is traversed in a single pass, and its
+    /// hash index is rebuilt only once, when the iterator is dropped. If the iterator is dropped
+    /// before it's exhausted, the elements that haven't been visited yet are kept.
+    ///
+    /// Computes in *O*(n) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{FnvIndexSet, Vec};
+    ///
+    /// let mut peers: FnvIndexSet<u16, 8> = [7, 12, 3, 40, 9].into_iter().collect();
+    ///
+    /// let stale: Vec<u16, 8> = peers.extract_if(|&id| id < 10).collect();
+    /// assert_eq!(stale, [7, 3, 9]);
+    ///
+    /// assert!(peers.iter().eq(&[12, 40]));
+    /// assert!(peers.contains(&40));
+    /// assert!(!peers.contains(&3));
+    /// ```
+    pub fn extract_if<F>(&mut self, f: F) -> ExtractIf<'_, T, F, N>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        ExtractIf {
+            extractor: self.map.extractor(),
+            pred: f,
+        }
+    }
 }
 
 impl<T, S, const N: usize> Clone for IndexSet<T, S, N>
@@ -519,6 +660,21 @@ where
     }
 }
 
+/// Affects [`insert`](IndexSet::insert), also when the value is already in the set.
+#[cfg(feature = "test-util")]
+impl<T, S, const N: usize> crate::test_util::FailureInjection for IndexSet<T, S, N> {
+    fn fail_next(&mut self, k: usize) {
+        self.map.fail_next(k);
+    }
+}
+
+#[cfg(capacity_stats)]
+impl<T, S, const N: usize> crate::capacity_stats::CapacityMonitor for IndexSet<T, S, N> {
+    fn monitor(&mut self, mark: &'static crate::capacity_stats::HighWaterMark) {
+        self.map.monitor(mark);
+    }
+}
+
 impl<T, S1, S2, const N1: usize, const N2: usize> PartialEq<IndexSet<T, S2, N2>>
     for IndexSet<T, S1, N1>
 where
@@ -609,6 +765,36 @@ impl<'a, T> Clone for Iter<'a, T> {
     }
 }
 
+/// An iterator that removes the elements matching a predicate from an [`IndexSet`].
+///
+/// This `struct` is created by the [`extract_if`](IndexSet::extract_if) method on [`IndexSet`].
+/// See its documentation for more.
+pub struct ExtractIf<'a, T, F, const N: usize>
+where
+    T: Eq + Hash,
+    F: FnMut(&T) -> bool,
+{
+    extractor: indexmap::Extractor<'a, T, (), N>,
+    pred: F,
+}
+
+impl<T, F, const N: usize> Iterator for ExtractIf<'_, T, F, N>
+where
+    T: Eq + Hash,
+    F: FnMut(&T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let pred = &mut self.pred;
+        self.extractor.next(|k, _| pred(k)).map(|(k, ())| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.extractor.remaining()))
+    }
+}
+
 pub struct Difference<'a, T, S, const N: usize>
 where
     S: BuildHasher,
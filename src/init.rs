@@ -0,0 +1,499 @@
+//! Primitives for initializing `static` containers exactly once, without `static mut`.
+//!
+//! A container in a `static mut` is created by a `const fn` and then handed out with
+//! `unsafe { &mut X }`, which is only sound as long as the program takes that reference once. The
+//! types in this module check that at runtime instead:
+//!
+//! - [`StaticCell`] moves a value in once, and hands out a single `&'static mut` to it, e.g. to
+//!   [`split`](crate::spsc::Queue::split) a queue.
+//! - [`OnceCell`] holds a value that is set once and then shared as a `&'static`, e.g. a lookup
+//!   table that's built at startup.
+//! - [`Once`] runs an initialization routine once, however many contexts call it.
+//! - [`SpinBarrier`] makes the cores of a multi-core chip wait for each other, e.g. until each of
+//!   them has initialized the containers it owns.
+//!
+//! None of them needs a mutex or an allocator. Contexts that find another context in the middle of
+//! the initialization spin until it's done. On a single core that would never end if an interrupt
+//! handler spins on the `main` context that it preempted, so handlers should only use the methods
+//! that never wait: [`OnceCell::get`], [`OnceCell::set`] and [`Once::is_completed`].
+//!
+//! NOTE: This module requires atomic CAS operations. On targets without them, they are emulated by
+//! the [`portable-atomic`](https://crates.io/crates/portable-atomic) crate, e.g. with critical
+//! sections.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{init::StaticCell, spsc::Queue};
+//!
+//! static QUEUE: StaticCell<Queue<u8, 4>> = StaticCell::new();
+//!
+//! let (mut producer, mut consumer) = QUEUE.init(Queue::new()).split();
+//! producer.enqueue(1).unwrap();
+//! assert_eq!(consumer.dequeue(), Some(1));
+//!
+//! // a second `&'static mut` would alias the first one
+//! assert!(QUEUE.try_init(Queue::new()).is_err());
+//! ```
+
+use core::{cell::UnsafeCell, fmt, hint, mem::MaybeUninit};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+const INCOMPLETE: u8 = 0;
+const RUNNING: u8 = 1;
+const COMPLETE: u8 = 2;
+
+/// Runs an initialization routine exactly once.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::init::Once;
+///
+/// static INIT: Once = Once::new();
+///
+/// let mut runs = 0;
+/// INIT.call_once(|| runs += 1);
+/// INIT.call_once(|| runs += 1);
+/// assert_eq!(runs, 1);
+/// assert!(INIT.is_completed());
+/// ```
+pub struct Once {
+    state: AtomicU8,
+}
+
+impl Once {
+    /// Creates a `Once` that hasn't run yet.
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(INCOMPLETE),
+        }
+    }
+
+    /// Returns `true` once an initialization routine has run to completion.
+    pub fn is_completed(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+
+    /// Runs `f` if no initialization routine has run yet.
+    ///
+    /// If another context is running its routine, this spins until that one is done. If that
+    /// routine panics, the next call runs its own routine instead.
+    pub fn call_once<F>(&self, f: F)
+    where
+        F: FnOnce(),
+    {
+        loop {
+            match self.start() {
+                Ok(guard) => {
+                    f();
+                    guard.complete();
+                    return;
+                }
+                Err(COMPLETE) => return,
+                Err(_) => hint::spin_loop(),
+            }
+        }
+    }
+
+    /// Claims the right to run the initialization routine, or returns the current state.
+    fn start(&self) -> Result<Running<'_>, u8> {
+        self.state
+            .compare_exchange(INCOMPLETE, RUNNING, Ordering::Acquire, Ordering::Acquire)
+            .map(|_| Running { once: self })
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Once {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Once")
+            .field("completed", &self.is_completed())
+            .finish()
+    }
+}
+
+/// Marks a [`Once`] as running until completed, or reset if the routine panics.
+struct Running<'a> {
+    once: &'a Once,
+}
+
+impl Running<'_> {
+    fn complete(self) {
+        self.once.state.store(COMPLETE, Ordering::Release);
+        core::mem::forget(self);
+    }
+}
+
+impl Drop for Running<'_> {
+    fn drop(&mut self) {
+        self.once.state.store(INCOMPLETE, Ordering::Release);
+    }
+}
+
+/// A cell that is written once and then shared.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{init::OnceCell, LinearMap};
+///
+/// static UNITS: OnceCell<LinearMap<&str, u32, 4>> = OnceCell::new();
+///
+/// fn scale(unit: &str) -> Option<u32> {
+///     let units = UNITS.get_or_init(|| {
+///         let mut units = LinearMap::new();
+///         units.insert("mV", 1).unwrap();
+///         units.insert("V", 1000).unwrap();
+///         units
+///     });
+///     units.get(unit).copied()
+/// }
+///
+/// assert_eq!(scale("V"), Some(1000));
+/// assert_eq!(scale("kV"), None);
+/// assert!(UNITS.set(LinearMap::new()).is_err());
+/// ```
+pub struct OnceCell<T> {
+    once: Once,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> OnceCell<T> {
+    /// Creates an empty cell.
+    pub const fn new() -> Self {
+        Self {
+            once: Once::new(),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Returns the value, or `None` if the cell hasn't been written yet.
+    ///
+    /// This never waits, so it can be called from an interrupt handler.
+    pub fn get(&self) -> Option<&T> {
+        if self.once.is_completed() {
+            // NOTE(unsafe) the value was written before `COMPLETE` was released, and is never
+            // written again while shared
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value mutably, or `None` if the cell hasn't been written yet.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if self.once.is_completed() {
+            Some(unsafe { self.value.get_mut().assume_init_mut() })
+        } else {
+            None
+        }
+    }
+
+    /// Writes `value` to the cell.
+    ///
+    /// Returns back the `value` if the cell has been written already, or another context is
+    /// writing to it. This never waits, so it can be called from an interrupt handler.
+    pub fn set(&self, value: T) -> Result<(), T> {
+        match self.once.start() {
+            Ok(guard) => {
+                // NOTE(unsafe) the guard excludes other writers, and readers wait for `COMPLETE`
+                unsafe { (*self.value.get()).write(value) };
+                guard.complete();
+                Ok(())
+            }
+            Err(_) => Err(value),
+        }
+    }
+
+    /// Returns the value, writing the result of `f` to the cell first if it's empty.
+    ///
+    /// If another context is writing to the cell, this spins until it's done.
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        self.once.call_once(|| {
+            // NOTE(unsafe) `call_once` excludes other writers, and readers wait for `COMPLETE`
+            unsafe { (*self.value.get()).write(f()) };
+        });
+        // NOTE(unsafe) `call_once` only returns once a value was written
+        unsafe { (*self.value.get()).assume_init_ref() }
+    }
+
+    /// Takes the value out of the cell, leaving it empty.
+    pub fn take(&mut self) -> Option<T> {
+        if self.once.is_completed() {
+            *self.once.state.get_mut() = INCOMPLETE;
+            Some(unsafe { self.value.get_mut().assume_init_read() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the value, or `None` if the cell hasn't been written yet.
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for OnceCell<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnceCell").field(&self.get()).finish()
+    }
+}
+
+impl<T> Drop for OnceCell<T> {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+unsafe impl<T> Sync for OnceCell<T> where T: Send + Sync {}
+
+/// A `static` that a value is moved into once, and that hands out a single `&'static mut` to it.
+///
+/// This replaces the `static mut` and `unsafe { &mut X }` pattern, which creates a second
+/// reference to the same container if it runs twice. See the
+/// [module-level documentation](self) for an example.
+pub struct StaticCell<T> {
+    taken: AtomicBool,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> StaticCell<T> {
+    /// Creates an empty cell.
+    pub const fn new() -> Self {
+        Self {
+            taken: AtomicBool::new(false),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    /// Moves `value` into the cell, and returns the only reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the cell has been initialized already.
+    pub fn init(&'static self, value: T) -> &'static mut T {
+        match self.try_init(value) {
+            Ok(value) => value,
+            Err(_) => panic!("StaticCell: initialized twice"),
+        }
+    }
+
+    /// Moves `value` into the cell, and returns the only reference to it.
+    ///
+    /// Returns back the `value` if the cell has been initialized already.
+    // the `taken` flag makes the reference unique
+    #[allow(clippy::mut_from_ref)]
+    pub fn try_init(&'static self, value: T) -> Result<&'static mut T, T> {
+        if self.taken.swap(true, Ordering::AcqRel) {
+            return Err(value);
+        }
+        // NOTE(unsafe) only the first call gets here, and the cell is never dropped since it's
+        // `'static`
+        Ok(unsafe { (*self.value.get()).write(value) })
+    }
+
+    /// Returns `true` if the cell has been initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.taken.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> Default for StaticCell<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for StaticCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The value is borrowed mutably by whoever initialized the cell.
+        f.debug_struct("StaticCell")
+            .field("initialized", &self.is_initialized())
+            .finish_non_exhaustive()
+    }
+}
+
+unsafe impl<T> Sync for StaticCell<T> where T: Send {}
+
+/// A barrier that a fixed number of contexts wait at, spinning, until all of them have arrived.
+///
+/// The barrier can be reused: once all contexts have passed it, it waits for the same number of
+/// contexts again.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::init::SpinBarrier;
+/// use std::thread;
+///
+/// static READY: SpinBarrier = SpinBarrier::new(2);
+///
+/// // e.g. the second core, which mustn't use the shared containers before they exist
+/// let core1 = thread::spawn(|| READY.wait());
+/// let leaders = [READY.wait(), core1.join().unwrap()];
+/// assert_eq!(leaders.iter().filter(|leader| **leader).count(), 1);
+/// ```
+pub struct SpinBarrier {
+    contexts: usize,
+    arrived: AtomicUsize,
+    generation: AtomicUsize,
+}
+
+impl SpinBarrier {
+    /// Creates a barrier for `contexts` contexts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `contexts` is 0.
+    pub const fn new(contexts: usize) -> Self {
+        assert!(contexts > 0, "SpinBarrier: no contexts to wait for");
+        Self {
+            contexts,
+            arrived: AtomicUsize::new(0),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    /// Spins until all contexts have called `wait`.
+    ///
+    /// Everything the contexts did before `wait` is visible to all of them after it. Returns `true`
+    /// in exactly one of the contexts, the last one to arrive.
+    pub fn wait(&self) -> bool {
+        let generation = self.generation.load(Ordering::Acquire);
+        if self.arrived.fetch_add(1, Ordering::AcqRel) + 1 == self.contexts {
+            // the others don't touch `arrived` again until they see the next generation
+            self.arrived.store(0, Ordering::Relaxed);
+            self.generation
+                .store(generation.wrapping_add(1), Ordering::Release);
+            true
+        } else {
+            while self.generation.load(Ordering::Acquire) == generation {
+                hint::spin_loop();
+            }
+            false
+        }
+    }
+}
+
+impl fmt::Debug for SpinBarrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SpinBarrier")
+            .field("contexts", &self.contexts)
+            .field("arrived", &self.arrived.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        panic::{catch_unwind, AssertUnwindSafe},
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    use super::{Once, OnceCell, SpinBarrier, StaticCell};
+
+    #[test]
+    fn once() {
+        let once = Once::new();
+        let result = catch_unwind(AssertUnwindSafe(|| once.call_once(|| panic!())));
+        assert!(result.is_err());
+        assert!(!once.is_completed());
+
+        // the panic left the routine to the next caller
+        let mut runs = 0;
+        once.call_once(|| runs += 1);
+        once.call_once(|| runs += 1);
+        assert_eq!(runs, 1);
+        assert!(once.is_completed());
+    }
+
+    #[test]
+    fn once_cell() {
+        droppable!();
+
+        let mut cell: OnceCell<Droppable> = OnceCell::new();
+        assert!(cell.get().is_none());
+        cell.set(Droppable::new()).ok().unwrap();
+        assert!(cell.set(Droppable::new()).is_err());
+        assert_eq!(Droppable::count(), 1);
+        assert!(cell.get_mut().is_some());
+
+        assert!(cell.take().is_some());
+        assert_eq!(Droppable::count(), 0);
+        cell.get_or_init(Droppable::new);
+        cell.get_or_init(|| unreachable!());
+        assert_eq!(Droppable::count(), 1);
+        core::mem::drop(cell);
+        assert_eq!(Droppable::count(), 0);
+
+        // a value that is written while another context reads is seen whole
+        static CELL: OnceCell<[usize; 4]> = OnceCell::new();
+        let reader = thread::spawn(|| loop {
+            if let Some(value) = CELL.get() {
+                return *value;
+            }
+            thread::yield_now();
+        });
+        CELL.set([1, 2, 3, 4]).unwrap();
+        assert_eq!(reader.join().unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn static_cell() {
+        static CELL: StaticCell<crate::Vec<u8, 4>> = StaticCell::new();
+        assert!(!CELL.is_initialized());
+        let v = CELL.init(crate::Vec::new());
+        v.push(1).unwrap();
+        assert!(CELL.try_init(crate::Vec::new()).is_err());
+        let result = catch_unwind(|| CELL.init(crate::Vec::new()));
+        assert!(result.is_err());
+        assert_eq!(v, &[1]);
+    }
+
+    #[test]
+    fn spin_barrier() {
+        static BARRIER: SpinBarrier = SpinBarrier::new(3);
+        static ROUNDS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+        let threads = [0, 1, 2].map(|_| {
+            thread::spawn(|| {
+                let mut leader = 0;
+                for round in ROUNDS.iter() {
+                    round.fetch_add(1, Ordering::Relaxed);
+                    leader += usize::from(BARRIER.wait());
+                    // everyone has arrived at this round
+                    assert_eq!(round.load(Ordering::Relaxed), 3);
+                }
+                leader
+            })
+        });
+        let leaders: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+        assert_eq!(leaders, 2);
+    }
+}
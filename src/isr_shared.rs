@@ -0,0 +1,204 @@
+//! A wrapper for containers shared between `main` and interrupt contexts, with debug-build
+//! accounting of unique access.
+//!
+//! Sharing a `static mut Vec` (or any other container) between `main` and an interrupt handler
+//! requires the programmer to guarantee that the two contexts never access it at the same time,
+//! usually by masking the interrupt or by relying on task priorities. Getting this wrong does not
+//! crash; it silently corrupts the container.
+//!
+//! [`IsrShared`] makes the exclusive access explicit: every access goes through
+//! [`IsrShared::claim`], which hands out a [`Claim`] guard that releases the container when
+//! dropped. In development builds (`debug_assertions` enabled) the wrapper tracks whether a claim
+//! is outstanding and panics as soon as a second context tries to claim the container, turning a
+//! data race into an immediate, debuggable failure. In release builds the accounting is compiled
+//! out and `IsrShared<T>` has the same size as `T`.
+//!
+//! NOTE: The claim flag only needs atomic load/store. This is sufficient to detect a preempting
+//! context on single-core systems, where an interrupt handler always runs to completion before
+//! the preempted context resumes. It is not a lock and does not make concurrent access from
+//! several cores sound.
+//!
+//! # Example
+//!
+//! ```
+//! use heapless::{isr_shared::IsrShared, Vec};
+//!
+//! static SAMPLES: IsrShared<Vec<u16, 8>> = IsrShared::new(Vec::new());
+//!
+//! // in the interrupt handler
+//! // Safety: `main` masks this interrupt while it holds a claim.
+//! unsafe { SAMPLES.claim() }.push(42).unwrap();
+//!
+//! // in `main`, with the interrupt masked
+//! let mut samples = unsafe { SAMPLES.claim() };
+//! assert_eq!(samples.pop(), Some(42));
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+#[cfg(all(debug_assertions, not(feature = "portable-atomic")))]
+use core::sync::atomic;
+#[cfg(all(debug_assertions, feature = "portable-atomic"))]
+use portable_atomic as atomic;
+
+#[cfg(debug_assertions)]
+use atomic::{AtomicBool, Ordering};
+
+/// A container shared between execution contexts, with claim/release accounting in debug builds.
+///
+/// See the [module-level documentation](self) for details.
+pub struct IsrShared<T> {
+    data: UnsafeCell<T>,
+    #[cfg(debug_assertions)]
+    claimed: AtomicBool,
+}
+
+impl<T> IsrShared<T> {
+    /// Wraps `value` for sharing between contexts.
+    pub const fn new(value: T) -> Self {
+        Self {
+            data: UnsafeCell::new(value),
+            #[cfg(debug_assertions)]
+            claimed: AtomicBool::new(false),
+        }
+    }
+
+    /// Claims unique access to the wrapped value until the returned guard is dropped.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the value is already claimed.
+    ///
+    /// # Safety
+    ///
+    /// No other [`Claim`] on this value may be alive for as long as the returned guard is, e.g.
+    /// because every other context that claims it is masked for that duration. In debug builds a
+    /// violation of this rule is detected and causes a panic instead of a data race.
+    pub unsafe fn claim(&self) -> Claim<'_, T> {
+        #[cfg(debug_assertions)]
+        {
+            assert!(
+                !self.claimed.load(Ordering::Acquire),
+                "IsrShared: value claimed while already claimed by another context"
+            );
+            self.claimed.store(true, Ordering::Release);
+        }
+
+        Claim { shared: self }
+    }
+
+    /// Returns a mutable reference to the wrapped value.
+    ///
+    /// This needs no claim since the exclusive borrow statically guarantees unique access.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Consumes the wrapper, returning the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T> Default for IsrShared<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for IsrShared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The value can't be printed without claiming it.
+        f.debug_struct("IsrShared").finish_non_exhaustive()
+    }
+}
+
+unsafe impl<T> Sync for IsrShared<T> where T: Send {}
+
+/// Unique access to the value of an [`IsrShared`], released on drop.
+///
+/// Comes from [`IsrShared::claim`].
+pub struct Claim<'a, T> {
+    shared: &'a IsrShared<T>,
+}
+
+impl<T> Claim<'_, T> {
+    /// Releases the claim. Same as calling `drop`.
+    #[inline]
+    pub fn release(self) {
+        drop(self)
+    }
+}
+
+impl<T> Deref for Claim<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.shared.data.get() }
+    }
+}
+
+impl<T> DerefMut for Claim<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.shared.data.get() }
+    }
+}
+
+impl<T> fmt::Debug for Claim<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T> Drop for Claim<'_, T> {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        self.shared.claimed.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IsrShared;
+    use crate::Vec;
+
+    #[test]
+    fn claim_release() {
+        static SHARED: IsrShared<Vec<u8, 4>> = IsrShared::new(Vec::new());
+
+        unsafe { SHARED.claim() }.push(1).unwrap();
+
+        let mut claim = unsafe { SHARED.claim() };
+        claim.push(2).unwrap();
+        claim.release();
+
+        assert_eq!(&**unsafe { SHARED.claim() }, &[1, 2]);
+    }
+
+    #[test]
+    fn get_mut_into_inner() {
+        let mut shared: IsrShared<Vec<u8, 4>> = IsrShared::new(Vec::new());
+        shared.get_mut().push(1).unwrap();
+        assert_eq!(shared.into_inner(), [1]);
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "already claimed")]
+    fn double_claim() {
+        let shared: IsrShared<Vec<u8, 4>> = IsrShared::new(Vec::new());
+
+        let _main = unsafe { shared.claim() };
+        let _isr = unsafe { shared.claim() };
+    }
+}
@@ -17,9 +17,9 @@
 //! assert_eq!(xs.pop(), Some(42));
 //!
 //! // in a `static` variable
-//! static mut XS: Vec<u8, 8> = Vec::new();
+//! static XS: heapless::init::StaticCell<Vec<u8, 8>> = heapless::init::StaticCell::new();
 //!
-//! let xs = unsafe { &mut XS };
+//! let xs = XS.init(Vec::new());
 //!
 //! xs.push(42);
 //! assert_eq!(xs.pop(), Some(42));
@@ -51,17 +51,33 @@
     doc = "- [`Box`](pool::boxed::Box) -- like `std::boxed::Box` but backed by a lock-free memory pool rather than `#[global_allocator]`"
 )]
 //! - [`BinaryHeap`] -- priority queue
+//! - [`FlatVec`] -- variable-length segments stored back to back in one pool
+//! - [`HybridVec`] -- `Vec` that moves to the heap when full, with the `alloc` feature
+//! - [`IdAllocator`] -- allocator of integer IDs backed by a bitset
 //! - [`IndexMap`] -- hash table
 //! - [`IndexSet`] -- hash set
 //! - [`LinearMap`]
+//! - [`RecordLog`] -- append-only log of byte records that drops the oldest records when full
 #![cfg_attr(
     any(arm_llsc, target_arch = "x86"),
     doc = "- [`Object`](pool::object::Object) -- objects managed by an object pool"
 )]
 //! - [`String`]
+//! - [`StrPool`] -- pool of strings that stores each distinct string once
 //! - [`Vec`]
 //! - [`mpmc::Q*`](mpmc) -- multiple producer multiple consumer lock-free queue
 //! - [`spsc::Queue`] -- single producer single consumer lock-free queue
+//! - [`atomic_deque::AtomicDeque`] -- ring buffer pushed to and popped from by two contexts
+//!   through a shared reference
+//! - [`work_queue::WorkQueue`] -- job queue with per-job completion notification
+//! - [`isr_shared::IsrShared`] -- debug-checked unique access to containers shared with interrupts
+//! - [`double_buffer::DoubleBuffer`] -- ping-pong buffer for handing blocks from a writer to a
+//!   reader
+//! - [`mailbox::Mailbox`] -- single-slot channel whose latest value wins
+//! - [`rcu::RcuCell`] -- double-buffered value that readers see consistently while it's replaced
+//! - [`seqlock::SeqLock`] -- value updated in place that readers copy out consistently or retry
+//! - [`init::StaticCell`], [`init::OnceCell`] -- `static`s that are initialized exactly once,
+//!   without `static mut`
 //!
 //! # Minimum Supported Rust Version (MSRV)
 //!
@@ -75,39 +91,116 @@
 #![deny(missing_docs)]
 #![deny(warnings)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+pub use backpressure::Backpressure;
 pub use binary_heap::BinaryHeap;
-pub use deque::Deque;
-pub use histbuf::{HistoryBuffer, OldestOrdered};
+pub use deque::{
+    ByteReader as DequeByteReader, CapacityView as DequeCapacityView, Deque,
+    MakeContiguous as DequeMakeContiguous,
+};
+pub use flat_vec::{FlatVec, Iter as FlatVecIter};
+pub use histbuf::{HistoryBuffer, ObservedHistoryBuffer, OldestOrdered};
+pub use hybrid_vec::HybridVec;
+pub use id_allocator::{IdAllocator, VersionedId, VersionedIdAllocator};
 pub use indexmap::{
-    Bucket, Entry, FnvIndexMap, IndexMap, Iter as IndexMapIter, IterMut as IndexMapIterMut,
-    Keys as IndexMapKeys, OccupiedEntry, Pos, VacantEntry, Values as IndexMapValues,
-    ValuesMut as IndexMapValuesMut,
+    Bucket, Entry, ExtractIf as IndexMapExtractIf, FnvIndexMap, IndexMap, Iter as IndexMapIter,
+    IterMut as IndexMapIterMut, Keys as IndexMapKeys, OccupiedEntry, Pos, VacantEntry,
+    Values as IndexMapValues, ValuesMut as IndexMapValuesMut,
 };
-pub use indexset::{FnvIndexSet, IndexSet, Iter as IndexSetIter};
+pub use indexset::{ExtractIf as IndexSetExtractIf, FnvIndexSet, IndexSet, Iter as IndexSetIter};
 pub use linear_map::LinearMap;
+pub use record_log::{Iter as RecordLogIter, RecordLog};
+pub use str_pool::{Iter as StrPoolIter, StrPool};
 pub use string::String;
-pub use vec::Vec;
+pub use try_collect::{TryCollect, TryFromIterator};
+pub use tx_chunks::{TxChunks, TxCursor};
+pub use vec::{CapacityView as VecCapacityView, FrozenVec, RetainProgress, Vec};
 
 #[macro_use]
 #[cfg(test)]
 mod test_helpers;
 
+#[macro_use]
+mod sync;
+
+mod backpressure;
 mod deque;
+mod flat_vec;
 mod histbuf;
+mod hybrid_vec;
+mod id_allocator;
 mod indexmap;
 mod indexset;
 mod linear_map;
+mod record_log;
+mod str_pool;
 pub mod string;
+mod try_collect;
+mod tx_chunks;
 mod vec;
 
 #[cfg(feature = "serde")]
-mod de;
+pub mod de;
 #[cfg(feature = "serde")]
 mod ser;
 
+pub mod aligned;
+#[cfg(any(
+    // assume we have all atomics available if we're using portable-atomic
+    feature = "portable-atomic",
+    // the checks for concurrent use need atomic read-modify-write operations
+    target_has_atomic = "ptr"
+))]
+pub mod atomic_deque;
 pub mod binary_heap;
+#[cfg(feature = "bits")]
+mod bits;
+pub mod capacity;
+// `capacity-stats` on a target with atomic read-modify-write operations, or with `portable-atomic`,
+// see `build.rs`
+#[cfg(capacity_stats)]
+pub mod capacity_stats;
+#[cfg(all(feature = "capacity-stats", not(capacity_stats)))]
+compile_error!(
+    "the `capacity-stats` feature needs atomic compare-and-swap, enable `portable-atomic` on this \
+     target"
+);
 #[cfg(feature = "defmt-03")]
 mod defmt;
+pub mod display;
+#[cfg(any(
+    // assume we have all atomics available if we're using portable-atomic
+    feature = "portable-atomic",
+    // swapping needs atomic read-modify-write operations
+    target_has_atomic = "ptr"
+))]
+pub mod double_buffer;
+pub mod fnv;
+#[cfg(any(
+    // assume we have all atomics available if we're using portable-atomic
+    feature = "portable-atomic",
+    // claiming the initialization needs atomic read-modify-write operations
+    target_has_atomic = "ptr"
+))]
+pub mod init;
+#[cfg(any(
+    // assume we have all atomics available if we're using portable-atomic
+    feature = "portable-atomic",
+    // the claim flag only needs atomic load/store, see `spsc` below.
+    target_has_atomic = "ptr",
+    has_atomic_load_store
+))]
+pub mod isr_shared;
+#[cfg(any(
+    // assume we have all atomics available if we're using portable-atomic
+    feature = "portable-atomic",
+    // publishing and taking values needs atomic read-modify-write operations
+    target_has_atomic = "ptr"
+))]
+pub mod mailbox;
+pub mod merge;
 #[cfg(any(
     // assume we have all atomics available if we're using portable-atomic
     feature = "portable-atomic",
@@ -118,6 +211,23 @@ mod defmt;
 pub mod mpmc;
 #[cfg(any(arm_llsc, target_arch = "x86"))]
 pub mod pool;
+pub mod prelude;
+#[cfg(any(
+    // assume we have all atomics available if we're using portable-atomic
+    feature = "portable-atomic",
+    // the reader counts need atomic read-modify-write operations
+    target_has_atomic = "ptr"
+))]
+pub mod rcu;
+#[cfg(any(
+    // assume we have all atomics available if we're using portable-atomic
+    feature = "portable-atomic",
+    // the generation counter only needs atomic load/store, see `spsc` below.
+    target_has_atomic = "ptr",
+    has_atomic_load_store
+))]
+pub mod seqlock;
+pub mod sip;
 pub mod sorted_linked_list;
 #[cfg(any(
     // assume we have all atomics available if we're using portable-atomic
@@ -129,7 +239,30 @@ pub mod sorted_linked_list;
     has_atomic_load_store
 ))]
 pub mod spsc;
+#[cfg(all(
+    // also used by the crate's own tests, see `test_helpers`
+    any(test, feature = "test-support"),
+    // the counters need atomic read-modify-write operations
+    any(feature = "portable-atomic", target_has_atomic = "ptr")
+))]
+pub mod test_support;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod traits;
+#[cfg(all(
+    any(
+        // the same requirements as `mpmc`, which this is built on
+        feature = "portable-atomic",
+        all(feature = "mpmc_large", target_has_atomic = "ptr"),
+        all(not(feature = "mpmc_large"), target_has_atomic = "8")
+    ),
+    // `new` is `const` and creates an `MpMcQueue`, which can't be done in const contexts with loom
+    not(loom)
+))]
+pub mod work_queue;
 
+#[cfg(feature = "postcard")]
+mod postcard;
 #[cfg(feature = "ufmt")]
 mod ufmt;
 
@@ -139,5 +272,5 @@ mod sealed;
 /// Do not use. Used for macros only. Not covered by semver guarantees.
 #[doc(hidden)]
 pub mod _export {
-    pub use crate::string::format;
+    pub use crate::string::{format, str_to_array};
 }
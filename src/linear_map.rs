@@ -4,7 +4,6 @@ use core::{borrow::Borrow, fmt, iter::FromIterator, mem, ops, slice};
 /// A fixed capacity map/dictionary that performs lookups via linear search.
 ///
 /// Note that as this map doesn't use hashing so most operations are *O*(n) instead of *O*(1).
-
 pub struct LinearMap<K, V, const N: usize> {
     pub(crate) buffer: Vec<(K, V), N>,
 }
@@ -48,6 +47,32 @@ where
         N
     }
 
+    /// Clones the map into a new map with a capacity of `M`.
+    ///
+    /// Returns `Err(())` without cloning any pair if the pairs don't fit. Slot indices stay the
+    /// same.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<_, _, 8> = LinearMap::new();
+    /// map.insert("a", 1).unwrap();
+    /// let small: LinearMap<_, _, 1> = map.clone_into_capacity().unwrap();
+    /// assert_eq!(small, map);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn clone_into_capacity<const M: usize>(&self) -> Result<LinearMap<K, V, M>, ()>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        Ok(LinearMap {
+            buffer: self.buffer.clone_into_capacity()?,
+        })
+    }
+
     /// Clears the map, removing all key-value pairs.
     ///
     /// Computes in *O*(1) time.
@@ -134,6 +159,49 @@ where
             .map(|(_, v)| v)
     }
 
+    /// Returns mutable references to the values corresponding to several keys at once.
+    ///
+    /// Returns `None` if any of the keys is missing or if two of the keys are equal, since that
+    /// would hand out two mutable references to the same value.
+    ///
+    /// Computes in *O*(n * M + M²) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut balances: LinearMap<_, _, 8> = LinearMap::new();
+    /// balances.insert("alice", 100).unwrap();
+    /// balances.insert("bob", 20).unwrap();
+    ///
+    /// let [from, to] = balances.get_many_mut([&"alice", &"bob"]).unwrap();
+    /// *from -= 30;
+    /// *to += 30;
+    /// assert_eq!(balances[&"alice"], 70);
+    /// assert_eq!(balances[&"bob"], 50);
+    ///
+    /// assert!(balances.get_many_mut([&"alice", &"alice"]).is_none());
+    /// assert!(balances.get_many_mut([&"alice", &"carol"]).is_none());
+    /// ```
+    pub fn get_many_mut<Q, const M: usize>(&mut self, keys: [&Q; M]) -> Option<[&mut V; M]>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        let mut indices = [0; M];
+        for (index, key) in indices.iter_mut().zip(keys) {
+            *index = self.buffer.iter().position(|(k, _)| k.borrow() == key)?;
+        }
+        if (1..M).any(|i| indices[..i].contains(&indices[i])) {
+            return None;
+        }
+
+        let buffer = self.buffer.as_mut_ptr();
+        // SAFETY: the indices are in bounds and pairwise distinct, so the references don't alias
+        Some(indices.map(|i| unsafe { &mut (*buffer.add(i)).1 }))
+    }
+
     /// Returns the number of elements in this map.
     ///
     /// Computes in *O*(1) time.
@@ -174,6 +242,11 @@ where
     /// assert_eq!(map[&37], "c");
     /// ```
     pub fn insert(&mut self, key: K, mut value: V) -> Result<Option<V>, (K, V)> {
+        #[cfg(feature = "test-util")]
+        if self.buffer.take_injected_failure() {
+            return Err((key, value));
+        }
+
         if let Some((_, v)) = self.iter_mut().find(|&(k, _)| *k == key) {
             mem::swap(v, &mut value);
             return Ok(Some(value));
@@ -300,6 +373,211 @@ where
         idx.map(|idx| self.buffer.swap_remove(idx).1)
     }
 
+    /// Removes all keys in `range` from the map, returning the number of pairs removed.
+    ///
+    /// The remaining pairs are compacted in a single pass and keep their relative order.
+    ///
+    /// Computes in *O*(n) time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<u32, &str, 8> = LinearMap::new();
+    /// for (block, name) in [(0x10, "a"), (0x40, "b"), (0x18, "c"), (0x20, "d")] {
+    ///     map.insert(block, name).unwrap();
+    /// }
+    ///
+    /// // invalidate the entries of the blocks in 0x10..0x20
+    /// assert_eq!(map.remove_range(0x10..0x20), 2);
+    /// assert!(map.iter().eq([(&0x40, &"b"), (&0x20, &"d")]));
+    /// ```
+    pub fn remove_range<Q, R>(&mut self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: ops::RangeBounds<Q>,
+    {
+        let len = self.buffer.len();
+        self.buffer.retain(|(k, _)| !range.contains(k.borrow()));
+        len - self.buffer.len()
+    }
+
+    /// Returns the slot index of `key`, or `None` if it's not in the map.
+    ///
+    /// A slot index keeps referring to the same pair as long as no pair is removed: inserting a
+    /// new key appends it, and replacing a value keeps its slot. Removing a pair moves the pair
+    /// in the last slot into the freed one.
+    ///
+    /// Computes in *O*(n) time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<_, _, 8> = LinearMap::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    ///
+    /// // look up the key once, then access its pair in O(1)
+    /// let cursor = map.index_of("b").unwrap();
+    /// map.insert("c", 3).unwrap();
+    /// *map.get_at_mut(cursor).unwrap().1 += 10;
+    /// assert_eq!(map.get_at(cursor), Some((&"b", &12)));
+    /// ```
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        self.keys().position(|k| k.borrow() == key)
+    }
+
+    /// Returns the pair in the slot `index`, or `None` if it's out of bounds.
+    ///
+    /// See [`index_of`](LinearMap::index_of) for when slot indices stay valid.
+    ///
+    /// Computes in *O*(1) time
+    pub fn get_at(&self, index: usize) -> Option<(&K, &V)> {
+        self.buffer.get(index).map(|(k, v)| (k, v))
+    }
+
+    /// Returns the pair in the slot `index`, with a mutable reference to the value, or `None` if
+    /// it's out of bounds.
+    ///
+    /// See [`index_of`](LinearMap::index_of) for when slot indices stay valid.
+    ///
+    /// Computes in *O*(1) time
+    pub fn get_at_mut(&mut self, index: usize) -> Option<(&K, &mut V)> {
+        self.buffer.get_mut(index).map(|(k, v)| (&*k, v))
+    }
+
+    /// Removes the pair in the slot `index` and returns it, or `None` if it's out of bounds.
+    ///
+    /// The pair in the last slot is moved into slot `index`.
+    ///
+    /// Computes in *O*(1) time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<_, _, 8> = LinearMap::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("c", 3).unwrap();
+    ///
+    /// assert_eq!(map.remove_at(0), Some(("a", 1)));
+    /// assert_eq!(map.get_at(0), Some((&"c", &3)));
+    /// assert_eq!(map.remove_at(2), None);
+    /// ```
+    pub fn remove_at(&mut self, index: usize) -> Option<(K, V)> {
+        self.buffer.swap_remove_checked(index)
+    }
+
+    /// An iterator visiting all pairs together with their slot indices, in slot order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<_, _, 8> = LinearMap::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    ///
+    /// let idle = map.iter_indexed().find(|(_, _, v)| **v == 2).map(|(i, _, _)| i);
+    /// assert_eq!(idle, Some(1));
+    /// ```
+    pub fn iter_indexed(&self) -> impl Iterator<Item = (usize, &K, &V)> {
+        self.iter().enumerate().map(|(i, (k, v))| (i, k, v))
+    }
+
+    /// Returns the slot indices of all pairs, ordered by their keys.
+    ///
+    /// The indices can be kept to visit the pairs in key order with
+    /// [`get_at`](LinearMap::get_at) later, e.g. on every save of a config map that must be
+    /// serialized deterministically; see [`index_of`](LinearMap::index_of) for when they stay
+    /// valid. The indices are sorted on the stack; no keys are copied.
+    ///
+    /// Computes in *O*(n \* log(n)) time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<_, _, 8> = LinearMap::new();
+    /// map.insert("rate", 9600).unwrap();
+    /// map.insert("bits", 8).unwrap();
+    ///
+    /// let order = map.sorted_indices();
+    /// map.insert("rate", 115200).unwrap();
+    /// let pairs = order.iter().map(|&i| map.get_at(i).unwrap());
+    /// assert!(pairs.eq([(&"bits", &8), (&"rate", &115200)]));
+    /// ```
+    pub fn sorted_indices(&self) -> Vec<usize, N>
+    where
+        K: Ord,
+    {
+        let mut order: Vec<usize, N> = (0..self.len()).collect();
+        // keys are unique, so an unstable sort gives the same order every time
+        order.sort_unstable_by(|&a, &b| self.buffer[a].0.cmp(&self.buffer[b].0));
+        order
+    }
+
+    /// An iterator visiting all pairs in key order.
+    ///
+    /// Computes in *O*(n \* log(n)) time, see [`sorted_indices`](LinearMap::sorted_indices) for
+    /// keeping the order around.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<_, _, 8> = LinearMap::new();
+    /// map.insert(3, "c").unwrap();
+    /// map.insert(1, "a").unwrap();
+    /// map.insert(2, "b").unwrap();
+    ///
+    /// assert!(map.iter_sorted().eq([(&1, &"a"), (&2, &"b"), (&3, &"c")]));
+    /// ```
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&K, &V)>
+    where
+        K: Ord,
+    {
+        self.sorted_indices().into_iter().map(|i| {
+            let (k, v) = &self.buffer[i];
+            (k, v)
+        })
+    }
+
+    /// An iterator visiting all keys in order.
+    ///
+    /// Computes in *O*(n \* log(n)) time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::LinearMap;
+    ///
+    /// let mut map: LinearMap<_, _, 8> = LinearMap::new();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("a", 1).unwrap();
+    ///
+    /// assert!(map.keys_sorted().eq(&["a", "b"]));
+    /// ```
+    pub fn keys_sorted(&self) -> impl Iterator<Item = &K>
+    where
+        K: Ord,
+    {
+        self.iter_sorted().map(|(k, _)| k)
+    }
+
     /// An iterator visiting all values in arbitrary order.
     ///
     /// # Examples
@@ -345,7 +623,7 @@ where
     }
 }
 
-impl<'a, K, V, Q, const N: usize> ops::Index<&'a Q> for LinearMap<K, V, N>
+impl<K, V, Q, const N: usize> ops::Index<&Q> for LinearMap<K, V, N>
 where
     K: Borrow<Q> + Eq,
     Q: Eq + ?Sized,
@@ -357,7 +635,7 @@ where
     }
 }
 
-impl<'a, K, V, Q, const N: usize> ops::IndexMut<&'a Q> for LinearMap<K, V, N>
+impl<K, V, Q, const N: usize> ops::IndexMut<&Q> for LinearMap<K, V, N>
 where
     K: Borrow<Q> + Eq,
     Q: Eq + ?Sized,
@@ -429,6 +707,20 @@ where
     }
 }
 
+impl<K, V, const N: usize> IntoIterator for LinearMap<K, V, N>
+where
+    K: Eq,
+{
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            inner: self.buffer.into_iter(),
+        }
+    }
+}
+
 impl<'a, K, V, const N: usize> IntoIterator for &'a LinearMap<K, V, N>
 where
     K: Eq,
@@ -476,7 +768,7 @@ where
         self.len() == other.len()
             && self
                 .iter()
-                .all(|(key, value)| other.get(key).map_or(false, |v| *value == *v))
+                .all(|(key, value)| other.get(key).is_some_and(|v| *value == *v))
     }
 }
 
@@ -546,4 +838,85 @@ mod test {
 
         assert_eq!(Droppable::count(), 0);
     }
+
+    #[test]
+    fn into_iter() {
+        let mut map: LinearMap<_, _, 4> = LinearMap::new();
+        map.insert("k1", 1).unwrap();
+        map.insert("k2", 2).unwrap();
+
+        let mut iter = map.into_iter();
+        assert_eq!(iter.next(), Some(("k1", 1)));
+        assert_eq!(iter.next(), Some(("k2", 2)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut map: LinearMap<u8, u8, 4> = [(0, 0), (1, 1), (2, 2)].into_iter().collect();
+
+        let [a, b, c] = map.get_many_mut([&2, &0, &1]).unwrap();
+        core::mem::swap(a, b);
+        *c += 10;
+        assert_eq!(
+            map.values().copied().collect::<crate::Vec<_, 4>>(),
+            [2, 11, 0]
+        );
+
+        assert!(map.get_many_mut([&0, &1, &0]).is_none());
+        assert!(map.get_many_mut([&0, &3]).is_none());
+        assert!(map.get_many_mut::<u8, 0>([]).is_some());
+    }
+
+    #[test]
+    fn sorted() {
+        let mut map: LinearMap<u8, u8, 8> = [(5, 0), (1, 1), (7, 2), (3, 3)].into_iter().collect();
+        assert_eq!(map.sorted_indices(), [1, 3, 0, 2]);
+        assert!(map.keys_sorted().eq(&[1, 3, 5, 7]));
+        assert!(map
+            .iter_sorted()
+            .eq([(&1, &1), (&3, &3), (&5, &0), (&7, &2)]));
+
+        // the last pair moves into the freed slot
+        map.remove(&1).unwrap();
+        assert_eq!(map.sorted_indices(), [1, 0, 2]);
+
+        let empty: LinearMap<u8, u8, 8> = LinearMap::new();
+        assert!(empty.sorted_indices().is_empty());
+        assert_eq!(empty.keys_sorted().next(), None);
+    }
+
+    #[test]
+    fn slot_indices() {
+        let mut map: LinearMap<u8, u8, 4> = [(0, 0), (1, 1), (2, 2)].into_iter().collect();
+        let cursor = map.index_of(&1).unwrap();
+
+        map.insert(1, 10).unwrap();
+        map.insert(3, 3).unwrap();
+        assert_eq!(map.get_at(cursor), Some((&1, &10)));
+        assert_eq!(map.get_at(4), None);
+
+        assert_eq!(map.remove_at(cursor), Some((1, 10)));
+        assert_eq!(map.index_of(&3), Some(cursor));
+        assert!(map
+            .iter_indexed()
+            .eq([(0, &0, &0), (1, &3, &3), (2, &2, &2)]));
+        assert_eq!(map.get_at_mut(3), None);
+    }
+
+    #[test]
+    fn remove_range() {
+        let mut map: LinearMap<u8, u8, 8> = [5, 1, 7, 3, 2, 6].iter().map(|&k| (k, k)).collect();
+        assert_eq!(map.remove_range(8..), 0);
+        assert_eq!(map.remove_range(2..=5), 3);
+        assert!(map.keys().eq(&[1, 7, 6]));
+        assert_eq!(map.remove_range(..7), 2);
+        assert!(map.keys().eq(&[7]));
+        assert_eq!(map.remove_range(..), 1);
+        assert!(map.is_empty());
+
+        let mut map: LinearMap<&str, u8, 4> = [("b", 0), ("aa", 1)].into_iter().collect();
+        assert_eq!(map.remove_range("a".."b"), 1);
+        assert_eq!(map.len(), 1);
+    }
 }
@@ -0,0 +1,408 @@
+//! A single-slot channel whose latest value wins, between one sender and one receiver.
+//!
+//! [`Mailbox`] holds at most one value. [`put`](Sender::put) replaces a value that wasn't taken
+//! yet instead of failing, and [`take`](Receiver::take) moves the value out. This suits commands
+//! where only the most recent one matters, e.g. a setpoint that one core of a dual-core SoC sends
+//! to the other: a queue would make the receiver work through stale setpoints, or make the sender
+//! fail once the receiver falls behind.
+//!
+//! Neither side ever waits for the other. The mailbox uses three slots: one that the sender writes
+//! to, one that holds the latest value, and one that the receiver moves values out of. Publishing
+//! and taking a value each swap a slot with the middle one in a single atomic operation.
+//!
+//! The receiver can also wait for a value asynchronously with [`poll_take`](Receiver::poll_take),
+//! which registers a [`Waker`] that the next [`put`](Sender::put) wakes.
+//!
+//! # Memory ordering
+//!
+//! Publishing a value is a release operation and taking it an acquire operation, so everything the
+//! sender wrote before [`put`](Sender::put) is visible to the receiver after the matching
+//! [`take`](Receiver::take), also when the two run on different cores. On cores without coherent
+//! caches, the mailbox itself must still be placed in memory that both cores see coherently.
+//!
+//! NOTE: This module requires atomic CAS operations. On targets without them, they are emulated by
+//! the [`portable-atomic`](https://crates.io/crates/portable-atomic) crate.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::mailbox::Mailbox;
+//!
+//! let mut mailbox: Mailbox<u32> = Mailbox::new();
+//! let (mut sender, mut receiver) = mailbox.split();
+//!
+//! // core 0 updates the setpoint twice before core 1 gets to it
+//! assert_eq!(sender.put(1200), None);
+//! assert_eq!(sender.put(1500), Some(1200));
+//!
+//! // core 1 only sees the latest one
+//! assert_eq!(receiver.take(), Some(1500));
+//! assert_eq!(receiver.take(), None);
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicUsize, Ordering};
+
+/// The index of the middle slot.
+const MIDDLE: usize = 0b11;
+/// The index of the sender's slot, shifted left by 2.
+const SENDER: usize = 0b11 << 2;
+/// The middle slot holds a value that wasn't taken yet.
+const FRESH: usize = 1 << 4;
+
+const fn middle(state: usize) -> usize {
+    state & MIDDLE
+}
+
+const fn sender(state: usize) -> usize {
+    (state & SENDER) >> 2
+}
+
+/// The receiver owns the slot that is neither the middle one nor the sender's.
+const fn receiver(state: usize) -> usize {
+    3 - middle(state) - sender(state)
+}
+
+/// A single-slot channel between one sender and one receiver, see the
+/// [module-level documentation](self).
+pub struct Mailbox<T> {
+    slots: [UnsafeCell<MaybeUninit<T>>; 3],
+    /// Only the middle slot holds a value, and only while `FRESH` is set.
+    state: AtomicUsize,
+    waker: AtomicWaker,
+}
+
+impl<T> Mailbox<T> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const EMPTY_SLOT: UnsafeCell<MaybeUninit<T>> = UnsafeCell::new(MaybeUninit::uninit());
+
+    /// Creates an empty mailbox.
+    pub const fn new() -> Self {
+        Self {
+            slots: [Self::EMPTY_SLOT; 3],
+            // the middle slot is 0, the sender's slot is 1 and the receiver's slot is 2
+            state: AtomicUsize::new(1 << 2),
+            waker: AtomicWaker::new(),
+        }
+    }
+
+    /// Splits the mailbox into its sender and receiver endpoints.
+    pub fn split(&mut self) -> (Sender<'_, T>, Receiver<'_, T>) {
+        (Sender { mailbox: self }, Receiver { mailbox: self })
+    }
+
+    /// Takes the value out of the mailbox, or returns `None` if it's empty.
+    ///
+    /// This needs no endpoint since the exclusive borrow statically guarantees that neither side
+    /// accesses the mailbox.
+    pub fn take(&mut self) -> Option<T> {
+        self.split().1.take()
+    }
+}
+
+impl<T> Default for Mailbox<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Mailbox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Mailbox")
+            .field("fresh", &(self.state.load(Ordering::Relaxed) & FRESH != 0))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Drop for Mailbox<T> {
+    fn drop(&mut self) {
+        let state = *self.state.get_mut();
+        if state & FRESH != 0 {
+            unsafe { self.slots[middle(state)].get_mut().assume_init_drop() }
+        }
+    }
+}
+
+unsafe impl<T> Sync for Mailbox<T> where T: Send {}
+
+/// The sender endpoint of a [`Mailbox`].
+pub struct Sender<'a, T> {
+    mailbox: &'a Mailbox<T>,
+}
+
+unsafe impl<T> Send for Sender<'_, T> where T: Send {}
+
+impl<T> Sender<'_, T> {
+    /// Puts `value` in the mailbox and wakes the receiver if it's waiting in
+    /// [`poll_take`](Receiver::poll_take).
+    ///
+    /// Returns the previous value if the receiver didn't take it.
+    pub fn put(&mut self, value: T) -> Option<T> {
+        let state = &self.mailbox.state;
+        // NOTE(ordering) only the sender changes the sender's slot
+        let mut current = state.load(Ordering::Relaxed);
+        let slot = sender(current);
+        // NOTE(unsafe) the sender's slot is empty, and only the sender accesses it
+        unsafe { (*self.mailbox.slots[slot].get()).write(value) };
+
+        loop {
+            let new = slot | (middle(current) << 2) | FRESH;
+            // NOTE(ordering) release the value to the receiver, and acquire the slot that becomes
+            // the sender's, which the receiver may have moved a value out of
+            match state.compare_exchange_weak(current, new, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+        self.mailbox.waker.wake();
+
+        if current & FRESH != 0 {
+            // NOTE(unsafe) the old middle slot, which is the sender's now, holds the replaced value
+            Some(unsafe { (*self.mailbox.slots[middle(current)].get()).assume_init_read() })
+        } else {
+            None
+        }
+    }
+}
+
+/// The receiver endpoint of a [`Mailbox`].
+pub struct Receiver<'a, T> {
+    mailbox: &'a Mailbox<T>,
+}
+
+unsafe impl<T> Send for Receiver<'_, T> where T: Send {}
+
+impl<T> Receiver<'_, T> {
+    /// Takes the value out of the mailbox, or returns `None` if it's empty.
+    pub fn take(&mut self) -> Option<T> {
+        let state = &self.mailbox.state;
+        let mut current = state.load(Ordering::Relaxed);
+        loop {
+            // only the receiver clears the flag, so once it's set the loop ends with a value
+            if current & FRESH == 0 {
+                return None;
+            }
+
+            // swap the receiver's empty slot with the middle one; the sender may be swapping its
+            // slot at the same time, in which case this is retried
+            let new = receiver(current) | (current & SENDER);
+            // NOTE(ordering) acquire the value from the sender, and release the slot that becomes
+            // the middle one, which the receiver last moved a value out of
+            match state.compare_exchange_weak(current, new, Ordering::AcqRel, Ordering::Relaxed) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+
+        // NOTE(unsafe) the old middle slot, which is the receiver's now, holds the latest value
+        Some(unsafe { (*self.mailbox.slots[middle(current)].get()).assume_init_read() })
+    }
+
+    /// Takes the value out of the mailbox, or registers the waker of `cx` to be woken by the next
+    /// [`put`](Sender::put) if it's empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::{
+    ///     future::{poll_fn, Future},
+    ///     pin::pin,
+    ///     task::{Context, Poll, Waker},
+    /// };
+    /// use heapless::mailbox::Mailbox;
+    ///
+    /// let mut mailbox: Mailbox<u8> = Mailbox::new();
+    /// let (mut sender, mut receiver) = mailbox.split();
+    ///
+    /// let mut command = pin!(poll_fn(|cx| receiver.poll_take(cx)));
+    /// let mut cx = Context::from_waker(Waker::noop());
+    /// assert!(command.as_mut().poll(&mut cx).is_pending());
+    ///
+    /// sender.put(7);
+    /// assert_eq!(command.as_mut().poll(&mut cx), Poll::Ready(7));
+    /// ```
+    pub fn poll_take(&mut self, cx: &mut Context<'_>) -> Poll<T> {
+        if let Some(value) = self.take() {
+            return Poll::Ready(value);
+        }
+
+        self.mailbox.waker.register(cx.waker());
+        // a value may have been put before the waker was registered
+        match self.take() {
+            Some(value) => Poll::Ready(value),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Returns `true` if there's a value to take.
+    pub fn ready(&self) -> bool {
+        self.mailbox.state.load(Ordering::Relaxed) & FRESH != 0
+    }
+}
+
+// Waker slot states
+const WAITING: usize = 0;
+const REGISTERING: usize = 1;
+const WAKING: usize = 2;
+
+/// A waker that one context registers and another wakes, without locking.
+struct AtomicWaker {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+impl AtomicWaker {
+    const fn new() -> Self {
+        Self {
+            state: AtomicUsize::new(WAITING),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Registers `waker`, replacing the previous one. Must not be called concurrently with itself.
+    fn register(&self, waker: &Waker) {
+        match self
+            .state
+            .compare_exchange(WAITING, REGISTERING, Ordering::Acquire, Ordering::Acquire)
+            .unwrap_or_else(|state| state)
+        {
+            WAITING => {
+                // NOTE(unsafe) the `REGISTERING` state excludes `wake` from the waker
+                unsafe {
+                    let current = &mut *self.waker.get();
+                    if !current.as_ref().is_some_and(|w| w.will_wake(waker)) {
+                        *current = Some(waker.clone());
+                    }
+                }
+
+                if self
+                    .state
+                    .compare_exchange(REGISTERING, WAITING, Ordering::AcqRel, Ordering::Acquire)
+                    .is_err()
+                {
+                    // `wake` was called during the registration and left the waking to us
+                    let waker = unsafe { (*self.waker.get()).take() };
+                    self.state.swap(WAITING, Ordering::AcqRel);
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            // `wake` is running, so wake the new waker right away
+            _ => waker.wake_by_ref(),
+        }
+    }
+
+    /// Wakes the registered waker, if any.
+    fn wake(&self) {
+        if self.state.fetch_or(WAKING, Ordering::AcqRel) == WAITING {
+            // NOTE(unsafe) the `WAKING` state excludes `register` from the waker
+            let waker = unsafe { (*self.waker.get()).take() };
+            self.state.fetch_and(!WAKING, Ordering::Release);
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use super::Mailbox;
+
+    #[test]
+    fn latest_wins() {
+        droppable!();
+
+        let mut mailbox: Mailbox<Droppable> = Mailbox::new();
+        {
+            let (mut sender, mut receiver) = mailbox.split();
+            assert!(receiver.take().is_none());
+            for _ in 0..10 {
+                assert!(sender.put(Droppable::new()).is_none());
+                assert!(receiver.ready());
+                let replaced = sender.put(Droppable::new()).unwrap();
+                drop(replaced);
+                assert_eq!(Droppable::count(), 1);
+                drop(receiver.take().unwrap());
+                assert!(!receiver.ready());
+                assert_eq!(Droppable::count(), 0);
+            }
+
+            sender.put(Droppable::new());
+        }
+        assert_eq!(Droppable::count(), 1);
+        drop(mailbox);
+        assert_eq!(Droppable::count(), 0);
+    }
+
+    #[test]
+    fn poll_take() {
+        struct Counter(AtomicUsize);
+
+        impl Wake for Counter {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let counter = Arc::new(Counter(AtomicUsize::new(0)));
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut mailbox: Mailbox<u8> = Mailbox::new();
+        let (mut sender, mut receiver) = mailbox.split();
+        assert_eq!(receiver.poll_take(&mut cx), Poll::Pending);
+        assert_eq!(receiver.poll_take(&mut cx), Poll::Pending);
+        sender.put(1);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        // the waker is only woken once per registration
+        sender.put(2);
+        assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+        assert_eq!(receiver.poll_take(&mut cx), Poll::Ready(2));
+        assert_eq!(mailbox.take(), None);
+    }
+
+    #[test]
+    fn threaded() {
+        let mut mailbox: Mailbox<[u32; 16]> = Mailbox::new();
+        let (mut sender, mut receiver) = mailbox.split();
+
+        std::thread::scope(|s| {
+            s.spawn(move || {
+                for i in 1..=1000 {
+                    sender.put([i; 16]);
+                }
+            });
+
+            let mut last = 0;
+            while last < 1000 {
+                if let Some(block) = receiver.take() {
+                    // never torn, never older than the previous one
+                    assert!(block.iter().all(|x| *x == block[0]));
+                    assert!(block[0] > last);
+                    last = block[0];
+                }
+            }
+        });
+    }
+}
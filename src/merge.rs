@@ -0,0 +1,170 @@
+//! Merging of sorted sequences without an intermediate buffer.
+//!
+//! [`merge_sorted`] combines two sorted iterators, e.g. over [`Vec`](crate::Vec)s or
+//! [`SortedLinkedList`](crate::sorted_linked_list::SortedLinkedList)s, into one sorted iterator
+//! in a single pass. [`merge_sorted_into`] pushes the result straight into a container.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{
+//!     merge,
+//!     sorted_linked_list::{Min, SortedLinkedList},
+//!     Deque, Vec,
+//! };
+//!
+//! // timestamps of events from two sources, each sorted
+//! let mut timer: SortedLinkedList<u32, _, Min, 4> = SortedLinkedList::new_u8();
+//! timer.push(30).unwrap();
+//! timer.push(10).unwrap();
+//! let gpio: Vec<u32, 4> = Vec::from_slice(&[5, 20, 40]).unwrap();
+//!
+//! let mut events: Deque<u32, 8> = Deque::new();
+//! merge::merge_sorted_into(gpio.iter().copied(), timer.iter().copied(), &mut events).unwrap();
+//! assert!(events.iter().eq(&[5, 10, 20, 30, 40]));
+//! ```
+
+use core::{
+    cmp::Ordering,
+    fmt,
+    iter::{FusedIterator, Peekable},
+};
+
+use crate::traits::PushBack;
+
+/// Returns an iterator that merges the sorted iterators `a` and `b` into one sorted iterator.
+///
+/// Equal items from `a` come before those from `b`. If either input isn't sorted, the output
+/// isn't either, but it still yields every item exactly once.
+pub fn merge_sorted<A, B>(a: A, b: B) -> MergeSorted<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: Ord,
+{
+    merge_sorted_by(a, b, Ord::cmp)
+}
+
+/// Like [`merge_sorted`], with the inputs sorted by `compare` instead of [`Ord`].
+pub fn merge_sorted_by<A, B, F>(a: A, b: B, compare: F) -> MergeSorted<A::IntoIter, B::IntoIter, F>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering,
+{
+    MergeSorted {
+        a: a.into_iter().peekable(),
+        b: b.into_iter().peekable(),
+        compare,
+    }
+}
+
+/// Merges the sorted iterators `a` and `b` and pushes the result to `out`.
+///
+/// Returns the first item that didn't fit, without pushing any of the items after it.
+pub fn merge_sorted_into<A, B, D>(a: A, b: B, out: &mut D) -> Result<(), A::Item>
+where
+    A: IntoIterator,
+    B: IntoIterator<Item = A::Item>,
+    A::Item: Ord,
+    D: PushBack<A::Item>,
+{
+    merge_sorted(a, b).try_for_each(|item| out.push_back(item))
+}
+
+/// An iterator that merges two sorted iterators.
+///
+/// Comes from [`merge_sorted`] or [`merge_sorted_by`].
+pub struct MergeSorted<A, B, F = fn(&<A as Iterator>::Item, &<A as Iterator>::Item) -> Ordering>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+{
+    a: Peekable<A>,
+    b: Peekable<B>,
+    compare: F,
+}
+
+impl<A, B, F> Iterator for MergeSorted<A, B, F>
+where
+    A: Iterator,
+    B: Iterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering,
+{
+    type Item = A::Item;
+
+    fn next(&mut self) -> Option<A::Item> {
+        let take_a = match (self.a.peek(), self.b.peek()) {
+            (Some(a), Some(b)) => (self.compare)(a, b) != Ordering::Greater,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+        if take_a {
+            self.a.next()
+        } else {
+            self.b.next()
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (a_lower, a_upper) = self.a.size_hint();
+        let (b_lower, b_upper) = self.b.size_hint();
+        let upper = match (a_upper, b_upper) {
+            (Some(a), Some(b)) => a.checked_add(b),
+            _ => None,
+        };
+        (a_lower.saturating_add(b_lower), upper)
+    }
+}
+
+impl<A, B, F> FusedIterator for MergeSorted<A, B, F>
+where
+    A: FusedIterator,
+    B: FusedIterator<Item = A::Item>,
+    F: FnMut(&A::Item, &A::Item) -> Ordering,
+{
+}
+
+impl<A, B, F> fmt::Debug for MergeSorted<A, B, F>
+where
+    A: Iterator + fmt::Debug,
+    B: Iterator<Item = A::Item> + fmt::Debug,
+    A::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MergeSorted")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge_sorted, merge_sorted_by, merge_sorted_into};
+    use crate::Vec;
+
+    #[test]
+    fn merge() {
+        let a = [1, 3, 3, 7];
+        let b = [0, 3, 8, 9];
+        let merged: Vec<_, 8> = merge_sorted(&a, &b).copied().collect();
+        assert_eq!(merged, [0, 1, 3, 3, 3, 7, 8, 9]);
+        assert_eq!(merge_sorted(&a, &b).size_hint(), (8, Some(8)));
+        assert!(merge_sorted(&a, &[]).eq(&a));
+        assert!(merge_sorted(&[], &b).eq(&b));
+
+        // ties are taken from `a` first
+        let a = [(1, 'a'), (2, 'a')];
+        let b = [(1, 'b'), (2, 'b')];
+        let merged: Vec<_, 4> = merge_sorted_by(a, b, |x, y| x.0.cmp(&y.0)).collect();
+        assert_eq!(merged, [(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]);
+    }
+
+    #[test]
+    fn into() {
+        let mut out: Vec<u8, 3> = Vec::new();
+        assert_eq!(merge_sorted_into([1, 4], [2, 3], &mut out), Err(4));
+        assert_eq!(out, [1, 2, 3]);
+    }
+}
@@ -67,10 +67,10 @@
 //!   not.
 //! - All execution times are in clock cycles. 1 clock cycle = 125 ns.
 //! - Execution time is *dependent* of `mem::size_of::<T>()`. Both operations include one
-//! `memcpy(T)` in their successful path.
+//!   `memcpy(T)` in their successful path.
 //! - The optimization level is indicated in parentheses.
 //! - The numbers reported correspond to the successful path (i.e. `Some` is returned by `dequeue`
-//! and `Ok` is returned by `enqueue`).
+//!   and `Ok` is returned by `enqueue`).
 //!
 //! # Portability
 //!
@@ -86,14 +86,20 @@
 //!
 //! [0]: http://www.1024cores.net/home/lock-free-algorithms/queues/bounded-mpmc-queue
 
-use core::{cell::UnsafeCell, mem::MaybeUninit};
+use core::mem::{self, MaybeUninit};
 
-#[cfg(not(feature = "portable-atomic"))]
-use core::sync::atomic;
-#[cfg(feature = "portable-atomic")]
-use portable_atomic as atomic;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
 
-use atomic::Ordering;
+use crate::{
+    backpressure::fill_ratio,
+    sync::{
+        atomic::{self, Ordering},
+        UnsafeCell,
+    },
+    traits::{PopFront, PushBack},
+    Backpressure,
+};
 
 #[cfg(feature = "mpmc_large")]
 type AtomicTargetSize = atomic::AtomicUsize;
@@ -105,6 +111,12 @@ type IntSize = usize;
 #[cfg(not(feature = "mpmc_large"))]
 type IntSize = u8;
 
+/// Widens an `IntSize`, which with `mpmc_large` is already a `usize`.
+#[allow(clippy::unnecessary_cast)]
+const fn to_usize(value: IntSize) -> usize {
+    value as usize
+}
+
 /// MPMC queue with a capability for 2 elements.
 pub type Q2<T> = MpMcQueue<T, 2>;
 
@@ -127,27 +139,77 @@ pub type Q64<T> = MpMcQueue<T, 64>;
 /// N must be a power of 2
 /// The max value of N is u8::MAX - 1 if `mpmc_large` feature is not enabled.
 pub struct MpMcQueue<T, const N: usize> {
-    buffer: UnsafeCell<[Cell<T>; N]>,
+    buffer: [Cell<T>; N],
     dequeue_pos: AtomicTargetSize,
     enqueue_pos: AtomicTargetSize,
+    #[cfg(feature = "mpmc_stats")]
+    enqueue_contention: atomic::AtomicUsize,
+    #[cfg(feature = "mpmc_stats")]
+    dequeue_contention: atomic::AtomicUsize,
+}
+
+/// The error returned by [`MpMcQueue::enqueue_bounded`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnqueueError<T> {
+    /// The queue is full.
+    Full(T),
+    /// The item wasn't enqueued within the retry budget because other contexts kept winning the
+    /// race for the next slot. The queue may have room.
+    Contended(T),
+}
+
+impl<T> EnqueueError<T> {
+    /// Returns the item that wasn't enqueued.
+    pub fn into_inner(self) -> T {
+        match self {
+            EnqueueError::Full(item) | EnqueueError::Contended(item) => item,
+        }
+    }
+}
+
+/// Counts of contention events on an [`MpMcQueue`], see [`MpMcQueue::contention`].
+#[cfg(feature = "mpmc_stats")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Contention {
+    /// The number of times an enqueue had to retry because another context got the slot first.
+    pub enqueue: usize,
+    /// The number of times a dequeue had to retry because another context got the slot first.
+    pub dequeue: usize,
 }
 
 impl<T, const N: usize> MpMcQueue<T, N> {
     const MASK: IntSize = (N - 1) as IntSize;
+    #[cfg(not(loom))]
+    #[allow(clippy::declare_interior_mutable_const)]
     const EMPTY_CELL: Cell<T> = Cell::new(0);
 
     const ASSERT: [(); 1] = [()];
 
-    /// Creates an empty queue
-    pub const fn new() -> Self {
-        // Const assert
-        crate::sealed::greater_than_1::<N>();
-        crate::sealed::power_of_two::<N>();
-
-        // Const assert on size.
-        #[allow(clippy::no_effect)]
-        Self::ASSERT[(N >= (IntSize::MAX as usize)) as usize];
+    const_fn! {
+        /// Creates an empty queue
+        pub fn new() -> Self {
+            // Const assert
+            crate::sealed::greater_than_1::<N>();
+            crate::sealed::power_of_two::<N>();
+
+            // Const assert on size.
+            #[allow(clippy::no_effect, clippy::unnecessary_operation)]
+            Self::ASSERT[(N >= to_usize(IntSize::MAX)) as usize];
+
+            Self {
+                buffer: Self::cells(),
+                dequeue_pos: AtomicTargetSize::new(0),
+                enqueue_pos: AtomicTargetSize::new(0),
+                #[cfg(feature = "mpmc_stats")]
+                enqueue_contention: atomic::AtomicUsize::new(0),
+                #[cfg(feature = "mpmc_stats")]
+                dequeue_contention: atomic::AtomicUsize::new(0),
+            }
+        }
+    }
 
+    #[cfg(not(loom))]
+    const fn cells() -> [Cell<T>; N] {
         let mut cell_count = 0;
 
         let mut result_cells: [Cell<T>; N] = [Self::EMPTY_CELL; N];
@@ -156,31 +218,97 @@ impl<T, const N: usize> MpMcQueue<T, N> {
             cell_count += 1;
         }
 
-        Self {
-            buffer: UnsafeCell::new(result_cells),
-            dequeue_pos: AtomicTargetSize::new(0),
-            enqueue_pos: AtomicTargetSize::new(0),
-        }
+        result_cells
+    }
+
+    #[cfg(loom)]
+    fn cells() -> [Cell<T>; N] {
+        core::array::from_fn(Cell::new)
     }
 
     /// Returns the item in the front of the queue, or `None` if the queue is empty
     pub fn dequeue(&self) -> Option<T> {
-        unsafe { dequeue(self.buffer.get() as *mut _, &self.dequeue_pos, Self::MASK) }
+        unsafe {
+            dequeue(
+                &self.buffer,
+                &self.dequeue_pos,
+                Self::MASK,
+                #[cfg(feature = "mpmc_stats")]
+                &self.dequeue_contention,
+            )
+        }
     }
 
     /// Adds an `item` to the end of the queue
     ///
     /// Returns back the `item` if the queue is full
     pub fn enqueue(&self, item: T) -> Result<(), T> {
+        self.enqueue_bounded(item, usize::MAX)
+            .map_err(EnqueueError::into_inner)
+    }
+
+    /// Adds an `item` to the end of the queue, giving up after `max_retries` lost races with
+    /// other contexts.
+    ///
+    /// Under contention, [`enqueue`](MpMcQueue::enqueue) keeps retrying for as long as other
+    /// contexts keep winning the race for the next slot. This variant bounds the worst case
+    /// execution time of a producer instead: with `max_retries = 0` it makes a single attempt.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::mpmc::{EnqueueError, Q2};
+    ///
+    /// let q = Q2::new();
+    /// q.enqueue_bounded(0, 4).unwrap();
+    /// q.enqueue_bounded(1, 4).unwrap();
+    /// assert_eq!(q.enqueue_bounded(2, 4), Err(EnqueueError::Full(2)));
+    /// ```
+    pub fn enqueue_bounded(&self, item: T, max_retries: usize) -> Result<(), EnqueueError<T>> {
         unsafe {
             enqueue(
-                self.buffer.get() as *mut _,
+                &self.buffer,
                 &self.enqueue_pos,
                 Self::MASK,
                 item,
+                max_retries,
+                #[cfg(feature = "mpmc_stats")]
+                &self.enqueue_contention,
             )
         }
     }
+
+    /// Returns the number of contention events since the queue was created.
+    ///
+    /// The counters wrap around on overflow.
+    #[cfg(feature = "mpmc_stats")]
+    pub fn contention(&self) -> Contention {
+        Contention {
+            enqueue: self.enqueue_contention.load(Ordering::Relaxed),
+            dequeue: self.dequeue_contention.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Dequeues and drops all items in the queue.
+    ///
+    /// This takes `&mut self`, so no other context can enqueue or dequeue at the same time, e.g.
+    /// at shutdown, after the producers and consumers are gone. The queue can be used again
+    /// afterwards. Dropping the queue drops its items as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::mpmc::Q4;
+    ///
+    /// let mut q = Q4::new();
+    /// q.enqueue([0u8; 16]).unwrap();
+    /// q.enqueue([1u8; 16]).unwrap();
+    /// q.clear();
+    /// assert_eq!(q.dequeue(), None);
+    /// ```
+    pub fn clear(&mut self) {
+        while self.dequeue().is_some() {}
+    }
 }
 
 impl<T, const N: usize> Default for MpMcQueue<T, N> {
@@ -189,47 +317,387 @@ impl<T, const N: usize> Default for MpMcQueue<T, N> {
     }
 }
 
+impl<T, const N: usize> Drop for MpMcQueue<T, N> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<T>() {
+            self.clear();
+        }
+    }
+}
+
 unsafe impl<T, const N: usize> Sync for MpMcQueue<T, N> where T: Send {}
 
+impl<T, const N: usize> PushBack<T> for MpMcQueue<T, N> {
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        self.enqueue(item)
+    }
+}
+
+impl<T, const N: usize> PopFront<T> for MpMcQueue<T, N> {
+    fn pop_front(&mut self) -> Option<T> {
+        self.dequeue()
+    }
+}
+
+impl<T, const N: usize> Backpressure for MpMcQueue<T, N> {
+    fn pressure(&self) -> u8 {
+        fill_ratio(approx_len(&self.dequeue_pos, &self.enqueue_pos, N), N)
+    }
+}
+
+/// A lock-free pool of `N` slots for values of type `T`, which hands out slot indices.
+///
+/// Any context can [`alloc`](MpMcPool::alloc) a slot and any other context can
+/// [`free`](MpMcPool::free) it again, e.g. after the index was passed on through a queue. Free
+/// indices are kept in an [`MpMcQueue`], so `N` has the same limits. `MpMcPool<(), N>` is a plain
+/// allocator of indices in `0..N`.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::mpmc::MpMcPool;
+///
+/// static BUFFERS: MpMcPool<[u8; 16], 4> = MpMcPool::new();
+///
+/// // producer interrupt
+/// let index = BUFFERS.alloc([0xaa; 16]).unwrap();
+///
+/// // consumer interrupt, after receiving `index`
+/// // Safety: `index` was allocated and is only used by this context from now on.
+/// let buffer = unsafe { BUFFERS.free(index) };
+/// assert_eq!(buffer, [0xaa; 16]);
+/// ```
+pub struct MpMcPool<T, const N: usize> {
+    slots: [UnsafeCell<MaybeUninit<T>>; N],
+    /// Indices that were freed.
+    free: MpMcQueue<IntSize, N>,
+    /// Slots from this index on were never allocated.
+    fresh: AtomicTargetSize,
+}
+
+impl<T, const N: usize> MpMcPool<T, N> {
+    const_fn! {
+        /// Creates a pool with all slots free.
+        pub fn new() -> Self {
+            Self {
+                slots: crate::sync::uninit_cells(),
+                free: MpMcQueue::new(),
+                fresh: AtomicTargetSize::new(0),
+            }
+        }
+    }
+
+    /// Moves `value` into a free slot and returns its index, or returns `value` back if all slots
+    /// are in use.
+    pub fn alloc(&self, value: T) -> Result<usize, T> {
+        let index = match self.free.dequeue() {
+            Some(index) => to_usize(index),
+            None => {
+                let mut fresh = self.fresh.load(Ordering::Relaxed);
+                loop {
+                    if to_usize(fresh) == N {
+                        return Err(value);
+                    }
+                    match self.fresh.compare_exchange_weak(
+                        fresh,
+                        fresh + 1,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    ) {
+                        Ok(_) => break to_usize(fresh),
+                        Err(current) => fresh = current,
+                    }
+                }
+            }
+        };
+
+        // NOTE(unsafe) the slot is free, so no other context accesses it
+        unsafe { self.slots[index].with_mut(|slot| (*slot).write(value)) };
+        Ok(index)
+    }
+
+    /// Returns a reference to the value in slot `index`.
+    ///
+    /// # Safety
+    ///
+    /// The slot must be allocated and stay allocated for as long as the reference is alive, and no
+    /// other context may access the value mutably at the same time.
+    pub unsafe fn get(&self, index: usize) -> &T {
+        self.slots[index].with(|slot| (*slot).assume_init_ref())
+    }
+
+    /// Returns a mutable reference to the value in slot `index`.
+    ///
+    /// # Safety
+    ///
+    /// The slot must be allocated and stay allocated for as long as the reference is alive, and no
+    /// other context may access the value at the same time.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn get_mut(&self, index: usize) -> &mut T {
+        self.slots[index].with_mut(|slot| (*slot).assume_init_mut())
+    }
+
+    /// Moves the value out of slot `index` and frees the slot.
+    ///
+    /// # Safety
+    ///
+    /// The slot must be allocated, and no other context may access it at the same time or later,
+    /// until it's handed out again by [`alloc`](MpMcPool::alloc).
+    pub unsafe fn free(&self, index: usize) -> T {
+        let value = self.slots[index].with(|slot| (*slot).assume_init_read());
+        // there's room since at most `N` indices are ever free
+        let _ = self.free.enqueue(index as IntSize);
+        value
+    }
+}
+
+impl<T, const N: usize> Default for MpMcPool<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for MpMcPool<T, N> {
+    fn drop(&mut self) {
+        let mut is_free = [false; N];
+        while let Some(index) = self.free.dequeue() {
+            is_free[to_usize(index)] = true;
+        }
+
+        let fresh = to_usize(self.fresh.load(Ordering::Relaxed));
+        for (slot, is_free) in self.slots[..fresh].iter().zip(is_free) {
+            if !is_free {
+                unsafe { slot.with_mut(|slot| (*slot).assume_init_drop()) }
+            }
+        }
+    }
+}
+
+unsafe impl<T, const N: usize> Sync for MpMcPool<T, N> where T: Send {}
+
+/// MPMC queue with a capacity chosen at runtime, stored on the heap.
+///
+/// This is the same queue as [`MpMcQueue`], for code that only knows the capacity at runtime, e.g.
+/// host-side simulations of firmware that size their queues from a configuration. The capacity
+/// has the same limits as `N`.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::mpmc::BoxedMpMcQueue;
+///
+/// let q = BoxedMpMcQueue::new(4);
+/// assert_eq!(q.capacity(), 4);
+/// q.enqueue(0).unwrap();
+/// assert_eq!(q.dequeue(), Some(0));
+/// ```
+#[cfg(feature = "alloc")]
+pub struct BoxedMpMcQueue<T> {
+    buffer: Box<[Cell<T>]>,
+    dequeue_pos: AtomicTargetSize,
+    enqueue_pos: AtomicTargetSize,
+    #[cfg(feature = "mpmc_stats")]
+    enqueue_contention: atomic::AtomicUsize,
+    #[cfg(feature = "mpmc_stats")]
+    dequeue_contention: atomic::AtomicUsize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T> BoxedMpMcQueue<T> {
+    /// Creates an empty queue with room for `capacity` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` isn't a power of 2 greater than 1, or is too large (see
+    /// [`MpMcQueue`]).
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 1 && capacity.is_power_of_two(),
+            "capacity must be a power of 2 greater than 1"
+        );
+        assert!(capacity < to_usize(IntSize::MAX), "capacity too large");
+
+        Self {
+            buffer: (0..capacity).map(Cell::new).collect(),
+            dequeue_pos: AtomicTargetSize::new(0),
+            enqueue_pos: AtomicTargetSize::new(0),
+            #[cfg(feature = "mpmc_stats")]
+            enqueue_contention: atomic::AtomicUsize::new(0),
+            #[cfg(feature = "mpmc_stats")]
+            dequeue_contention: atomic::AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns the maximum number of elements the queue can hold.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn mask(&self) -> IntSize {
+        (self.buffer.len() - 1) as IntSize
+    }
+
+    /// Returns the item in the front of the queue, or `None` if the queue is empty
+    pub fn dequeue(&self) -> Option<T> {
+        unsafe {
+            dequeue(
+                &self.buffer,
+                &self.dequeue_pos,
+                self.mask(),
+                #[cfg(feature = "mpmc_stats")]
+                &self.dequeue_contention,
+            )
+        }
+    }
+
+    /// Adds an `item` to the end of the queue
+    ///
+    /// Returns back the `item` if the queue is full
+    pub fn enqueue(&self, item: T) -> Result<(), T> {
+        self.enqueue_bounded(item, usize::MAX)
+            .map_err(EnqueueError::into_inner)
+    }
+
+    /// Adds an `item` to the end of the queue, giving up after `max_retries` lost races with
+    /// other contexts.
+    ///
+    /// See [`MpMcQueue::enqueue_bounded`].
+    pub fn enqueue_bounded(&self, item: T, max_retries: usize) -> Result<(), EnqueueError<T>> {
+        unsafe {
+            enqueue(
+                &self.buffer,
+                &self.enqueue_pos,
+                self.mask(),
+                item,
+                max_retries,
+                #[cfg(feature = "mpmc_stats")]
+                &self.enqueue_contention,
+            )
+        }
+    }
+
+    /// Returns the number of contention events since the queue was created.
+    ///
+    /// The counters wrap around on overflow.
+    #[cfg(feature = "mpmc_stats")]
+    pub fn contention(&self) -> Contention {
+        Contention {
+            enqueue: self.enqueue_contention.load(Ordering::Relaxed),
+            dequeue: self.dequeue_contention.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Dequeues and drops all items in the queue.
+    ///
+    /// See [`MpMcQueue::clear`].
+    pub fn clear(&mut self) {
+        while self.dequeue().is_some() {}
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Drop for BoxedMpMcQueue<T> {
+    fn drop(&mut self) {
+        if mem::needs_drop::<T>() {
+            self.clear();
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+unsafe impl<T> Sync for BoxedMpMcQueue<T> where T: Send {}
+
+#[cfg(feature = "alloc")]
+impl<T> PushBack<T> for BoxedMpMcQueue<T> {
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        self.enqueue(item)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> PopFront<T> for BoxedMpMcQueue<T> {
+    fn pop_front(&mut self) -> Option<T> {
+        self.dequeue()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T> Backpressure for BoxedMpMcQueue<T> {
+    fn pressure(&self) -> u8 {
+        let capacity = self.capacity();
+        fill_ratio(
+            approx_len(&self.dequeue_pos, &self.enqueue_pos, capacity),
+            capacity,
+        )
+    }
+}
+
 struct Cell<T> {
-    data: MaybeUninit<T>,
+    data: UnsafeCell<MaybeUninit<T>>,
     sequence: AtomicTargetSize,
 }
 
 impl<T> Cell<T> {
-    const fn new(seq: usize) -> Self {
-        Self {
-            data: MaybeUninit::uninit(),
-            sequence: AtomicTargetSize::new(seq as IntSize),
+    const_fn! {
+        fn new(seq: usize) -> Self {
+            Self {
+                data: UnsafeCell::new(MaybeUninit::uninit()),
+                sequence: AtomicTargetSize::new(seq as IntSize),
+            }
         }
     }
 }
 
+/// Returns the number of elements in a queue, clamped to `0..=capacity`.
+///
+/// The two positions are loaded separately, without synchronizing with each other, so the result
+/// is only a snapshot: if the queue was busy between the loads, the difference may exceed
+/// `capacity`, or go "negative" and wrap around when the `enqueue_pos` we see is older than the
+/// `dequeue_pos`.
+fn approx_len(
+    dequeue_pos: &AtomicTargetSize,
+    enqueue_pos: &AtomicTargetSize,
+    capacity: usize,
+) -> usize {
+    let dequeue_pos = dequeue_pos.load(Ordering::Relaxed);
+    let enqueue_pos = enqueue_pos.load(Ordering::Relaxed);
+    let len = to_usize(enqueue_pos.wrapping_sub(dequeue_pos));
+    if len <= capacity {
+        len
+    } else if len > to_usize(IntSize::MAX / 2) {
+        // a negative difference, the queue was drained in between
+        0
+    } else {
+        capacity
+    }
+}
+
 unsafe fn dequeue<T>(
-    buffer: *mut Cell<T>,
+    buffer: &[Cell<T>],
     dequeue_pos: &AtomicTargetSize,
     mask: IntSize,
+    #[cfg(feature = "mpmc_stats")] contention: &atomic::AtomicUsize,
 ) -> Option<T> {
     let mut pos = dequeue_pos.load(Ordering::Relaxed);
 
     let mut cell;
     loop {
-        cell = buffer.add(usize::from(pos & mask));
-        let seq = (*cell).sequence.load(Ordering::Acquire);
+        cell = buffer.get_unchecked(to_usize(pos & mask));
+        let seq = cell.sequence.load(Ordering::Acquire);
         let dif = (seq as i8).wrapping_sub((pos.wrapping_add(1)) as i8);
 
         match dif.cmp(&0) {
             core::cmp::Ordering::Equal => {
-                if dequeue_pos
-                    .compare_exchange_weak(
-                        pos,
-                        pos.wrapping_add(1),
-                        Ordering::Relaxed,
-                        Ordering::Relaxed,
-                    )
-                    .is_ok()
-                {
-                    break;
+                match dequeue_pos.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    // retry at the position that won, instead of at `pos`, whose sequence number
+                    // only changes once the winner is done with it
+                    Err(current) => pos = current,
                 }
             }
             core::cmp::Ordering::Less => {
@@ -239,62 +707,73 @@ unsafe fn dequeue<T>(
                 pos = dequeue_pos.load(Ordering::Relaxed);
             }
         }
+
+        #[cfg(feature = "mpmc_stats")]
+        contention.fetch_add(1, Ordering::Relaxed);
     }
 
-    let data = (*cell).data.as_ptr().read();
-    (*cell)
-        .sequence
+    let data = cell.data.with(|data| (*data).as_ptr().read());
+    cell.sequence
         .store(pos.wrapping_add(mask).wrapping_add(1), Ordering::Release);
     Some(data)
 }
 
 unsafe fn enqueue<T>(
-    buffer: *mut Cell<T>,
+    buffer: &[Cell<T>],
     enqueue_pos: &AtomicTargetSize,
     mask: IntSize,
     item: T,
-) -> Result<(), T> {
+    mut max_retries: usize,
+    #[cfg(feature = "mpmc_stats")] contention: &atomic::AtomicUsize,
+) -> Result<(), EnqueueError<T>> {
     let mut pos = enqueue_pos.load(Ordering::Relaxed);
 
     let mut cell;
     loop {
-        cell = buffer.add(usize::from(pos & mask));
-        let seq = (*cell).sequence.load(Ordering::Acquire);
+        cell = buffer.get_unchecked(to_usize(pos & mask));
+        let seq = cell.sequence.load(Ordering::Acquire);
         let dif = (seq as i8).wrapping_sub(pos as i8);
 
         match dif.cmp(&0) {
             core::cmp::Ordering::Equal => {
-                if enqueue_pos
-                    .compare_exchange_weak(
-                        pos,
-                        pos.wrapping_add(1),
-                        Ordering::Relaxed,
-                        Ordering::Relaxed,
-                    )
-                    .is_ok()
-                {
-                    break;
+                match enqueue_pos.compare_exchange_weak(
+                    pos,
+                    pos.wrapping_add(1),
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    // retry at the position that won, instead of at `pos`, whose sequence number
+                    // only changes once the winner is done with it
+                    Err(current) => pos = current,
                 }
             }
             core::cmp::Ordering::Less => {
-                return Err(item);
+                return Err(EnqueueError::Full(item));
             }
             core::cmp::Ordering::Greater => {
                 pos = enqueue_pos.load(Ordering::Relaxed);
             }
         }
+
+        #[cfg(feature = "mpmc_stats")]
+        contention.fetch_add(1, Ordering::Relaxed);
+
+        if max_retries == 0 {
+            return Err(EnqueueError::Contended(item));
+        }
+        max_retries -= 1;
     }
 
-    (*cell).data.as_mut_ptr().write(item);
-    (*cell)
-        .sequence
-        .store(pos.wrapping_add(1), Ordering::Release);
+    cell.data.with_mut(|data| (*data).as_mut_ptr().write(item));
+    cell.sequence.store(pos.wrapping_add(1), Ordering::Release);
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Q2;
+    use super::{EnqueueError, MpMcPool, Ordering, Q2, Q4};
+    use crate::Backpressure;
 
     #[test]
     fn sanity() {
@@ -308,6 +787,87 @@ mod tests {
         assert_eq!(q.dequeue(), None);
     }
 
+    #[test]
+    fn pressure() {
+        let q = Q4::new();
+        assert_eq!(q.pressure(), 0);
+
+        // also across wrap-around of the positions
+        for i in 0..300 {
+            q.enqueue(i).unwrap();
+            q.enqueue(i).unwrap();
+            assert_eq!(q.pressure(), 127);
+            q.dequeue().unwrap();
+            q.dequeue().unwrap();
+        }
+
+        for i in 0..4 {
+            q.enqueue(i).unwrap();
+        }
+        assert_eq!(q.pressure(), 255);
+
+        // positions loaded while the queue was busy: a stale `enqueue_pos` behind the
+        // `dequeue_pos` means the queue was empty, one far ahead of it that it was full
+        q.dequeue_pos.store(6, Ordering::Relaxed);
+        q.enqueue_pos.store(4, Ordering::Relaxed);
+        assert_eq!(q.pressure(), 0);
+        q.enqueue_pos.store(13, Ordering::Relaxed);
+        assert_eq!(q.pressure(), 255);
+    }
+
+    #[test]
+    fn enqueue_bounded() {
+        let q = Q2::new();
+        q.enqueue_bounded(0, 0).unwrap();
+
+        // rewind the enqueue position, as if another producer claimed slot 0 after we read the
+        // position, and kept doing so on every retry
+        q.enqueue_pos.store(0, Ordering::Relaxed);
+        assert_eq!(q.enqueue_bounded(1, 3), Err(EnqueueError::Contended(1)));
+        #[cfg(feature = "mpmc_stats")]
+        assert_eq!(q.contention().enqueue, 4);
+
+        q.enqueue_pos.store(1, Ordering::Relaxed);
+        q.enqueue_bounded(1, 0).unwrap();
+        assert_eq!(
+            q.enqueue_bounded(2, 0).map_err(EnqueueError::into_inner),
+            Err(2)
+        );
+        assert_eq!(q.dequeue(), Some(0));
+        assert_eq!(q.dequeue(), Some(1));
+    }
+
+    #[test]
+    fn clear() {
+        droppable!();
+
+        let mut q: Q4<Droppable> = Q4::new();
+        for _ in 0..3 {
+            q.enqueue(Droppable::new()).ok().unwrap();
+        }
+        drop(q.dequeue());
+        q.clear();
+        assert_eq!(Droppable::count(), 0);
+
+        // the positions have moved on, so the queue wraps around now
+        for _ in 0..4 {
+            q.enqueue(Droppable::new()).ok().unwrap();
+        }
+        assert!(q.enqueue(Droppable::new()).is_err());
+        assert_eq!(Droppable::count(), 4);
+        drop(q);
+        assert_eq!(Droppable::count(), 0);
+
+        #[cfg(feature = "alloc")]
+        {
+            let q = super::BoxedMpMcQueue::new(2);
+            q.enqueue(Droppable::new()).ok().unwrap();
+            assert_eq!(Droppable::count(), 1);
+            drop(q);
+            assert_eq!(Droppable::count(), 0);
+        }
+    }
+
     #[test]
     fn drain_at_pos255() {
         let q = Q2::new();
@@ -331,4 +891,86 @@ mod tests {
         // this should not block forever
         assert!(q.enqueue(0).is_err());
     }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn boxed() {
+        use super::BoxedMpMcQueue;
+
+        let q = BoxedMpMcQueue::new(4);
+        assert_eq!(q.capacity(), 4);
+        for _ in 0..100 {
+            for i in 0..4 {
+                q.enqueue(i).unwrap();
+            }
+            assert_eq!(q.enqueue(4), Err(4));
+            assert_eq!(q.pressure(), 255);
+            for i in 0..4 {
+                assert_eq!(q.dequeue(), Some(i));
+            }
+            assert_eq!(q.dequeue(), None);
+        }
+        // a stale `enqueue_pos` behind the `dequeue_pos`
+        let dequeue_pos = q.dequeue_pos.load(Ordering::Relaxed);
+        q.enqueue_pos
+            .store(dequeue_pos.wrapping_sub(2), Ordering::Relaxed);
+        assert_eq!(q.pressure(), 0);
+
+        let q = BoxedMpMcQueue::new(64);
+        std::thread::scope(|s| {
+            for _ in 0..2 {
+                s.spawn(|| {
+                    for i in 0..1000 {
+                        while q.enqueue(i).is_err() {}
+                    }
+                });
+            }
+            let mut sum = 0;
+            for _ in 0..2000 {
+                sum += loop {
+                    if let Some(i) = q.dequeue() {
+                        break i;
+                    }
+                };
+            }
+            assert_eq!(sum, 2 * 999 * 1000 / 2);
+        });
+    }
+
+    #[test]
+    fn pool() {
+        droppable!();
+
+        let pool: MpMcPool<Droppable, 4> = MpMcPool::new();
+        let a = pool.alloc(Droppable::new()).ok().unwrap();
+        let b = pool.alloc(Droppable::new()).ok().unwrap();
+        assert_ne!(a, b);
+        drop(unsafe { pool.free(a) });
+        assert_eq!(Droppable::count(), 1);
+
+        let mut indices = [b, 0, 0, 0];
+        for index in &mut indices[1..] {
+            *index = pool.alloc(Droppable::new()).ok().unwrap();
+        }
+        indices.sort();
+        assert_eq!(indices, [0, 1, 2, 3]);
+        assert!(pool.alloc(Droppable::new()).is_err());
+        assert_eq!(Droppable::count(), 4);
+
+        drop(unsafe { pool.free(b) });
+        drop(pool);
+        assert_eq!(Droppable::count(), 0);
+
+        let indices: MpMcPool<(), 2> = MpMcPool::new();
+        assert_eq!(indices.alloc(()), Ok(0));
+        assert_eq!(indices.alloc(()), Ok(1));
+        assert_eq!(indices.alloc(()), Err(()));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    #[should_panic]
+    fn boxed_capacity() {
+        super::BoxedMpMcQueue::<u8>::new(3);
+    }
 }
@@ -52,8 +52,13 @@
 //! Note that the execution time won't include `T`'s initialization nor `T`'s destructor which will
 //! be present in the general case for `Box` and `Arc`.
 
+// with `cfg(loom)`, only the stack is model checked, since the pools are `static`s
+#[cfg_attr(loom, allow(dead_code))]
 mod treiber;
 
+#[cfg(not(loom))]
 pub mod arc;
+#[cfg(not(loom))]
 pub mod boxed;
+#[cfg(not(loom))]
 pub mod object;
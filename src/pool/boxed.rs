@@ -77,14 +77,59 @@
 //!     P.manage(block);
 //! }
 //! ```
+//!
+//! # Leak detection
+//!
+//! With the `pool-leak-detection` feature, each block records which `OwnerTag` it was allocated
+//! for, and `BoxPool::for_each_outstanding` lists the tags of the blocks that are currently
+//! allocated. This makes it possible to find out which task holds on to, or leaked, pooled
+//! buffers. The bookkeeping costs three words per block and a few atomic operations per allocation.
+//!
+//! ```
+//! # #[cfg(feature = "pool-leak-detection")] {
+//! use heapless::{
+//!     box_pool,
+//!     pool::boxed::{Box, BoxBlock, BoxPool, OwnerTag},
+//! };
+//!
+//! box_pool!(P: [u8; 64]);
+//!
+//! static UART_RX: OwnerTag = OwnerTag::new("uart rx");
+//!
+//! P.manage(unsafe {
+//!     static mut B: BoxBlock<[u8; 64]> = BoxBlock::new();
+//!     &mut B
+//! });
+//! P.manage(unsafe {
+//!     static mut B: BoxBlock<[u8; 64]> = BoxBlock::new();
+//!     &mut B
+//! });
+//!
+//! let frame = P::alloc_tagged([0; 64], &UART_RX).unwrap();
+//! let _untagged = P.alloc([0; 64]).unwrap();
+//! assert_eq!(Box::owner(&frame).map(OwnerTag::name), Some("uart rx"));
+//!
+//! let mut outstanding = 0;
+//! P::for_each_outstanding(|owner| {
+//!     outstanding += 1;
+//!     // e.g. log `owner`
+//!     # let _ = owner;
+//! });
+//! assert_eq!(outstanding, 2);
+//! # }
+//! ```
 
 use core::{
     fmt,
     hash::{Hash, Hasher},
     mem::{ManuallyDrop, MaybeUninit},
-    ops, ptr,
+    ops,
+    ptr::{self, NonNull},
 };
 
+#[cfg(feature = "pool-leak-detection")]
+use core::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+
 use stable_deref_trait::StableDeref;
 
 use super::treiber::{NonNullPtr, Stack, UnionNode};
@@ -167,6 +212,68 @@ pub trait BoxPool: Sized {
     fn manage(block: &'static mut BoxBlock<Self::Data>) {
         Self::singleton().manage(block)
     }
+
+    /// Like [`alloc`](BoxPool::alloc), but records `owner` as the owner of the block, see
+    /// [`for_each_outstanding`](BoxPool::for_each_outstanding).
+    ///
+    /// # Errors
+    ///
+    /// The `Err`or variant is returned when the memory pool has run out of memory blocks
+    #[cfg(feature = "pool-leak-detection")]
+    fn alloc_tagged(value: Self::Data, owner: &'static OwnerTag) -> Result<Box<Self>, Self::Data> {
+        let boxed = Self::alloc(value)?;
+        boxed.tag().set_owner(Some(owner));
+        Ok(boxed)
+    }
+
+    /// Calls `f` with the owner of each block that's currently allocated, or `None` for blocks
+    /// allocated with [`alloc`](BoxPool::alloc).
+    ///
+    /// Blocks that are allocated or freed concurrently may or may not be visited.
+    #[cfg(feature = "pool-leak-detection")]
+    fn for_each_outstanding<F>(mut f: F)
+    where
+        F: FnMut(Option<&'static OwnerTag>),
+    {
+        let mut block = Self::singleton().blocks.load(Ordering::Acquire);
+        while !block.is_null() {
+            // SAFETY: managed blocks are `'static` and never leave the list
+            let tag = unsafe { BoxBlock::tag(block) };
+            if tag.allocated.load(Ordering::Acquire) {
+                f(tag.owner());
+            }
+            block = tag.next_managed.load(Ordering::Acquire);
+        }
+    }
+}
+
+/// The owner of a pooled block, for leak detection.
+///
+/// Blocks refer to their owner by reference, so tags are usually `static`s.
+#[cfg(feature = "pool-leak-detection")]
+#[derive(Debug)]
+pub struct OwnerTag {
+    name: &'static str,
+}
+
+#[cfg(feature = "pool-leak-detection")]
+impl OwnerTag {
+    /// Creates a tag with the given name.
+    pub const fn new(name: &'static str) -> Self {
+        Self { name }
+    }
+
+    /// Returns the name of the tag.
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[cfg(feature = "pool-leak-detection")]
+impl fmt::Display for OwnerTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
 }
 
 /// Like `std::boxed::Box` but managed by memory pool `P` rather than `#[global_allocator]`
@@ -177,13 +284,41 @@ where
     node_ptr: NonNullPtr<UnionNode<MaybeUninit<P::Data>>>,
 }
 
+#[cfg(feature = "pool-leak-detection")]
+impl<P> Box<P>
+where
+    P: BoxPool,
+{
+    fn tag(&self) -> &BlockTag<P::Data> {
+        // NOTE(cast) `BoxBlock` is `repr(C)` with the node as its first field, and the node
+        // pointer was made from a pointer to the whole block in `manage`
+        unsafe { BoxBlock::tag(self.node_ptr.as_ptr().cast()) }
+    }
+
+    /// Returns the owner recorded for this box's block.
+    ///
+    /// This is an associated function so that it doesn't shadow methods of `P::Data`.
+    pub fn owner(this: &Self) -> Option<&'static OwnerTag> {
+        this.tag().owner()
+    }
+
+    /// Records `owner` as the owner of this box's block, e.g. when handing the box to another
+    /// task.
+    pub fn set_owner(this: &mut Self, owner: &'static OwnerTag) {
+        this.tag().set_owner(Some(owner))
+    }
+}
+
 impl<A> Clone for Box<A>
 where
     A: BoxPool,
     A::Data: Clone,
 {
     fn clone(&self) -> Self {
-        A::alloc((**self).clone()).ok().expect("OOM")
+        let boxed = A::alloc((**self).clone()).ok().expect("OOM");
+        #[cfg(feature = "pool-leak-detection")]
+        boxed.tag().set_owner(Box::owner(self));
+        boxed
     }
 }
 
@@ -238,6 +373,9 @@ where
 
         unsafe { ptr::drop_in_place(node.as_ptr().cast::<P::Data>()) }
 
+        #[cfg(feature = "pool-leak-detection")]
+        self.tag().allocated.store(false, Ordering::Release);
+
         unsafe { P::singleton().stack.push(node) }
     }
 }
@@ -313,12 +451,17 @@ where
 #[doc(hidden)]
 pub struct BoxPoolImpl<T> {
     stack: Stack<UnionNode<MaybeUninit<T>>>,
+    /// All managed blocks, linked through `BoxBlock::next_managed`.
+    #[cfg(feature = "pool-leak-detection")]
+    blocks: AtomicPtr<BoxBlock<T>>,
 }
 
 impl<T> BoxPoolImpl<T> {
     pub const fn new() -> Self {
         Self {
             stack: Stack::new(),
+            #[cfg(feature = "pool-leak-detection")]
+            blocks: AtomicPtr::new(ptr::null_mut()),
         }
     }
 
@@ -326,6 +469,15 @@ impl<T> BoxPoolImpl<T> {
         if let Some(node_ptr) = self.stack.try_pop() {
             unsafe { node_ptr.as_ptr().cast::<T>().write(value) }
 
+            #[cfg(feature = "pool-leak-detection")]
+            {
+                // NOTE(cast) `BoxBlock` is `repr(C)` with the node as its first field, and the node
+                // pointer was made from a pointer to the whole block in `manage`
+                let tag = unsafe { BoxBlock::tag(node_ptr.as_ptr().cast::<BoxBlock<T>>()) };
+                tag.set_owner(None);
+                tag.allocated.store(true, Ordering::Release);
+            }
+
             Ok(node_ptr)
         } else {
             Err(value)
@@ -333,17 +485,53 @@ impl<T> BoxPoolImpl<T> {
     }
 
     fn manage(&self, block: &'static mut BoxBlock<T>) {
-        let node: &'static mut _ = &mut block.node;
+        // all pointers to the block are derived from this one, which covers the whole block, so
+        // that leak detection can get from the node to the fields after it
+        let block = NonNull::from(block);
+
+        #[cfg(feature = "pool-leak-detection")]
+        {
+            let tag = unsafe { BoxBlock::tag(block.as_ptr()) };
+            let mut head = self.blocks.load(Ordering::Relaxed);
+            loop {
+                tag.next_managed.store(head, Ordering::Relaxed);
+                match self.blocks.compare_exchange_weak(
+                    head,
+                    block.as_ptr(),
+                    Ordering::Release,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    Err(current) => head = current,
+                }
+            }
+        }
 
-        unsafe { self.stack.push(NonNullPtr::from_static_mut_ref(node)) }
+        // NOTE(cast) `BoxBlock` is `repr(C)` with the node as its first field
+        unsafe {
+            self.stack
+                .push(NonNullPtr::from_static_non_null(block.cast()))
+        }
     }
 }
 
 unsafe impl<T> Sync for BoxPoolImpl<T> {}
 
 /// A chunk of memory that a `BoxPool` singleton can manage
+// NOTE(repr(C)) leak detection finds the block from a pointer to its node
+#[repr(C)]
 pub struct BoxBlock<T> {
     node: UnionNode<MaybeUninit<T>>,
+    #[cfg(feature = "pool-leak-detection")]
+    tag: BlockTag<T>,
+}
+
+/// The leak detection state of a [`BoxBlock`].
+#[cfg(feature = "pool-leak-detection")]
+struct BlockTag<T> {
+    owner: AtomicPtr<OwnerTag>,
+    allocated: AtomicBool,
+    next_managed: AtomicPtr<BoxBlock<T>>,
 }
 
 impl<T> BoxBlock<T> {
@@ -353,8 +541,39 @@ impl<T> BoxBlock<T> {
             node: UnionNode {
                 data: ManuallyDrop::new(MaybeUninit::uninit()),
             },
+            #[cfg(feature = "pool-leak-detection")]
+            tag: BlockTag {
+                owner: AtomicPtr::new(ptr::null_mut()),
+                allocated: AtomicBool::new(false),
+                next_managed: AtomicPtr::new(ptr::null_mut()),
+            },
         }
     }
+
+    /// Returns the leak detection state of the block at `block`.
+    ///
+    /// This doesn't borrow the node, which a `Box` may be borrowing mutably.
+    ///
+    /// # Safety
+    ///
+    /// `block` must point to a `'static` block.
+    #[cfg(feature = "pool-leak-detection")]
+    unsafe fn tag<'a>(block: *const Self) -> &'a BlockTag<T> {
+        &*ptr::addr_of!((*block).tag)
+    }
+}
+
+#[cfg(feature = "pool-leak-detection")]
+impl<T> BlockTag<T> {
+    fn owner(&self) -> Option<&'static OwnerTag> {
+        // SAFETY: only ever set from `&'static OwnerTag`s
+        unsafe { self.owner.load(Ordering::Relaxed).as_ref() }
+    }
+
+    fn set_owner(&self, owner: Option<&'static OwnerTag>) {
+        let owner = owner.map_or(ptr::null_mut(), |owner| owner as *const _ as *mut _);
+        self.owner.store(owner, Ordering::Relaxed);
+    }
 }
 
 #[cfg(test)]
@@ -558,6 +777,80 @@ mod tests {
         assert!(twice.is_ok());
     }
 
+    #[cfg(feature = "pool-leak-detection")]
+    #[test]
+    fn leak_detection() {
+        static RX: OwnerTag = OwnerTag::new("rx");
+        static TX: OwnerTag = OwnerTag::new("tx");
+
+        box_pool!(P: u8);
+
+        let blocks: &'static mut [BoxBlock<u8>] = {
+            #[allow(clippy::declare_interior_mutable_const)]
+            const BLOCK: BoxBlock<u8> = BoxBlock::new();
+            static mut BLOCKS: [BoxBlock<u8>; 3] = [BLOCK; 3];
+            unsafe { &mut BLOCKS }
+        };
+        for block in blocks {
+            P.manage(block);
+        }
+
+        let owners = || {
+            let mut owners = std::vec::Vec::new();
+            P::for_each_outstanding(|owner| owners.push(owner.map(OwnerTag::name)));
+            owners.sort();
+            owners
+        };
+
+        let rx = P::alloc_tagged(1, &RX).unwrap();
+        let mut tx = P.alloc(2).unwrap();
+        assert_eq!(owners(), [None, Some("rx")]);
+
+        Box::set_owner(&mut tx, &TX);
+        let tx2 = tx.clone();
+        assert_eq!(Box::owner(&tx2).map(OwnerTag::name), Some("tx"));
+        assert_eq!(owners(), [Some("rx"), Some("tx"), Some("tx")]);
+
+        drop(rx);
+        drop(tx);
+        assert_eq!(owners(), [Some("tx")]);
+
+        // a reused block starts out untagged
+        let untagged = P.alloc(3).unwrap();
+        assert!(Box::owner(&untagged).is_none());
+    }
+
+    // run under miri, see the CI workflow: the tags are reached from pointers to the nodes, and
+    // read while the data is borrowed mutably
+    #[cfg(feature = "pool-leak-detection")]
+    #[test]
+    fn leak_detection_aliasing() {
+        static RX: OwnerTag = OwnerTag::new("rx");
+
+        box_pool!(P: u8);
+
+        let block = unsafe {
+            static mut B: BoxBlock<u8> = BoxBlock::new();
+            &mut B
+        };
+        P.manage(block);
+
+        let mut boxed = P::alloc_tagged(1, &RX).unwrap();
+        let data: &mut u8 = &mut boxed;
+        let mut outstanding = 0;
+        P::for_each_outstanding(|owner| {
+            assert_eq!(owner.map(OwnerTag::name), Some("rx"));
+            outstanding += 1;
+        });
+        *data = 2;
+        assert_eq!(outstanding, 1);
+
+        Box::set_owner(&mut boxed, &RX);
+        assert_eq!(*boxed, 2);
+        drop(boxed);
+        P::for_each_outstanding(|_| panic!("the block was freed"));
+    }
+
     #[test]
     fn box_pool_case() {
         // https://github.com/rust-embedded/heapless/issues/411
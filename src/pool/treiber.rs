@@ -17,9 +17,11 @@ impl<N> Stack<N>
 where
     N: Node,
 {
-    pub const fn new() -> Self {
-        Self {
-            top: AtomicPtr::null(),
+    const_fn! {
+        pub fn new() -> Self {
+            Self {
+                top: AtomicPtr::null(),
+            }
         }
     }
 
@@ -88,4 +90,50 @@ mod tests {
 
         assert_ne!(mem::size_of::<UnionNode<Zst>>(), 0);
     }
+
+    // run with `cargo test --target i686-unknown-linux-gnu --lib pool::treiber` and
+    // `RUSTFLAGS="--cfg loom"`, see `crate::sync`
+    #[cfg(loom)]
+    #[test]
+    fn loom_pop_push() {
+        use loom::{sync::Arc, thread};
+
+        struct Shared(Stack<StructNode<u8>>);
+
+        unsafe impl Send for Shared {}
+        unsafe impl Sync for Shared {}
+
+        fn node(data: u8) -> NonNullPtr<StructNode<u8>> {
+            NonNullPtr::from_static_mut_ref(std::boxed::Box::leak(std::boxed::Box::new(
+                StructNode {
+                    next: ManuallyDrop::new(AtomicPtr::null()),
+                    data: ManuallyDrop::new(data),
+                },
+            )))
+        }
+
+        loom::model(|| {
+            let stack = Arc::new(Shared(Stack::new()));
+            for data in 0..2 {
+                unsafe { stack.0.push(node(data)) };
+            }
+
+            // each thread holds at most one node at a time, so there's always one to pop
+            let threads: [_; 2] = core::array::from_fn(|_| {
+                let stack = stack.clone();
+                thread::spawn(move || {
+                    let node = stack.0.try_pop().unwrap();
+                    unsafe { stack.0.push(node) };
+                })
+            });
+            for thread in threads {
+                thread.join().unwrap();
+            }
+
+            let mut data = [0, 1].map(|_| unsafe { *(*stack.0.try_pop().unwrap().as_ptr()).data });
+            data.sort();
+            assert_eq!(data, [0, 1]);
+            assert!(stack.0.try_pop().is_none());
+        });
+    }
 }
@@ -2,10 +2,10 @@ use core::{
     marker::PhantomData,
     num::{NonZeroU32, NonZeroU64},
     ptr::NonNull,
-    sync::atomic::{AtomicU64, Ordering},
 };
 
 use super::{Node, Stack};
+use crate::sync::atomic::{AtomicU64, Ordering};
 
 pub struct AtomicPtr<N>
 where
@@ -19,10 +19,12 @@ impl<N> AtomicPtr<N>
 where
     N: Node,
 {
-    pub const fn null() -> Self {
-        Self {
-            inner: AtomicU64::new(0),
-            _marker: PhantomData,
+    const_fn! {
+        pub fn null() -> Self {
+            Self {
+                inner: AtomicU64::new(0),
+                _marker: PhantomData,
+            }
         }
     }
 
@@ -93,6 +95,16 @@ where
         Self::from_non_null(non_null)
     }
 
+    /// Like `from_static_mut_ref`, for a pointer that may also cover memory around the node, e.g.
+    /// the block that contains it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid, and not be used otherwise, for the rest of the program.
+    pub unsafe fn from_static_non_null(ptr: NonNull<N>) -> Self {
+        Self::from_non_null(ptr)
+    }
+
     fn from_non_null(ptr: NonNull<N>) -> Self {
         let address = ptr.as_ptr() as u32;
         let tag = initial_tag().get();
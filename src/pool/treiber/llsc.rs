@@ -43,6 +43,16 @@ where
             inner: NonNull::from(ref_),
         }
     }
+
+    /// Like `from_static_mut_ref`, for a pointer that may also cover memory around the node, e.g.
+    /// the block that contains it.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid, and not be used otherwise, for the rest of the program.
+    pub unsafe fn from_static_non_null(ptr: NonNull<N>) -> Self {
+        Self { inner: ptr }
+    }
 }
 
 impl<N> Clone for NonNullPtr<N>
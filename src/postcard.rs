@@ -0,0 +1,118 @@
+//! `postcard` maximum serialized size for heapless types
+//!
+//! postcard encodes sequences, maps and strings as a varint length prefix followed by the
+//! elements, so the worst case is a full container of worst case elements.
+
+use postcard::experimental::max_size::MaxSize;
+
+use crate::{
+    binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, IndexMap, IndexSet, LinearMap, String,
+    Vec,
+};
+
+/// Size of the varint length prefix for a length of at most `max_n`.
+const fn varint_size(max_n: usize) -> usize {
+    const BITS_PER_VARINT_BYTE: usize = 7;
+
+    if max_n == 0 {
+        return 1;
+    }
+
+    // How many data bits do we need for `max_n`.
+    let bits = usize::BITS as usize - max_n.leading_zeros() as usize;
+
+    // Round up to a whole number of varint bytes.
+    bits.div_ceil(BITS_PER_VARINT_BYTE)
+}
+
+const fn seq_max_size(element_max_size: usize, n: usize) -> usize {
+    varint_size(n) + element_max_size * n
+}
+
+impl<T, const N: usize> MaxSize for Vec<T, N>
+where
+    T: MaxSize,
+{
+    const POSTCARD_MAX_SIZE: usize = seq_max_size(T::POSTCARD_MAX_SIZE, N);
+}
+
+impl<T, const N: usize> MaxSize for Deque<T, N>
+where
+    T: MaxSize,
+{
+    const POSTCARD_MAX_SIZE: usize = seq_max_size(T::POSTCARD_MAX_SIZE, N);
+}
+
+impl<T, K, const N: usize> MaxSize for BinaryHeap<T, K, N>
+where
+    T: MaxSize + Ord,
+    K: BinaryHeapKind,
+{
+    const POSTCARD_MAX_SIZE: usize = seq_max_size(T::POSTCARD_MAX_SIZE, N);
+}
+
+impl<const N: usize> MaxSize for String<N> {
+    const POSTCARD_MAX_SIZE: usize = seq_max_size(1, N);
+}
+
+impl<K, V, const N: usize> MaxSize for LinearMap<K, V, N>
+where
+    K: MaxSize,
+    V: MaxSize,
+{
+    const POSTCARD_MAX_SIZE: usize = seq_max_size(K::POSTCARD_MAX_SIZE + V::POSTCARD_MAX_SIZE, N);
+}
+
+impl<K, V, S, const N: usize> MaxSize for IndexMap<K, V, S, N>
+where
+    K: MaxSize,
+    V: MaxSize,
+{
+    const POSTCARD_MAX_SIZE: usize = seq_max_size(K::POSTCARD_MAX_SIZE + V::POSTCARD_MAX_SIZE, N);
+}
+
+impl<T, S, const N: usize> MaxSize for IndexSet<T, S, N>
+where
+    T: MaxSize,
+{
+    const POSTCARD_MAX_SIZE: usize = seq_max_size(T::POSTCARD_MAX_SIZE, N);
+}
+
+#[cfg(test)]
+mod tests {
+    use postcard::experimental::max_size::MaxSize;
+
+    use crate::{FnvIndexMap, LinearMap, String, Vec};
+
+    #[test]
+    fn max_size() {
+        assert_eq!(Vec::<u8, 0>::POSTCARD_MAX_SIZE, 1);
+        assert_eq!(Vec::<u8, 127>::POSTCARD_MAX_SIZE, 1 + 127);
+        assert_eq!(Vec::<u8, 128>::POSTCARD_MAX_SIZE, 2 + 128);
+        assert_eq!(Vec::<u32, 4>::POSTCARD_MAX_SIZE, 1 + 4 * 5);
+        assert_eq!(String::<300>::POSTCARD_MAX_SIZE, 2 + 300);
+        assert_eq!(LinearMap::<u8, u16, 2>::POSTCARD_MAX_SIZE, 1 + 2 * (1 + 3));
+        assert_eq!(
+            FnvIndexMap::<u8, u16, 2>::POSTCARD_MAX_SIZE,
+            1 + 2 * (1 + 3)
+        );
+    }
+
+    #[test]
+    fn worst_case_fits() {
+        let mut buf = [0; Vec::<u32, 200>::POSTCARD_MAX_SIZE];
+
+        let vec: Vec<u32, 200> = Vec::from_slice(&[u32::MAX; 200]).unwrap();
+        let used = postcard::to_slice(&vec, &mut buf).unwrap();
+        assert_eq!(used.len(), buf.len());
+
+        let mut buf = [0; String::<200>::POSTCARD_MAX_SIZE];
+
+        let s: String<200> = core::str::from_utf8(&[b'a'; 200])
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let used = postcard::to_slice(&s, &mut buf).unwrap();
+        assert_eq!(used.len(), buf.len());
+    }
+}
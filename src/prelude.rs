@@ -0,0 +1,22 @@
+//! The `heapless` prelude.
+//!
+//! Glob-import it to get the common container types and the traits for writing code that's
+//! generic over them.
+//!
+//! ```
+//! use heapless::prelude::*;
+//!
+//! fn log<B: PushBack<u8> + Capacity>(buf: &mut B, msg: &[u8]) -> bool {
+//!     msg.len() <= buf.remaining() && buf.try_extend(msg.iter().copied()).is_ok()
+//! }
+//!
+//! let mut buf: Vec<u8, 8> = Vec::new();
+//! assert!(log(&mut buf, b"hello"));
+//! assert!(!log(&mut buf, b"world"));
+//! ```
+
+pub use crate::traits::{AsVecView, Capacity, PopFront, PriorityQueueOps, PushBack, TryExtend};
+pub use crate::{
+    Backpressure, BinaryHeap, Deque, FnvIndexMap, FnvIndexSet, HistoryBuffer, IndexMap, IndexSet,
+    LinearMap, String, TryCollect, Vec,
+};
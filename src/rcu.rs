@@ -0,0 +1,249 @@
+//! A double-buffered cell for read-mostly data that's replaced as a whole.
+//!
+//! [`RcuCell`] holds two versions of a value: the current one, which readers see, and a spare one,
+//! which the writer prepares the next version in. Publishing a new version is a single atomic
+//! generation bump, so a reader always gets a consistent `&T` -- either the old version or the
+//! new one, never a half-updated one. This suits configuration such as a routing table that an
+//! interrupt handler reads while the main loop occasionally replaces it.
+//!
+//! Neither side ever waits for the other. Readers retry for at most as long as the writer keeps
+//! publishing, and an update fails with an error instead of blocking when a reader still holds
+//! the spare version, e.g. because it started reading before the previous update was published.
+//!
+//! NOTE: This module requires atomic CAS operations. On targets without them, they are emulated by
+//! the [`portable-atomic`](https://crates.io/crates/portable-atomic) crate.
+//!
+//! # Example
+//!
+//! ```
+//! use heapless::{rcu::RcuCell, LinearMap};
+//!
+//! static ROUTES: RcuCell<LinearMap<u8, u8, 8>> = RcuCell::new(LinearMap::new(), LinearMap::new());
+//!
+//! // main loop: prepare the next version of the table from the current one and publish it
+//! ROUTES
+//!     .update(|current, next| {
+//!         next.clone_from(current);
+//!         next.insert(1, 42).unwrap();
+//!     })
+//!     .unwrap();
+//!
+//! // interrupt handler
+//! let routes = ROUTES.read();
+//! assert_eq!(routes.get(&1), Some(&42));
+//! ```
+
+use core::{cell::UnsafeCell, fmt, ops::Deref};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// A cell whose value is replaced by publishing a new version, see the
+/// [module-level documentation](self).
+pub struct RcuCell<T> {
+    slots: [UnsafeCell<T>; 2],
+    /// The current version is in `slots[generation % 2]`.
+    generation: AtomicUsize,
+    readers: [AtomicUsize; 2],
+    writing: AtomicBool,
+}
+
+impl<T> RcuCell<T> {
+    /// Creates a cell holding `value`.
+    ///
+    /// `spare` provides the storage the first update is prepared in; it's never visible to
+    /// readers.
+    pub const fn new(value: T, spare: T) -> Self {
+        Self {
+            slots: [UnsafeCell::new(value), UnsafeCell::new(spare)],
+            generation: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            writing: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the current version of the value.
+    ///
+    /// Versions published while the returned guard is alive don't affect it. While it's alive,
+    /// the version it refers to can't be reused for the update after the next one.
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        loop {
+            let generation = self.generation.load(Ordering::SeqCst);
+            let slot = generation % 2;
+            self.readers[slot].fetch_add(1, Ordering::SeqCst);
+
+            // If nothing was published in between, the writer has seen our reader count before
+            // it could start preparing a version in this slot.
+            if self.generation.load(Ordering::SeqCst) == generation {
+                return ReadGuard { cell: self, slot };
+            }
+
+            self.readers[slot].fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Prepares a new version with `f` and publishes it.
+    ///
+    /// `f` gets the current version and the spare one, which holds an outdated version. It has to
+    /// bring the spare one up to date, e.g. by `clone_from`-ing the current version and then
+    /// modifying it.
+    ///
+    /// Returns `Err` without calling `f` if a reader still holds the spare version, or if another
+    /// update is in progress.
+    #[allow(clippy::result_unit_err)]
+    pub fn update<F>(&self, f: F) -> Result<(), ()>
+    where
+        F: FnOnce(&T, &mut T),
+    {
+        if self.writing.swap(true, Ordering::Acquire) {
+            return Err(());
+        }
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let current = generation % 2;
+        let spare = 1 - current;
+
+        if self.readers[spare].load(Ordering::SeqCst) != 0 {
+            self.writing.store(false, Ordering::Release);
+            return Err(());
+        }
+
+        // NOTE(unsafe) readers only ever access the current slot, and the check above guarantees
+        // that no reader that started before the last publication is still in the spare slot.
+        unsafe { f(&*self.slots[current].get(), &mut *self.slots[spare].get()) };
+
+        self.generation
+            .store(generation.wrapping_add(1), Ordering::SeqCst);
+        self.writing.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    /// Publishes `value` as the new version.
+    ///
+    /// Returns back the `value` if a reader still holds the spare version, or if another update
+    /// is in progress.
+    pub fn publish(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        match self.update(|_, next| *next = value.take().unwrap()) {
+            Ok(()) => Ok(()),
+            Err(()) => Err(value.unwrap()),
+        }
+    }
+
+    /// Returns a mutable reference to the current version.
+    ///
+    /// This needs no synchronization since the exclusive borrow statically guarantees that there
+    /// are no readers.
+    pub fn get_mut(&mut self) -> &mut T {
+        let slot = *self.generation.get_mut() % 2;
+        self.slots[slot].get_mut()
+    }
+
+    /// Consumes the cell, returning the current version.
+    pub fn into_inner(self) -> T {
+        let [a, b] = self.slots;
+        match self.generation.into_inner() % 2 {
+            0 => a.into_inner(),
+            _ => b.into_inner(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for RcuCell<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RcuCell").field(&*self.read()).finish()
+    }
+}
+
+unsafe impl<T> Sync for RcuCell<T> where T: Send + Sync {}
+
+/// A version of the value in an [`RcuCell`], which can't be reused while this guard is alive.
+///
+/// Comes from [`RcuCell::read`].
+pub struct ReadGuard<'a, T> {
+    cell: &'a RcuCell<T>,
+    slot: usize,
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.cell.slots[self.slot].get() }
+    }
+}
+
+impl<T> fmt::Debug for ReadGuard<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        T::fmt(self, f)
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.cell.readers[self.slot].fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RcuCell;
+    use crate::Vec;
+
+    #[test]
+    fn publish_read() {
+        let cell: RcuCell<u32> = RcuCell::new(1, 0);
+        assert_eq!(*cell.read(), 1);
+
+        let old = cell.read();
+        cell.publish(2).unwrap();
+        assert_eq!(*old, 1);
+        assert_eq!(*cell.read(), 2);
+
+        // `old` still holds the spare slot
+        assert_eq!(cell.publish(3), Err(3));
+        drop(old);
+        cell.publish(3).unwrap();
+        assert_eq!(*cell.read(), 3);
+        assert_eq!(cell.into_inner(), 3);
+    }
+
+    #[test]
+    fn update() {
+        let mut cell: RcuCell<Vec<u8, 4>> = RcuCell::new(Vec::new(), Vec::new());
+
+        for i in 0..4 {
+            cell.update(|current, next| {
+                next.clone_from(current);
+                next.push(i).unwrap();
+            })
+            .unwrap();
+        }
+        assert_eq!(*cell.read(), [0, 1, 2, 3]);
+
+        cell.get_mut().clear();
+        assert!(cell.read().is_empty());
+    }
+
+    #[test]
+    fn nested_update() {
+        let cell: RcuCell<u8> = RcuCell::new(0, 0);
+
+        cell.update(|_, next| {
+            assert_eq!(cell.publish(2), Err(2));
+            *next = 1;
+        })
+        .unwrap();
+        assert_eq!(*cell.read(), 1);
+    }
+}
@@ -0,0 +1,298 @@
+use core::fmt;
+use core::iter::FusedIterator;
+
+/// Length prefix that marks the rest of the buffer as unused; the next record starts at offset 0.
+const WRAP: u16 = u16::MAX;
+
+/// An append-only log of variable-length byte records, stored in a ring buffer of `N` bytes.
+///
+/// Each record is stored contiguously behind a 2-byte length prefix. When a new record doesn't
+/// fit, the oldest records are dropped to make room for it, so the log always holds the most
+/// recent records. Unlike a [`HistoryBuffer<u8, N>`](crate::HistoryBuffer), the log keeps the
+/// boundaries between records, and unlike a [`Deque`](crate::Deque) a record is written with a
+/// single copy.
+///
+/// Since records don't wrap around the end of the buffer, the space between the last record and
+/// the end of the buffer can stay unused. A record can be at most
+/// [`max_record_len`](RecordLog::max_record_len) bytes long.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::RecordLog;
+///
+/// let mut log: RecordLog<16> = RecordLog::new();
+/// log.append(b"boot").unwrap();
+/// log.append(b"link up").unwrap();
+/// assert!(log.iter().eq([&b"boot"[..], b"link up"]));
+///
+/// // the oldest record makes room for the newest one
+/// log.append(b"temp").unwrap();
+/// assert!(log.iter().eq([&b"link up"[..], b"temp"]));
+///
+/// // records that can never fit are rejected
+/// assert!(log.append(&[0; 15]).is_err());
+/// ```
+pub struct RecordLog<const N: usize> {
+    buffer: [u8; N],
+    /// Offset of the length prefix of the oldest record.
+    head: usize,
+    /// Offset the next record is written at.
+    tail: usize,
+    records: usize,
+}
+
+impl<const N: usize> RecordLog<N> {
+    /// Constructs a new, empty log.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::RecordLog;
+    ///
+    /// static mut LOG: RecordLog<256> = RecordLog::new();
+    /// ```
+    pub const fn new() -> Self {
+        // Const assert
+        crate::sealed::greater_than_eq::<N, 3>();
+
+        Self {
+            buffer: [0; N],
+            head: 0,
+            tail: 0,
+            records: 0,
+        }
+    }
+
+    /// Returns the length of the longest record the log can hold.
+    pub const fn max_record_len() -> usize {
+        let max = N - 2;
+        if max < WRAP as usize {
+            max
+        } else {
+            WRAP as usize - 1
+        }
+    }
+
+    /// Appends `record`, dropping the oldest records as needed to make room for it.
+    ///
+    /// Returns `Err` without modifying the log if `record` is longer than
+    /// [`max_record_len`](RecordLog::max_record_len).
+    #[allow(clippy::result_unit_err)]
+    pub fn append(&mut self, record: &[u8]) -> Result<(), ()> {
+        if record.len() > Self::max_record_len() {
+            return Err(());
+        }
+
+        let needed = 2 + record.len();
+        let at = loop {
+            if self.records == 0 {
+                self.head = 0;
+                self.tail = 0;
+            }
+
+            if self.records != 0 && self.tail <= self.head {
+                // free space is between the newest and the oldest record
+                if self.head - self.tail >= needed {
+                    break self.tail;
+                }
+            } else if N - self.tail >= needed {
+                break self.tail;
+            } else if self.head >= needed {
+                if N - self.tail >= 2 {
+                    self.buffer[self.tail..self.tail + 2].copy_from_slice(&WRAP.to_le_bytes());
+                }
+                break 0;
+            }
+
+            self.remove_oldest();
+        };
+
+        self.buffer[at..at + 2].copy_from_slice(&(record.len() as u16).to_le_bytes());
+        self.buffer[at + 2..at + needed].copy_from_slice(record);
+        self.tail = at + needed;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Drops the oldest record.
+    ///
+    /// Returns `false` if the log is empty.
+    pub fn remove_oldest(&mut self) -> bool {
+        if self.records == 0 {
+            return false;
+        }
+
+        self.head += 2 + self.record_len(self.head);
+        self.records -= 1;
+        if self.records != 0 {
+            self.head = self.resolve_wrap(self.head);
+        }
+        true
+    }
+
+    /// Returns the oldest record, or `None` if the log is empty.
+    pub fn oldest(&self) -> Option<&[u8]> {
+        self.iter().next()
+    }
+
+    /// Returns the newest record, or `None` if the log is empty.
+    pub fn newest(&self) -> Option<&[u8]> {
+        self.iter().last()
+    }
+
+    /// Returns the number of records in the log.
+    pub fn len(&self) -> usize {
+        self.records
+    }
+
+    /// Returns `true` if the log holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.records == 0
+    }
+
+    /// Drops all records.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.tail = 0;
+        self.records = 0;
+    }
+
+    /// Returns an iterator over the records, from the oldest to the newest.
+    pub fn iter(&self) -> Iter<'_, N> {
+        Iter {
+            log: self,
+            at: self.head,
+            remaining: self.records,
+        }
+    }
+
+    fn record_len(&self, at: usize) -> usize {
+        usize::from(u16::from_le_bytes([self.buffer[at], self.buffer[at + 1]]))
+    }
+
+    /// Maps the offset after the last record before a wrap-around to 0.
+    fn resolve_wrap(&self, at: usize) -> usize {
+        if N - at < 2 || self.record_len(at) == usize::from(WRAP) {
+            0
+        } else {
+            at
+        }
+    }
+}
+
+impl<const N: usize> Default for RecordLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Clone for RecordLog<N> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer,
+            head: self.head,
+            tail: self.tail,
+            records: self.records,
+        }
+    }
+}
+
+impl<const N: usize> fmt::Debug for RecordLog<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, const N: usize> IntoIterator for &'a RecordLog<N> {
+    type Item = &'a [u8];
+    type IntoIter = Iter<'a, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the records of a [`RecordLog`], from the oldest to the newest.
+///
+/// Created by [`RecordLog::iter`].
+#[derive(Clone)]
+pub struct Iter<'a, const N: usize> {
+    log: &'a RecordLog<N>,
+    at: usize,
+    remaining: usize,
+}
+
+impl<'a, const N: usize> Iterator for Iter<'a, N> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let at = self.log.resolve_wrap(self.at);
+        let len = self.log.record_len(at);
+        self.at = at + 2 + len;
+        self.remaining -= 1;
+        Some(&self.log.buffer[at + 2..at + 2 + len])
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for Iter<'_, N> {}
+
+impl<const N: usize> FusedIterator for Iter<'_, N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::RecordLog;
+
+    #[test]
+    fn wrap_around() {
+        let mut log: RecordLog<10> = RecordLog::new();
+        assert_eq!(RecordLog::<10>::max_record_len(), 8);
+        assert_eq!(log.oldest(), None);
+
+        log.append(b"abc").unwrap();
+        log.append(b"de").unwrap();
+        assert_eq!(log.len(), 2);
+        // doesn't fit behind "de", so "abc" is dropped and it goes to the start
+        log.append(b"fgh").unwrap();
+        assert!(log.iter().eq([&b"de"[..], b"fgh"]));
+        // "fgh" ends where "de" starts, so the log is full
+        log.append(b"").unwrap();
+        assert!(log.iter().eq([&b"fgh"[..], b""]));
+        log.append(b"i").unwrap();
+        assert!(log.iter().eq([&b"fgh"[..], b"", b"i"]));
+        assert_eq!(log.newest(), Some(&b"i"[..]));
+
+        log.append(b"jklmnopq").unwrap();
+        assert!(log.iter().eq([&b"jklmnopq"[..]]));
+        assert_eq!(log.append(b"jklmnopqr"), Err(()));
+        assert_eq!(log.len(), 1);
+
+        assert!(log.remove_oldest());
+        assert!(!log.remove_oldest());
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn many_records() {
+        let mut log: RecordLog<64> = RecordLog::new();
+        for i in 0..1000u32 {
+            let record = &i.to_le_bytes()[..(i % 5) as usize];
+            log.append(record).unwrap();
+
+            assert_eq!(log.newest(), Some(record));
+            assert!(log.iter().map(|r| r.len() + 2).sum::<usize>() <= 64);
+            assert_eq!(log.iter().count(), log.len());
+        }
+
+        log.clear();
+        assert_eq!(log.iter().next(), None);
+    }
+}
@@ -3,6 +3,11 @@ pub(crate) const fn smaller_than<const N: usize, const MAX: usize>() {
     Assert::<N, MAX>::LESS;
 }
 
+#[allow(dead_code, path_statements, clippy::no_effect)]
+pub(crate) const fn greater_than_eq<const N: usize, const MIN: usize>() {
+    Assert::<N, MIN>::GREATER_EQ;
+}
+
 #[allow(dead_code, path_statements, clippy::no_effect)]
 pub(crate) const fn greater_than_eq_0<const N: usize>() {
     Assert::<N, 0>::GREATER_EQ;
@@ -24,10 +29,58 @@ pub(crate) const fn power_of_two<const N: usize>() {
     Assert::<N, 0>::POWER_OF_TWO;
 }
 
+#[allow(dead_code, path_statements, clippy::no_effect)]
+pub(crate) const fn sum_at_most<const A: usize, const B: usize, const MAX: usize>() {
+    Sum::<A, B, MAX>::AT_MOST;
+}
+
+#[allow(dead_code, path_statements, clippy::no_effect)]
+pub(crate) const fn product_at_most<const A: usize, const B: usize, const MAX: usize>() {
+    Product::<A, B, MAX>::AT_MOST;
+}
+
+#[allow(dead_code, path_statements, clippy::no_effect)]
+pub(crate) const fn words_for_bits<const BITS: usize, const W: usize>() {
+    Words::<BITS, W>::EXACT;
+}
+
+#[allow(dead_code)]
+/// Const assert hack
+pub struct Words<const BITS: usize, const W: usize>;
+
+#[allow(dead_code)]
+impl<const BITS: usize, const W: usize> Words<BITS, W> {
+    /// Const assert hack
+    pub const EXACT: () = assert!(
+        W == BITS.div_ceil(32),
+        "the word count must be the number of IDs divided by 32, rounded up"
+    );
+}
+
 #[allow(dead_code)]
 /// Const assert hack
 pub struct Assert<const L: usize, const R: usize>;
 
+#[allow(dead_code)]
+/// Const assert hack
+pub struct Sum<const A: usize, const B: usize, const MAX: usize>;
+
+#[allow(dead_code)]
+impl<const A: usize, const B: usize, const MAX: usize> Sum<A, B, MAX> {
+    /// Const assert hack
+    pub const AT_MOST: () = assert!(matches!(A.checked_add(B), Some(s) if s <= MAX));
+}
+
+#[allow(dead_code)]
+/// Const assert hack
+pub struct Product<const A: usize, const B: usize, const MAX: usize>;
+
+#[allow(dead_code)]
+impl<const A: usize, const B: usize, const MAX: usize> Product<A, B, MAX> {
+    /// Const assert hack
+    pub const AT_MOST: () = assert!(matches!(A.checked_mul(B), Some(p) if p <= MAX));
+}
+
 #[allow(dead_code)]
 impl<const L: usize, const R: usize> Assert<L, R> {
     /// Const assert hack
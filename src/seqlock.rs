@@ -0,0 +1,237 @@
+//! A sequence lock for containers that one context updates in place while others read them.
+//!
+//! [`SeqLock`] pairs a value with a generation counter that is odd while a write is in progress
+//! and is bumped once more when the write completes. A reader copies the value out and then checks
+//! that the counter is even and didn't change in the meantime. If it did, the copy may be torn and
+//! is discarded, and [`SeqLock::read`] returns an error so that the reader can retry. This suits
+//! e.g. a map that an interrupt handler updates and the main loop iterates: the main loop iterates
+//! a consistent copy, and the interrupt handler never waits.
+//!
+//! Values are only ever read as a bitwise copy, so they must implement [`Snapshot`]. It's
+//! implemented for `Copy` types and for `Vec`, `Deque`, `String`, `LinearMap` and `IndexMap` with
+//! `Copy` contents.
+//!
+//! NOTE: The generation counter only needs atomic load/store, since there is a single writer at a
+//! time. Readers on other cores are supported, but a writer must not be preempted by another
+//! writer.
+//!
+//! # Example
+//!
+//! ```
+//! use heapless::{seqlock::SeqLock, LinearMap};
+//!
+//! static LEVELS: SeqLock<LinearMap<u8, u16, 8>> = SeqLock::new(LinearMap::new());
+//!
+//! // in the interrupt handler, the only writer
+//! // Safety: no other context writes to `LEVELS`.
+//! unsafe { LEVELS.write() }.insert(3, 1200).unwrap();
+//!
+//! // in `main`
+//! let levels = loop {
+//!     if let Ok(levels) = LEVELS.read() {
+//!         break levels;
+//!     }
+//! };
+//! for (channel, level) in levels.iter() {
+//!     assert_eq!((*channel, *level), (3, 1200));
+//! }
+//! ```
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+    ptr,
+};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicUsize, Ordering};
+use hash32::BuildHasherDefault;
+
+use crate::{Deque, IndexMap, LinearMap, String, Vec};
+
+/// Types whose bitwise copy is a valid value that can be used and dropped independently of the
+/// original.
+///
+/// # Safety
+///
+/// Bitwise copies of the value must not share ownership of anything with the original, and
+/// dropping a copy must not affect the original.
+pub unsafe trait Snapshot {}
+
+unsafe impl<T> Snapshot for T where T: Copy {}
+unsafe impl<T, const N: usize> Snapshot for Vec<T, N> where T: Copy {}
+unsafe impl<T, const N: usize> Snapshot for Deque<T, N> where T: Copy {}
+unsafe impl<const N: usize> Snapshot for String<N> {}
+unsafe impl<K, V, const N: usize> Snapshot for LinearMap<K, V, N>
+where
+    K: Copy,
+    V: Copy,
+{
+}
+unsafe impl<K, V, H, const N: usize> Snapshot for IndexMap<K, V, BuildHasherDefault<H>, N>
+where
+    K: Copy,
+    V: Copy,
+{
+}
+
+/// A value that is updated in place by one writer and read as consistent copies, see the
+/// [module-level documentation](self).
+pub struct SeqLock<T> {
+    data: UnsafeCell<T>,
+    /// Odd while a write is in progress.
+    generation: AtomicUsize,
+}
+
+impl<T> SeqLock<T> {
+    /// Creates a lock holding `value`.
+    pub const fn new(value: T) -> Self {
+        Self {
+            data: UnsafeCell::new(value),
+            generation: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns twice the number of completed writes, plus one while a write is in progress.
+    pub fn generation(&self) -> usize {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Returns a copy of the value.
+    ///
+    /// Returns `Err(())` if the value was written to during the copy, or a write is in progress,
+    /// in which case the read can be retried.
+    #[allow(clippy::result_unit_err)]
+    pub fn read(&self) -> Result<T, ()>
+    where
+        T: Snapshot,
+    {
+        let before = self.generation.load(Ordering::Acquire);
+        if before % 2 == 1 {
+            return Err(());
+        }
+
+        // NOTE(volatile) the writer may change the value while it's being copied
+        let copy = unsafe { ptr::read_volatile(self.data.get() as *const MaybeUninit<T>) };
+
+        atomic::fence(Ordering::Acquire);
+        if self.generation.load(Ordering::Relaxed) != before {
+            return Err(());
+        }
+        // Safety: the generation didn't change, so the value wasn't written to during the copy
+        Ok(unsafe { copy.assume_init() })
+    }
+
+    /// Starts a write, which completes when the returned guard is dropped.
+    ///
+    /// # Safety
+    ///
+    /// No other [`WriteGuard`] on this lock may be alive for as long as the returned guard is,
+    /// e.g. because only one context ever writes. Readers don't need to be excluded.
+    pub unsafe fn write(&self) -> WriteGuard<'_, T> {
+        let generation = self.generation.load(Ordering::Relaxed);
+        self.generation
+            .store(generation.wrapping_add(1), Ordering::Relaxed);
+        atomic::fence(Ordering::Release);
+        WriteGuard {
+            lock: self,
+            generation,
+        }
+    }
+
+    /// Returns a mutable reference to the value.
+    ///
+    /// This needs no write guard since the exclusive borrow statically guarantees that there are
+    /// no readers.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Consumes the lock, returning the value.
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T> Default for SeqLock<T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> fmt::Debug for SeqLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeqLock")
+            .field("generation", &self.generation())
+            .finish_non_exhaustive()
+    }
+}
+
+unsafe impl<T> Sync for SeqLock<T> where T: Send {}
+
+/// Write access to the value of a [`SeqLock`]; the write completes when this is dropped.
+///
+/// Comes from [`SeqLock::write`].
+pub struct WriteGuard<'a, T> {
+    lock: &'a SeqLock<T>,
+    /// The generation before the write started.
+    generation: usize,
+}
+
+impl<T> Deref for WriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock
+            .generation
+            .store(self.generation.wrapping_add(2), Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SeqLock;
+    use crate::{FnvIndexMap, LinearMap};
+
+    #[test]
+    fn read_write() {
+        let lock: SeqLock<FnvIndexMap<u8, u8, 4>> = SeqLock::new(FnvIndexMap::new());
+        assert_eq!(lock.generation(), 0);
+
+        let mut guard = unsafe { lock.write() };
+        guard.insert(1, 2).unwrap();
+        assert_eq!(lock.generation(), 1);
+        assert_eq!(lock.read(), Err(()));
+        drop(guard);
+
+        assert_eq!(lock.generation(), 2);
+        let map = lock.read().unwrap();
+        assert!(map.iter().eq([(&1, &2)]));
+
+        let mut lock = SeqLock::new(LinearMap::<u8, u8, 2>::new());
+        lock.get_mut().insert(0, 0).unwrap();
+        assert_eq!(lock.read().unwrap().len(), 1);
+        assert_eq!(lock.into_inner().len(), 1);
+    }
+}
@@ -1,8 +1,8 @@
 use core::hash::{BuildHasher, Hash};
 
 use crate::{
-    binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, IndexMap, IndexSet, LinearMap, String,
-    Vec,
+    binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, HistoryBuffer, IndexMap, IndexSet,
+    LinearMap, String, Vec,
 };
 use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
 
@@ -74,6 +74,23 @@ where
     }
 }
 
+/// Serializes the values from the oldest to the most recent.
+impl<T, const N: usize> Serialize for HistoryBuffer<T, N>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for element in self.oldest_ordered() {
+            seq.serialize_element(element)?;
+        }
+        seq.end()
+    }
+}
+
 // Dictionaries
 
 impl<K, V, S, const N: usize> Serialize for IndexMap<K, V, S, N>
@@ -118,6 +135,6 @@ impl<const N: usize> Serialize for String<N> {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&*self)
+        serializer.serialize_str(self)
     }
 }
@@ -0,0 +1,171 @@
+//! A keyed SipHash-1-3 hasher, for [`IndexMap`](crate::IndexMap)s and
+//! [`IndexSet`](crate::IndexSet)s whose keys come from untrusted input.
+//!
+//! [`FnvIndexMap`](crate::FnvIndexMap) hashes keys deterministically, so whoever controls the keys
+//! (e.g. the IDs of network peers) can pick ones that collide and make every lookup probe the whole
+//! table. [`SipBuildHasher`] mixes a secret 128-bit key into every hash instead. The key should
+//! differ between devices, e.g. be derived from the device's unique ID or a random number
+//! generator, but is fixed for the lifetime of a map, so iteration order stays deterministic on a
+//! given device.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{sip::SipBuildHasher, IndexMap};
+//!
+//! // e.g. read from the device's unique ID registers
+//! let device_id = [0x42; 16];
+//!
+//! let mut peers: IndexMap<u32, &str, SipBuildHasher, 8> =
+//!     IndexMap::with_hasher(SipBuildHasher::from_key(device_id));
+//! peers.insert(0x1234_5678, "gateway").unwrap();
+//! assert_eq!(peers.get(&0x1234_5678), Some(&"gateway"));
+//! ```
+
+use core::hash::{BuildHasher, Hasher};
+
+/// A [`BuildHasher`] that creates [`SipHasher13`]s with a fixed key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SipBuildHasher {
+    k0: u64,
+    k1: u64,
+}
+
+impl SipBuildHasher {
+    /// Creates a builder whose hashers use the key formed by `k0` and `k1`.
+    pub const fn new(k0: u64, k1: u64) -> Self {
+        Self { k0, k1 }
+    }
+
+    /// Creates a builder whose hashers use `key`, read as two little-endian words.
+    pub const fn from_key(key: [u8; 16]) -> Self {
+        let [a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p] = key;
+        Self::new(
+            u64::from_le_bytes([a, b, c, d, e, f, g, h]),
+            u64::from_le_bytes([i, j, k, l, m, n, o, p]),
+        )
+    }
+}
+
+impl BuildHasher for SipBuildHasher {
+    type Hasher = SipHasher13;
+
+    fn build_hasher(&self) -> SipHasher13 {
+        SipHasher13::new_with_keys(self.k0, self.k1)
+    }
+}
+
+/// The SipHash-1-3 hasher, as used by the standard library's `HashMap`.
+#[derive(Clone, Copy, Debug)]
+pub struct SipHasher13 {
+    v0: u64,
+    v1: u64,
+    v2: u64,
+    v3: u64,
+    /// Bytes that don't fill a whole word yet, little-endian.
+    tail: u64,
+    /// Total number of bytes written.
+    length: usize,
+}
+
+impl SipHasher13 {
+    /// Creates a hasher with the key formed by `k0` and `k1`.
+    pub const fn new_with_keys(k0: u64, k1: u64) -> Self {
+        Self {
+            v0: k0 ^ 0x736f_6d65_7073_6575,
+            v1: k1 ^ 0x646f_7261_6e64_6f6d,
+            v2: k0 ^ 0x6c79_6765_6e65_7261,
+            v3: k1 ^ 0x7465_6462_7974_6573,
+            tail: 0,
+            length: 0,
+        }
+    }
+
+    fn round(&mut self) {
+        self.v0 = self.v0.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(13) ^ self.v0;
+        self.v0 = self.v0.rotate_left(32);
+        self.v2 = self.v2.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(16) ^ self.v2;
+        self.v0 = self.v0.wrapping_add(self.v3);
+        self.v3 = self.v3.rotate_left(21) ^ self.v0;
+        self.v2 = self.v2.wrapping_add(self.v1);
+        self.v1 = self.v1.rotate_left(17) ^ self.v2;
+        self.v2 = self.v2.rotate_left(32);
+    }
+
+    fn compress(&mut self, word: u64) {
+        self.v3 ^= word;
+        self.round();
+        self.v0 ^= word;
+    }
+}
+
+impl Hasher for SipHasher13 {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.tail |= u64::from(byte) << (8 * (self.length % 8));
+            self.length = self.length.wrapping_add(1);
+            if self.length.is_multiple_of(8) {
+                let word = self.tail;
+                self.compress(word);
+                self.tail = 0;
+            }
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut state = *self;
+        state.compress(((self.length as u64) << 56) | self.tail);
+        state.v2 ^= 0xff;
+        for _ in 0..3 {
+            state.round();
+        }
+        state.v0 ^ state.v1 ^ state.v2 ^ state.v3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::{BuildHasher, Hasher};
+
+    use super::{SipBuildHasher, SipHasher13};
+    use crate::IndexMap;
+
+    #[test]
+    fn reference() {
+        // SipHash-1-3 of the bytes 0..15 with the key 0..16, as the standard library computes it
+        let key: [u8; 16] = core::array::from_fn(|i| i as u8);
+        let message: [u8; 15] = core::array::from_fn(|i| i as u8);
+        let build = SipBuildHasher::from_key(key);
+
+        let mut hasher = build.build_hasher();
+        hasher.write(&message);
+        assert_eq!(hasher.finish(), 0xd320_d86d_2a51_9956);
+
+        // split writes hash the same as one write
+        let mut hasher = build.build_hasher();
+        hasher.write(&message[..3]);
+        hasher.write(&message[3..]);
+        assert_eq!(hasher.finish(), 0xd320_d86d_2a51_9956);
+    }
+
+    #[test]
+    fn keyed() {
+        let a = SipBuildHasher::new(1, 2);
+        let b = SipBuildHasher::new(1, 3);
+        assert_eq!(a.hash_one("peer"), a.hash_one("peer"));
+        assert_ne!(a.hash_one("peer"), b.hash_one("peer"));
+        assert_ne!(
+            SipHasher13::new_with_keys(0, 0).finish(),
+            SipHasher13::new_with_keys(0, 1).finish()
+        );
+
+        let mut map: IndexMap<u16, u16, _, 4> = IndexMap::with_hasher(a);
+        for i in 0..4 {
+            map.insert(i, i * 2).unwrap();
+        }
+        assert!(map.insert(4, 8).is_err());
+        assert!(map.iter().eq([(&0, &0), (&1, &2), (&2, &4), (&3, &6)]));
+    }
+}
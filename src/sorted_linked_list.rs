@@ -30,7 +30,7 @@ use core::cmp::Ordering;
 use core::fmt;
 use core::marker::PhantomData;
 use core::mem::MaybeUninit;
-use core::ops::{Deref, DerefMut};
+use core::ops::{Bound, Deref, DerefMut, RangeBounds};
 use core::ptr;
 
 /// Trait for defining an index for the linked list, never implemented by users.
@@ -400,6 +400,99 @@ where
         None
     }
 
+    /// Get an iterator over the elements that lie within `range`, in list order.
+    ///
+    /// Complexity is worst-case *O*(n), but the search stops as soon as the end of the range has
+    /// been passed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use heapless::sorted_linked_list::{Min, SortedLinkedList};
+    /// let mut ll: SortedLinkedList<_, _, Min, 8> = SortedLinkedList::new_usize();
+    ///
+    /// for deadline in [30, 5, 12, 8, 21] {
+    ///     ll.push(deadline).unwrap();
+    /// }
+    ///
+    /// // Everything due in the next 10 ms
+    /// let mut due = ll.range(..=10);
+    /// assert_eq!(due.next(), Some(&5));
+    /// assert_eq!(due.next(), Some(&8));
+    /// assert_eq!(due.next(), None);
+    ///
+    /// assert!(ll.range(10..20).eq([12].iter()));
+    /// ```
+    pub fn range<R>(&self, range: R) -> Range<'_, T, Idx, K, R, N>
+    where
+        R: RangeBounds<T>,
+    {
+        Range {
+            iter: self.iter(),
+            range,
+        }
+    }
+
+    /// Find all elements that lie within `range`, so they can be changed and resorted.
+    ///
+    /// The elements are visited in list order with [`FindAllMut::next`]. Elements that have been
+    /// visited are resorted into place when the returned value is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use heapless::sorted_linked_list::{Min, SortedLinkedList};
+    /// let mut ll: SortedLinkedList<_, _, Min, 8> = SortedLinkedList::new_usize();
+    ///
+    /// for deadline in [30, 5, 12, 8, 21] {
+    ///     ll.push(deadline).unwrap();
+    /// }
+    ///
+    /// // Postpone everything due in the next 10 ms
+    /// let mut due = ll.find_all_mut(..=10);
+    /// while let Some(deadline) = due.next() {
+    ///     *deadline += 20;
+    /// }
+    /// due.finish();
+    ///
+    /// assert!(ll.iter().eq([12, 21, 25, 28, 30].iter()));
+    /// ```
+    pub fn find_all_mut<R>(&mut self, range: R) -> FindAllMut<'_, T, Idx, K, N>
+    where
+        R: RangeBounds<T>,
+    {
+        let mut prev = Idx::none();
+        let mut first = Idx::none();
+        let mut len = 0;
+        let mut current = self.head;
+
+        while let Some(index) = current.option() {
+            let val = self.read_data_in_node_at(index);
+
+            if range.contains(val) {
+                if len == 0 {
+                    first = current;
+                }
+                len += 1;
+            } else if len != 0 || past_range::<T, K>(val, &range) {
+                break;
+            } else {
+                prev = current;
+            }
+
+            current = self.node_at(index).next;
+        }
+
+        FindAllMut {
+            list: self,
+            prev_index: prev,
+            first_index: first,
+            index: first,
+            remaining: len,
+            visited: 0,
+        }
+    }
+
     /// Peek at the first element.
     ///
     /// # Example
@@ -543,6 +636,150 @@ where
     }
 }
 
+/// Returns `true` if `val`, and therefore every element after it, lies beyond `range` in list
+/// order.
+fn past_range<T, K>(val: &T, range: &impl RangeBounds<T>) -> bool
+where
+    T: Ord,
+    K: Kind,
+{
+    if K::ordering() == Ordering::Less {
+        // Ascending (`Min`) list, compare against the end of the range
+        match range.end_bound() {
+            Bound::Included(end) => val > end,
+            Bound::Excluded(end) => val >= end,
+            Bound::Unbounded => false,
+        }
+    } else {
+        // Descending (`Max`) list, compare against the start of the range
+        match range.start_bound() {
+            Bound::Included(start) => val < start,
+            Bound::Excluded(start) => val <= start,
+            Bound::Unbounded => false,
+        }
+    }
+}
+
+/// Iterator over the elements of the linked list that lie within a range.
+///
+/// Comes from [`SortedLinkedList::range`].
+pub struct Range<'a, T, Idx, K, R, const N: usize>
+where
+    T: Ord,
+    Idx: SortedLinkedListIndex,
+    K: Kind,
+{
+    iter: Iter<'a, T, Idx, K, N>,
+    range: R,
+}
+
+impl<'a, T, Idx, K, R, const N: usize> Iterator for Range<'a, T, Idx, K, R, N>
+where
+    T: Ord,
+    Idx: SortedLinkedListIndex,
+    K: Kind,
+    R: RangeBounds<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let val = self.iter.next()?;
+
+            if self.range.contains(val) {
+                return Some(val);
+            }
+
+            if past_range::<T, K>(val, &self.range) {
+                self.iter.index = Idx::none();
+                return None;
+            }
+        }
+    }
+}
+
+/// Comes from [`SortedLinkedList::find_all_mut`].
+pub struct FindAllMut<'a, T, Idx, K, const N: usize>
+where
+    T: Ord,
+    Idx: SortedLinkedListIndex,
+    K: Kind,
+{
+    list: &'a mut SortedLinkedList<T, Idx, K, N>,
+    prev_index: Idx,
+    first_index: Idx,
+    index: Idx,
+    remaining: usize,
+    visited: usize,
+}
+
+impl<T, Idx, K, const N: usize> FindAllMut<'_, T, Idx, K, N>
+where
+    T: Ord,
+    Idx: SortedLinkedListIndex,
+    K: Kind,
+{
+    /// Returns the next element within the range, or `None` once all have been visited.
+    ///
+    /// The list is not resorted until `self` is dropped, so changing an element doesn't affect
+    /// which elements are visited.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&mut T> {
+        if self.remaining == 0 {
+            return None;
+        }
+
+        let index = unsafe { self.index.get_unchecked() };
+        self.index = self.list.node_at(index).next;
+        self.remaining -= 1;
+        self.visited += 1;
+
+        Some(self.list.read_mut_data_in_node_at(index))
+    }
+
+    /// This will resort the visited elements into the correct position in the list.
+    ///
+    /// Same as calling `drop`.
+    ///
+    /// Complexity is worst-case *O*(n * m), where m is the number of visited elements.
+    #[inline]
+    pub fn finish(self) {
+        drop(self)
+    }
+}
+
+impl<T, Idx, K, const N: usize> Drop for FindAllMut<'_, T, Idx, K, N>
+where
+    T: Ord,
+    Idx: SortedLinkedListIndex,
+    K: Kind,
+{
+    fn drop(&mut self) {
+        if self.visited == 0 {
+            return;
+        }
+
+        // Unlink the visited run of elements
+        let list = &mut *self.list;
+        match self.prev_index.option() {
+            Some(prev) => list.node_at_mut(prev).next = self.index,
+            None => list.head = self.index,
+        }
+
+        // Move each element to the free list and push it back into place
+        let mut current = self.first_index;
+        for _ in 0..self.visited {
+            let index = unsafe { current.get_unchecked() };
+            current = list.node_at(index).next;
+
+            let val = list.extract_data_in_node_at(index);
+            list.node_at_mut(index).next = list.free;
+            list.free = unsafe { Idx::new_unchecked(index) };
+            unsafe { list.push_unchecked(val) };
+        }
+    }
+}
+
 /// Comes from [`SortedLinkedList::find_mut`].
 pub struct FindMut<'a, T, Idx, K, const N: usize>
 where
@@ -866,4 +1103,53 @@ mod tests {
 
         assert_eq!(ll.peek().unwrap(), &1001);
     }
+
+    #[test]
+    fn test_range() {
+        let mut ll: SortedLinkedList<u32, LinkedIndexU8, Max, 6> = SortedLinkedList::new_u8();
+        for v in [4, 1, 6, 3, 5, 2] {
+            ll.push(v).unwrap();
+        }
+
+        assert!(ll.range(2..5).eq([4, 3, 2].iter()));
+        assert!(ll.range(..=2).eq([2, 1].iter()));
+        assert!(ll.range(5..).eq([6, 5].iter()));
+        assert_eq!(ll.range(7..).next(), None);
+        assert_eq!(ll.range(0..1).next(), None);
+    }
+
+    #[test]
+    fn test_find_all_mut() {
+        let mut ll: SortedLinkedList<u32, LinkedIndexU8, Max, 6> = SortedLinkedList::new_u8();
+        for v in [4, 1, 6, 3, 5, 2] {
+            ll.push(v).unwrap();
+        }
+
+        // Only the visited elements are resorted
+        let mut find = ll.find_all_mut(2..=4);
+        *find.next().unwrap() = 0;
+        *find.next().unwrap() = 10;
+        find.finish();
+        assert!(ll.iter().eq([10, 6, 5, 2, 1, 0].iter()));
+
+        // Including the head
+        let mut find = ll.find_all_mut(5..);
+        while let Some(v) = find.next() {
+            *v -= 5;
+        }
+        drop(find);
+        assert!(ll.iter().eq([5, 2, 1, 1, 0, 0].iter()));
+
+        let mut find = ll.find_all_mut(3..5);
+        assert_eq!(find.next(), None);
+        drop(find);
+        assert!(ll.iter().eq([5, 2, 1, 1, 0, 0].iter()));
+
+        // The free list is intact
+        while ll.pop().is_ok() {}
+        for v in 0..6 {
+            ll.push(v).unwrap();
+        }
+        assert!(ll.is_full());
+    }
 }
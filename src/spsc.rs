@@ -29,13 +29,16 @@
 //!
 //! - `Queue` can be `split` and then be used in Single Producer Single Consumer mode.
 //!
-//! "no alloc" applications can create a `&'static mut` reference to a `Queue` -- using a static
-//! variable -- and then `split` it: this consumes the static reference. The resulting `Consumer`
-//! and `Producer` can then be moved into different execution contexts (threads, interrupt handlers,
-//! etc.)
+//! "no alloc" applications can create a `&'static mut` reference to a `Queue` -- using a
+//! [`StaticCell`](crate::init::StaticCell) -- and then `split` it: this consumes the static
+//! reference. The resulting `Consumer` and `Producer` can then be moved into different execution
+//! contexts (threads, interrupt handlers, etc.)
 //!
 //! ```
-//! use heapless::spsc::{Producer, Queue};
+//! use heapless::{
+//!     init::StaticCell,
+//!     spsc::{Producer, Queue},
+//! };
 //!
 //! enum Event {
 //!     A,
@@ -43,10 +46,8 @@
 //! }
 //!
 //! fn main() {
-//!     let queue: &'static mut Queue<Event, 4> = {
-//!         static mut Q: Queue<Event, 4> = Queue::new();
-//!         unsafe { &mut Q }
-//!     };
+//!     static Q: StaticCell<Queue<Event, 4>> = StaticCell::new();
+//!     let queue: &'static mut Queue<Event, 4> = Q.init(Queue::new());
 //!
 //!     let (producer, mut consumer) = queue.split();
 //!
@@ -92,19 +93,22 @@
 //!
 //! - All execution times are in clock cycles. 1 clock cycle = 125 ns.
 //! - Execution time is *dependent* of `mem::size_of::<T>()`. Both operations include one
-//! `memcpy(T)` in their successful path.
+//!   `memcpy(T)` in their successful path.
 //! - The optimization level is indicated in the first row.
 //! - The numbers reported correspond to the successful path (i.e. `Some` is returned by `dequeue`
-//! and `Ok` is returned by `enqueue`).
+//!   and `Ok` is returned by `enqueue`).
 
-use core::{cell::UnsafeCell, fmt, hash, mem::MaybeUninit, ptr};
+use core::{fmt, hash, mem::MaybeUninit, ptr};
 
-#[cfg(not(feature = "portable-atomic"))]
-use core::sync::atomic;
-#[cfg(feature = "portable-atomic")]
-use portable_atomic as atomic;
-
-use atomic::{AtomicUsize, Ordering};
+use crate::{
+    backpressure::fill_ratio,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        UnsafeCell,
+    },
+    traits::{Capacity, PopFront, PushBack},
+    Backpressure, Deque, Vec,
+};
 
 /// A statically allocated single producer single consumer queue with a capacity of `N - 1` elements
 ///
@@ -121,22 +125,22 @@ pub struct Queue<T, const N: usize> {
 }
 
 impl<T, const N: usize> Queue<T, N> {
-    const INIT: UnsafeCell<MaybeUninit<T>> = UnsafeCell::new(MaybeUninit::uninit());
-
     #[inline]
     fn increment(val: usize) -> usize {
         (val + 1) % N
     }
 
-    /// Creates an empty queue with a fixed capacity of `N - 1`
-    pub const fn new() -> Self {
-        // Const assert N > 1
-        crate::sealed::greater_than_1::<N>();
+    const_fn! {
+        /// Creates an empty queue with a fixed capacity of `N - 1`
+        pub fn new() -> Self {
+            // Const assert N > 1
+            crate::sealed::greater_than_1::<N>();
 
-        Queue {
-            head: AtomicUsize::new(0),
-            tail: AtomicUsize::new(0),
-            buffer: [Self::INIT; N],
+            Queue {
+                head: AtomicUsize::new(0),
+                tail: AtomicUsize::new(0),
+                buffer: crate::sync::uninit_cells(),
+            }
         }
     }
 
@@ -200,6 +204,35 @@ impl<T, const N: usize> Queue<T, N> {
         unsafe { self.inner_dequeue() }
     }
 
+    /// Adds an `item` to the end of the queue, dequeuing the front item first if the queue is
+    /// full.
+    ///
+    /// Returns the dequeued item, if any. This keeps the most recent items, e.g. for telemetry
+    /// that prefers dropping old samples to dropping new ones.
+    ///
+    /// NOTE: This needs exclusive access to the queue. There's no [`Producer`] counterpart: the
+    /// front of the queue is owned by the [`Consumer`], which may be dequeuing the very item that
+    /// would be overwritten, and coordinating the two would need compare-and-swap on the head
+    /// index, which this queue avoids so it works on targets without it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::spsc::Queue;
+    ///
+    /// let mut samples: Queue<u8, 3> = Queue::new();
+    /// assert_eq!(samples.enqueue_overwrite(1), None);
+    /// assert_eq!(samples.enqueue_overwrite(2), None);
+    /// assert_eq!(samples.enqueue_overwrite(3), Some(1));
+    /// assert!(samples.iter().eq(&[2, 3]));
+    /// ```
+    pub fn enqueue_overwrite(&mut self, val: T) -> Option<T> {
+        let oldest = if self.is_full() { self.dequeue() } else { None };
+        // NOTE(unsafe) there's room for `val` now
+        unsafe { self.inner_enqueue_unchecked(val) };
+        oldest
+    }
+
     /// Returns a reference to the item in the front of the queue without dequeuing, or
     /// `None` if the queue is empty.
     ///
@@ -218,7 +251,11 @@ impl<T, const N: usize> Queue<T, N> {
     pub fn peek(&self) -> Option<&T> {
         if !self.is_empty() {
             let head = self.head.load(Ordering::Relaxed);
-            Some(unsafe { &*(self.buffer.get_unchecked(head).get() as *const T) })
+            Some(unsafe {
+                self.buffer
+                    .get_unchecked(head)
+                    .with(|slot| &*(slot as *const T))
+            })
         } else {
             None
         }
@@ -232,7 +269,9 @@ impl<T, const N: usize> Queue<T, N> {
         let next_tail = Self::increment(current_tail);
 
         if next_tail != self.head.load(Ordering::Acquire) {
-            (self.buffer.get_unchecked(current_tail).get()).write(MaybeUninit::new(val));
+            self.buffer
+                .get_unchecked(current_tail)
+                .with_mut(|slot| slot.write(MaybeUninit::new(val)));
             self.tail.store(next_tail, Ordering::Release);
 
             Ok(())
@@ -247,7 +286,9 @@ impl<T, const N: usize> Queue<T, N> {
     unsafe fn inner_enqueue_unchecked(&self, val: T) {
         let current_tail = self.tail.load(Ordering::Relaxed);
 
-        (self.buffer.get_unchecked(current_tail).get()).write(MaybeUninit::new(val));
+        self.buffer
+            .get_unchecked(current_tail)
+            .with_mut(|slot| slot.write(MaybeUninit::new(val)));
         self.tail
             .store(Self::increment(current_tail), Ordering::Release);
     }
@@ -273,7 +314,10 @@ impl<T, const N: usize> Queue<T, N> {
         if current_head == self.tail.load(Ordering::Acquire) {
             None
         } else {
-            let v = (self.buffer.get_unchecked(current_head).get() as *const T).read();
+            let v = self
+                .buffer
+                .get_unchecked(current_head)
+                .with(|slot| (slot as *const T).read());
 
             self.head
                 .store(Self::increment(current_head), Ordering::Release);
@@ -287,7 +331,10 @@ impl<T, const N: usize> Queue<T, N> {
     // items without doing pointer arithmetic and accessing internal fields of this type.
     unsafe fn inner_dequeue_unchecked(&self) -> T {
         let current_head = self.head.load(Ordering::Relaxed);
-        let v = (self.buffer.get_unchecked(current_head).get() as *const T).read();
+        let v = self
+            .buffer
+            .get_unchecked(current_head)
+            .with(|slot| (slot as *const T).read());
 
         self.head
             .store(Self::increment(current_head), Ordering::Release);
@@ -295,6 +342,70 @@ impl<T, const N: usize> Queue<T, N> {
         v
     }
 
+    // NOTE: `tail` is only loaded once, so the items that the producer enqueues in the meantime
+    // are left for the next call.
+    unsafe fn inner_drain_up_to<F>(&self, n: usize, mut f: F) -> usize
+    where
+        F: FnMut(T),
+    {
+        let mut current_head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+
+        let mut drained = 0;
+        while drained < n && current_head != tail {
+            let v = self
+                .buffer
+                .get_unchecked(current_head)
+                .with(|slot| (slot as *const T).read());
+            current_head = Self::increment(current_head);
+            // NOTE(ordering) the slot is released before `f` runs, so a panic in `f` can't cause
+            // `v` to be read again
+            self.head.store(current_head, Ordering::Release);
+            drained += 1;
+            f(v);
+        }
+        drained
+    }
+
+    // Moves up to `max` items to `dst`, in one copy per contiguous part of the queue, and returns
+    // the number of items moved.
+    // NOTE: like in `inner_drain_up_to`, `tail` is only loaded once
+    unsafe fn inner_dequeue_into(&self, dst: *mut T, max: usize) -> usize {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::Acquire);
+
+        let len = if tail >= head {
+            tail - head
+        } else {
+            tail + N - head
+        };
+        let moved = len.min(max);
+        let first = moved.min(N - head);
+        self.read_slots(head, dst, first);
+        self.read_slots(0, dst.add(first), moved - first);
+
+        self.head.store((head + moved) % N, Ordering::Release);
+        moved
+    }
+
+    // Reads the items in `start..start + count`, which must be initialized and in bounds.
+    unsafe fn read_slots(&self, start: usize, dst: *mut T, count: usize) {
+        // NOTE(ptr::copy_nonoverlapping) the cells are `repr(transparent)`, so the buffer has the
+        // layout of `[T; N]`
+        #[cfg(not(loom))]
+        ptr::copy_nonoverlapping(self.buffer.as_ptr().add(start).cast::<T>(), dst, count);
+
+        // NOTE loom has to see each access to a cell
+        #[cfg(loom)]
+        for i in 0..count {
+            let v = self
+                .buffer
+                .get_unchecked(start + i)
+                .with(|slot| (slot as *const T).read());
+            dst.add(i).write(v);
+        }
+    }
+
     /// Returns the item in the front of the queue, without checking if there is something in the
     /// queue
     ///
@@ -305,6 +416,16 @@ impl<T, const N: usize> Queue<T, N> {
         self.inner_dequeue_unchecked()
     }
 
+    /// Dequeues at most `n` items, passing each to `f`, and returns the number of items dequeued.
+    ///
+    /// See [`Consumer::drain_up_to`].
+    pub fn drain_up_to<F>(&mut self, n: usize, f: F) -> usize
+    where
+        F: FnMut(T),
+    {
+        unsafe { self.inner_drain_up_to(n, f) }
+    }
+
     /// Splits a queue into producer and consumer endpoints
     pub fn split(&mut self) -> (Producer<'_, T, N>, Consumer<'_, T, N>) {
         (Producer { rb: self }, Consumer { rb: self })
@@ -381,7 +502,12 @@ impl<'a, T, const N: usize> Iterator for Iter<'a, T, N> {
             let i = (head + self.index) % N;
             self.index += 1;
 
-            Some(unsafe { &*(self.rb.buffer.get_unchecked(i).get() as *const T) })
+            Some(unsafe {
+                self.rb
+                    .buffer
+                    .get_unchecked(i)
+                    .with(|slot| &*(slot as *const T))
+            })
         } else {
             None
         }
@@ -398,7 +524,12 @@ impl<'a, T, const N: usize> Iterator for IterMut<'a, T, N> {
             let i = (head + self.index) % N;
             self.index += 1;
 
-            Some(unsafe { &mut *(self.rb.buffer.get_unchecked(i).get() as *mut T) })
+            Some(unsafe {
+                self.rb
+                    .buffer
+                    .get_unchecked(i)
+                    .with_mut(|slot| &mut *(slot as *mut T))
+            })
         } else {
             None
         }
@@ -413,7 +544,12 @@ impl<'a, T, const N: usize> DoubleEndedIterator for Iter<'a, T, N> {
             // self.len > 0, since it's larger than self.index > 0
             let i = (head + self.len - 1) % N;
             self.len -= 1;
-            Some(unsafe { &*(self.rb.buffer.get_unchecked(i).get() as *const T) })
+            Some(unsafe {
+                self.rb
+                    .buffer
+                    .get_unchecked(i)
+                    .with(|slot| &*(slot as *const T))
+            })
         } else {
             None
         }
@@ -428,7 +564,12 @@ impl<'a, T, const N: usize> DoubleEndedIterator for IterMut<'a, T, N> {
             // self.len > 0, since it's larger than self.index > 0
             let i = (head + self.len - 1) % N;
             self.len -= 1;
-            Some(unsafe { &mut *(self.rb.buffer.get_unchecked(i).get() as *mut T) })
+            Some(unsafe {
+                self.rb
+                    .buffer
+                    .get_unchecked(i)
+                    .with_mut(|slot| &mut *(slot as *mut T))
+            })
         } else {
             None
         }
@@ -518,6 +659,37 @@ impl<'a, T, const N: usize> Consumer<'a, T, N> {
         self.rb.inner_dequeue_unchecked()
     }
 
+    /// Dequeues at most `n` items, passing each to `f`, and returns the number of items dequeued.
+    ///
+    /// This bounds the work done per call, e.g. by a cooperative task that shares the CPU with
+    /// others. The producer's position is read only once, so items enqueued during the call are
+    /// left for the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::spsc::Queue;
+    ///
+    /// let mut queue: Queue<u8, 8> = Queue::new();
+    /// let (mut producer, mut consumer) = queue.split();
+    /// for i in 0..5 {
+    ///     producer.enqueue(i).unwrap();
+    /// }
+    ///
+    /// let mut sum = 0;
+    /// assert_eq!(consumer.drain_up_to(3, |x| sum += x), 3);
+    /// assert_eq!(sum, 0 + 1 + 2);
+    /// assert_eq!(consumer.drain_up_to(3, |x| sum += x), 2);
+    /// assert_eq!(consumer.drain_up_to(3, |x| sum += x), 0);
+    /// ```
+    #[inline]
+    pub fn drain_up_to<F>(&mut self, n: usize, f: F) -> usize
+    where
+        F: FnMut(T),
+    {
+        unsafe { self.rb.inner_drain_up_to(n, f) }
+    }
+
     /// Returns if there are any items to dequeue. When this returns `true`, at least the
     /// first subsequent dequeue will succeed
     #[inline]
@@ -628,11 +800,289 @@ impl<'a, T, const N: usize> Producer<'a, T, N> {
     }
 }
 
+impl<T, const N: usize> Capacity for Queue<T, N> {
+    fn capacity(&self) -> usize {
+        Queue::capacity(self)
+    }
+
+    fn len(&self) -> usize {
+        Queue::len(self)
+    }
+}
+
+impl<T, const N: usize> PushBack<T> for Queue<T, N> {
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        self.enqueue(item)
+    }
+}
+
+impl<T, const N: usize> PopFront<T> for Queue<T, N> {
+    fn pop_front(&mut self) -> Option<T> {
+        self.dequeue()
+    }
+}
+
+impl<T, const N: usize> Capacity for Consumer<'_, T, N> {
+    fn capacity(&self) -> usize {
+        Consumer::capacity(self)
+    }
+
+    fn len(&self) -> usize {
+        Consumer::len(self)
+    }
+}
+
+impl<T, const N: usize> PopFront<T> for Consumer<'_, T, N> {
+    fn pop_front(&mut self) -> Option<T> {
+        self.dequeue()
+    }
+}
+
+impl<T, const N: usize> Capacity for Producer<'_, T, N> {
+    fn capacity(&self) -> usize {
+        Producer::capacity(self)
+    }
+
+    fn len(&self) -> usize {
+        Producer::len(self)
+    }
+}
+
+impl<T, const N: usize> PushBack<T> for Producer<'_, T, N> {
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        self.enqueue(item)
+    }
+}
+
+impl<T, const N: usize> Backpressure for Queue<T, N> {
+    fn pressure(&self) -> u8 {
+        fill_ratio(self.len(), self.capacity())
+    }
+}
+
+impl<T, const N: usize> Backpressure for Consumer<'_, T, N> {
+    fn pressure(&self) -> u8 {
+        self.rb.pressure()
+    }
+}
+
+impl<T, const N: usize> Backpressure for Producer<'_, T, N> {
+    fn pressure(&self) -> u8 {
+        self.rb.pressure()
+    }
+}
+
+/// Fair polling of several consumers, e.g. the queues of several interrupt handlers.
+///
+/// [`dequeue`](Select::dequeue) checks the consumers round-robin, starting after the one it last
+/// dequeued from, so a busy queue can't starve the others.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::spsc::{Queue, Select};
+///
+/// let mut uart: Queue<u8, 4> = Queue::new();
+/// let mut spi: Queue<u8, 4> = Queue::new();
+/// let (mut uart_tx, uart_rx) = uart.split();
+/// let (mut spi_tx, spi_rx) = spi.split();
+///
+/// uart_tx.enqueue(1).unwrap();
+/// uart_tx.enqueue(2).unwrap();
+/// spi_tx.enqueue(10).unwrap();
+///
+/// let mut select = Select::new([uart_rx, spi_rx]);
+/// assert_eq!(select.dequeue(), Some((0, 1)));
+/// assert_eq!(select.dequeue(), Some((1, 10)));
+/// assert_eq!(select.dequeue(), Some((0, 2)));
+/// assert_eq!(select.dequeue(), None);
+/// ```
+pub struct Select<'a, T, const N: usize, const Q: usize> {
+    consumers: [Consumer<'a, T, N>; Q],
+    /// The index of the consumer to check first.
+    next: usize,
+}
+
+impl<'a, T, const N: usize, const Q: usize> Select<'a, T, N, Q> {
+    /// Creates a `Select` over `consumers`, which starts with the first consumer.
+    pub fn new(consumers: [Consumer<'a, T, N>; Q]) -> Self {
+        Self { consumers, next: 0 }
+    }
+
+    /// Returns the front item of the next consumer in turn that isn't empty, together with the
+    /// index of that consumer, or `None` if all of them are empty.
+    pub fn dequeue(&mut self) -> Option<(usize, T)> {
+        for i in (self.next..Q).chain(0..self.next) {
+            if let Some(item) = self.consumers[i].dequeue() {
+                self.next = if i + 1 == Q { 0 } else { i + 1 };
+                return Some((i, item));
+            }
+        }
+        None
+    }
+
+    /// Returns `true` if any of the consumers has an item to dequeue.
+    pub fn ready(&self) -> bool {
+        self.consumers.iter().any(Consumer::ready)
+    }
+
+    /// Returns the consumers.
+    pub fn consumers(&self) -> &[Consumer<'a, T, N>; Q] {
+        &self.consumers
+    }
+
+    /// Returns the consumers mutably, e.g. to dequeue from one of them directly.
+    pub fn consumers_mut(&mut self) -> &mut [Consumer<'a, T, N>; Q] {
+        &mut self.consumers
+    }
+
+    /// Consumes the `Select`, returning the consumers.
+    pub fn into_inner(self) -> [Consumer<'a, T, N>; Q] {
+        self.consumers
+    }
+}
+
+impl<T, const N: usize> Vec<T, N> {
+    /// Moves as many items from `consumer` into the vector as fit, returning the number of items
+    /// moved.
+    ///
+    /// Items that don't fit stay in the queue. The items are copied in at most two steps, one for
+    /// each contiguous part of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{spsc::Queue, Vec};
+    ///
+    /// let mut queue: Queue<u8, 8> = Queue::new();
+    /// let (mut producer, mut consumer) = queue.split();
+    /// for i in 0..5 {
+    ///     producer.enqueue(i).unwrap();
+    /// }
+    ///
+    /// let mut vec: Vec<u8, 4> = Vec::from_slice(&[42]).unwrap();
+    /// assert_eq!(vec.fill_from_consumer(&mut consumer), 3);
+    /// assert_eq!(vec, [42, 0, 1, 2]);
+    /// assert_eq!(consumer.len(), 2);
+    /// ```
+    pub fn fill_from_consumer<const M: usize>(
+        &mut self,
+        consumer: &mut Consumer<'_, T, M>,
+    ) -> usize {
+        let len = self.len();
+        unsafe {
+            let moved = consumer
+                .rb
+                .inner_dequeue_into(self.as_mut_ptr().add(len), N - len);
+            // NOTE(unsafe) the queue initialized the next `moved` slots
+            self.set_len(len + moved);
+            moved
+        }
+    }
+}
+
+impl<T, const N: usize> Deque<T, N> {
+    /// Moves as many items from `consumer` to the back of the deque as fit, returning the number
+    /// of items moved.
+    ///
+    /// Items that don't fit stay in the queue. The items are copied in one step for each
+    /// contiguous part of the queue and of the deque's free space, so at most three.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{spsc::Queue, Deque};
+    ///
+    /// let mut queue: Queue<u8, 8> = Queue::new();
+    /// let (mut producer, mut consumer) = queue.split();
+    /// for i in 0..5 {
+    ///     producer.enqueue(i).unwrap();
+    /// }
+    ///
+    /// let mut deque: Deque<u8, 4> = Deque::new();
+    /// assert_eq!(deque.fill_from_consumer(&mut consumer), 4);
+    /// assert!(deque.iter().eq([0, 1, 2, 3].iter()));
+    /// assert_eq!(consumer.dequeue(), Some(4));
+    /// ```
+    pub fn fill_from_consumer<const M: usize>(
+        &mut self,
+        consumer: &mut Consumer<'_, T, M>,
+    ) -> usize {
+        let mut moved = 0;
+        // runs at most twice, as the free space wraps around at most once
+        while !self.is_full() {
+            let spare = self.spare_back_mut();
+            let len = spare.len();
+            let n = unsafe {
+                consumer
+                    .rb
+                    .inner_dequeue_into(spare.as_mut_ptr().cast(), len)
+            };
+            // NOTE(unsafe) the queue initialized the first `n` spare slots
+            unsafe { self.commit_back(n) };
+            moved += n;
+            if n < len {
+                break;
+            }
+        }
+        moved
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::hash::{Hash, Hasher};
 
-    use crate::spsc::Queue;
+    use crate::{
+        spsc::{Queue, Select},
+        sync::atomic::Ordering,
+        Backpressure, Deque, Vec,
+    };
+
+    #[test]
+    fn select() {
+        let mut a: Queue<u8, 8> = Queue::new();
+        let mut b: Queue<u8, 8> = Queue::new();
+        let mut c: Queue<u8, 8> = Queue::new();
+        let (mut pa, ca) = a.split();
+        let (_, cb) = b.split();
+        let (mut pc, cc) = c.split();
+
+        let mut select = Select::new([ca, cb, cc]);
+        assert!(!select.ready());
+        assert_eq!(select.dequeue(), None);
+
+        for i in 0..3 {
+            pa.enqueue(i).unwrap();
+        }
+        pc.enqueue(10).unwrap();
+        assert!(select.ready());
+        assert_eq!(select.dequeue(), Some((0, 0)));
+        assert_eq!(select.dequeue(), Some((2, 10)));
+        // wraps around to the first consumer
+        assert_eq!(select.dequeue(), Some((0, 1)));
+        pc.enqueue(11).unwrap();
+        assert_eq!(select.dequeue(), Some((2, 11)));
+        assert_eq!(select.consumers_mut()[0].dequeue(), Some(2));
+        assert_eq!(select.dequeue(), None);
+        assert_eq!(select.into_inner().len(), 3);
+    }
+
+    #[test]
+    fn pressure() {
+        let mut rb: Queue<u8, 5> = Queue::new();
+        let (mut p, mut c) = rb.split();
+
+        assert_eq!(p.pressure(), 0);
+        p.enqueue(0).unwrap();
+        p.enqueue(1).unwrap();
+        assert_eq!(c.pressure(), 127);
+        p.enqueue(2).unwrap();
+        p.enqueue(3).unwrap();
+        assert_eq!(p.pressure(), 255);
+        c.dequeue().unwrap();
+        assert_eq!(c.pressure(), 191);
+    }
 
     #[test]
     fn full() {
@@ -717,6 +1167,7 @@ mod tests {
         assert_eq!(c.dequeue(), Some(0));
     }
 
+    #[cfg(not(loom))]
     #[test]
     fn static_new() {
         static mut _Q: Queue<i32, 4> = Queue::new();
@@ -847,6 +1298,61 @@ mod tests {
         assert_eq!(rb.len(), 2);
     }
 
+    #[test]
+    fn enqueue_overwrite() {
+        droppable!();
+
+        let mut rb: Queue<Droppable, 3> = Queue::new();
+        assert!(rb.enqueue_overwrite(Droppable::new()).is_none());
+        assert!(rb.enqueue_overwrite(Droppable::new()).is_none());
+        assert!(rb.enqueue_overwrite(Droppable::new()).is_some());
+        assert_eq!(Droppable::count(), 2);
+        core::mem::drop(rb);
+        assert_eq!(Droppable::count(), 0);
+
+        let mut rb: Queue<u8, 4> = Queue::new();
+        for i in 0..8 {
+            rb.enqueue_overwrite(i);
+        }
+        assert!(rb.iter().eq(&[5, 6, 7]));
+        let (mut p, mut c) = rb.split();
+        assert_eq!(c.dequeue(), Some(5));
+        p.enqueue(8).unwrap();
+        assert_eq!(rb.enqueue_overwrite(9), Some(6));
+        assert!(rb.iter().eq(&[7, 8, 9]));
+    }
+
+    #[test]
+    fn drain_up_to() {
+        let mut rb: Queue<i32, 4> = Queue::new();
+        rb.enqueue(0).unwrap();
+        rb.enqueue(1).unwrap();
+        rb.dequeue().unwrap();
+        rb.dequeue().unwrap();
+        for i in 2..5 {
+            rb.enqueue(i).unwrap();
+        }
+
+        // wraps around the end of the buffer
+        let (mut p, mut c) = rb.split();
+        let mut drained = std::vec::Vec::new();
+        assert_eq!(c.drain_up_to(2, |x| drained.push(x)), 2);
+        assert_eq!(c.drain_up_to(0, |x| drained.push(x)), 0);
+        p.enqueue(5).unwrap();
+        assert_eq!(c.drain_up_to(usize::MAX, |x| drained.push(x)), 2);
+        assert_eq!(drained, [2, 3, 4, 5]);
+        assert!(c.is_empty());
+
+        // an item that was passed to a panicking `f` isn't dequeued again
+        rb.enqueue(6).unwrap();
+        rb.enqueue(7).unwrap();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            rb.drain_up_to(2, |_| panic!())
+        }));
+        assert!(result.is_err());
+        assert_eq!(rb.dequeue(), Some(7));
+    }
+
     #[test]
     fn ready_flag() {
         let mut rb: Queue<i32, 3> = Queue::new();
@@ -943,4 +1449,84 @@ mod tests {
         };
         assert_eq!(hash1, hash2);
     }
+
+    // leaves `5..10` in the queue, in slots 5, 6, 7, 0 and 1
+    fn wrapped(q: &mut Queue<u8, 8>) {
+        for i in 0..10 {
+            q.enqueue(i).unwrap();
+            if i < 5 {
+                q.dequeue().unwrap();
+            }
+        }
+        assert_eq!((q.head.load(Ordering::Relaxed), q.len()), (5, 5));
+    }
+
+    #[test]
+    fn vec_fill_from_consumer() {
+        let mut q: Queue<u8, 8> = Queue::new();
+        wrapped(&mut q);
+        let (_, mut c) = q.split();
+        let mut vec: Vec<u8, 5> = Vec::from_slice(&[42]).unwrap();
+        assert_eq!(vec.fill_from_consumer(&mut c), 4);
+        assert_eq!(vec, [42, 5, 6, 7, 8]);
+        assert_eq!(vec.fill_from_consumer(&mut c), 0);
+        assert_eq!(c.dequeue(), Some(9));
+
+        let mut q: Queue<u8, 8> = Queue::new();
+        wrapped(&mut q);
+        let (mut p, mut c) = q.split();
+        let mut vec: Vec<u8, 8> = Vec::from_slice(&[42]).unwrap();
+        assert_eq!(vec.fill_from_consumer(&mut c), 5);
+        assert_eq!(vec, [42, 5, 6, 7, 8, 9]);
+        assert_eq!(vec.fill_from_consumer(&mut c), 0);
+        p.enqueue(10).unwrap();
+        assert_eq!(vec.fill_from_consumer(&mut c), 1);
+        assert_eq!(vec, [42, 5, 6, 7, 8, 9, 10]);
+
+        droppable!();
+        let mut q: Queue<Droppable, 4> = Queue::new();
+        for _ in 0..2 {
+            q.enqueue(Droppable::new()).ok().unwrap();
+            q.dequeue().unwrap();
+        }
+        for _ in 0..3 {
+            q.enqueue(Droppable::new()).ok().unwrap();
+        }
+        let (_, mut c) = q.split();
+        let mut vec: Vec<Droppable, 2> = Vec::new();
+        assert_eq!(vec.fill_from_consumer(&mut c), 2);
+        assert_eq!(Droppable::count(), 3);
+        core::mem::drop(vec);
+        assert_eq!(Droppable::count(), 1);
+        core::mem::drop(q);
+        assert_eq!(Droppable::count(), 0);
+    }
+
+    #[test]
+    fn deque_fill_from_consumer() {
+        // the deque's free space wraps around, in slots 3, 0 and 1
+        let mut deque: Deque<u8, 4> = Deque::new();
+        for i in 0..3 {
+            deque.push_back(i).unwrap();
+        }
+        deque.pop_front();
+        deque.pop_front();
+
+        let mut q: Queue<u8, 8> = Queue::new();
+        wrapped(&mut q);
+        let (_, mut c) = q.split();
+        assert_eq!(deque.fill_from_consumer(&mut c), 3);
+        assert!(deque.is_full());
+        assert!(deque.iter().eq(&[2, 5, 6, 7]));
+        assert_eq!(deque.fill_from_consumer(&mut c), 0);
+        assert_eq!(c.len(), 2);
+
+        // the queue runs out before the deque's free space wraps around
+        deque.clear();
+        deque.push_back(1).unwrap();
+        assert_eq!(deque.fill_from_consumer(&mut c), 2);
+        assert!(deque.iter().eq(&[1, 8, 9]));
+        assert_eq!(deque.len(), 3);
+        assert!(c.dequeue().is_none());
+    }
 }
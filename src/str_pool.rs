@@ -0,0 +1,272 @@
+use core::{
+    cell::{Cell, UnsafeCell},
+    fmt,
+    iter::FusedIterator,
+    mem::MaybeUninit,
+    slice, str,
+};
+
+/// A pool of `BYTES` bytes that stores each distinct string once.
+///
+/// Keeping e.g. the topic names from a configuration as `String<32>`s reserves 32 bytes for each
+/// of them, and again for each repetition. [`intern`](StrPool::intern) instead copies a string
+/// into the pool only the first time it's seen, and returns a `&str` that points into the pool.
+/// It takes `&self`, so the references handed out earlier stay valid; only
+/// [`reset`](StrPool::reset) frees the pool.
+///
+/// Each string costs its length plus one byte, or more for strings of 128 bytes or longer. Looking
+/// up a string compares it with all the strings in the pool, so the pool is meant for a few dozen
+/// strings.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::StrPool;
+///
+/// let pool: StrPool<32> = StrPool::new();
+/// let topics = ["sensors/temp", "alarms", "sensors/temp", "alarms"];
+/// let interned: [&str; 4] = topics.map(|topic| pool.intern(topic).unwrap());
+///
+/// assert_eq!(pool.len(), 2);
+/// assert_eq!(pool.used(), 20);
+/// // repetitions share their bytes
+/// assert!(core::ptr::eq(interned[0], interned[2]));
+/// assert!(pool.intern("this one doesn't fit anymore").is_err());
+/// ```
+pub struct StrPool<const BYTES: usize> {
+    buffer: UnsafeCell<[MaybeUninit<u8>; BYTES]>,
+    /// The number of bytes in use; the bytes after this are never borrowed.
+    used: Cell<usize>,
+    len: Cell<usize>,
+}
+
+impl<const BYTES: usize> StrPool<BYTES> {
+    /// Creates an empty pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{init::StaticCell, StrPool};
+    ///
+    /// static NAMES: StaticCell<StrPool<256>> = StaticCell::new();
+    ///
+    /// // the interned strings live as long as the pool
+    /// let names: &'static StrPool<256> = NAMES.init(StrPool::new());
+    /// let name: &'static str = names.intern("uart0").unwrap();
+    /// assert_eq!(name, "uart0");
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            buffer: UnsafeCell::new([MaybeUninit::uninit(); BYTES]),
+            used: Cell::new(0),
+            len: Cell::new(0),
+        }
+    }
+
+    /// Returns the number of strings in the pool.
+    pub fn len(&self) -> usize {
+        self.len.get()
+    }
+
+    /// Returns `true` if the pool holds no strings.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of bytes in use, including the length of each string.
+    pub fn used(&self) -> usize {
+        self.used.get()
+    }
+
+    /// Returns the size of the pool in bytes.
+    pub const fn capacity(&self) -> usize {
+        BYTES
+    }
+
+    /// Returns the copy of `s` in the pool, adding it first if it isn't in the pool yet.
+    ///
+    /// Returns `Err(())` if `s` isn't in the pool and doesn't fit in it.
+    #[allow(clippy::result_unit_err)]
+    pub fn intern(&self, s: &str) -> Result<&str, ()> {
+        if let Some(interned) = self.get(s) {
+            return Ok(interned);
+        }
+
+        let mut prefix = [0; 10];
+        let prefix = encode_len(s.len(), &mut prefix);
+        let start = self.used.get();
+        let end = start
+            .checked_add(prefix.len() + s.len())
+            .filter(|end| *end <= BYTES)
+            .ok_or(())?;
+
+        let buffer = self.buffer.get() as *mut u8;
+        // NOTE(unsafe) `start..end` is in bounds and after `used`, so it's not borrowed, and the
+        // copied bytes are valid UTF-8
+        unsafe {
+            let data = buffer.add(start);
+            data.copy_from_nonoverlapping(prefix.as_ptr(), prefix.len());
+            let data = data.add(prefix.len());
+            data.copy_from_nonoverlapping(s.as_ptr(), s.len());
+            self.used.set(end);
+            self.len.set(self.len.get() + 1);
+            Ok(str::from_utf8_unchecked(slice::from_raw_parts(
+                data,
+                s.len(),
+            )))
+        }
+    }
+
+    /// Returns the copy of `s` in the pool, or `None` if it isn't in the pool.
+    pub fn get(&self, s: &str) -> Option<&str> {
+        self.iter().find(|interned| *interned == s)
+    }
+
+    /// Returns `true` if `s` is in the pool.
+    pub fn contains(&self, s: &str) -> bool {
+        self.get(s).is_some()
+    }
+
+    /// Returns an iterator over the strings in the pool, in the order they were added.
+    pub fn iter(&self) -> Iter<'_> {
+        // NOTE(unsafe) the first `used` bytes are initialized and no longer written to
+        let bytes =
+            unsafe { slice::from_raw_parts(self.buffer.get() as *const u8, self.used.get()) };
+        Iter { bytes }
+    }
+
+    /// Removes all strings from the pool.
+    pub fn reset(&mut self) {
+        self.used.set(0);
+        self.len.set(0);
+    }
+}
+
+impl<const BYTES: usize> Default for StrPool<BYTES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const BYTES: usize> Clone for StrPool<BYTES> {
+    fn clone(&self) -> Self {
+        let new = Self::new();
+        for s in self.iter() {
+            // the strings are distinct and fit in the same pool size
+            let _ = new.intern(s);
+        }
+        new
+    }
+}
+
+impl<const BYTES: usize> fmt::Debug for StrPool<BYTES> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, const BYTES: usize> IntoIterator for &'a StrPool<BYTES> {
+    type Item = &'a str;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the strings in a [`StrPool`].
+///
+/// This struct is created by [`StrPool::iter`].
+#[derive(Clone)]
+pub struct Iter<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.bytes.is_empty() {
+            return None;
+        }
+        let (len, rest) = decode_len(self.bytes);
+        let (s, rest) = rest.split_at(len);
+        self.bytes = rest;
+        // NOTE(unsafe) only `intern` writes strings to the pool
+        Some(unsafe { str::from_utf8_unchecked(s) })
+    }
+}
+
+impl FusedIterator for Iter<'_> {}
+
+impl fmt::Debug for Iter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+// lengths are stored in 7 bit groups, least significant first, with the high bit set on all groups
+// but the last
+fn encode_len(mut len: usize, buffer: &mut [u8; 10]) -> &[u8] {
+    let mut i = 0;
+    while len >= 0x80 {
+        buffer[i] = (len as u8) | 0x80;
+        len >>= 7;
+        i += 1;
+    }
+    buffer[i] = len as u8;
+    &buffer[..=i]
+}
+
+fn decode_len(bytes: &[u8]) -> (usize, &[u8]) {
+    let mut len = 0;
+    for (i, byte) in bytes.iter().enumerate() {
+        len |= usize::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return (len, &bytes[i + 1..]);
+        }
+    }
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StrPool;
+
+    #[test]
+    fn intern() {
+        let pool: StrPool<16> = StrPool::new();
+        let a = pool.intern("a").unwrap();
+        let empty = pool.intern("").unwrap();
+        let bc = pool.intern("bc").unwrap();
+        assert_eq!((a, empty, bc), ("a", "", "bc"));
+        assert!(core::ptr::eq(pool.intern("bc").unwrap(), bc));
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.used(), 6);
+
+        // exactly fills the pool
+        assert_eq!(pool.intern("ñandúes").unwrap(), "ñandúes");
+        assert_eq!(pool.used(), 16);
+        assert!(pool.intern("d").is_err());
+        assert!(pool.contains("a"));
+        assert_eq!(pool.get("d"), None);
+        assert!(pool.iter().eq(["a", "", "bc", "ñandúes"]));
+
+        let mut clone = pool.clone();
+        assert!(clone.iter().eq(pool.iter()));
+        clone.reset();
+        assert!(clone.is_empty());
+        assert_eq!(clone.intern("d"), Ok("d"));
+    }
+
+    #[test]
+    fn long_strings() {
+        let long = "x".repeat(200);
+        let pool: StrPool<256> = StrPool::new();
+        assert_eq!(pool.intern(&long), Ok(&long[..]));
+        assert_eq!(pool.used(), 202);
+        assert_eq!(pool.intern("y"), Ok("y"));
+        assert!(pool.iter().eq([&long[..], "y"]));
+        assert!(pool.intern(&long[..60]).is_err());
+    }
+}
@@ -5,11 +5,16 @@ use core::{
     cmp::Ordering,
     fmt,
     fmt::{Arguments, Write},
-    hash, iter, ops,
+    hash, iter,
+    mem::MaybeUninit,
+    ops,
     str::{self, Utf8Error},
 };
 
-use crate::Vec;
+use crate::{
+    vec::{capacity_overflow, checked_slice_range},
+    Vec,
+};
 
 /// A possible error value when converting a [`String`] from a UTF-16 byte slice.
 ///
@@ -34,6 +39,20 @@ impl fmt::Display for FromUtf16Error {
 }
 
 /// A fixed capacity [`String`](https://doc.rust-lang.org/std/string/struct.String.html).
+///
+/// # Panic-free subset
+///
+/// Besides the methods that report a full buffer through their return value
+/// ([`push`](String::push), [`push_str`](String::push_str), ...),
+/// [`truncate_checked`](String::truncate_checked) and [`remove_checked`](String::remove_checked)
+/// are the non-panicking counterparts of [`truncate`](String::truncate) and
+/// [`remove`](String::remove). These are verified with the `no-panic` crate in release builds.
+///
+/// # Layout
+///
+/// `String<N>` is `#[repr(transparent)]` over [`Vec<u8, N>`](Vec), so it has the same
+/// [layout](Vec#layout): it starts with the length in bytes followed by the storage for `N` bytes.
+#[repr(transparent)]
 pub struct String<const N: usize> {
     vec: Vec<u8, N>,
 }
@@ -96,6 +115,79 @@ impl<const N: usize> String<N> {
         Ok(s)
     }
 
+    /// Appends the string encoded as UTF-16 to `out`.
+    ///
+    /// This is the counterpart of [`from_utf16`](String::from_utf16), for handing a string to a
+    /// UTF-16 host without a temporary buffer.
+    ///
+    /// Returns `Err(())` and leaves `out` unchanged if the encoded string doesn't fit in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{String, Vec};
+    ///
+    /// let s: String<10> = String::try_from("𝄞mu").unwrap();
+    ///
+    /// let mut out: Vec<u16, 4> = Vec::new();
+    /// s.encode_utf16_into(&mut out).unwrap();
+    /// assert_eq!(out, [0xD834, 0xDD1E, 0x006d, 0x0075]);
+    ///
+    /// assert!(s.encode_utf16_into(&mut out).is_err());
+    /// assert_eq!(out.len(), 4);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn encode_utf16_into<const M: usize>(&self, out: &mut Vec<u16, M>) -> Result<(), ()> {
+        let len = self.encode_utf16().count();
+        if out.capacity() - out.len() < len {
+            return Err(());
+        }
+        for unit in self.encode_utf16() {
+            // NOTE(unsafe) the capacity was checked above
+            unsafe { out.push_unchecked(unit) };
+        }
+        Ok(())
+    }
+
+    /// Appends the string encoded as Latin-1 (ISO 8859-1) to `out`, replacing every character
+    /// outside of Latin-1 with `replacement`.
+    ///
+    /// Latin-1 has one byte per character, and its bytes are the code points `U+0000` to `U+00FF`.
+    ///
+    /// Returns `Err(())` and leaves `out` unchanged if the encoded string doesn't fit in it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{String, Vec};
+    ///
+    /// let s: String<16> = String::try_from("Grüße €5").unwrap();
+    ///
+    /// let mut out: Vec<u8, 8> = Vec::new();
+    /// s.encode_latin1_lossy_into(&mut out, b'?').unwrap();
+    /// assert_eq!(out, b"Gr\xfc\xdfe ?5");
+    ///
+    /// assert!(s.encode_latin1_lossy_into(&mut out, b'?').is_err());
+    /// assert_eq!(out.len(), 8);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn encode_latin1_lossy_into<const M: usize>(
+        &self,
+        out: &mut Vec<u8, M>,
+        replacement: u8,
+    ) -> Result<(), ()> {
+        let len = self.chars().count();
+        if out.capacity() - out.len() < len {
+            return Err(());
+        }
+        for c in self.chars() {
+            let byte = u8::try_from(u32::from(c)).unwrap_or(replacement);
+            // NOTE(unsafe) the capacity was checked above
+            unsafe { out.push_unchecked(byte) };
+        }
+        Ok(())
+    }
+
     /// Convert UTF-8 bytes into a `String`.
     ///
     /// # Examples
@@ -154,7 +246,7 @@ impl<const N: usize> String<N> {
     /// assert_eq!("💖", sparkle_heart);
     /// ```
     #[inline]
-    pub unsafe fn from_utf8_unchecked(vec: Vec<u8, N>) -> Self {
+    pub const unsafe fn from_utf8_unchecked(vec: Vec<u8, N>) -> Self {
         Self { vec }
     }
 
@@ -181,6 +273,65 @@ impl<const N: usize> String<N> {
         self.vec
     }
 
+    /// Copies the string into a new string with a capacity of `M` bytes.
+    ///
+    /// Returns `Err(())` if the string doesn't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<32> = String::try_from("ok").unwrap();
+    /// let reply: String<4> = s.clone_into_capacity().unwrap();
+    /// assert_eq!(reply, "ok");
+    /// assert!(s.clone_into_capacity::<1>().is_err());
+    /// ```
+    #[inline]
+    #[allow(clippy::result_unit_err)]
+    pub fn clone_into_capacity<const M: usize>(&self) -> Result<String<M>, ()> {
+        Ok(String {
+            vec: self.vec.clone_into_capacity()?,
+        })
+    }
+
+    /// Decomposes the string into its storage and its length in bytes.
+    ///
+    /// The first `len` bytes of the storage are initialized and valid UTF-8.
+    /// [`from_raw_parts`](String::from_raw_parts) puts the string back together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<8> = String::try_from("héllo").unwrap();
+    /// let (buffer, len) = s.into_raw_parts();
+    /// assert_eq!(len, 6);
+    ///
+    /// // Safety: the first `len` bytes of `buffer` are initialized and valid UTF-8
+    /// let s = unsafe { String::from_raw_parts(buffer, len) };
+    /// assert_eq!(s, "héllo");
+    /// ```
+    #[inline]
+    pub fn into_raw_parts(self) -> ([MaybeUninit<u8>; N], usize) {
+        self.vec.into_raw_parts()
+    }
+
+    /// Creates a string from its storage and its length in bytes, e.g. as returned by
+    /// [`into_raw_parts`](String::into_raw_parts).
+    ///
+    /// # Safety
+    ///
+    /// `len` must be at most `N`, and the first `len` bytes of `buffer` must be initialized and
+    /// valid UTF-8.
+    #[inline]
+    pub const unsafe fn from_raw_parts(buffer: [MaybeUninit<u8>; N], len: usize) -> Self {
+        Self {
+            vec: Vec::from_raw_parts(buffer, len),
+        }
+    }
+
     /// Extracts a string slice containing the entire string.
     ///
     /// # Examples
@@ -198,7 +349,7 @@ impl<const N: usize> String<N> {
     /// # Ok::<(), ()>(())
     /// ```
     #[inline]
-    pub fn as_str(&self) -> &str {
+    pub const fn as_str(&self) -> &str {
         unsafe { str::from_utf8_unchecked(self.vec.as_slice()) }
     }
 
@@ -221,6 +372,42 @@ impl<const N: usize> String<N> {
         unsafe { str::from_utf8_unchecked_mut(self.vec.as_mut_slice()) }
     }
 
+    /// Returns the substring at byte positions `range`, or `None` if it's out of bounds or doesn't
+    /// lie on [`char`] boundaries.
+    ///
+    /// Unlike indexing with a range this never panics, so offsets from untrusted input can be
+    /// used without checking them first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<8> = String::try_from("aöb")?;
+    /// assert_eq!(s.get_range(1..3), Some("ö"));
+    /// assert_eq!(s.get_range(1..2), None);
+    /// assert_eq!(s.get_range(..9), None);
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn get_range<R>(&self, range: R) -> Option<&str>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        self.as_str().get(checked_slice_range(range, self.len())?)
+    }
+
+    /// Returns the substring at byte positions `range` mutably, or `None` if it's out of bounds or
+    /// doesn't lie on [`char`] boundaries.
+    ///
+    /// See [`get_range`](String::get_range).
+    pub fn get_range_mut<R>(&mut self, range: R) -> Option<&mut str>
+    where
+        R: ops::RangeBounds<usize>,
+    {
+        let range = checked_slice_range(range, self.len())?;
+        self.as_mut_str().get_mut(range)
+    }
+
     /// Returns a mutable reference to the contents of this `String`.
     ///
     /// # Safety
@@ -276,6 +463,156 @@ impl<const N: usize> String<N> {
         self.vec.extend_from_slice(string.as_bytes())
     }
 
+    /// Appends `parts` with `separator` between each of them, like `[&str]::join`.
+    ///
+    /// Returns `Err` without modifying the string if the result doesn't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut line: String<32> = String::try_from("$GPGLL,")?;
+    /// line.push_joined(["4916.45", "N", "12311.12", "W"], ",")?;
+    /// assert_eq!(line, "$GPGLL,4916.45,N,12311.12,W");
+    ///
+    /// assert!(line.push_joined([",225444", "A"], ",").is_err());
+    /// assert_eq!(line, "$GPGLL,4916.45,N,12311.12,W");
+    /// # Ok::<(), ()>(())
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn push_joined<I>(&mut self, parts: I, separator: &str) -> Result<(), ()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let len = self.len();
+        for (i, part) in parts.into_iter().enumerate() {
+            let sep = if i == 0 { "" } else { separator };
+            if self
+                .push_str(sep)
+                .and_then(|_| self.push_str(part.as_ref()))
+                .is_err()
+            {
+                self.vec.truncate(len);
+                return Err(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a string from `parts` with `separator` between each of them, like `[&str]::join`.
+    ///
+    /// Returns `Err` if the result doesn't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let fields = ["12", "", "ok"];
+    /// let row: String<16> = String::from_joined(fields.iter(), ";")?;
+    /// assert_eq!(row, "12;;ok");
+    ///
+    /// assert!(String::<4>::from_joined(fields, ";").is_err());
+    /// # Ok::<(), ()>(())
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn from_joined<I>(parts: I, separator: &str) -> Result<Self, ()>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut s = Self::new();
+        s.push_joined(parts, separator)?;
+        Ok(s)
+    }
+
+    /// Appends `bytes` as lowercase hexadecimal digits, two per byte.
+    ///
+    /// This is a lightweight alternative to `write!(s, "{:02x}", byte)` for each byte. Returns
+    /// `Err` without modifying the string if the digits don't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<17> = String::new();
+    /// for (i, byte) in [0x00, 0x1b, 0x63, 0xa4, 0xff, 0x0e].iter().enumerate() {
+    ///     if i != 0 {
+    ///         s.push(':').unwrap();
+    ///     }
+    ///     s.push_hex(&[*byte]).unwrap();
+    /// }
+    /// assert_eq!(s, "00:1b:63:a4:ff:0e");
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn push_hex(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        self.push_digits(bytes, 4, b"0123456789abcdef")
+    }
+
+    /// Appends `bytes` as uppercase hexadecimal digits, two per byte.
+    ///
+    /// Returns `Err` without modifying the string if the digits don't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<8> = String::new();
+    /// s.push_hex_upper(&[0xde, 0xad, 0xbe, 0xef]).unwrap();
+    /// assert_eq!(s, "DEADBEEF");
+    /// assert!(s.push_hex_upper(&[0]).is_err());
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn push_hex_upper(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        self.push_digits(bytes, 4, b"0123456789ABCDEF")
+    }
+
+    /// Appends `bytes` as binary digits, eight per byte, most significant bit first.
+    ///
+    /// Returns `Err` without modifying the string if the digits don't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<16> = String::new();
+    /// s.push_bin(&[0b1010_0101, 0x01]).unwrap();
+    /// assert_eq!(s, "1010010100000001");
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn push_bin(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        self.push_digits(bytes, 1, b"01")
+    }
+
+    /// Appends the digits of each byte in base `2^bits`, most significant digit first.
+    fn push_digits(&mut self, bytes: &[u8], bits: u32, digits: &[u8]) -> Result<(), ()> {
+        let per_byte = (8 / bits) as usize;
+        match bytes.len().checked_mul(per_byte) {
+            Some(len) if len <= self.capacity() - self.len() => {}
+            _ => return Err(()),
+        }
+
+        #[cfg(feature = "test-util")]
+        if self.vec.take_injected_failure() {
+            return Err(());
+        }
+
+        let mask = (1 << bits) - 1;
+        for byte in bytes {
+            for i in (0..per_byte as u32).rev() {
+                let digit = digits[usize::from((byte >> (i * bits)) & mask)];
+                // NOTE(unsafe) the capacity was checked above, and the digits are ASCII
+                unsafe { self.vec.push_unchecked(digit) };
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the maximum number of elements the String can hold.
     ///
     /// # Examples
@@ -289,10 +626,51 @@ impl<const N: usize> String<N> {
     /// assert!(s.capacity() == 4);
     /// ```
     #[inline]
-    pub fn capacity(&self) -> usize {
+    pub const fn capacity(&self) -> usize {
         self.vec.capacity()
     }
 
+    /// Returns the length of this `String`, in bytes.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<8> = String::try_from("héllo")?;
+    /// assert_eq!(s.len(), 6);
+    /// # Ok::<(), ()>(())
+    /// ```
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    /// Returns `true` if this `String` has a length of zero.
+    ///
+    /// # Examples
+    ///
+    /// Basic usage:
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// static EMPTY: String<8> = String::new();
+    /// const _: () = assert!(EMPTY.is_empty());
+    /// ```
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Returns the contents of this `String` as a byte slice.
+    #[inline]
+    pub const fn as_bytes(&self) -> &[u8] {
+        self.vec.as_slice()
+    }
+
     /// Appends the given [`char`] to the end of this `String`.
     ///
     /// # Examples
@@ -358,6 +736,147 @@ impl<const N: usize> String<N> {
         }
     }
 
+    /// Shortens this `String` to the specified length.
+    ///
+    /// If `new_len` is greater than the string's current length, this has no
+    /// effect.
+    ///
+    /// Returns `Err(())` and leaves the string untouched if `new_len` does not lie on a [`char`]
+    /// boundary. Unlike [`truncate`](String::truncate) this never panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<8> = String::try_from("héllo")?;
+    ///
+    /// assert_eq!(s.truncate_checked(2), Err(()));
+    /// assert_eq!(s.truncate_checked(3), Ok(()));
+    /// assert_eq!("hé", s);
+    /// # Ok::<(), ()>(())
+    /// ```
+    #[inline]
+    #[allow(clippy::result_unit_err)]
+    pub fn truncate_checked(&mut self, new_len: usize) -> Result<(), ()> {
+        if new_len <= self.len() {
+            if !self.is_char_boundary(new_len) {
+                return Err(());
+            }
+            self.vec.truncate(new_len)
+        }
+        Ok(())
+    }
+
+    /// Shortens this `String` to at most `max_chars` [`char`]s, and returns whether it was
+    /// shortened.
+    ///
+    /// Unlike [`truncate`](String::truncate), which counts bytes, this fits text onto displays
+    /// that have a fixed number of character cells.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<16> = String::try_from("Füllstand")?;
+    ///
+    /// assert!(s.truncate_chars(4));
+    /// assert_eq!(s, "Füll");
+    /// assert!(!s.truncate_chars(4));
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn truncate_chars(&mut self, max_chars: usize) -> bool {
+        match self.char_indices().nth(max_chars) {
+            Some((end, _)) => {
+                self.vec.truncate(end);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Shortens this `String` to at most `max_chars` [`char`]s, cutting at the end of a word if
+    /// possible, and returns whether it was shortened.
+    ///
+    /// The whitespace before the cut is removed as well. If the first word alone is longer than
+    /// `max_chars`, it's cut like [`truncate_chars`](String::truncate_chars) does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<32> = String::try_from("Battery low, please charge")?;
+    ///
+    /// assert!(s.truncate_words(16));
+    /// assert_eq!(s, "Battery low,");
+    ///
+    /// assert!(s.truncate_words(4));
+    /// assert_eq!(s, "Batt");
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn truncate_words(&mut self, max_chars: usize) -> bool {
+        let Some((end, next)) = self.char_indices().nth(max_chars) else {
+            return false;
+        };
+
+        let end = if next.is_whitespace() {
+            end
+        } else {
+            // back to the start of the word that would be cut
+            self[..end]
+                .char_indices()
+                .rev()
+                .find(|(_, c)| c.is_whitespace())
+                .map_or(end, |(i, _)| i)
+        };
+        let end = match self[..end].trim_end().len() {
+            // only whitespace before the word
+            0 => self.char_indices().nth(max_chars).map_or(0, |(i, _)| i),
+            trimmed => trimmed,
+        };
+        self.vec.truncate(end);
+        true
+    }
+
+    /// Shortens this `String` to at most `max_chars` [`char`]s including `ellipsis`, which is
+    /// appended if the string was shortened, and returns whether it was shortened.
+    ///
+    /// If `ellipsis` alone is longer than `max_chars` or doesn't fit in the capacity, the string is
+    /// shortened without it. A multi-byte `ellipsis` may replace more characters than it has, to
+    /// fit in the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<16> = String::try_from("Temperature")?;
+    ///
+    /// assert!(s.truncate_chars_with(8, "…"));
+    /// assert_eq!(s, "Tempera…");
+    /// assert_eq!(s.chars().count(), 8);
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn truncate_chars_with(&mut self, max_chars: usize, ellipsis: &str) -> bool {
+        if self.chars().count() <= max_chars {
+            return false;
+        }
+        match max_chars.checked_sub(ellipsis.chars().count()) {
+            Some(keep) if ellipsis.len() <= N => {
+                self.truncate_chars(keep);
+                while self.push_str(ellipsis).is_err() {
+                    self.pop();
+                }
+            }
+            _ => {
+                self.truncate_chars(max_chars);
+            }
+        }
+        true
+    }
+
     /// Removes the last character from the string buffer and returns it.
     ///
     /// Returns [`None`] if this `String` is empty.
@@ -381,14 +900,163 @@ impl<const N: usize> String<N> {
     pub fn pop(&mut self) -> Option<char> {
         let ch = self.chars().next_back()?;
 
-        // pop bytes that correspond to `ch`
-        for _ in 0..ch.len_utf8() {
-            unsafe {
-                self.vec.pop_unchecked();
-            }
+        // pop bytes that correspond to `ch`
+        for _ in 0..ch.len_utf8() {
+            unsafe {
+                self.vec.pop_unchecked();
+            }
+        }
+
+        Some(ch)
+    }
+
+    /// Removes the last word and the whitespace after it, like Ctrl-W in a terminal, and returns
+    /// the number of [`char`]s removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<16> = String::try_from("set led  ön ")?;
+    ///
+    /// assert_eq!(s.pop_word(), 3);
+    /// assert_eq!(s, "set led  ");
+    /// assert_eq!(s.pop_word(), 5);
+    /// assert_eq!(s.pop_word(), 4);
+    /// assert_eq!(s.pop_word(), 0);
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn pop_word(&mut self) -> usize {
+        let end = self.trim_end().len();
+        let start = self[..end]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(i, c)| i + c.len_utf8());
+
+        let removed = self[start..].chars().count();
+        self.vec.truncate(start);
+        removed
+    }
+
+    /// Returns the byte position of the [`char`] before byte position `index`, or 0 if there is
+    /// none.
+    ///
+    /// `index` doesn't need to lie on a [`char`] boundary, and is clamped to the length of the
+    /// string. Together with [`next_char_boundary`](String::next_char_boundary) this moves a
+    /// cursor over the string without panicking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<8> = String::try_from("aöb")?;
+    ///
+    /// assert_eq!(s.prev_char_boundary(4), 3);
+    /// assert_eq!(s.prev_char_boundary(3), 1);
+    /// assert_eq!(s.prev_char_boundary(2), 1);
+    /// assert_eq!(s.prev_char_boundary(0), 0);
+    /// assert_eq!(s.prev_char_boundary(9), 3);
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn prev_char_boundary(&self, index: usize) -> usize {
+        let mut index = index.min(self.len());
+        while index > 0 {
+            index -= 1;
+            if self.is_char_boundary(index) {
+                break;
+            }
+        }
+        index
+    }
+
+    /// Returns the byte position of the [`char`] after the one at byte position `index`, or the
+    /// length of the string if there is none.
+    ///
+    /// `index` doesn't need to lie on a [`char`] boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<8> = String::try_from("aöb")?;
+    ///
+    /// assert_eq!(s.next_char_boundary(0), 1);
+    /// assert_eq!(s.next_char_boundary(1), 3);
+    /// assert_eq!(s.next_char_boundary(2), 3);
+    /// assert_eq!(s.next_char_boundary(4), 4);
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn next_char_boundary(&self, index: usize) -> usize {
+        let mut index = index;
+        while index < self.len() {
+            index += 1;
+            if self.is_char_boundary(index) {
+                break;
+            }
+        }
+        index.min(self.len())
+    }
+
+    /// Splits the string after the first `A` bytes, which is checked at compile time to be at
+    /// most the capacity.
+    ///
+    /// Returns `None` if the string is shorter than `A` bytes, or if byte position `A` doesn't lie
+    /// on a [`char`] boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let record: String<16> = String::try_from("0042OK")?;
+    /// assert_eq!(record.split_at_fixed::<4>(), Some(("0042", "OK")));
+    /// assert_eq!(record.split_at_fixed::<7>(), None);
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn split_at_fixed<const A: usize>(&self) -> Option<(&str, &str)> {
+        // Const assert A <= N
+        crate::sealed::greater_than_eq::<N, A>();
+
+        if self.is_char_boundary(A) {
+            Some(self.split_at(A))
+        } else {
+            None
+        }
+    }
+
+    /// Splits the first `K * W` bytes of the string into `K` fields of `W` bytes each, for
+    /// fixed-column records.
+    ///
+    /// That the fields fit in the capacity is checked at compile time. The bytes after the last
+    /// field are not returned, and can be taken with `&s[K * W..]`.
+    ///
+    /// Returns `None` if the string is shorter than `K * W` bytes, or if a field boundary doesn't
+    /// lie on a [`char`] boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// // hours, minutes and seconds of an NMEA timestamp
+    /// let time: String<16> = String::try_from("092751.000")?;
+    /// let [h, m, s] = time.split_fixed::<2, 3>().unwrap();
+    /// assert_eq!((h, m, s), ("09", "27", "51"));
+    /// assert_eq!(&time[6..], ".000");
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn split_fixed<const W: usize, const K: usize>(&self) -> Option<[&str; K]> {
+        // Const assert W * K <= N
+        crate::sealed::product_at_most::<W, K, N>();
+
+        if self.len() < W * K || !(0..=K).all(|i| self.is_char_boundary(i * W)) {
+            return None;
         }
-
-        Some(ch)
+        Some(core::array::from_fn(|i| &self[i * W..(i + 1) * W]))
     }
 
     /// Removes a [`char`] from this `String` at a byte position and returns it.
@@ -421,13 +1089,40 @@ impl<const N: usize> String<N> {
             None => panic!("cannot remove a char from the end of a string"),
         };
 
+        unsafe { self.remove_char_at(index, ch) }
+    }
+
+    /// Removes a [`char`] from this `String` at a byte position and returns it.
+    ///
+    /// Returns `None` if `index` is larger than or equal to the `String`'s length, or if it does
+    /// not lie on a [`char`] boundary. Unlike [`remove`](String::remove) this never panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let mut s: String<8> = String::try_from("fö").unwrap();
+    ///
+    /// assert_eq!(s.remove_checked(2), None);
+    /// assert_eq!(s.remove_checked(3), None);
+    /// assert_eq!(s.remove_checked(1), Some('ö'));
+    /// assert_eq!("f", s);
+    /// ```
+    #[inline]
+    pub fn remove_checked(&mut self, index: usize) -> Option<char> {
+        let ch = self.get(index..)?.chars().next()?;
+
+        Some(unsafe { self.remove_char_at(index, ch) })
+    }
+
+    /// Assumes that `ch` is the `char` starting at byte position `index`.
+    unsafe fn remove_char_at(&mut self, index: usize, ch: char) -> char {
         let next = index + ch.len_utf8();
         let len = self.len();
         let ptr = self.vec.as_mut_ptr();
-        unsafe {
-            core::ptr::copy(ptr.add(next), ptr.add(index), len - next);
-            self.vec.set_len(len - (next - index));
-        }
+        core::ptr::copy(ptr.add(next), ptr.add(index), len - next);
+        self.vec.set_len(len - (next - index));
         ch
     }
 
@@ -456,14 +1151,165 @@ impl<const N: usize> String<N> {
     pub fn clear(&mut self) {
         self.vec.clear()
     }
+
+    /// Returns an iterator over the lines of this `String` wrapped to at most `width` characters.
+    ///
+    /// Lines longer than `width` are broken at the character boundary after `width` characters.
+    /// Line breaks in the string are kept, and a trailing `\n` or `\r\n` doesn't start another
+    /// line, as in [`str::lines`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<32> = String::try_from("temperature\nok")?;
+    /// assert!(s.wrap_lines(5).eq(["tempe", "ratur", "e", "ok"]));
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn wrap_lines(&self, width: usize) -> WrapLines<'_> {
+        WrapLines::new(self.as_str(), width, false)
+    }
+
+    /// Returns an iterator over the lines of this `String` wrapped at whitespace to at most
+    /// `width` characters.
+    ///
+    /// Lines longer than `width` are broken at their last whitespace that keeps them within
+    /// `width` characters, and the whitespace around the break is dropped. Words longer than
+    /// `width` are broken as in [`wrap_lines`](String::wrap_lines).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::String;
+    ///
+    /// let s: String<32> = String::try_from("low battery, connect charger")?;
+    /// assert!(s.wrap_words(12).eq(["low battery,", "connect", "charger"]));
+    /// # Ok::<(), ()>(())
+    /// ```
+    pub fn wrap_words(&self, width: usize) -> WrapLines<'_> {
+        WrapLines::new(self.as_str(), width, true)
+    }
+}
+
+/// An iterator over the wrapped lines of a string.
+///
+/// This struct is created by the [`wrap_lines`](String::wrap_lines) and
+/// [`wrap_words`](String::wrap_words) methods on [`String`]. See their documentation for more.
+#[derive(Clone, Debug)]
+pub struct WrapLines<'a> {
+    rest: &'a str,
+    width: usize,
+    words: bool,
+}
+
+impl<'a> WrapLines<'a> {
+    fn new(s: &'a str, width: usize, words: bool) -> Self {
+        assert!(width > 0, "wrap width must be greater than 0");
+        Self {
+            rest: s,
+            width,
+            words,
+        }
+    }
+
+    /// Returns `line` and continues after `skip` more bytes.
+    fn split(&mut self, line: usize, skip: usize) -> &'a str {
+        let (line, rest) = self.rest.split_at(line);
+        self.rest = &rest[skip..];
+        line
+    }
+}
+
+impl<'a> Iterator for WrapLines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        if self.rest.is_empty() {
+            return None;
+        }
+
+        // the offset of the first character that's past `width`
+        let mut end = None;
+        for (count, (i, c)) in self.rest.char_indices().enumerate() {
+            if c == '\n' {
+                let line = self.split(i, 1);
+                return Some(line.strip_suffix('\r').unwrap_or(line));
+            } else if count == self.width {
+                end = Some(i);
+                break;
+            }
+        }
+
+        let end = match end {
+            Some(end) => end,
+            None => return Some(self.split(self.rest.len(), 0)),
+        };
+
+        if self.words {
+            let head = &self.rest[..end];
+            let at_break = self.rest[end..].starts_with(char::is_whitespace);
+            let line = if at_break {
+                Some(head)
+            } else {
+                head.rfind(char::is_whitespace).map(|i| &head[..i])
+            }
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty());
+
+            if let Some(line) = line {
+                let rest = self.rest[line.len()..]
+                    .trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+                // the break replaces a line break that directly follows it
+                let rest = rest.strip_prefix("\r\n").unwrap_or(rest);
+                self.rest = rest.strip_prefix('\n').unwrap_or(rest);
+                return Some(line);
+            }
+        }
+
+        // a line break right at the width ends this line instead of adding an empty one
+        let line_break = if self.rest[end..].starts_with("\r\n") {
+            2
+        } else {
+            0
+        };
+        Some(self.split(end, line_break))
+    }
 }
 
+impl core::iter::FusedIterator for WrapLines<'_> {}
+
 impl<const N: usize> Default for String<N> {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Affects [`push`](String::push) and [`push_str`](String::push_str), and the operations built on
+/// them.
+#[cfg(feature = "test-util")]
+impl<const N: usize> crate::test_util::FailureInjection for String<N> {
+    fn fail_next(&mut self, k: usize) {
+        self.vec.fail_next(k);
+    }
+}
+
+/// Reports the length in bytes.
+#[cfg(capacity_stats)]
+impl<const N: usize> crate::capacity_stats::CapacityMonitor for String<N> {
+    fn monitor(&mut self, mark: &'static crate::capacity_stats::HighWaterMark) {
+        self.vec.monitor(mark);
+    }
+}
+
 impl<'a, const N: usize> TryFrom<&'a str> for String<N> {
     type Error = ();
     fn try_from(s: &'a str) -> Result<Self, Self::Error> {
@@ -487,7 +1333,10 @@ impl<const N: usize> iter::FromIterator<char> for String<N> {
     fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
         let mut new = String::new();
         for c in iter {
-            new.push(c).unwrap();
+            if new.push(c).is_err() {
+                capacity_overflow("String::from_iter overflow");
+                break;
+            }
         }
         new
     }
@@ -497,7 +1346,10 @@ impl<'a, const N: usize> iter::FromIterator<&'a char> for String<N> {
     fn from_iter<T: IntoIterator<Item = &'a char>>(iter: T) -> Self {
         let mut new = String::new();
         for c in iter {
-            new.push(*c).unwrap();
+            if new.push(*c).is_err() {
+                capacity_overflow("String::from_iter overflow");
+                break;
+            }
         }
         new
     }
@@ -507,7 +1359,10 @@ impl<'a, const N: usize> iter::FromIterator<&'a str> for String<N> {
     fn from_iter<T: IntoIterator<Item = &'a str>>(iter: T) -> Self {
         let mut new = String::new();
         for c in iter {
-            new.push_str(c).unwrap();
+            if new.push_str(c).is_err() {
+                capacity_overflow("String::from_iter overflow");
+                break;
+            }
         }
         new
     }
@@ -533,6 +1388,7 @@ impl<const N: usize> fmt::Display for String<N> {
     }
 }
 
+/// Hashes like `str`, so strings with equal contents hash identically whatever their capacity.
 impl<const N: usize> hash::Hash for String<N> {
     #[inline]
     fn hash<H: hash::Hasher>(&self, hasher: &mut H) {
@@ -550,6 +1406,172 @@ impl<const N: usize> fmt::Write for String<N> {
     }
 }
 
+/// A [`fmt::Write`] sink that is either a [`String`] or a byte [`Vec`], chosen at runtime.
+///
+/// Code that formats into a `&mut dyn fmt::Write` (see [`as_dyn`](WriteSink::as_dyn)) can be
+/// pointed at either buffer without being generic over it. The string holds up to `N` bytes and
+/// the vector up to `M`, which defaults to `N`.
+///
+/// # Examples
+///
+/// ```
+/// use core::fmt::Write;
+/// use heapless::{string::WriteSink, String, Vec};
+///
+/// fn log(sink: &mut dyn Write, code: u8) {
+///     write!(sink, "error {}", code).unwrap();
+/// }
+///
+/// let mut text: String<16> = String::new();
+/// let mut raw: Vec<u8, 64> = Vec::new();
+///
+/// for use_raw in [false, true] {
+///     let mut sink = if use_raw {
+///         WriteSink::from(&mut raw)
+///     } else {
+///         WriteSink::from(&mut text)
+///     };
+///     log(sink.as_dyn(), 7);
+///     assert_eq!(sink.as_bytes(), b"error 7");
+/// }
+/// ```
+#[derive(Debug)]
+pub enum WriteSink<'a, const N: usize, const M: usize = N> {
+    /// Writes to a string.
+    String(&'a mut String<N>),
+    /// Writes the UTF-8 bytes to a byte vector.
+    Bytes(&'a mut Vec<u8, M>),
+}
+
+impl<const N: usize, const M: usize> WriteSink<'_, N, M> {
+    /// Returns the sink as a trait object.
+    pub fn as_dyn(&mut self) -> &mut dyn fmt::Write {
+        self
+    }
+
+    /// Returns the bytes written to the underlying buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            WriteSink::String(s) => s.as_bytes(),
+            WriteSink::Bytes(v) => v,
+        }
+    }
+}
+
+impl<'a, const N: usize, const M: usize> From<&'a mut String<N>> for WriteSink<'a, N, M> {
+    fn from(s: &'a mut String<N>) -> Self {
+        WriteSink::String(s)
+    }
+}
+
+impl<'a, const N: usize, const M: usize> From<&'a mut Vec<u8, M>> for WriteSink<'a, N, M> {
+    fn from(v: &'a mut Vec<u8, M>) -> Self {
+        WriteSink::Bytes(v)
+    }
+}
+
+impl<const N: usize, const M: usize> fmt::Write for WriteSink<'_, N, M> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self {
+            WriteSink::String(string) => string.write_str(s),
+            WriteSink::Bytes(bytes) => bytes.write_str(s),
+        }
+    }
+}
+
+/// A [`fmt::Write`] adapter that passes at most `max` bytes on to the inner writer, including an
+/// ellipsis if the output was cut.
+///
+/// Writes past the limit are dropped without an error, so formatting user-controlled data into a
+/// bounded buffer can't fail halfway through. Room for the ellipsis is kept free from the start,
+/// so output is cut once it's longer than `max` minus the length of the ellipsis, even if it would
+/// have fit in `max` bytes without one. Output is only cut at [`char`] boundaries.
+///
+/// # Examples
+///
+/// ```
+/// use core::fmt::Write;
+/// use heapless::{string::Truncating, String};
+///
+/// let mut w = Truncating::new(String::<8>::new(), 8);
+/// write!(w, "user: {}", "mallory").unwrap();
+/// assert!(w.is_truncated());
+/// assert_eq!(w.into_inner(), "user:...");
+/// ```
+#[derive(Debug)]
+pub struct Truncating<W> {
+    inner: W,
+    remaining: usize,
+    ellipsis: &'static str,
+    truncated: bool,
+}
+
+impl<W> Truncating<W>
+where
+    W: fmt::Write,
+{
+    /// Wraps `inner`, passing on at most `max` bytes, ending in `"..."` if the output is cut.
+    pub fn new(inner: W, max: usize) -> Self {
+        Self::with_ellipsis(inner, max, "...")
+    }
+
+    /// Wraps `inner`, passing on at most `max` bytes, ending in `ellipsis` if the output is cut.
+    ///
+    /// An `ellipsis` longer than `max` is shortened to fit.
+    pub fn with_ellipsis(inner: W, max: usize, ellipsis: &'static str) -> Self {
+        let mut end = ellipsis.len().min(max);
+        while !ellipsis.is_char_boundary(end) {
+            end -= 1;
+        }
+        let ellipsis = &ellipsis[..end];
+        Self {
+            inner,
+            remaining: max - ellipsis.len(),
+            ellipsis,
+            truncated: false,
+        }
+    }
+
+    /// Returns `true` if the output was cut.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W> fmt::Write for Truncating<W>
+where
+    W: fmt::Write,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.truncated {
+            return Ok(());
+        }
+        if s.len() <= self.remaining {
+            self.remaining -= s.len();
+            return self.inner.write_str(s);
+        }
+
+        let mut end = self.remaining;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        self.remaining = 0;
+        self.truncated = true;
+        self.inner.write_str(&s[..end])?;
+        self.inner.write_str(self.ellipsis)
+    }
+}
+
 impl<const N: usize> ops::Deref for String<N> {
     type Target = str;
 
@@ -641,9 +1663,9 @@ impl<const N: usize> Ord for String<N> {
 /// There are two possible error cases. Both return the unit type [`core::fmt::Error`].
 ///
 /// - In case the formatting exceeds the string's capacity. This error does not exist in
-/// the standard library as the string would just grow.
+///   the standard library as the string would just grow.
 /// - If a formatting trait implementation returns an error. The standard library panics
-/// in this case.
+///   in this case.
 ///
 /// [`format!`]: crate::format!
 #[doc(hidden)]
@@ -672,9 +1694,9 @@ pub fn format<const N: usize>(args: Arguments<'_>) -> Result<String<N>, fmt::Err
 /// There are two possible error cases. Both return the unit type [`core::fmt::Error`].
 ///
 /// - In case the formatting exceeds the string's capacity. This error does not exist in
-/// the standard library as the string would just grow.
+///   the standard library as the string would just grow.
 /// - If a formatting trait implementation returns an error. The standard library panics
-/// in this case.
+///   in this case.
 ///
 /// # Examples
 ///
@@ -707,6 +1729,103 @@ macro_rules! format {
     }};
 }
 
+/// Copies the bytes of `s` into an array. Used by [`string_literal!`] and [`vec_from_str!`].
+///
+/// `M` must be the length of `s`.
+#[doc(hidden)]
+pub const fn str_to_array<const M: usize>(s: &str) -> [u8; M] {
+    let bytes = s.as_bytes();
+    assert!(bytes.len() == M);
+    let mut array = [0; M];
+    let mut i = 0;
+    while i < M {
+        array[i] = bytes[i];
+        i += 1;
+    }
+    array
+}
+
+/// Macro that creates a fixed capacity [`String`] from a string literal, checking at compile time
+/// that it fits.
+///
+/// Unlike `String::try_from("...").unwrap()`, this can't fail at runtime: a literal that is longer
+/// than the capacity fails the build instead. It works in `const` and `static` initializers.
+///
+/// As with [`format!`], the capacity can be given as the first argument, separated by a
+/// semicolon; otherwise it's inferred.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{string_literal, String};
+///
+/// static GREETING: String<16> = string_literal!("hello");
+/// assert_eq!(GREETING, "hello");
+///
+/// let s = string_literal!(8; "héllo");
+/// assert_eq!(s.capacity(), 8);
+/// assert_eq!(s.len(), 6);
+/// ```
+///
+/// A literal that doesn't fit fails the build:
+///
+/// ```compile_fail
+/// use heapless::{string_literal, String};
+///
+/// let s: String<4> = string_literal!("hello");
+/// ```
+#[macro_export]
+macro_rules! string_literal {
+    ($max:expr; $s:expr) => {{
+        let res: $crate::String<$max> = $crate::string_literal!($s);
+        res
+    }};
+    ($s:expr) => {{
+        const S: &str = $s;
+        const BYTES: [u8; S.len()] = $crate::_export::str_to_array::<{ S.len() }>(S);
+        // Safety: `BYTES` are the bytes of a `str`
+        unsafe { $crate::String::from_utf8_unchecked($crate::Vec::from_array(BYTES)) }
+    }};
+}
+
+/// Macro that creates a fixed capacity [`Vec<u8, N>`](crate::Vec) from the bytes of a string
+/// literal, checking at compile time that they fit.
+///
+/// This is [`string_literal!`] for byte buffers, e.g. for protocol constants. For byte string
+/// literals, [`Vec::from_array`](crate::Vec::from_array) does the same: `Vec::from_array(*b"AT")`.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{vec_from_str, Vec};
+///
+/// const COMMAND: Vec<u8, 16> = vec_from_str!("AT+RST\r\n");
+/// assert_eq!(COMMAND, b"AT+RST\r\n");
+///
+/// let v = vec_from_str!(2; "ok");
+/// assert!(v.is_full());
+/// ```
+///
+/// A literal that doesn't fit fails the build:
+///
+/// ```compile_fail
+/// use heapless::{vec_from_str, Vec};
+///
+/// let v: Vec<u8, 4> = vec_from_str!("AT+RST");
+/// ```
+#[macro_export]
+macro_rules! vec_from_str {
+    ($max:expr; $s:expr) => {{
+        let res: $crate::Vec<u8, $max> = $crate::vec_from_str!($s);
+        res
+    }};
+    ($s:expr) => {{
+        const S: &str = $s;
+        const BYTES: [u8; S.len()] = $crate::_export::str_to_array::<{ S.len() }>(S);
+        $crate::Vec::from_array(BYTES)
+    }};
+}
+
 macro_rules! impl_try_from_num {
     ($num:ty, $size:expr) => {
         impl<const N: usize> core::convert::TryFrom<$num> for String<N> {
@@ -848,7 +1967,7 @@ mod tests {
         let s: String<4> = String::try_from("ab").unwrap();
         let b: Vec<u8, 4> = s.into_bytes();
         assert_eq!(b.len(), 2);
-        assert_eq!(&[b'a', b'b'], &b[..]);
+        assert_eq!(b"ab", &b[..]);
     }
 
     #[test]
@@ -861,6 +1980,30 @@ mod tests {
         // s.push('c'); // <- cannot borrow `s` as mutable because it is also borrowed as immutable
     }
 
+    #[test]
+    fn transcode() {
+        let s: String<16> = String::try_from("a\u{e9}\u{1d11e}").unwrap();
+
+        let mut utf16: Vec<u16, 5> = Vec::from_slice(&[0]).unwrap();
+        s.encode_utf16_into(&mut utf16).unwrap();
+        assert_eq!(utf16, [0, 0x61, 0xe9, 0xd834, 0xdd1e]);
+        let back: String<16> = String::from_utf16(&utf16[1..]).unwrap();
+        assert_eq!(back, s);
+        assert!(s.encode_utf16_into(&mut Vec::<u16, 3>::new()).is_err());
+
+        let mut latin1: Vec<u8, 4> = Vec::from_slice(b">").unwrap();
+        s.encode_latin1_lossy_into(&mut latin1, 0x1a).unwrap();
+        assert_eq!(latin1, [b'>', b'a', 0xe9, 0x1a]);
+        assert!(s.encode_latin1_lossy_into(&mut latin1, 0x1a).is_err());
+        assert_eq!(latin1.len(), 4);
+
+        let empty: String<4> = String::new();
+        empty.encode_utf16_into(&mut Vec::<u16, 0>::new()).unwrap();
+        empty
+            .encode_latin1_lossy_into(&mut Vec::<u8, 0>::new(), 0)
+            .unwrap();
+    }
+
     #[test]
     fn as_mut_str() {
         let mut s: String<4> = String::try_from("ab").unwrap();
@@ -907,6 +2050,32 @@ mod tests {
         assert_eq!(s, "he");
     }
 
+    #[test]
+    fn raw_parts() {
+        use core::mem::{align_of, size_of};
+
+        assert_eq!(size_of::<String<5>>(), size_of::<Vec<u8, 5>>());
+        assert_eq!(align_of::<String<5>>(), align_of::<Vec<u8, 5>>());
+
+        let s: String<8> = String::try_from("aöb").unwrap();
+        let (buffer, len) = s.into_raw_parts();
+        let s = unsafe { String::<8>::from_raw_parts(buffer, len) };
+        assert_eq!(s, "aöb");
+    }
+
+    #[test]
+    fn get_range() {
+        let mut s: String<8> = String::try_from("aöb").unwrap();
+        assert_eq!(s.get_range(..), Some("aöb"));
+        assert_eq!(s.get_range(3..=3), Some("b"));
+        assert_eq!(s.get_range(2..), None);
+        assert_eq!(s.get_range(..=usize::MAX), None);
+
+        s.get_range_mut(3..).unwrap().make_ascii_uppercase();
+        assert_eq!(s, "aöB");
+        assert_eq!(s.get_range_mut(0..2), None);
+    }
+
     #[test]
     fn pop() {
         let mut s: String<8> = String::try_from("foo").unwrap();
@@ -916,6 +2085,83 @@ mod tests {
         assert_eq!(s.pop(), None);
     }
 
+    #[test]
+    fn truncate_chars() {
+        let mut s: String<16> = String::try_from("  abcdef gh").unwrap();
+        assert!(!s.clone().truncate_words(11));
+        assert!(s.clone().truncate_words(10));
+        let mut t = s.clone();
+        t.truncate_words(10);
+        assert_eq!(t, "  abcdef");
+        // no word fits
+        assert!(s.truncate_words(4));
+        assert_eq!(s, "  ab");
+
+        let mut s: String<16> = String::try_from("ab cd").unwrap();
+        s.truncate_words(3);
+        assert_eq!(s, "ab");
+
+        // the ellipsis needs more bytes than the chars it replaces
+        let mut s: String<8> = String::try_from("abcdefgh").unwrap();
+        assert!(s.truncate_chars_with(7, "…"));
+        assert_eq!(s, "abcde…");
+        let mut s: String<8> = String::try_from("abcdefgh").unwrap();
+        assert!(s.truncate_chars_with(2, "..."));
+        assert_eq!(s, "ab");
+        assert!(!s.truncate_chars_with(2, "..."));
+    }
+
+    #[test]
+    fn split_fixed() {
+        let s: String<8> = String::try_from("aöbc").unwrap();
+        assert_eq!(s.split_at_fixed::<0>(), Some(("", "aöbc")));
+        assert_eq!(s.split_at_fixed::<2>(), None);
+        assert_eq!(s.split_at_fixed::<5>(), Some(("aöbc", "")));
+        assert_eq!(s.split_at_fixed::<6>(), None);
+
+        assert_eq!(s.split_fixed::<1, 1>(), Some(["a"]));
+        assert_eq!(s.split_fixed::<1, 2>(), None);
+        assert_eq!(s.split_fixed::<3, 1>(), Some(["aö"]));
+        assert_eq!(s.split_fixed::<0, 8>(), Some([""; 8]));
+
+        let s: String<8> = String::try_from("12345678").unwrap();
+        assert_eq!(s.split_fixed::<4, 2>(), Some(["1234", "5678"]));
+        assert_eq!(s.split_fixed::<3, 2>(), Some(["123", "456"]));
+    }
+
+    #[test]
+    fn joined() {
+        let empty: [&str; 0] = [];
+        assert_eq!(String::<0>::from_joined(empty, ",").unwrap(), "");
+        assert_eq!(String::<3>::from_joined(["abc"], ",").unwrap(), "abc");
+
+        // the separator alone overflows
+        let mut s: String<4> = String::try_from("ab").unwrap();
+        assert!(s.push_joined(["c", "d"], "::").is_err());
+        assert_eq!(s, "ab");
+    }
+
+    #[test]
+    fn pop_word() {
+        let mut s: String<16> = String::try_from("a\u{3000}bc\tdé").unwrap();
+        assert_eq!(s.pop_word(), 2);
+        assert_eq!(s, "a\u{3000}bc\t");
+        assert_eq!(s.pop_word(), 3);
+        assert_eq!(s.pop_word(), 2);
+        assert_eq!(s.pop_word(), 0);
+
+        let s: String<16> = String::try_from("a\u{3000}b").unwrap();
+        let mut cursor = s.len();
+        let mut back = Vec::<usize, 4>::new();
+        while cursor > 0 {
+            cursor = s.prev_char_boundary(cursor);
+            back.push(cursor).unwrap();
+        }
+        assert_eq!(back, [4, 1, 0]);
+        assert_eq!(s.next_char_boundary(1), 4);
+        assert_eq!(s.next_char_boundary(s.len()), s.len());
+    }
+
     #[test]
     fn pop_uenc() {
         let mut s: String<8> = String::try_from("é").unwrap();
@@ -996,4 +2242,127 @@ mod tests {
         let formatted = format!(2; "123");
         assert_eq!(formatted, Err(core::fmt::Error))
     }
+
+    #[test]
+    fn literals() {
+        const NAME: &str = "sensor-ü";
+        static S: String<16> = string_literal!(NAME);
+        assert_eq!(S, NAME);
+        assert_eq!(S.capacity(), 16);
+
+        let exact = string_literal!(9; NAME);
+        assert_eq!(exact, NAME);
+        let empty: String<0> = string_literal!("");
+        assert!(empty.is_empty());
+
+        let v: Vec<u8, 8> = vec_from_str!("ab");
+        assert_eq!(v, b"ab");
+        assert!(vec_from_str!(2; "ab").is_full());
+    }
+
+    #[test]
+    fn truncating() {
+        use core::fmt::Write;
+
+        use super::Truncating;
+
+        let mut w = Truncating::with_ellipsis(Vec::<u8, 8>::new(), 5, "~");
+        w.write_str("ab").unwrap();
+        w.write_str("cd").unwrap();
+        assert!(!w.is_truncated());
+        w.write_str("e").unwrap();
+        w.write_str("f").unwrap();
+        assert!(w.is_truncated());
+        assert_eq!(w.get_ref(), b"abcd~");
+
+        // `max` is the capacity of the inner writer, which the ellipsis still fits in
+        let mut w = Truncating::new(String::<8>::new(), 8);
+        w.write_str("abcd").unwrap();
+        w.write_str("efgh").unwrap();
+        assert_eq!(w.into_inner(), "abcde...");
+
+        // cut at a char boundary
+        let mut w = Truncating::new(String::<8>::new(), 6);
+        w.write_str("éé").unwrap();
+        assert_eq!(w.into_inner(), "é...");
+
+        // an ellipsis longer than `max` is cut too
+        let mut w = Truncating::with_ellipsis(String::<8>::new(), 3, "ab…");
+        w.write_str("cdef").unwrap();
+        assert_eq!(w.into_inner(), "cab");
+
+        // the inner writer's errors are passed on
+        let mut w = Truncating::new(String::<2>::new(), 8);
+        assert!(w.write_str("abc").is_err());
+    }
+
+    #[test]
+    fn write_sink() {
+        use core::fmt::Write;
+
+        use super::WriteSink;
+
+        let mut text: String<4> = String::new();
+        let mut raw: Vec<u8, 8> = Vec::new();
+
+        // each backend is limited by its own capacity
+        let mut sink: WriteSink<'_, 4, 8> = WriteSink::from(&mut text);
+        sink.write_str("abc").unwrap();
+        assert!(sink.write_str("de").is_err());
+        assert_eq!(sink.as_bytes(), b"abc");
+
+        let mut sink: WriteSink<'_, 4, 8> = WriteSink::from(&mut raw);
+        write!(sink.as_dyn(), "{}-é", 123).unwrap();
+        assert!(sink.write_str("abc").is_err());
+        assert_eq!(sink.as_bytes(), "123-é".as_bytes());
+        assert!(matches!(sink, WriteSink::Bytes(_)));
+
+        assert_eq!(text, "abc");
+        assert_eq!(raw, "123-é".as_bytes());
+
+        // the vector's capacity defaults to the string's
+        let mut raw: Vec<u8, 4> = Vec::new();
+        let mut sink: WriteSink<'_, 4> = WriteSink::from(&mut raw);
+        sink.write_str("abcd").unwrap();
+        assert!(sink.write_char('e').is_err());
+    }
+
+    #[test]
+    fn push_digits() {
+        let mut s: String<10> = String::new();
+        s.push_hex(&[]).unwrap();
+        s.push_hex(&[0x01, 0xab]).unwrap();
+        s.push_hex_upper(&[0xcd]).unwrap();
+        assert_eq!(s, "01abCD");
+        assert!(s.push_bin(&[0]).is_err());
+        assert!(s.push_hex(&[0, 0, 0]).is_err());
+        assert_eq!(s, "01abCD");
+        s.clear();
+        s.push_bin(&[0x10]).unwrap();
+        assert_eq!(s, "00010000");
+    }
+
+    #[test]
+    fn wrap() {
+        let s: String<64> = String::try_from("héllo wörld\r\n\nabc  de \nf").unwrap();
+        assert!(s
+            .wrap_lines(4)
+            .eq(["héll", "o wö", "rld", "", "abc ", " de ", "f"]));
+        assert!(s
+            .wrap_words(4)
+            .eq(["héll", "o", "wörl", "d", "", "abc", "de ", "f"]));
+        assert!(s
+            .wrap_words(5)
+            .eq(["héllo", "wörld", "", "abc", "de ", "f"]));
+        assert!(s.wrap_words(64).eq(s.lines()));
+
+        let s: String<16> = String::try_from("   abc").unwrap();
+        assert!(s.wrap_words(2).eq(["  ", " a", "bc"]));
+        assert_eq!(String::<4>::new().wrap_lines(1).next(), None);
+
+        // the width ends on the `\r` of a CRLF
+        let s: String<16> = String::try_from("abcd\r\nx").unwrap();
+        assert!(s.wrap_lines(4).eq(s.lines()));
+        assert!(s.wrap_words(4).eq(s.lines()));
+    }
 }
@@ -0,0 +1,94 @@
+//! The atomics and cells that the lock-free data structures are built from.
+//!
+//! `spsc`, `mpmc` and the `pool` take their atomics and `UnsafeCell` from here instead of from
+//! `core`, so that building with `--cfg loom` swaps them for
+//! [`loom`](https://crates.io/crates/loom)'s. loom runs a test under every interleaving of its
+//! threads, and every outcome the memory orderings allow, and reports data races on the cells.
+//! The model tests are in `tests/loom.rs` and `pool::treiber`:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --test loom
+//! RUSTFLAGS="--cfg loom" cargo test --target i686-unknown-linux-gnu --lib pool::treiber
+//! ```
+//!
+//! The `pool` is only built for 32-bit x86 and ARM, so its model test needs a 32-bit target; on
+//! other hosts the second command builds no tests.
+//!
+//! loom's types can't be created in const contexts, so with `cfg(loom)` the constructors defined
+//! with `const_fn!` aren't `const`, and the modules that need them to be, e.g. `work_queue`, are
+//! left out. That's why this is a `cfg` rather than a feature: a feature turned on anywhere in a
+//! dependency graph would break the `static`s of every other crate in it.
+
+// not every target builds all of the modules that use this
+#![allow(dead_code, unused_imports, unused_macros)]
+
+#[cfg(not(loom))]
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+
+#[cfg(all(not(loom), not(feature = "portable-atomic")))]
+pub(crate) use core::sync::atomic;
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic;
+#[cfg(all(not(loom), feature = "portable-atomic"))]
+pub(crate) use portable_atomic as atomic;
+
+#[cfg(loom)]
+pub(crate) use loom::cell::UnsafeCell;
+
+/// A `core::cell::UnsafeCell` with the closure based access of `loom::cell::UnsafeCell`, which is
+/// how loom sees every access.
+#[cfg(not(loom))]
+#[repr(transparent)]
+pub(crate) struct UnsafeCell<T>(core::cell::UnsafeCell<T>);
+
+#[cfg(not(loom))]
+impl<T> UnsafeCell<T> {
+    pub(crate) const fn new(data: T) -> Self {
+        Self(core::cell::UnsafeCell::new(data))
+    }
+
+    #[inline(always)]
+    pub(crate) fn with<R>(&self, f: impl FnOnce(*const T) -> R) -> R {
+        f(self.0.get())
+    }
+
+    #[inline(always)]
+    pub(crate) fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+        f(self.0.get())
+    }
+}
+
+#[cfg(not(loom))]
+struct Uninit<T>(PhantomData<T>);
+
+#[cfg(not(loom))]
+impl<T> Uninit<T> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const CELL: UnsafeCell<MaybeUninit<T>> = UnsafeCell::new(MaybeUninit::uninit());
+}
+
+/// Returns an array of cells with uninitialized contents.
+#[cfg(not(loom))]
+pub(crate) const fn uninit_cells<T, const N: usize>() -> [UnsafeCell<MaybeUninit<T>>; N] {
+    [Uninit::<T>::CELL; N]
+}
+
+/// Returns an array of cells with uninitialized contents.
+#[cfg(loom)]
+pub(crate) fn uninit_cells<T, const N: usize>() -> [UnsafeCell<MaybeUninit<T>>; N] {
+    core::array::from_fn(|_| UnsafeCell::new(MaybeUninit::uninit()))
+}
+
+/// Defines a `const fn`, which is a plain `fn` with `cfg(loom)`.
+macro_rules! const_fn {
+    ($(#[$attr:meta])* $vis:vis fn $($rest:tt)*) => {
+        #[cfg(not(loom))]
+        $(#[$attr])*
+        $vis const fn $($rest)*
+
+        #[cfg(loom)]
+        $(#[$attr])*
+        $vis fn $($rest)*
+    };
+}
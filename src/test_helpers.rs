@@ -1,22 +1,17 @@
 macro_rules! droppable {
     () => {
-        static COUNT: core::sync::atomic::AtomicI32 = core::sync::atomic::AtomicI32::new(0);
+        static COUNTER: $crate::test_support::DropCounter =
+            $crate::test_support::DropCounter::new();
 
         #[derive(Eq, Ord, PartialEq, PartialOrd)]
-        struct Droppable(i32);
+        struct Droppable($crate::test_support::Tracked<'static, i32>);
         impl Droppable {
             fn new() -> Self {
-                COUNT.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
-                Droppable(Self::count())
+                Droppable(COUNTER.track(Self::count() + 1))
             }
 
             fn count() -> i32 {
-                COUNT.load(core::sync::atomic::Ordering::Relaxed)
-            }
-        }
-        impl Drop for Droppable {
-            fn drop(&mut self) {
-                COUNT.fetch_sub(1, core::sync::atomic::Ordering::Relaxed);
+                COUNTER.live() as i32
             }
         }
     };
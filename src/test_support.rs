@@ -0,0 +1,285 @@
+//! A harness for testing that containers drop every element exactly once.
+//!
+//! This is meant for crates that build containers on top of `heapless`. A [`DropCounter`] counts
+//! the [`Tracked`] values it creates and the ones that are dropped, and can make cloning them
+//! panic, to test the panic safety of operations that clone elements. Each test uses its own
+//! counter, so tests can run in parallel.
+//!
+//! NOTE: This module is only available with the `test-support` feature. It needs atomic CAS
+//! operations, which are emulated by the `portable-atomic` crate on targets without them.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{test_support::DropCounter, Vec};
+//!
+//! let counter = DropCounter::new();
+//! let mut v: Vec<_, 4> = Vec::new();
+//! v.push(counter.track(1)).unwrap();
+//! v.push(counter.track(2)).unwrap();
+//! v.truncate(1);
+//! assert_eq!((counter.created(), counter.dropped()), (2, 1));
+//!
+//! // the clone of the first element succeeds, the second one panics
+//! counter.panic_on_clone_after(1);
+//! v.push(counter.track(3)).unwrap();
+//! let result = std::panic::catch_unwind(|| Vec::<_, 4>::from_slice(&v));
+//! assert!(result.is_err());
+//!
+//! drop(v);
+//! counter.assert_all_dropped();
+//! ```
+
+use core::{
+    cmp::Ordering,
+    fmt, hash,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr,
+};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+/// Counts the creation and destruction of [`Tracked`] values.
+pub struct DropCounter {
+    created: AtomicUsize,
+    dropped: AtomicUsize,
+    /// The number of clones that may still succeed.
+    clones_left: AtomicUsize,
+}
+
+impl DropCounter {
+    /// Creates a counter with no values tracked yet.
+    pub const fn new() -> Self {
+        Self {
+            created: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+            clones_left: AtomicUsize::new(usize::MAX),
+        }
+    }
+
+    /// Wraps `value` so that this counter counts it and its clones.
+    pub fn track<T>(&self, value: T) -> Tracked<'_, T> {
+        self.created.fetch_add(1, AtomicOrdering::Relaxed);
+        Tracked {
+            value,
+            counter: self,
+        }
+    }
+
+    /// Returns the number of values created, including clones.
+    pub fn created(&self) -> usize {
+        self.created.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Returns the number of values dropped.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Returns the number of values that are alive.
+    pub fn live(&self) -> usize {
+        self.created() - self.dropped()
+    }
+
+    /// Panics if any value is still alive.
+    #[track_caller]
+    pub fn assert_all_dropped(&self) {
+        assert!(
+            self.live() == 0,
+            "{} of {} tracked values were not dropped",
+            self.live(),
+            self.created()
+        );
+    }
+
+    /// Makes cloning a tracked value panic after `clones` more successful clones.
+    pub fn panic_on_clone_after(&self, clones: usize) {
+        self.clones_left.store(clones, AtomicOrdering::Relaxed);
+    }
+
+    /// Lets tracked values be cloned again, undoing [`panic_on_clone_after`].
+    ///
+    /// [`panic_on_clone_after`]: DropCounter::panic_on_clone_after
+    pub fn allow_clones(&self) {
+        self.clones_left.store(usize::MAX, AtomicOrdering::Relaxed);
+    }
+}
+
+impl Default for DropCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for DropCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DropCounter")
+            .field("created", &self.created())
+            .field("dropped", &self.dropped())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A value whose creation and destruction are counted by a [`DropCounter`].
+///
+/// Comparisons, hashing and formatting are those of the wrapped value.
+pub struct Tracked<'a, T> {
+    value: T,
+    counter: &'a DropCounter,
+}
+
+impl<T> Tracked<'_, T> {
+    /// Unwraps the value, which counts as dropping the tracked value.
+    pub fn into_inner(self) -> T {
+        let this = ManuallyDrop::new(self);
+        this.counter.dropped.fetch_add(1, AtomicOrdering::Relaxed);
+        // NOTE(unsafe) `this` is not dropped, so the value is moved out exactly once
+        unsafe { ptr::read(&this.value) }
+    }
+}
+
+impl<T> Clone for Tracked<'_, T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        let clones_left = &self.counter.clones_left;
+        match clones_left.load(AtomicOrdering::Relaxed) {
+            0 => panic!("cloned a tracked value after `panic_on_clone_after`"),
+            usize::MAX => {}
+            n => clones_left.store(n - 1, AtomicOrdering::Relaxed),
+        }
+        self.counter.track(self.value.clone())
+    }
+}
+
+impl<T> Drop for Tracked<'_, T> {
+    fn drop(&mut self) {
+        let dropped = self.counter.dropped.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+        assert!(
+            dropped <= self.counter.created(),
+            "dropped more tracked values than were created"
+        );
+    }
+}
+
+impl<T> Deref for Tracked<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Tracked<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+impl<T> fmt::Debug for Tracked<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+impl<T> PartialEq for Tracked<'_, T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Tracked<'_, T> where T: Eq {}
+
+impl<T> PartialOrd for Tracked<'_, T>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T> Ord for Tracked<'_, T>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T> hash::Hash for Tracked<'_, T>
+where
+    T: hash::Hash,
+{
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DropCounter;
+    use crate::{Deque, FnvIndexMap, Vec};
+
+    #[test]
+    fn counts() {
+        let counter = DropCounter::new();
+        {
+            let mut d: Deque<_, 4> = Deque::new();
+            for i in 0..6 {
+                d.push_back_overwrite(counter.track(i));
+            }
+            assert_eq!(counter.live(), 4);
+
+            let mut m: FnvIndexMap<_, _, 4> = FnvIndexMap::new();
+            m.insert(counter.track(0), counter.track("a")).unwrap();
+            m.insert(counter.track(0), counter.track("b")).unwrap();
+            assert_eq!((counter.created(), counter.live()), (10, 6));
+            assert_eq!(m.values().next().map(|v| **v), Some("b"));
+
+            assert_eq!(counter.track(7).into_inner(), 7);
+        }
+        assert_eq!(counter.created(), 11);
+        counter.assert_all_dropped();
+    }
+
+    #[test]
+    fn panic_on_clone() {
+        let counter = DropCounter::new();
+        let v: Vec<_, 4> = (0..4).map(|i| counter.track(i)).collect();
+
+        counter.panic_on_clone_after(2);
+        let result = std::panic::catch_unwind(|| v.clone());
+        assert!(result.is_err());
+        assert_eq!(counter.created(), 6);
+        assert_eq!(counter.live(), 4);
+
+        counter.allow_clones();
+        let w = v.clone();
+        assert_eq!(w, v);
+        drop((v, w));
+        counter.assert_all_dropped();
+    }
+
+    #[test]
+    #[should_panic(expected = "1 of 1 tracked values were not dropped")]
+    fn leak() {
+        let counter = DropCounter::new();
+        core::mem::forget(counter.track(()));
+        counter.assert_all_dropped();
+    }
+}
@@ -0,0 +1,159 @@
+//! Utilities for testing code that uses the containers.
+//!
+//! NOTE: This module is only available with the `test-util` feature. It adds a counter to
+//! [`Vec`] and [`Deque`], so it's not meant to be enabled in production builds.
+
+use crate::{binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, LinearMap, Vec};
+
+/// A container whose capacity exhaustion can be simulated.
+///
+/// This makes the "buffer full" error paths of an application reachable in unit tests, without
+/// having to fill the containers first.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{test_util::FailureInjection, Vec};
+///
+/// fn log(buf: &mut Vec<u8, 64>, msg: &[u8]) -> usize {
+///     // drop messages that don't fit, counting them
+///     buf.extend_from_slice(msg).map_or(1, |_| 0)
+/// }
+///
+/// let mut buf = Vec::new();
+/// buf.fail_next(1);
+/// assert_eq!(log(&mut buf, b"dropped"), 1);
+/// assert_eq!(log(&mut buf, b"kept"), 0);
+/// assert_eq!(buf, b"kept");
+/// ```
+pub trait FailureInjection {
+    /// Makes the next `k` operations that add elements fail as if the container were full.
+    ///
+    /// This replaces the count of a previous call. The failed operations leave the container
+    /// unchanged. Operations that can't fail, such as the `unchecked` variants, are not affected.
+    fn fail_next(&mut self, k: usize);
+}
+
+/// The number of operations left to fail.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct FailNext(usize);
+
+impl FailNext {
+    pub(crate) const fn new() -> Self {
+        Self(0)
+    }
+
+    /// Returns `true` if the current operation is to fail.
+    pub(crate) fn take(&mut self) -> bool {
+        if self.0 == 0 {
+            false
+        } else {
+            self.0 -= 1;
+            true
+        }
+    }
+}
+
+/// Affects [`push`](Vec::push), [`insert`](Vec::insert), [`insert_checked`](Vec::insert_checked)
+/// and [`extend_from_slice`](Vec::extend_from_slice), and the operations built on them. Also
+/// affects the same operations on a [`VecCapacityView`](crate::VecCapacityView) of the vector.
+impl<T, const N: usize> FailureInjection for Vec<T, N> {
+    fn fail_next(&mut self, k: usize) {
+        self.fail_next = FailNext(k);
+    }
+}
+
+/// Affects [`push_back`](Deque::push_back) and [`push_front`](Deque::push_front), also on a
+/// [`DequeCapacityView`](crate::DequeCapacityView) of the deque.
+impl<T, const N: usize> FailureInjection for Deque<T, N> {
+    fn fail_next(&mut self, k: usize) {
+        self.fail_next = FailNext(k);
+    }
+}
+
+/// Affects [`insert`](LinearMap::insert), also when it would replace a value.
+impl<K, V, const N: usize> FailureInjection for LinearMap<K, V, N> {
+    fn fail_next(&mut self, k: usize) {
+        self.buffer.fail_next(k);
+    }
+}
+
+/// Affects [`push`](BinaryHeap::push).
+impl<T, K, const N: usize> FailureInjection for BinaryHeap<T, K, N>
+where
+    K: BinaryHeapKind,
+{
+    fn fail_next(&mut self, k: usize) {
+        self.data.fail_next(k);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FailureInjection;
+    use crate::{
+        binary_heap::Max, BinaryHeap, Deque, FnvIndexMap, FnvIndexSet, LinearMap, String, Vec,
+    };
+
+    #[test]
+    fn fail_next() {
+        let mut v: Vec<u8, 4> = Vec::new();
+        v.fail_next(2);
+        assert_eq!(v.push(0), Err(0));
+        assert_eq!(v.insert(0, 1), Err(1));
+        v.push(2).unwrap();
+        assert_eq!(v, [2]);
+
+        // views share the count of their container
+        v.fail_next(2);
+        let mut view = v.shrink_capacity_view(3);
+        assert_eq!(view.push(3), Err(3));
+        assert!(view.extend_from_slice(&[4]).is_err());
+        view.push(5).unwrap();
+        assert_eq!(v, [2, 5]);
+
+        let mut d: Deque<u8, 4> = Deque::new();
+        d.fail_next(1);
+        assert_eq!(d.push_front(0), Err(0));
+        d.push_back(1).unwrap();
+        d.fail_next(1);
+        let (item, rest) = d.fill_from_iter_back([2, 3]).unwrap_err();
+        assert_eq!((item, rest.as_slice()), (2, &[3][..]));
+        assert_eq!(d.fill_from_iter_front([4, 5]).ok(), Some(2));
+        assert!(d.iter().eq(&[5, 4, 1]));
+        d.fail_next(2);
+        let mut view = d.shrink_capacity_view(4);
+        assert_eq!(view.push_back(6), Err(6));
+        assert_eq!(view.push_front(7), Err(7));
+        view.push_back(8).unwrap();
+        assert!(d.iter().eq(&[5, 4, 1, 8]));
+
+        let mut s: String<4> = String::new();
+        s.fail_next(2);
+        assert!(s.push('a').is_err());
+        assert!(s.push_str("b").is_err());
+        s.push('c').unwrap();
+        assert_eq!(s, "c");
+
+        let mut m: LinearMap<u8, u8, 4> = LinearMap::new();
+        m.insert(0, 0).unwrap();
+        m.fail_next(1);
+        assert_eq!(m.insert(0, 1), Err((0, 1)));
+        assert_eq!(m[&0], 0);
+
+        let mut h: BinaryHeap<u8, Max, 4> = BinaryHeap::new();
+        h.fail_next(1);
+        assert_eq!(h.push(0), Err(0));
+        assert!(h.is_empty());
+
+        let mut im: FnvIndexMap<u8, u8, 4> = FnvIndexMap::new();
+        im.fail_next(1);
+        assert_eq!(im.insert(0, 0), Err((0, 0)));
+        im.insert(0, 0).unwrap();
+
+        let mut is: FnvIndexSet<u8, 4> = FnvIndexSet::new();
+        is.fail_next(1);
+        assert_eq!(is.insert(0), Err(0));
+        assert_eq!(is.insert(0), Ok(true));
+    }
+}
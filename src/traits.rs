@@ -0,0 +1,616 @@
+//! Capability traits for writing code that's generic over the container type.
+//!
+//! These traits let middleware be written once and run over a [`Vec`], a [`Deque`] or a queue
+//! endpoint, depending on what a given build uses. They are also exported from the
+//! [`prelude`](crate::prelude).
+//!
+//! [`BoundedSink`] goes the other way, and lets code that only knows the `core` traits write into
+//! a heapless container.
+//!
+//! # Examples
+//!
+//! ```
+//! use heapless::{prelude::*, spsc::Queue};
+//!
+//! /// Forwards as many frames as fit, returning the first one that didn't.
+//! fn forward<S, D>(src: &mut S, dst: &mut D) -> Option<u8>
+//! where
+//!     S: PopFront<u8>,
+//!     D: PushBack<u8>,
+//! {
+//!     while let Some(frame) = src.pop_front() {
+//!         if let Err(frame) = dst.push_back(frame) {
+//!             return Some(frame);
+//!         }
+//!     }
+//!     None
+//! }
+//!
+//! let mut rx: Deque<u8, 8> = Deque::new();
+//! rx.try_extend([1, 2, 3]).unwrap();
+//!
+//! let mut queue: Queue<u8, 3> = Queue::new();
+//! let (mut producer, _consumer) = queue.split();
+//! assert_eq!(forward(&mut rx, &mut producer), Some(3));
+//! assert!(producer.is_full());
+//! ```
+
+use core::{
+    fmt,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::{
+    binary_heap::{Kind as BinaryHeapKind, SmallPriorityQueue},
+    sorted_linked_list::{Kind as SortedLinkedListKind, SortedLinkedList, SortedLinkedListIndex},
+    BinaryHeap, Deque, HistoryBuffer, IndexMap, IndexSet, LinearMap, String, Vec, VecCapacityView,
+};
+
+/// A container with a fixed capacity.
+pub trait Capacity {
+    /// Returns the maximum number of elements the container can hold.
+    fn capacity(&self) -> usize;
+
+    /// Returns the number of elements in the container.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the container holds no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the container is at its capacity.
+    fn is_full(&self) -> bool {
+        self.len() >= self.capacity()
+    }
+
+    /// Returns the number of elements that can still be added.
+    fn remaining(&self) -> usize {
+        self.capacity().saturating_sub(self.len())
+    }
+}
+
+/// A container that elements can be appended to.
+pub trait PushBack<T> {
+    /// Appends `item` to the back of the container.
+    ///
+    /// Returns back the `item` if the container is full.
+    fn push_back(&mut self, item: T) -> Result<(), T>;
+}
+
+/// A container that elements can be taken from in the order they were appended.
+pub trait PopFront<T> {
+    /// Removes the element at the front of the container, or returns `None` if it's empty.
+    fn pop_front(&mut self) -> Option<T>;
+}
+
+/// A priority queue, i.e. a container that hands out its elements in order of priority.
+///
+/// This trait is object safe, so that e.g. a scheduler can hold a `&mut dyn PriorityQueueOps<T>`
+/// and be tested against, or built with, a [`BinaryHeap`], a
+/// [`SmallPriorityQueue`] or a
+/// [`SortedLinkedList`](crate::sorted_linked_list::SortedLinkedList).
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{
+///     binary_heap::{Min, SmallPriorityQueue},
+///     prelude::*,
+///     sorted_linked_list::{self, SortedLinkedList},
+/// };
+///
+/// /// Returns the deadlines that are due at `now`.
+/// fn expire(deadlines: &mut dyn PriorityQueueOps<u32>, now: u32) -> usize {
+///     let mut expired = 0;
+///     while deadlines.peek().is_some_and(|&deadline| deadline <= now) {
+///         deadlines.pop();
+///         expired += 1;
+///     }
+///     expired
+/// }
+///
+/// let mut heap: BinaryHeap<u32, Min, 4> = BinaryHeap::new();
+/// let mut small: SmallPriorityQueue<u32, Min, 4> = SmallPriorityQueue::new();
+/// let mut list: SortedLinkedList<u32, _, sorted_linked_list::Min, 4> =
+///     SortedLinkedList::new_u8();
+/// for queue in [
+///     &mut heap as &mut dyn PriorityQueueOps<u32>,
+///     &mut small,
+///     &mut list,
+/// ] {
+///     for deadline in [30, 10, 20] {
+///         queue.push(deadline).unwrap();
+///     }
+///     assert_eq!(expire(queue, 20), 2);
+///     assert_eq!(queue.peek(), Some(&30));
+/// }
+/// ```
+pub trait PriorityQueueOps<T> {
+    /// Adds `item` to the queue.
+    ///
+    /// Returns back the `item` if the queue is full.
+    fn push(&mut self, item: T) -> Result<(), T>;
+
+    /// Returns the element with the highest priority, or `None` if the queue is empty.
+    fn peek(&self) -> Option<&T>;
+
+    /// Removes the element with the highest priority, or returns `None` if the queue is empty.
+    fn pop(&mut self) -> Option<T>;
+
+    /// Returns `true` if the queue holds no elements.
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    /// Returns `true` if no more elements can be added.
+    fn is_full(&self) -> bool;
+}
+
+/// A vector of any capacity, which can be lent out as a [`VecCapacityView`].
+///
+/// Implemented for [`Vec`], [`VecCapacityView`] and mutable references to them, so that a function
+/// that fills a buffer needs just this bound instead of being generic over the capacity.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::prelude::*;
+///
+/// fn read_sensor<V: AsVecView<u8>>(mut buf: V) -> usize {
+///     let mut view = buf.as_vec_view();
+///     let _ = view.extend_from_slice(&[0x12, 0x34]);
+///     view.len()
+/// }
+///
+/// let mut small: Vec<u8, 2> = Vec::new();
+/// let mut large: Vec<u8, 64> = Vec::new();
+/// assert_eq!(read_sensor(&mut small), 2);
+/// assert_eq!(read_sensor(large.shrink_capacity_view(1)), 0);
+/// assert_eq!(read_sensor(&mut large), 2);
+/// ```
+pub trait AsVecView<T> {
+    /// Returns a view of the vector with its full capacity.
+    fn as_vec_view(&mut self) -> VecCapacityView<'_, T>;
+}
+
+impl<T, const N: usize> AsVecView<T> for Vec<T, N> {
+    fn as_vec_view(&mut self) -> VecCapacityView<'_, T> {
+        self.shrink_capacity_view(N)
+    }
+}
+
+impl<T> AsVecView<T> for VecCapacityView<'_, T> {
+    fn as_vec_view(&mut self) -> VecCapacityView<'_, T> {
+        self.reborrow()
+    }
+}
+
+impl<T, V> AsVecView<T> for &mut V
+where
+    V: AsVecView<T> + ?Sized,
+{
+    fn as_vec_view(&mut self) -> VecCapacityView<'_, T> {
+        (**self).as_vec_view()
+    }
+}
+
+/// A container that can be extended with the items of an iterator, without panicking when full.
+///
+/// Implemented for all [`PushBack`] containers.
+pub trait TryExtend<T> {
+    /// Appends the items of `iter` until the container is full.
+    ///
+    /// Returns the first item that didn't fit, if any. The items after it are not consumed.
+    fn try_extend<I>(&mut self, iter: I) -> Result<(), T>
+    where
+        I: IntoIterator<Item = T>;
+}
+
+impl<C, T> TryExtend<T> for C
+where
+    C: PushBack<T>,
+{
+    fn try_extend<I>(&mut self, iter: I) -> Result<(), T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            self.push_back(item)?;
+        }
+        Ok(())
+    }
+}
+
+/// An adapter that implements [`Extend`] and [`fmt::Write`] for a [`PushBack`] container, and
+/// counts the items that didn't fit instead of panicking.
+///
+/// The `Extend` impls of [`Vec`] and [`Deque`] panic when the container overflows, and
+/// `fmt::Write` for [`String`] stops the formatting with an error, which a caller may well
+/// `unwrap`. Lending a third-party parser or formatter a `BoundedSink` instead truncates the output
+/// at the capacity, and leaves it to the caller to check [`dropped`](BoundedSink::dropped).
+///
+/// Once an item has been dropped, the sink drops all later ones too, even those that would still
+/// fit, e.g. a one byte `char` after a two byte one in a [`String`]. So the container always ends
+/// with a prefix of what was written.
+///
+/// # Examples
+///
+/// ```
+/// use core::fmt::Write;
+/// use heapless::{traits::BoundedSink, String, Vec};
+///
+/// let mut samples: Vec<u8, 4> = Vec::new();
+/// let mut sink = BoundedSink::new(&mut samples);
+/// sink.extend(0..6);
+/// assert_eq!(sink.dropped(), 2);
+/// assert_eq!(samples, [0, 1, 2, 3]);
+///
+/// let mut line: String<8> = String::new();
+/// let mut sink = BoundedSink::new(&mut line);
+/// write!(sink, "t={}ms", 12345).unwrap();
+/// assert!(sink.overflowed());
+/// assert_eq!(line, "t=12345m");
+/// ```
+#[derive(Debug)]
+pub struct BoundedSink<'a, C: ?Sized> {
+    container: &'a mut C,
+    dropped: usize,
+}
+
+impl<'a, C: ?Sized> BoundedSink<'a, C> {
+    /// Creates a sink that appends to `container`.
+    pub fn new(container: &'a mut C) -> Self {
+        Self {
+            container,
+            dropped: 0,
+        }
+    }
+
+    /// Returns the number of items that didn't fit in the container.
+    ///
+    /// When the sink is written to as a [`fmt::Write`], this counts `char`s.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Returns `true` if any item didn't fit in the container.
+    pub fn overflowed(&self) -> bool {
+        self.dropped != 0
+    }
+
+    /// Returns the container.
+    pub fn into_inner(self) -> &'a mut C {
+        self.container
+    }
+}
+
+/// Consumes the whole iterator, and drops the items from the first one that doesn't fit on.
+impl<T, C> Extend<T> for BoundedSink<'_, C>
+where
+    C: PushBack<T> + ?Sized,
+{
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        for item in iter {
+            if self.overflowed() || self.container.push_back(item).is_err() {
+                self.dropped += 1;
+            }
+        }
+    }
+}
+
+/// Never returns an error; the `char`s from the first one that doesn't fit on are dropped.
+impl<C> fmt::Write for BoundedSink<'_, C>
+where
+    C: PushBack<char> + ?Sized,
+{
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.extend(s.chars());
+        Ok(())
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.extend([c]);
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> Capacity for Vec<T, N> {
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+}
+
+impl<T, const N: usize> PushBack<T> for Vec<T, N> {
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        self.push(item)
+    }
+}
+
+/// Takes the first element of the vector. This shifts all other elements and takes *O*(n) time.
+impl<T, const N: usize> PopFront<T> for Vec<T, N> {
+    fn pop_front(&mut self) -> Option<T> {
+        self.remove_checked(0)
+    }
+}
+
+impl<T, const N: usize> Capacity for Deque<T, N> {
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        Deque::len(self)
+    }
+}
+
+impl<T, const N: usize> PushBack<T> for Deque<T, N> {
+    fn push_back(&mut self, item: T) -> Result<(), T> {
+        Deque::push_back(self, item)
+    }
+}
+
+impl<T, const N: usize> PopFront<T> for Deque<T, N> {
+    fn pop_front(&mut self) -> Option<T> {
+        Deque::pop_front(self)
+    }
+}
+
+/// The capacity and length of a string are counted in bytes.
+impl<const N: usize> Capacity for String<N> {
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        self.as_str().len()
+    }
+}
+
+impl<const N: usize> PushBack<char> for String<N> {
+    fn push_back(&mut self, c: char) -> Result<(), char> {
+        self.push(c).map_err(|_| c)
+    }
+}
+
+impl<T, K, const N: usize> Capacity for BinaryHeap<T, K, N>
+where
+    T: Ord,
+    K: BinaryHeapKind,
+{
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        BinaryHeap::len(self)
+    }
+}
+
+impl<T, const N: usize> Capacity for HistoryBuffer<T, N> {
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        HistoryBuffer::len(self)
+    }
+}
+
+impl<K, V, const N: usize> Capacity for LinearMap<K, V, N>
+where
+    K: Eq,
+{
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        LinearMap::len(self)
+    }
+}
+
+impl<K, V, S, const N: usize> Capacity for IndexMap<K, V, S, N>
+where
+    K: Eq + Hash,
+    S: BuildHasher,
+{
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        IndexMap::len(self)
+    }
+}
+
+impl<T, S, const N: usize> Capacity for IndexSet<T, S, N>
+where
+    T: Eq + Hash,
+    S: BuildHasher,
+{
+    fn capacity(&self) -> usize {
+        N
+    }
+
+    fn len(&self) -> usize {
+        IndexSet::len(self)
+    }
+}
+
+impl<T, K, const N: usize> PriorityQueueOps<T> for BinaryHeap<T, K, N>
+where
+    T: Ord,
+    K: BinaryHeapKind,
+{
+    fn push(&mut self, item: T) -> Result<(), T> {
+        BinaryHeap::push(self, item)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        BinaryHeap::peek(self)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        BinaryHeap::pop(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        BinaryHeap::is_empty(self)
+    }
+
+    fn is_full(&self) -> bool {
+        BinaryHeap::len(self) == N
+    }
+}
+
+impl<T, K, const N: usize> PriorityQueueOps<T> for SmallPriorityQueue<T, K, N>
+where
+    T: Ord,
+    K: BinaryHeapKind,
+{
+    fn push(&mut self, item: T) -> Result<(), T> {
+        SmallPriorityQueue::push(self, item)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        SmallPriorityQueue::peek(self)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        SmallPriorityQueue::pop(self)
+    }
+
+    fn is_empty(&self) -> bool {
+        SmallPriorityQueue::is_empty(self)
+    }
+
+    fn is_full(&self) -> bool {
+        SmallPriorityQueue::is_full(self)
+    }
+}
+
+impl<T, Idx, K, const N: usize> PriorityQueueOps<T> for SortedLinkedList<T, Idx, K, N>
+where
+    T: Ord,
+    Idx: SortedLinkedListIndex,
+    K: SortedLinkedListKind,
+{
+    fn push(&mut self, item: T) -> Result<(), T> {
+        SortedLinkedList::push(self, item)
+    }
+
+    fn peek(&self) -> Option<&T> {
+        SortedLinkedList::peek(self)
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        SortedLinkedList::pop(self).ok()
+    }
+
+    fn is_empty(&self) -> bool {
+        SortedLinkedList::is_empty(self)
+    }
+
+    fn is_full(&self) -> bool {
+        SortedLinkedList::is_full(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::fmt::Write;
+
+    use super::{AsVecView, BoundedSink, Capacity, PopFront, PushBack, TryExtend};
+    use crate::{Deque, String, Vec};
+
+    fn fill<C>(c: &mut C) -> usize
+    where
+        C: Capacity + PushBack<u8>,
+    {
+        let mut pushed = 0;
+        while c.remaining() > 0 {
+            c.push_back(pushed as u8).unwrap();
+            pushed += 1;
+        }
+        assert!(c.is_full());
+        pushed
+    }
+
+    #[test]
+    fn generic() {
+        let mut v: Vec<u8, 4> = Vec::new();
+        assert_eq!(fill(&mut v), 4);
+        assert_eq!(PopFront::pop_front(&mut v), Some(0));
+        assert_eq!(v, [1, 2, 3]);
+
+        let mut d: Deque<u8, 3> = Deque::new();
+        d.push_back(9).unwrap();
+        assert_eq!(fill(&mut d), 2);
+        assert!(d.iter().eq(&[9, 0, 1]));
+    }
+
+    #[test]
+    fn try_extend() {
+        let mut s: String<4> = String::new();
+        assert_eq!(s.try_extend("abcde".chars()), Err('e'));
+        assert_eq!(s, "abcd");
+        assert_eq!(Capacity::len(&s), 4);
+
+        let mut v: Vec<u8, 4> = Vec::new();
+        let mut iter = 0..10;
+        assert_eq!(v.try_extend(&mut iter), Err(4));
+        assert_eq!(iter.next(), Some(5));
+    }
+
+    #[test]
+    fn as_vec_view() {
+        fn push_all(mut v: impl AsVecView<u8>) -> usize {
+            let mut view = v.as_vec_view();
+            let mut pushed = 0;
+            while view.push(pushed as u8).is_ok() {
+                pushed += 1;
+            }
+            pushed
+        }
+
+        let mut v: Vec<u8, 8> = Vec::from_slice(&[9]).unwrap();
+        assert_eq!(push_all(v.shrink_capacity_view(3)), 2);
+        let mut view = v.shrink_capacity_view(5);
+        assert_eq!(push_all(&mut view), 2);
+        assert!(view.is_full());
+        assert_eq!(push_all(&mut v), 3);
+        assert_eq!(v, [9, 0, 1, 0, 1, 0, 1, 2]);
+        assert_eq!(push_all(v), 0);
+    }
+
+    #[test]
+    fn bounded_sink() {
+        let mut d: Deque<u8, 3> = Deque::new();
+        let mut sink = BoundedSink::new(&mut d);
+        sink.extend([1, 2]);
+        assert!(!sink.overflowed());
+        sink.extend([3, 4, 5]);
+        assert_eq!(sink.dropped(), 2);
+        let d = sink.into_inner();
+        assert!(d.iter().eq(&[1, 2, 3]));
+
+        // a multi-byte char that doesn't fit is dropped whole, and so are later ones that would fit
+        let mut s: String<4> = String::new();
+        let mut sink = BoundedSink::new(&mut s);
+        let c = 'ñ';
+        write!(sink, "a{}", c).unwrap();
+        assert_eq!(sink.dropped(), 0);
+        sink.write_str("€c").unwrap();
+        sink.write_char('d').unwrap();
+        assert_eq!(sink.dropped(), 3);
+        assert_eq!(s, "añ");
+    }
+}
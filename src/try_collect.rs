@@ -0,0 +1,320 @@
+//! Non-panicking collection into fixed capacity containers.
+//!
+//! The [`FromIterator`](core::iter::FromIterator) implementations of `heapless` containers panic
+//! when the iterator yields more items than fit. [`TryCollect::try_collect`] reports this as an
+//! error instead, handing back the first item that didn't fit.
+
+use core::{
+    cmp::Ordering,
+    hash::{BuildHasher, Hash},
+};
+
+use crate::{
+    binary_heap::Kind as BinaryHeapKind, BinaryHeap, Deque, IndexMap, IndexSet, LinearMap, String,
+    Vec,
+};
+
+/// Conversion from an [`Iterator`] that fails if the items don't fit.
+///
+/// This is the fallible counterpart of [`FromIterator`](core::iter::FromIterator). It's usually
+/// used through [`TryCollect::try_collect`].
+pub trait TryFromIterator<A>: Sized {
+    /// The error returned when the items don't fit.
+    type Error;
+
+    /// Creates a value from an iterator, failing if it yields more items than fit.
+    fn try_from_iter<I>(iter: I) -> Result<Self, Self::Error>
+    where
+        I: IntoIterator<Item = A>;
+}
+
+/// Extension trait adding [`try_collect`](TryCollect::try_collect) to all iterators.
+pub trait TryCollect: Iterator + Sized {
+    /// Transforms an iterator into a collection, failing instead of panicking if the iterator
+    /// yields more items than the collection can hold.
+    ///
+    /// For all `heapless` containers, the error is the first item that didn't fit. The items
+    /// after it are not consumed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{TryCollect, Vec};
+    ///
+    /// let v = (0..4).try_collect::<Vec<_, 4>>();
+    /// assert_eq!(v.unwrap(), [0, 1, 2, 3]);
+    ///
+    /// let v = (0..5).try_collect::<Vec<_, 4>>();
+    /// assert_eq!(v, Err(4));
+    /// ```
+    fn try_collect<C>(self) -> Result<C, C::Error>
+    where
+        C: TryFromIterator<Self::Item>,
+    {
+        C::try_from_iter(self)
+    }
+
+    /// Collects the items into a [`Vec`] that's kept sorted as they arrive, failing if there are
+    /// more items than fit.
+    ///
+    /// Each item is inserted after the equal items that came before it, so the sort is stable.
+    /// This takes *O*(n²) time in the worst case, like an insertion sort, but needs no space
+    /// besides the vector. The error is the first item that didn't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{TryCollect, Vec};
+    ///
+    /// let front = [21, 19];
+    /// let rear = [23, 18];
+    /// let report: Vec<u8, 4> = front.into_iter().chain(rear).try_collect_sorted().unwrap();
+    /// assert_eq!(report, [18, 19, 21, 23]);
+    ///
+    /// assert_eq!((0..5).rev().try_collect_sorted::<4>(), Err(0));
+    /// ```
+    fn try_collect_sorted<const N: usize>(self) -> Result<Vec<Self::Item, N>, Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        self.try_collect_sorted_by(Ord::cmp)
+    }
+
+    /// Like [`try_collect_sorted`](TryCollect::try_collect_sorted), with the items sorted by
+    /// `compare`.
+    fn try_collect_sorted_by<F, const N: usize>(
+        self,
+        mut compare: F,
+    ) -> Result<Vec<Self::Item, N>, Self::Item>
+    where
+        F: FnMut(&Self::Item, &Self::Item) -> Ordering,
+    {
+        let mut vec = Vec::new();
+        for item in self {
+            if vec.is_full() {
+                return Err(item);
+            }
+            let index = vec.partition_point(|x| compare(x, &item) != Ordering::Greater);
+            // NOTE the vector isn't full, so this can't fail
+            vec.insert(index, item).ok();
+        }
+        Ok(vec)
+    }
+
+    /// Like [`try_collect_sorted`](TryCollect::try_collect_sorted), with the items sorted by the
+    /// key `f` extracts from them.
+    fn try_collect_sorted_by_key<K, F, const N: usize>(
+        self,
+        mut f: F,
+    ) -> Result<Vec<Self::Item, N>, Self::Item>
+    where
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        self.try_collect_sorted_by(|a, b| f(a).cmp(&f(b)))
+    }
+}
+
+impl<I> TryCollect for I where I: Iterator {}
+
+impl<T, const N: usize> TryFromIterator<T> for Vec<T, N> {
+    type Error = T;
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut vec = Vec::new();
+        for item in iter {
+            vec.push(item)?;
+        }
+        Ok(vec)
+    }
+}
+
+impl<T, const N: usize> TryFromIterator<T> for Deque<T, N> {
+    type Error = T;
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut deque = Deque::new();
+        for item in iter {
+            deque.push_back(item)?;
+        }
+        Ok(deque)
+    }
+}
+
+impl<T, K, const N: usize> TryFromIterator<T> for BinaryHeap<T, K, N>
+where
+    T: Ord,
+    K: BinaryHeapKind,
+{
+    type Error = T;
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut heap = BinaryHeap::new();
+        for item in iter {
+            heap.push(item)?;
+        }
+        Ok(heap)
+    }
+}
+
+impl<const N: usize> TryFromIterator<char> for String<N> {
+    type Error = char;
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, char>
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let mut string = String::new();
+        for c in iter {
+            string.push(c).map_err(|_| c)?;
+        }
+        Ok(string)
+    }
+}
+
+impl<'a, const N: usize> TryFromIterator<&'a str> for String<N> {
+    type Error = &'a str;
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, &'a str>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut string = String::new();
+        for s in iter {
+            string.push_str(s).map_err(|_| s)?;
+        }
+        Ok(string)
+    }
+}
+
+impl<K, V, const N: usize> TryFromIterator<(K, V)> for LinearMap<K, V, N>
+where
+    K: Eq,
+{
+    type Error = (K, V);
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, (K, V)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = LinearMap::new();
+        for (key, value) in iter {
+            map.insert(key, value)?;
+        }
+        Ok(map)
+    }
+}
+
+impl<K, V, S, const N: usize> TryFromIterator<(K, V)> for IndexMap<K, V, S, N>
+where
+    K: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    type Error = (K, V);
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, (K, V)>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = IndexMap::default();
+        for (key, value) in iter {
+            map.insert(key, value)?;
+        }
+        Ok(map)
+    }
+}
+
+impl<T, S, const N: usize> TryFromIterator<T> for IndexSet<T, S, N>
+where
+    T: Eq + Hash,
+    S: BuildHasher + Default,
+{
+    type Error = T;
+
+    fn try_from_iter<I>(iter: I) -> Result<Self, T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut set = IndexSet::default();
+        for item in iter {
+            set.insert(item)?;
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        binary_heap::Max, BinaryHeap, Deque, FnvIndexMap, FnvIndexSet, LinearMap, String,
+        TryCollect, TryFromIterator, Vec,
+    };
+
+    #[test]
+    fn try_collect() {
+        let s: String<4> = "abcd".chars().try_collect().unwrap();
+        assert_eq!(s, "abcd");
+        assert_eq!("abcde".chars().try_collect::<String<4>>(), Err('e'));
+        assert_eq!(
+            ["ab", "cde"].into_iter().try_collect::<String<4>>(),
+            Err("cde")
+        );
+
+        let d: Deque<u8, 2> = (0..2).try_collect().unwrap();
+        assert_eq!(d.back(), Some(&1));
+        assert_eq!((0..3).try_collect::<Deque<u8, 2>>().err(), Some(2));
+
+        let h: BinaryHeap<u8, Max, 2> = (0..2).try_collect().unwrap();
+        assert_eq!(h.peek(), Some(&1));
+
+        // Duplicate keys don't take up capacity
+        let m: LinearMap<u8, u8, 2> = [(0, 0), (1, 1), (0, 2)].into_iter().try_collect().unwrap();
+        assert_eq!(m[&0], 2);
+        let m: FnvIndexMap<u8, u8, 2> = [(0, 0), (1, 1), (0, 2)].into_iter().try_collect().unwrap();
+        assert_eq!(m[&0], 2);
+        assert_eq!(
+            (0..3)
+                .map(|i| (i, i))
+                .try_collect::<FnvIndexMap<u8, u8, 2>>(),
+            Err((2, 2))
+        );
+
+        let s: FnvIndexSet<u8, 2> = [0, 1, 1, 0].into_iter().try_collect().unwrap();
+        assert_eq!(s.len(), 2);
+
+        // Items after the one that didn't fit are not consumed
+        let mut iter = 0..10;
+        assert_eq!(Vec::<u8, 4>::try_from_iter(&mut iter), Err(4));
+        assert_eq!(iter.next(), Some(5));
+    }
+
+    #[test]
+    fn try_collect_sorted() {
+        let v: Vec<u8, 6> = [3, 1, 2, 1, 0, 3].into_iter().try_collect_sorted().unwrap();
+        assert_eq!(v, [0, 1, 1, 2, 3, 3]);
+        let v: Vec<u8, 0> = [].into_iter().try_collect_sorted().unwrap();
+        assert!(v.is_empty());
+
+        // stable
+        let readings = [(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+        let v: Vec<_, 4> = readings
+            .into_iter()
+            .try_collect_sorted_by_key(|r| r.0)
+            .unwrap();
+        assert_eq!(v, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+        let v: Vec<_, 4> = readings
+            .into_iter()
+            .try_collect_sorted_by(|a, b| b.0.cmp(&a.0))
+            .unwrap();
+        assert_eq!(v, [(2, 'a'), (2, 'c'), (1, 'b'), (1, 'd')]);
+    }
+}
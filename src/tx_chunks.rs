@@ -0,0 +1,193 @@
+//! Chunked, resumable transmission of byte buffers.
+
+use core::iter::FusedIterator;
+
+use crate::{String, Vec};
+
+impl<const N: usize> Vec<u8, N> {
+    /// Returns an iterator over the contents of the vector in chunks of at most `mtu` bytes.
+    ///
+    /// See [`TxChunks`] for how to resume a transmission that was interrupted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mtu` is 0.
+    pub fn chunks_for_tx(&self, mtu: usize) -> TxChunks<'_> {
+        TxChunks::new(self, mtu)
+    }
+}
+
+impl<const N: usize> String<N> {
+    /// Returns an iterator over the bytes of the string in chunks of at most `mtu` bytes.
+    ///
+    /// Chunks may end in the middle of a multi-byte character. See [`TxChunks`] for how to resume
+    /// a transmission that was interrupted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mtu` is 0.
+    pub fn chunks_for_tx(&self, mtu: usize) -> TxChunks<'_> {
+        TxChunks::new(self.as_bytes(), mtu)
+    }
+}
+
+/// The position of a transmission, for resuming it with [`TxChunks::resume`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TxCursor {
+    offset: usize,
+}
+
+impl TxCursor {
+    /// Returns a cursor at the start of a transmission.
+    pub const fn new() -> Self {
+        Self { offset: 0 }
+    }
+
+    /// Returns the number of bytes transmitted.
+    pub const fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+/// An iterator over a byte buffer in chunks of at most an MTU, which can be suspended and resumed.
+///
+/// This `struct` is created by the `chunks_for_tx` methods on [`Vec`] and [`String`], or by
+/// [`TxChunks::new`] for any byte slice. [`peek`](TxChunks::peek) returns the next chunk without
+/// consuming it, so that a chunk is only consumed once it was sent, and
+/// [`cursor`](TxChunks::cursor) records the position to resume from later.
+///
+/// # Examples
+///
+/// ```
+/// use heapless::{TxCursor, Vec};
+///
+/// let payload: Vec<u8, 64> = (0..50).collect();
+/// let mut cursor = TxCursor::new();
+/// let mut sent = 0;
+///
+/// // a radio that takes two packets per connection event
+/// for _event in 0..4 {
+///     let mut budget = 2;
+///     let mut chunks = payload.chunks_for_tx(20).resume(cursor);
+///     while let Some(chunk) = chunks.peek() {
+///         if budget == 0 {
+///             break;
+///         }
+///         budget -= 1;
+///         sent += chunk.len();
+///         chunks.next();
+///     }
+///     cursor = chunks.cursor();
+/// }
+///
+/// assert_eq!(sent, 50);
+/// assert_eq!(cursor.offset(), 50);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TxChunks<'a> {
+    data: &'a [u8],
+    offset: usize,
+    mtu: usize,
+}
+
+impl<'a> TxChunks<'a> {
+    /// Returns an iterator over `data` in chunks of at most `mtu` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `mtu` is 0.
+    pub fn new(data: &'a [u8], mtu: usize) -> Self {
+        assert!(mtu > 0, "MTU must be greater than 0");
+        Self {
+            data,
+            offset: 0,
+            mtu,
+        }
+    }
+
+    /// Continues the transmission at `cursor`.
+    ///
+    /// The remaining chunks are cut at the same offsets as before only if the MTU is the same.
+    /// A cursor beyond the end of the data resumes at the end.
+    pub fn resume(mut self, cursor: TxCursor) -> Self {
+        self.offset = cursor.offset.min(self.data.len());
+        self
+    }
+
+    /// Returns the position of the next chunk.
+    pub fn cursor(&self) -> TxCursor {
+        TxCursor {
+            offset: self.offset,
+        }
+    }
+
+    /// Returns the next chunk without consuming it.
+    pub fn peek(&self) -> Option<&'a [u8]> {
+        let rest = &self.data[self.offset..];
+        if rest.is_empty() {
+            None
+        } else {
+            Some(&rest[..rest.len().min(self.mtu)])
+        }
+    }
+
+    /// Returns the number of bytes that are left to transmit.
+    pub fn remaining(&self) -> usize {
+        self.data.len() - self.offset
+    }
+
+    /// Returns `true` if all bytes were transmitted.
+    pub fn is_done(&self) -> bool {
+        self.offset == self.data.len()
+    }
+}
+
+impl<'a> Iterator for TxChunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let chunk = self.peek()?;
+        self.offset += chunk.len();
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining().div_ceil(self.mtu);
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for TxChunks<'_> {}
+
+impl FusedIterator for TxChunks<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{TxChunks, TxCursor};
+    use crate::{String, Vec};
+
+    #[test]
+    fn chunks() {
+        let v: Vec<u8, 8> = Vec::from_slice(&[0, 1, 2, 3, 4, 5, 6]).unwrap();
+        let mut chunks = v.chunks_for_tx(3);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks.next(), Some(&[0, 1, 2][..]));
+        let cursor = chunks.cursor();
+        assert_eq!(cursor.offset(), 3);
+
+        let mut resumed = v.chunks_for_tx(3).resume(cursor);
+        assert_eq!(resumed.peek(), Some(&[3, 4, 5][..]));
+        assert_eq!(resumed.remaining(), 4);
+        assert!(resumed.by_ref().eq([&[3, 4, 5][..], &[6]]));
+        assert!(resumed.is_done());
+        assert_eq!(resumed.next(), None);
+
+        let s: String<8> = String::try_from("héllo").unwrap();
+        assert!(s.chunks_for_tx(4).eq([&b"h\xc3\xa9l"[..], b"lo"]));
+        assert_eq!(
+            s.chunks_for_tx(4).resume(TxCursor { offset: 9 }).next(),
+            None
+        );
+        assert_eq!(TxChunks::new(&[], 1).next(), None);
+    }
+}
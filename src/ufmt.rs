@@ -1,4 +1,5 @@
-use crate::{string::String, vec::Vec};
+use crate::{display::DisplayJoined, string::String, vec::Vec};
+use ufmt::{uDisplay, Formatter};
 use ufmt_write::uWrite;
 
 impl<const N: usize> uWrite for String<N> {
@@ -15,6 +16,25 @@ impl<const N: usize> uWrite for Vec<u8, N> {
     }
 }
 
+impl<I> uDisplay for DisplayJoined<'_, I>
+where
+    I: Iterator + Clone,
+    I::Item: uDisplay,
+{
+    fn fmt<W>(&self, f: &mut Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: uWrite + ?Sized,
+    {
+        for (i, item) in self.items.clone().enumerate() {
+            if i != 0 {
+                f.write_str(self.separator)?;
+            }
+            item.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -55,4 +75,14 @@ mod tests {
 
         assert_eq!(v, b"123 -> Pair { x: 0, y: 1234 }");
     }
+
+    #[test]
+    fn test_display_joined() {
+        let v = Vec::<u32, 4>::from_slice(&[1, 20, 300]).unwrap();
+
+        let mut s = String::<32>::new();
+        uwrite!(s, "[{}]", v.display_joined(", ")).unwrap();
+
+        assert_eq!(s, "[1, 20, 300]");
+    }
 }
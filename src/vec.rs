@@ -1,4 +1,13 @@
-use core::{cmp::Ordering, fmt, hash, iter::FromIterator, mem::MaybeUninit, ops, ptr, slice};
+use core::{
+    cmp::Ordering,
+    fmt, hash,
+    iter::{FromIterator, FusedIterator},
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{self, Bound, Range, RangeBounds},
+    ptr, slice,
+};
+
+use crate::{display::DisplayJoined, String};
 
 /// A fixed capacity [`Vec`](https://doc.rust-lang.org/std/vec/struct.Vec.html).
 ///
@@ -28,6 +37,30 @@ use core::{cmp::Ordering, fmt, hash, iter::FromIterator, mem::MaybeUninit, ops,
 /// }
 /// assert_eq!(*vec, [7, 1, 2, 3]);
 /// ```
+///
+/// # Panic-free subset
+///
+/// Code that must not contain panic branches can restrict itself to methods that report failure
+/// through their return value: [`push`](Vec::push), [`pop`](Vec::pop),
+/// [`extend_from_slice`](Vec::extend_from_slice), [`truncate`](Vec::truncate),
+/// [`resize`](Vec::resize), [`clear`](Vec::clear), [`insert_checked`](Vec::insert_checked),
+/// [`remove_checked`](Vec::remove_checked), [`swap_remove_checked`](Vec::swap_remove_checked)
+/// and the non-indexing slice methods such as `get` and `get_mut`. These are verified with the
+/// `no-panic` crate in release builds, see `tests/no_panic.rs`.
+///
+/// # Layout
+///
+/// `Vec<T, N>` is `#[repr(C)]` and starts with the length, a `usize`, followed by the storage for
+/// `N` elements, of which the first `len` are initialized. In C this prefix is
+/// `struct { size_t len; T buffer[N]; }`, so a pointer to a `Vec` can be handed across an FFI
+/// boundary, e.g. as an RTOS message.
+///
+/// Only the offsets of these two fields are guaranteed, not the size of the `Vec`: the
+/// `test-util` feature adds a field after the storage, and so does `capacity-stats`, which is
+/// built on targets with atomic compare-and-swap or with `portable-atomic`.
+///
+/// See also [`into_raw_parts`](Vec::into_raw_parts) and [`from_raw_parts`](Vec::from_raw_parts).
+#[repr(C)]
 pub struct Vec<T, const N: usize> {
     // NOTE order is important for optimizations. the `len` first layout lets the compiler optimize
     // `new` to: reserve stack space and zero the first word. With the fields in the reverse order
@@ -36,6 +69,12 @@ pub struct Vec<T, const N: usize> {
     len: usize,
 
     buffer: [MaybeUninit<T>; N],
+
+    #[cfg(feature = "test-util")]
+    pub(crate) fail_next: crate::test_util::FailNext,
+
+    #[cfg(capacity_stats)]
+    pub(crate) monitor: crate::capacity_stats::Monitor,
 }
 
 impl<T, const N: usize> Vec<T, N> {
@@ -60,6 +99,10 @@ impl<T, const N: usize> Vec<T, N> {
         Self {
             len: 0,
             buffer: Self::INIT,
+            #[cfg(feature = "test-util")]
+            fail_next: crate::test_util::FailNext::new(),
+            #[cfg(capacity_stats)]
+            monitor: crate::capacity_stats::Monitor::new(),
         }
     }
 
@@ -85,6 +128,40 @@ impl<T, const N: usize> Vec<T, N> {
         Ok(v)
     }
 
+    /// Constructs a new vector with a fixed capacity of `N`, holding the elements of `src`.
+    ///
+    /// This works in `const` contexts, so it can build static tables whose properties are then
+    /// checked at compile time. `N` must be at least `M`, which is checked at compile time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// static TABLE: Vec<u16, 8> = Vec::from_array([1, 2, 4, 8]);
+    /// const _: () = assert!(TABLE.len() == 4 && !TABLE.is_full());
+    /// const _: () = assert!(TABLE.as_slice()[3] == 8);
+    ///
+    /// assert_eq!(TABLE, [1, 2, 4, 8]);
+    /// ```
+    pub const fn from_array<const M: usize>(src: [T; M]) -> Self {
+        // Const assert
+        crate::sealed::greater_than_eq::<N, M>();
+
+        let src = ManuallyDrop::new(src);
+        let src = &src as *const ManuallyDrop<[T; M]> as *const T;
+        let mut v = Self::new();
+        let mut i = 0;
+        while i < M {
+            // NOTE(unsafe) each element is moved out of `src` exactly once, and `src` is not
+            // dropped
+            v.buffer[i] = MaybeUninit::new(unsafe { ptr::read(src.add(i)) });
+            i += 1;
+        }
+        v.len = M;
+        v
+    }
+
     /// Clones a vec into a new vec
     pub(crate) fn clone(&self) -> Self
     where
@@ -100,8 +177,38 @@ impl<T, const N: usize> Vec<T, N> {
         new
     }
 
+    /// Clones the vector into a new vector with a capacity of `M`.
+    ///
+    /// Returns `Err(())` without cloning any element if the elements don't fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let v: Vec<u8, 16> = Vec::from_slice(&[1, 2, 3]).unwrap();
+    /// let small: Vec<u8, 4> = v.clone_into_capacity().unwrap();
+    /// assert_eq!(small, [1, 2, 3]);
+    /// assert!(v.clone_into_capacity::<2>().is_err());
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn clone_into_capacity<const M: usize>(&self) -> Result<Vec<T, M>, ()>
+    where
+        T: Clone,
+    {
+        if self.len > M {
+            return Err(());
+        }
+        let mut new = Vec::new();
+        for elem in self {
+            // NOTE(unsafe) there are at most `M` elements
+            unsafe { new.push_unchecked(elem.clone()) };
+        }
+        Ok(new)
+    }
+
     /// Returns a raw pointer to the vector’s buffer.
-    pub fn as_ptr(&self) -> *const T {
+    pub const fn as_ptr(&self) -> *const T {
         self.buffer.as_ptr() as *const T
     }
 
@@ -121,7 +228,7 @@ impl<T, const N: usize> Vec<T, N> {
     /// let buffer: Vec<u8, 5> = Vec::from_slice(&[1, 2, 3, 5, 8]).unwrap();
     /// assert_eq!(buffer.as_slice(), &[1, 2, 3, 5, 8]);
     /// ```
-    pub fn as_slice(&self) -> &[T] {
+    pub const fn as_slice(&self) -> &[T] {
         // NOTE(unsafe) avoid bound checks in the slicing operation
         // &buffer[..self.len]
         unsafe { slice::from_raw_parts(self.buffer.as_ptr() as *const T, self.len) }
@@ -153,6 +260,72 @@ impl<T, const N: usize> Vec<T, N> {
         }
     }
 
+    /// Decomposes the vector into its storage and its length.
+    ///
+    /// The first `len` elements of the storage are initialized; they are no longer dropped by the
+    /// vector. [`from_raw_parts`](Vec::from_raw_parts) puts the vector back together.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let v: Vec<u8, 4> = Vec::from_slice(&[1, 2]).unwrap();
+    /// let (buffer, len) = v.into_raw_parts();
+    /// assert_eq!(len, 2);
+    ///
+    /// // Safety: the first `len` elements of `buffer` are initialized
+    /// let v = unsafe { Vec::from_raw_parts(buffer, len) };
+    /// assert_eq!(v, [1, 2]);
+    /// ```
+    pub fn into_raw_parts(self) -> ([MaybeUninit<T>; N], usize) {
+        let this = ManuallyDrop::new(self);
+        // NOTE(unsafe) `this` is not dropped, so the elements are moved out exactly once
+        (unsafe { ptr::read(&this.buffer) }, this.len)
+    }
+
+    /// Creates a vector from its storage and its length, e.g. as returned by
+    /// [`into_raw_parts`](Vec::into_raw_parts).
+    ///
+    /// # Safety
+    ///
+    /// `len` must be at most `N`, and the first `len` elements of `buffer` must be initialized.
+    pub const unsafe fn from_raw_parts(buffer: [MaybeUninit<T>; N], len: usize) -> Self {
+        Self {
+            len,
+            buffer,
+            #[cfg(feature = "test-util")]
+            fail_next: crate::test_util::FailNext::new(),
+            #[cfg(capacity_stats)]
+            monitor: crate::capacity_stats::Monitor::new(),
+        }
+    }
+
+    /// Makes the vector immutable.
+    ///
+    /// This is one-way: a [`FrozenVec`] can be read and cloned, but never modified or turned back
+    /// into a `Vec`. This is useful for tables that are built once at start-up and then shared,
+    /// e.g. with interrupt handlers, through a `&'static FrozenVec`, which is `Copy`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{FrozenVec, Vec};
+    ///
+    /// static GAINS: FrozenVec<u16, 4> = Vec::from_array([1, 2, 4, 8]).freeze();
+    ///
+    /// fn gain(channel: usize, table: &'static FrozenVec<u16, 4>) -> u16 {
+    ///     table[channel]
+    /// }
+    ///
+    /// let table = &GAINS;
+    /// assert_eq!(gain(2, table), 4);
+    /// assert_eq!(table.iter().sum::<u16>(), 15);
+    /// ```
+    pub const fn freeze(self) -> FrozenVec<T, N> {
+        FrozenVec { vec: self }
+    }
+
     /// Extracts a mutable slice containing the entire vector.
     ///
     /// Equivalent to `&mut s[..]`.
@@ -171,27 +344,102 @@ impl<T, const N: usize> Vec<T, N> {
         unsafe { slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut T, self.len) }
     }
 
+    /// Returns the elements in `range`, or `None` if it's out of bounds.
+    ///
+    /// Unlike indexing with a range this never panics, so offsets from untrusted input can be
+    /// used without checking them first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let frame: Vec<u8, 8> = Vec::from_slice(&[0x02, 0xaa, 0xbb, 0x03]).unwrap();
+    /// assert_eq!(frame.get_range(1..3), Some(&[0xaa, 0xbb][..]));
+    /// assert_eq!(frame.get_range(1..=4), None);
+    /// ```
+    pub fn get_range<R>(&self, range: R) -> Option<&[T]>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.as_slice().get(checked_slice_range(range, self.len)?)
+    }
+
+    /// Returns the elements in `range` mutably, or `None` if it's out of bounds.
+    ///
+    /// See [`get_range`](Vec::get_range).
+    pub fn get_range_mut<R>(&mut self, range: R) -> Option<&mut [T]>
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = checked_slice_range(range, self.len)?;
+        self.as_mut_slice().get_mut(range)
+    }
+
     /// Returns the maximum number of elements the vector can hold.
     pub const fn capacity(&self) -> usize {
         N
     }
 
+    /// Returns the number of elements in the vector.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
     /// Clears the vector, removing all values.
     pub fn clear(&mut self) {
         self.truncate(0);
     }
 
+    /// Lends out the vector with its capacity restricted to the first `capacity` slots.
+    ///
+    /// The returned view can add elements only up to `capacity`, so a callee that gets it can't
+    /// use more of a shared buffer than its quota. The capacity of the view is clamped to the
+    /// range `len..=N`. The view doesn't carry `N` in its type, so callees need not be generic
+    /// over it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{Vec, VecCapacityView};
+    ///
+    /// fn fill(buf: &mut VecCapacityView<'_, u8>) {
+    ///     while buf.push(0xaa).is_ok() {}
+    /// }
+    ///
+    /// let mut buf: Vec<u8, 64> = Vec::new();
+    /// buf.push(1).unwrap();
+    /// fill(&mut buf.shrink_capacity_view(4));
+    /// assert_eq!(buf, [1, 0xaa, 0xaa, 0xaa]);
+    /// ```
+    pub fn shrink_capacity_view(&mut self, capacity: usize) -> CapacityView<'_, T> {
+        let capacity = capacity.clamp(self.len, N);
+        CapacityView {
+            buffer: &mut self.buffer[..capacity],
+            len: &mut self.len,
+            #[cfg(feature = "test-util")]
+            fail_next: &mut self.fail_next,
+            #[cfg(capacity_stats)]
+            monitor: self.monitor,
+        }
+    }
+
     /// Extends the vec from an iterator.
     ///
     /// # Panic
     ///
-    /// Panics if the vec cannot hold all elements of the iterator.
+    /// Panics if the vec cannot hold all elements of the iterator. With the `disable-panic-paths`
+    /// feature this only panics in debug builds, release builds stop at capacity instead.
     pub fn extend<I>(&mut self, iter: I)
     where
         I: IntoIterator<Item = T>,
     {
         for elem in iter {
-            self.push(elem).ok().unwrap()
+            if self.push(elem).is_err() {
+                capacity_overflow("Vec::extend overflow");
+                return;
+            }
         }
     }
 
@@ -215,6 +463,11 @@ impl<T, const N: usize> Vec<T, N> {
     where
         T: Clone,
     {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(());
+        }
+
         if self.len + other.len() > self.capacity() {
             // won't fit in the `Vec`; don't modify anything and return an error
             Err(())
@@ -241,6 +494,11 @@ impl<T, const N: usize> Vec<T, N> {
     ///
     /// Returns back the `item` if the vector is full
     pub fn push(&mut self, item: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(item);
+        }
+
         if self.len < self.capacity() {
             unsafe { self.push_unchecked(item) }
             Ok(())
@@ -274,6 +532,8 @@ impl<T, const N: usize> Vec<T, N> {
         *self.buffer.get_unchecked_mut(self.len) = MaybeUninit::new(item);
 
         self.len += 1;
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len);
     }
 
     /// Shortens the vector, keeping the first `len` elements and dropping the rest.
@@ -433,7 +693,108 @@ impl<T, const N: usize> Vec<T, N> {
     pub unsafe fn set_len(&mut self, new_len: usize) {
         debug_assert!(new_len <= self.capacity());
 
-        self.len = new_len
+        self.len = new_len;
+        #[cfg(capacity_stats)]
+        self.monitor.record(new_len);
+    }
+
+    /// Lets `f` initialize elements in the spare capacity of the vector, and appends the first
+    /// elements it reports as initialized.
+    ///
+    /// `f` gets the `N - len` uninitialized slots after the last element, e.g. to hand them to a
+    /// driver that writes to a raw buffer, and returns how many of the first slots it initialized.
+    /// This returns that number. See [`init_extend`](Vec::init_extend) for a safe alternative.
+    ///
+    /// # Safety
+    ///
+    /// `f` must have initialized the first `k` slots when it returns `k`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` returns a number larger than the number of slots it got.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::mem::MaybeUninit;
+    /// use heapless::Vec;
+    ///
+    /// fn read_fifo(buf: &mut [MaybeUninit<u8>]) -> usize {
+    ///     let n = buf.len().min(3);
+    ///     for (i, slot) in buf[..n].iter_mut().enumerate() {
+    ///         slot.write(i as u8);
+    ///     }
+    ///     n
+    /// }
+    ///
+    /// let mut vec: Vec<u8, 8> = Vec::from_slice(&[42]).unwrap();
+    /// // SAFETY: `read_fifo` initializes as many slots as it returns
+    /// assert_eq!(unsafe { vec.assume_init_extend(read_fifo) }, 3);
+    /// assert_eq!(vec, [42, 0, 1, 2]);
+    /// ```
+    pub unsafe fn assume_init_extend<F>(&mut self, f: F) -> usize
+    where
+        F: FnOnce(&mut [MaybeUninit<T>]) -> usize,
+    {
+        let len = self.len;
+        let spare = &mut self.buffer[len..];
+        let spare_len = spare.len();
+        let added = f(spare);
+        assert!(
+            added <= spare_len,
+            "initialized (is {}) should be <= spare capacity (is {})",
+            added,
+            spare_len
+        );
+        self.len += added;
+        #[cfg(capacity_stats)]
+        self.monitor.record(self.len);
+        added
+    }
+
+    /// Lets `f` fill elements in the spare capacity of the vector, and appends the first elements
+    /// it reports as filled.
+    ///
+    /// This is the safe counterpart of [`assume_init_extend`](Vec::assume_init_extend): the
+    /// `N - len` slots after the last element are set to clones of `value` first, so `f` gets
+    /// initialized elements. `f` returns how many of the first elements it filled, which this
+    /// returns too; the elements after those are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `f` returns a number larger than the number of elements it got.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut vec: Vec<u8, 8> = Vec::from_slice(&[42]).unwrap();
+    /// let filled = vec.init_extend(0, |buf| {
+    ///     buf[..2].copy_from_slice(&[1, 2]);
+    ///     2
+    /// });
+    /// assert_eq!(filled, 2);
+    /// assert_eq!(vec, [42, 1, 2]);
+    /// ```
+    pub fn init_extend<F>(&mut self, value: T, f: F) -> usize
+    where
+        T: Clone,
+        F: FnOnce(&mut [T]) -> usize,
+    {
+        let len = self.len;
+        self.resize(N, value).ok();
+        let spare = &mut self[len..];
+        let spare_len = spare.len();
+        let added = f(spare);
+        assert!(
+            added <= spare_len,
+            "filled (is {}) should be <= spare capacity (is {})",
+            added,
+            spare_len
+        );
+        self.truncate(len + added);
+        added
     }
 
     /// Removes an element from the vector and returns it.
@@ -469,6 +830,33 @@ impl<T, const N: usize> Vec<T, N> {
         unsafe { self.swap_remove_unchecked(index) }
     }
 
+    /// Removes an element from the vector and returns it, or `None` if `index` is out of bounds.
+    ///
+    /// The removed element is replaced by the last element of the vector.
+    ///
+    /// This does not preserve ordering, but is *O*(1). Unlike [`swap_remove`](Vec::swap_remove)
+    /// this never panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut v: Vec<_, 8> = Vec::from_slice(&["foo", "bar", "baz"]).unwrap();
+    ///
+    /// assert_eq!(v.swap_remove_checked(0), Some("foo"));
+    /// assert_eq!(&*v, ["baz", "bar"]);
+    /// assert_eq!(v.swap_remove_checked(2), None);
+    /// ```
+    #[inline]
+    pub fn swap_remove_checked(&mut self, index: usize) -> Option<T> {
+        if index < self.len {
+            Some(unsafe { self.swap_remove_unchecked(index) })
+        } else {
+            None
+        }
+    }
+
     /// Removes an element from the vector and returns it.
     ///
     /// The removed element is replaced by the last element of the vector.
@@ -506,15 +894,22 @@ impl<T, const N: usize> Vec<T, N> {
         value
     }
 
+    /// Returns `true` if the next operation adding an element is to fail, see
+    /// [`FailureInjection`](crate::test_util::FailureInjection).
+    #[cfg(feature = "test-util")]
+    pub(crate) fn take_injected_failure(&mut self) -> bool {
+        self.fail_next.take()
+    }
+
     /// Returns true if the vec is full
     #[inline]
-    pub fn is_full(&self) -> bool {
+    pub const fn is_full(&self) -> bool {
         self.len == self.capacity()
     }
 
     /// Returns true if the vec is empty
     #[inline]
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.len == 0
     }
 
@@ -564,6 +959,83 @@ impl<T, const N: usize> Vec<T, N> {
         v >= n && needle == &self[v - n..]
     }
 
+    /// Binary searches this sorted vector for the first element equal to `x`.
+    ///
+    /// Unlike [`binary_search`](slice::binary_search), which returns any of the matches when
+    /// there are several, this returns `Ok` with the index of the first one. If there is no match,
+    /// returns `Err` with the index where `x` could be inserted to keep the vector sorted, like
+    /// `binary_search`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let v: Vec<u8, 8> = Vec::from_slice(&[1, 2, 2, 2, 5]).unwrap();
+    /// assert_eq!(v.binary_search_first_of(&2), Ok(1));
+    /// assert_eq!(v.binary_search_last_of(&2), Ok(3));
+    /// assert_eq!(v.binary_search_first_of(&3), Err(4));
+    /// ```
+    pub fn binary_search_first_of(&self, x: &T) -> Result<usize, usize>
+    where
+        T: Ord,
+    {
+        let index = self.partition_point(|elem| elem < x);
+        match self.get(index) {
+            Some(elem) if elem == x => Ok(index),
+            _ => Err(index),
+        }
+    }
+
+    /// Binary searches this sorted vector for the last element equal to `x`.
+    ///
+    /// Returns `Ok` with the index of the last match, or `Err` with the index where `x` could be
+    /// inserted to keep the vector sorted if there is none. Together with
+    /// [`binary_search_first_of`](Vec::binary_search_first_of) this gives the range of elements
+    /// equal to `x`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let v: Vec<u8, 8> = Vec::from_slice(&[1, 2, 2, 2, 5]).unwrap();
+    /// if let (Ok(first), Ok(last)) = (v.binary_search_first_of(&2), v.binary_search_last_of(&2)) {
+    ///     assert_eq!(v[first..=last], [2, 2, 2]);
+    /// }
+    /// assert_eq!(v.binary_search_last_of(&0), Err(0));
+    /// ```
+    pub fn binary_search_last_of(&self, x: &T) -> Result<usize, usize>
+    where
+        T: Ord,
+    {
+        let end = self.partition_point(|elem| elem <= x);
+        match end.checked_sub(1) {
+            Some(index) if self[index] == *x => Ok(index),
+            _ => Err(end),
+        }
+    }
+
+    /// Returns an adapter that displays the elements separated by `separator`, without building
+    /// a [`String`].
+    ///
+    /// See the [`display`](crate::display) module for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let v: Vec<u8, 8> = Vec::from_slice(&[1, 2, 3]).unwrap();
+    /// assert_eq!(format!("{}", v.display_joined(", ")), "1, 2, 3");
+    /// ```
+    pub fn display_joined<'a>(
+        &'a self,
+        separator: &'a str,
+    ) -> DisplayJoined<'a, slice::Iter<'a, T>> {
+        DisplayJoined::new(self.as_slice(), separator)
+    }
+
     /// Inserts an element at position `index` within the vector, shifting all
     /// elements after it to the right.
     ///
@@ -593,8 +1065,36 @@ impl<T, const N: usize> Vec<T, N> {
             );
         }
 
+        self.insert_checked(index, element)
+    }
+
+    /// Inserts an element at position `index` within the vector, shifting all
+    /// elements after it to the right.
+    ///
+    /// Returns back the `element` if the vector is full or if `index > len`. Unlike
+    /// [`insert`](Vec::insert) this never panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut vec: Vec<_, 4> = Vec::from_slice(&[1, 2, 3]).unwrap();
+    /// assert_eq!(vec.insert_checked(5, 4), Err(4));
+    /// assert_eq!(vec.insert_checked(1, 4), Ok(()));
+    /// assert_eq!(vec, [1, 4, 2, 3]);
+    /// assert_eq!(vec.insert_checked(0, 5), Err(5));
+    /// ```
+    pub fn insert_checked(&mut self, index: usize, element: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(element);
+        }
+
+        let len = self.len();
+
         // check there's space for the new element
-        if self.is_full() {
+        if index > len || self.is_full() {
             return Err(element);
         }
 
@@ -646,52 +1146,349 @@ impl<T, const N: usize> Vec<T, N> {
         if index >= len {
             panic!("removal index (is {}) should be < len (is {})", index, len);
         }
-        unsafe {
-            // infallible
-            let ret;
-            {
-                // the place we are taking from.
-                let ptr = self.as_mut_ptr().add(index);
-                // copy it out, unsafely having a copy of the value on
-                // the stack and in the vector at the same time.
-                ret = ptr::read(ptr);
-
-                // Shift everything down to fill in that spot.
-                ptr::copy(ptr.offset(1), ptr, len - index - 1);
-            }
-            self.set_len(len - 1);
-            ret
-        }
+        unsafe { self.remove_unchecked(index) }
     }
 
-    /// Retains only the elements specified by the predicate.
+    /// Removes and returns the element at position `index` within the vector,
+    /// shifting all elements after it to the left.
     ///
-    /// In other words, remove all elements `e` for which `f(&e)` returns `false`.
-    /// This method operates in place, visiting each element exactly once in the
-    /// original order, and preserves the order of the retained elements.
+    /// Returns `None` if `index` is out of bounds. Unlike [`remove`](Vec::remove) this never
+    /// panics.
     ///
     /// # Examples
     ///
     /// ```
     /// use heapless::Vec;
     ///
-    /// let mut vec: Vec<_, 8> = Vec::from_slice(&[1, 2, 3, 4]).unwrap();
-    /// vec.retain(|&x| x % 2 == 0);
-    /// assert_eq!(vec, [2, 4]);
+    /// let mut v: Vec<_, 8> = Vec::from_slice(&[1, 2, 3]).unwrap();
+    /// assert_eq!(v.remove_checked(1), Some(2));
+    /// assert_eq!(v.remove_checked(2), None);
+    /// assert_eq!(v, [1, 3]);
     /// ```
+    #[inline]
+    pub fn remove_checked(&mut self, index: usize) -> Option<T> {
+        if index < self.len {
+            Some(unsafe { self.remove_unchecked(index) })
+        } else {
+            None
+        }
+    }
+
+    /// Assumes `index < len`.
+    unsafe fn remove_unchecked(&mut self, index: usize) -> T {
+        let len = self.len();
+        let ret;
+        {
+            // the place we are taking from.
+            let ptr = self.as_mut_ptr().add(index);
+            // copy it out, unsafely having a copy of the value on
+            // the stack and in the vector at the same time.
+            ret = ptr::read(ptr);
+
+            // Shift everything down to fill in that spot.
+            ptr::copy(ptr.offset(1), ptr, len - index - 1);
+        }
+        self.set_len(len - 1);
+        ret
+    }
+
+    /// Returns an iterator that removes `K` elements at a time from the front of the vector and
+    /// yields them as arrays.
     ///
-    /// Because the elements are visited exactly once in the original order,
-    /// external state may be used to decide which elements to keep.
+    /// Each chunk is moved out with a single bulk copy, and the elements that are left are moved to
+    /// the front of the vector only once, when the iterator is dropped. Elements that are not
+    /// yielded, including the last `len % K` elements, stay in the vector.
+    ///
+    /// If the iterator is leaked (e.g. with [`mem::forget`](core::mem::forget)), the vector is
+    /// left empty and its elements are leaked.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use heapless::Vec;
     ///
-    /// let mut vec: Vec<_, 8> = Vec::from_slice(&[1, 2, 3, 4, 5]).unwrap();
-    /// let keep = [false, true, true, false, true];
-    /// let mut iter = keep.iter();
-    /// vec.retain(|_| *iter.next().unwrap());
-    /// assert_eq!(vec, [2, 3, 5]);
-    /// ```
+    /// let mut vec: Vec<u8, 8> = Vec::from_slice(&[0, 1, 2, 3, 4, 5, 6]).unwrap();
+    ///
+    /// let mut chunks = vec.drain_chunks::<3>();
+    /// assert_eq!(chunks.next(), Some([0, 1, 2]));
+    /// assert_eq!(chunks.next(), Some([3, 4, 5]));
+    /// assert_eq!(chunks.next(), None);
+    /// drop(chunks);
+    ///
+    /// assert_eq!(vec, [6]);
+    /// ```
+    pub fn drain_chunks<const K: usize>(&mut self) -> DrainChunks<'_, T, N, K> {
+        // Const assert K > 0
+        crate::sealed::greater_than_0::<K>();
+
+        let len = self.len;
+        // Leak amplification: the elements are owned by the iterator until it's dropped.
+        self.len = 0;
+
+        DrainChunks {
+            vec: self,
+            next: 0,
+            len,
+        }
+    }
+
+    /// Returns an iterator that moves the elements out of the vector in runs, yielding each run as
+    /// a vector.
+    ///
+    /// `pred` is called with each pair of consecutive elements, and a new run starts wherever it
+    /// returns `false`. This is the owned counterpart of the slice methods
+    /// [`chunk_by`](slice::chunk_by) and [`chunk_by_mut`](slice::chunk_by_mut), which `Vec` also
+    /// provides through `Deref`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// // (sensor, reading), sorted by sensor
+    /// let readings: Vec<(u8, u16), 8> =
+    ///     Vec::from_slice(&[(1, 10), (1, 12), (2, 7), (3, 1), (3, 3)]).unwrap();
+    ///
+    /// // borrowed runs
+    /// assert_eq!(readings.chunk_by(|a, b| a.0 == b.0).count(), 3);
+    ///
+    /// // owned runs
+    /// let mut runs = readings.into_chunk_by(|a, b| a.0 == b.0);
+    /// assert_eq!(runs.next().unwrap(), [(1, 10), (1, 12)]);
+    /// assert_eq!(runs.next().unwrap(), [(2, 7)]);
+    /// assert_eq!(runs.next().unwrap(), [(3, 1), (3, 3)]);
+    /// assert_eq!(runs.next(), None);
+    /// ```
+    pub fn into_chunk_by<F>(self, pred: F) -> IntoChunkBy<T, N, F>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        IntoChunkBy {
+            iter: self.into_iter(),
+            pending: None,
+            pred,
+        }
+    }
+
+    /// Moves the elements in `range` so that they start at index `dest`, shifting the elements in
+    /// between to make room.
+    ///
+    /// This is the same as removing the elements in `range` and inserting them back at `dest`,
+    /// but done in place with a single rotation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds, or if `dest` is greater than `self.len()` minus the
+    /// length of `range`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut vec: Vec<u8, 8> = Vec::from_slice(&[0, 1, 2, 3, 4, 5]).unwrap();
+    /// vec.move_within(4.., 1);
+    /// assert_eq!(vec, [0, 4, 5, 1, 2, 3]);
+    /// vec.move_within(..2, 4);
+    /// assert_eq!(vec, [5, 1, 2, 3, 0, 4]);
+    /// ```
+    pub fn move_within<R>(&mut self, range: R, dest: usize)
+    where
+        R: RangeBounds<usize>,
+    {
+        let Range { start, end } = slice_range(range, self.len());
+        let count = end - start;
+        assert!(
+            dest <= self.len() - count,
+            "destination index (is {}) should be <= len - range length (is {})",
+            dest,
+            self.len() - count
+        );
+
+        if dest < start {
+            self[dest..end].rotate_right(count);
+        } else {
+            self[start..dest + count].rotate_left(count);
+        }
+    }
+
+    /// Moves the `K` largest elements to the front of the vector, in unspecified order, and returns
+    /// them as a slice.
+    ///
+    /// This is built on [`select_nth_unstable`](slice::select_nth_unstable), so it takes *O*(n)
+    /// time instead of the *O*(n \* log(n)) of sorting the whole vector. The order of the other
+    /// elements is unspecified as well. If the vector holds `K` elements or fewer, all of them are
+    /// returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut vec: Vec<u8, 8> = Vec::from_slice(&[3, 9, 1, 7, 4, 8]).unwrap();
+    /// let top = vec.top_k::<3>();
+    /// top.sort_unstable();
+    /// assert_eq!(top, [7, 8, 9]);
+    /// ```
+    pub fn top_k<const K: usize>(&mut self) -> &mut [T]
+    where
+        T: Ord,
+    {
+        self.top_k_by::<K, _>(T::cmp)
+    }
+
+    /// Moves the `K` largest elements according to `compare` to the front of the vector, in
+    /// unspecified order, and returns them as a slice.
+    ///
+    /// See [`top_k`](Vec::top_k) for details.
+    pub fn top_k_by<const K: usize, F>(&mut self, mut compare: F) -> &mut [T]
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if K == 0 {
+            return &mut [];
+        }
+        if self.len() > K {
+            self.select_nth_unstable_by(K - 1, |a, b| compare(b, a));
+        }
+
+        let len = self.len().min(K);
+        &mut self[..len]
+    }
+
+    /// Moves the `K` elements with the largest keys to the front of the vector, in unspecified
+    /// order, and returns them as a slice.
+    ///
+    /// See [`top_k`](Vec::top_k) for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// struct Channel {
+    ///     number: u8,
+    ///     rssi: i8,
+    /// }
+    ///
+    /// let mut scan: Vec<Channel, 16> = [(1, -80), (6, -40), (11, -62), (3, -90)]
+    ///     .into_iter()
+    ///     .map(|(number, rssi)| Channel { number, rssi })
+    ///     .collect();
+    ///
+    /// let strongest = scan.top_k_by_key::<2, _, _>(|c| c.rssi);
+    /// strongest.sort_unstable_by_key(|c| c.number);
+    /// assert_eq!(strongest[0].number, 6);
+    /// assert_eq!(strongest[1].number, 11);
+    /// ```
+    pub fn top_k_by_key<const K: usize, Key, F>(&mut self, mut f: F) -> &mut [T]
+    where
+        F: FnMut(&T) -> Key,
+        Key: Ord,
+    {
+        self.top_k_by::<K, _>(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Sorts the vector by the key that `f` extracts from each element, calling `f` only once per
+    /// element.
+    ///
+    /// Like the standard library's `sort_by_cached_key`, this is for keys that are expensive to
+    /// compute, e.g. a CRC over each element, which [`sort_by_key`](slice::sort_by_key) would
+    /// compute *O*(n \* log(n)) times. The keys are cached in a buffer of `N` keys and indices on
+    /// the stack. The sort is stable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut calls = 0;
+    /// let mut records: Vec<&str, 8> = Vec::from_slice(&["ccc", "a", "bb", "dd"]).unwrap();
+    /// records.sort_cached_by_key(|record| {
+    ///     calls += 1;
+    ///     record.len()
+    /// });
+    /// assert_eq!(records, ["a", "bb", "dd", "ccc"]);
+    /// assert_eq!(calls, 4);
+    /// ```
+    pub fn sort_cached_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        let mut keys: Vec<(K, usize), N> = Vec::new();
+        for (index, elem) in self.iter().enumerate() {
+            // NOTE(unsafe) there are at most `N` elements
+            unsafe { keys.push_unchecked((f(elem), index)) };
+        }
+        // the indices make all pairs distinct, so this is stable
+        keys.sort_unstable();
+
+        // `keys[i].1` is the position the element that goes to `i` had before the sort; it may have
+        // been swapped away since, to a position that is found by following the chain
+        for i in 0..keys.len() {
+            let mut index = keys[i].1;
+            while index < i {
+                index = keys[index].1;
+            }
+            keys[i].1 = index;
+            self.swap(i, index);
+        }
+    }
+
+    /// Moves the elements for which `pred` returns `true` before those for which it returns
+    /// `false`, and returns the number of the former.
+    ///
+    /// The partition is stable: both groups keep their relative order. `pred` is called exactly
+    /// once per element, in the original order. No extra buffer is needed; elements are moved
+    /// with rotations, which takes *O*(n \* log(n)) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut commands: Vec<(u8, bool), 8> =
+    ///     Vec::from_slice(&[(0, false), (1, true), (2, false), (3, true)]).unwrap();
+    /// let urgent = commands.partition_in_place(|&(_, urgent)| urgent);
+    /// assert_eq!(urgent, 2);
+    /// assert_eq!(commands, [(1, true), (3, true), (0, false), (2, false)]);
+    /// ```
+    pub fn partition_in_place<F>(&mut self, mut pred: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        stable_partition(self, &mut pred)
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// In other words, remove all elements `e` for which `f(&e)` returns `false`.
+    /// This method operates in place, visiting each element exactly once in the
+    /// original order, and preserves the order of the retained elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut vec: Vec<_, 8> = Vec::from_slice(&[1, 2, 3, 4]).unwrap();
+    /// vec.retain(|&x| x % 2 == 0);
+    /// assert_eq!(vec, [2, 4]);
+    /// ```
+    ///
+    /// Because the elements are visited exactly once in the original order,
+    /// external state may be used to decide which elements to keep.
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// let mut vec: Vec<_, 8> = Vec::from_slice(&[1, 2, 3, 4, 5]).unwrap();
+    /// let keep = [false, true, true, false, true];
+    /// let mut iter = keep.iter();
+    /// vec.retain(|_| *iter.next().unwrap());
+    /// assert_eq!(vec, [2, 3, 5]);
+    /// ```
     pub fn retain<F>(&mut self, mut f: F)
     where
         F: FnMut(&T) -> bool,
@@ -738,37 +1535,7 @@ impl<T, const N: usize> Vec<T, N> {
         // Hole: Moved or dropped element slot.
         // Unchecked: Unchecked valid elements.
         //
-        // This drop guard will be invoked when predicate or `drop` of element panicked.
-        // It shifts unchecked elements to cover holes and `set_len` to the correct length.
-        // In cases when predicate and `drop` never panick, it will be optimized out.
-        struct BackshiftOnDrop<'a, T, const N: usize> {
-            v: &'a mut Vec<T, N>,
-            processed_len: usize,
-            deleted_cnt: usize,
-            original_len: usize,
-        }
-
-        impl<T, const N: usize> Drop for BackshiftOnDrop<'_, T, N> {
-            fn drop(&mut self) {
-                if self.deleted_cnt > 0 {
-                    // SAFETY: Trailing unchecked items must be valid since we never touch them.
-                    unsafe {
-                        ptr::copy(
-                            self.v.as_ptr().add(self.processed_len),
-                            self.v
-                                .as_mut_ptr()
-                                .add(self.processed_len - self.deleted_cnt),
-                            self.original_len - self.processed_len,
-                        );
-                    }
-                }
-                // SAFETY: After filling holes, all items are in contiguous memory.
-                unsafe {
-                    self.v.set_len(self.original_len - self.deleted_cnt);
-                }
-            }
-        }
-
+        // The drop guard is invoked when predicate or `drop` of element panicked.
         let mut g = BackshiftOnDrop {
             v: self,
             processed_len: 0,
@@ -821,6 +1588,249 @@ impl<T, const N: usize> Vec<T, N> {
         // All item are processed. This can be optimized to `set_len` by LLVM.
         drop(g);
     }
+
+    /// Retains only the elements specified by the predicate, moving the others to the back of
+    /// `other` instead of dropping them.
+    ///
+    /// Like [`retain`](Vec::retain), this visits each element exactly once in the original order,
+    /// and preserves the order of the retained elements, as well as of the moved ones.
+    ///
+    /// Returns `Err(())` if `other` ran out of room. The rejected elements that didn't fit are
+    /// retained instead, so no element is lost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::Vec;
+    ///
+    /// // (key, hits), demote the entries with few hits
+    /// let mut hot: Vec<(u8, u32), 8> =
+    ///     Vec::from_slice(&[(1, 90), (2, 3), (3, 50), (4, 1)]).unwrap();
+    /// let mut cold: Vec<(u8, u32), 8> = Vec::new();
+    /// hot.retain_into(|&(_, hits)| hits >= 10, &mut cold).unwrap();
+    /// assert_eq!(hot, [(1, 90), (3, 50)]);
+    /// assert_eq!(cold, [(2, 3), (4, 1)]);
+    ///
+    /// // `cold` only has room for one more
+    /// let mut cold: Vec<(u8, u32), 3> = Vec::from_slice(&[(5, 0), (6, 0)]).unwrap();
+    /// assert!(hot.retain_into(|_| false, &mut cold).is_err());
+    /// assert_eq!(hot, [(3, 50)]);
+    /// assert_eq!(cold, [(5, 0), (6, 0), (1, 90)]);
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn retain_into<F, const M: usize>(
+        &mut self,
+        mut f: F,
+        other: &mut Vec<T, M>,
+    ) -> Result<(), ()>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        // the same scheme as in `retain_mut`, except that the holes are left by moving the
+        // rejected elements to `other`
+        let original_len = self.len();
+        unsafe { self.set_len(0) };
+
+        let mut g = BackshiftOnDrop {
+            v: self,
+            processed_len: 0,
+            deleted_cnt: 0,
+            original_len,
+        };
+
+        let mut all_fit = true;
+        while g.processed_len != original_len {
+            let p = g.v.as_mut_ptr();
+            // SAFETY: Unchecked element must be valid.
+            let cur = unsafe { &mut *p.add(g.processed_len) };
+            if !f(cur) {
+                if other.is_full() {
+                    all_fit = false;
+                } else {
+                    g.processed_len += 1;
+                    g.deleted_cnt += 1;
+                    // SAFETY: `other` has room, and we never touch this element again.
+                    unsafe { other.push_unchecked(ptr::read(cur)) };
+                    continue;
+                }
+            }
+            if g.deleted_cnt > 0 {
+                // SAFETY: `deleted_cnt` > 0, so the hole slot doesn't overlap the current element.
+                unsafe {
+                    let hole_slot = p.add(g.processed_len - g.deleted_cnt);
+                    ptr::copy_nonoverlapping(cur, hole_slot, 1);
+                }
+            }
+            g.processed_len += 1;
+        }
+
+        drop(g);
+        if all_fit {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Does part of the work of [`retain_mut`](Vec::retain_mut), so that a long vector can be
+    /// filtered a bit at a time, e.g. within a fixed time slice.
+    ///
+    /// Each call visits or drops at most `budget` elements and records where it stopped in
+    /// `progress`. Call this repeatedly with the same `progress` until it returns `true`; the
+    /// retained elements then keep their order, like with `retain_mut`.
+    ///
+    /// The vector stays usable between calls. The elements that were already rejected stay in it,
+    /// behind the retained ones, until the last calls drop them. If the vector is modified
+    /// between calls, the remaining calls still work on its current contents, but which elements
+    /// they visit is unspecified; start over with a new [`RetainProgress`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{RetainProgress, Vec};
+    ///
+    /// let mut vec: Vec<u32, 64> = (0..64).collect();
+    /// let mut progress = RetainProgress::new();
+    /// let mut calls = 1;
+    /// while !vec.retain_incremental(&mut progress, 16, |x| *x % 3 == 0) {
+    ///     // yield to more urgent work
+    ///     calls += 1;
+    /// }
+    /// assert_eq!(vec.len(), 22);
+    /// assert!(vec.iter().all(|x| x % 3 == 0));
+    /// assert_eq!(calls, 7);
+    /// ```
+    pub fn retain_incremental<F>(
+        &mut self,
+        progress: &mut RetainProgress,
+        mut budget: usize,
+        mut f: F,
+    ) -> bool
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        // [retained][rejected][unvisited]
+        //           ^ write   ^ read
+        let len = self.len();
+        progress.read = progress.read.min(len);
+        progress.write = progress.write.min(progress.read);
+
+        while progress.read < self.len() {
+            if budget == 0 {
+                return false;
+            }
+            budget -= 1;
+
+            if f(&mut self[progress.read]) {
+                self.swap(progress.write, progress.read);
+                progress.write += 1;
+            }
+            progress.read += 1;
+        }
+
+        while self.len() > progress.write {
+            if budget == 0 {
+                return false;
+            }
+            budget -= 1;
+            self.pop();
+        }
+
+        *progress = RetainProgress::new();
+        true
+    }
+}
+
+// The drop guard of `retain_mut` and `retain_into`.
+// It shifts unchecked elements to cover holes and `set_len` to the correct length.
+// In cases when predicate and `drop` never panick, it will be optimized out.
+struct BackshiftOnDrop<'a, T, const N: usize> {
+    v: &'a mut Vec<T, N>,
+    processed_len: usize,
+    deleted_cnt: usize,
+    original_len: usize,
+}
+
+impl<T, const N: usize> Drop for BackshiftOnDrop<'_, T, N> {
+    fn drop(&mut self) {
+        if self.deleted_cnt > 0 {
+            // SAFETY: Trailing unchecked items must be valid since we never touch them.
+            unsafe {
+                ptr::copy(
+                    self.v.as_ptr().add(self.processed_len),
+                    self.v
+                        .as_mut_ptr()
+                        .add(self.processed_len - self.deleted_cnt),
+                    self.original_len - self.processed_len,
+                );
+            }
+        }
+        // SAFETY: After filling holes, all items are in contiguous memory.
+        unsafe {
+            self.v.set_len(self.original_len - self.deleted_cnt);
+        }
+    }
+}
+
+impl<T, const N: usize> Vec<MaybeUninit<T>, N> {
+    /// Converts a vector of possibly uninitialized elements into a vector of the elements.
+    ///
+    /// # Safety
+    ///
+    /// All elements of the vector must be initialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::mem::MaybeUninit;
+    /// use heapless::Vec;
+    ///
+    /// let mut vec: Vec<MaybeUninit<u32>, 4> = Vec::new();
+    /// vec.resize(4, MaybeUninit::uninit()).unwrap();
+    /// for (i, x) in vec.iter_mut().enumerate() {
+    ///     x.write(i as u32 * 2);
+    /// }
+    ///
+    /// // SAFETY: all elements were written above
+    /// let vec: Vec<u32, 4> = unsafe { vec.assume_init() };
+    /// assert_eq!(vec, [0, 2, 4, 6]);
+    /// ```
+    pub unsafe fn assume_init(self) -> Vec<T, N> {
+        let this = ManuallyDrop::new(self);
+        let mut vec = Vec::new();
+        ptr::copy_nonoverlapping(this.as_ptr() as *const T, vec.as_mut_ptr(), this.len);
+        vec.set_len(this.len);
+        vec
+    }
+}
+
+impl<S, const N: usize> Vec<S, N>
+where
+    S: AsRef<str>,
+{
+    /// Joins the strings with `separator` between each of them into a new `String<M>`.
+    ///
+    /// Returns `Err` if the result doesn't fit. See also [`String::from_joined`] for joining
+    /// arbitrary iterators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use heapless::{String, Vec};
+    ///
+    /// let mut cells: Vec<String<8>, 4> = Vec::new();
+    /// cells.push(String::try_from("id").unwrap()).unwrap();
+    /// cells.push(String::try_from("temp").unwrap()).unwrap();
+    ///
+    /// let line = cells.join_into::<16>(",")?;
+    /// assert_eq!(line, "id,temp");
+    /// assert!(cells.join_into::<6>(",").is_err());
+    /// # Ok::<(), ()>(())
+    /// ```
+    #[allow(clippy::result_unit_err)]
+    pub fn join_into<const M: usize>(&self, separator: &str) -> Result<String<M>, ()> {
+        String::from_joined(self, separator)
+    }
 }
 
 // Trait implementations
@@ -887,6 +1897,8 @@ where
     }
 }
 
+/// Hashes like the slice `[T]`, so vectors with equal contents hash identically whatever their
+/// capacity.
 impl<T, const N: usize> hash::Hash for Vec<T, N>
 where
     T: core::hash::Hash,
@@ -914,6 +1926,19 @@ impl<'a, T, const N: usize> IntoIterator for &'a mut Vec<T, N> {
     }
 }
 
+/// Reports that an `Extend` or `FromIterator` impl, which can't return an error, ran out of
+/// capacity.
+///
+/// This panics, unless the `disable-panic-paths` feature is enabled: then it only panics in debug
+/// builds and the caller drops the remaining items, so that release builds have no panic path.
+#[track_caller]
+pub(crate) fn capacity_overflow(msg: &'static str) {
+    #[cfg(not(feature = "disable-panic-paths"))]
+    panic!("{}", msg);
+    #[cfg(feature = "disable-panic-paths")]
+    debug_assert!(false, "{}", msg);
+}
+
 impl<T, const N: usize> FromIterator<T> for Vec<T, N> {
     fn from_iter<I>(iter: I) -> Self
     where
@@ -921,12 +1946,99 @@ impl<T, const N: usize> FromIterator<T> for Vec<T, N> {
     {
         let mut vec = Vec::new();
         for i in iter {
-            vec.push(i).ok().expect("Vec::from_iter overflow");
+            if vec.push(i).is_err() {
+                capacity_overflow("Vec::from_iter overflow");
+                break;
+            }
         }
         vec
     }
 }
 
+/// An iterator that moves chunks of `K` elements out of the front of a [`Vec`].
+///
+/// This struct is created by calling the `drain_chunks` method on [`Vec`].
+pub struct DrainChunks<'a, T, const N: usize, const K: usize> {
+    vec: &'a mut Vec<T, N>,
+    /// Index of the first element that hasn't been yielded yet.
+    next: usize,
+    /// Length of the vector before draining.
+    len: usize,
+}
+
+impl<T, const N: usize, const K: usize> Iterator for DrainChunks<'_, T, N, K> {
+    type Item = [T; K];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.len - self.next < K {
+            return None;
+        }
+
+        let chunk = unsafe { (self.vec.as_ptr().add(self.next) as *const [T; K]).read() };
+        self.next += K;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.len - self.next) / K;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize, const K: usize> ExactSizeIterator for DrainChunks<'_, T, N, K> {}
+impl<T, const N: usize, const K: usize> FusedIterator for DrainChunks<'_, T, N, K> {}
+
+impl<T, const N: usize, const K: usize> Drop for DrainChunks<'_, T, N, K> {
+    fn drop(&mut self) {
+        let remaining = self.len - self.next;
+        unsafe {
+            let base = self.vec.as_mut_ptr();
+            ptr::copy(base.add(self.next), base, remaining);
+            self.vec.set_len(remaining);
+        }
+    }
+}
+
+/// An iterator that moves runs of elements out of a [`Vec`].
+///
+/// This struct is created by calling the `into_chunk_by` method on [`Vec`].
+pub struct IntoChunkBy<T, const N: usize, F> {
+    iter: IntoIter<T, N>,
+    /// The first element of the next run, which was taken to end the previous one.
+    pending: Option<T>,
+    pred: F,
+}
+
+impl<T, const N: usize, F> Iterator for IntoChunkBy<T, N, F>
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    type Item = Vec<T, N>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.pending.take().or_else(|| self.iter.next())?;
+        let mut run = Vec::new();
+        // SAFETY: the runs hold at most the `N` elements of the original vector
+        unsafe { run.push_unchecked(first) };
+        for item in &mut self.iter {
+            if (self.pred)(run.last().unwrap(), &item) {
+                unsafe { run.push_unchecked(item) };
+            } else {
+                self.pending = Some(item);
+                break;
+            }
+        }
+        Some(run)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.iter.len() + usize::from(self.pending.is_some());
+        (usize::from(len > 0), Some(len))
+    }
+}
+
+impl<T, const N: usize, F> FusedIterator for IntoChunkBy<T, N, F> where F: FnMut(&T, &T) -> bool {}
+
 /// An iterator that moves out of an [`Vec`][`Vec`].
 ///
 /// This struct is created by calling the `into_iter` method on [`Vec`][`Vec`].
@@ -946,8 +2058,35 @@ impl<T, const N: usize> Iterator for IntoIter<T, N> {
             None
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.vec.len() - self.next;
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.next < self.vec.len() {
+            // NOTE shrinking the vector keeps the element from being dropped by `IntoIter::drop`
+            self.vec.len -= 1;
+            Some(unsafe {
+                self.vec
+                    .buffer
+                    .get_unchecked_mut(self.vec.len)
+                    .as_ptr()
+                    .read()
+            })
+        } else {
+            None
+        }
+    }
 }
 
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> FusedIterator for IntoIter<T, N> {}
+
 impl<T, const N: usize> Clone for IntoIter<T, N>
 where
     T: Clone,
@@ -1102,79 +2241,357 @@ where
     }
 }
 
-// Implements Eq if underlying data is Eq
-impl<T, const N: usize> Eq for Vec<T, N> where T: Eq {}
+// Implements Eq if underlying data is Eq
+impl<T, const N: usize> Eq for Vec<T, N> where T: Eq {}
+
+impl<T, const N1: usize, const N2: usize> PartialOrd<Vec<T, N2>> for Vec<T, N1>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Vec<T, N2>) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T, const N: usize> Ord for Vec<T, N>
+where
+    T: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<T, const N: usize> ops::Deref for Vec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> ops::DerefMut for Vec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, const N: usize> AsRef<Vec<T, N>> for Vec<T, N> {
+    #[inline]
+    fn as_ref(&self) -> &Self {
+        self
+    }
+}
+
+impl<T, const N: usize> AsMut<Vec<T, N>> for Vec<T, N> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut Self {
+        self
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for Vec<T, N> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> AsMut<[T]> for Vec<T, N> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
+
+impl<T, const N: usize> Clone for Vec<T, N>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        self.clone()
+    }
+}
+
+/// Where a [`Vec::retain_incremental`] operation stopped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RetainProgress {
+    /// The number of retained elements.
+    write: usize,
+    /// The number of visited elements.
+    read: usize,
+}
+
+impl RetainProgress {
+    /// Returns the progress of an operation that hasn't started yet.
+    pub const fn new() -> Self {
+        Self { write: 0, read: 0 }
+    }
+}
+
+/// A [`Vec`] that can no longer be modified.
+///
+/// This `struct` is created by the [`freeze`](Vec::freeze) method on [`Vec`]. It dereferences to
+/// a slice, and has no methods that modify it.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FrozenVec<T, const N: usize> {
+    vec: Vec<T, N>,
+}
+
+impl<T, const N: usize> FrozenVec<T, N> {
+    /// Returns the elements as a slice.
+    pub const fn as_slice(&self) -> &[T] {
+        self.vec.as_slice()
+    }
+}
+
+impl<T, const N: usize> ops::Deref for FrozenVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.vec.as_slice()
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for FrozenVec<T, N> {
+    fn as_ref(&self) -> &[T] {
+        self.vec.as_slice()
+    }
+}
 
-impl<T, const N1: usize, const N2: usize> PartialOrd<Vec<T, N2>> for Vec<T, N1>
+impl<T, const N: usize> fmt::Debug for FrozenVec<T, N>
 where
-    T: PartialOrd,
+    T: fmt::Debug,
 {
-    fn partial_cmp(&self, other: &Vec<T, N2>) -> Option<Ordering> {
-        PartialOrd::partial_cmp(&**self, &**other)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <[T] as fmt::Debug>::fmt(self, f)
     }
 }
 
-impl<T, const N: usize> Ord for Vec<T, N>
+impl<T, const N: usize> hash::Hash for FrozenVec<T, N>
 where
-    T: Ord,
+    T: hash::Hash,
 {
-    #[inline]
-    fn cmp(&self, other: &Self) -> Ordering {
-        Ord::cmp(&**self, &**other)
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.vec.hash(state)
     }
 }
 
-impl<T, const N: usize> ops::Deref for Vec<T, N> {
-    type Target = [T];
+impl<'a, T, const N: usize> IntoIterator for &'a FrozenVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
 
-    fn deref(&self) -> &[T] {
-        self.as_slice()
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.iter()
     }
 }
 
-impl<T, const N: usize> ops::DerefMut for Vec<T, N> {
-    fn deref_mut(&mut self) -> &mut [T] {
-        self.as_mut_slice()
-    }
+/// A [`Vec`] with a restricted capacity.
+///
+/// This `struct` is created by the [`shrink_capacity_view`](Vec::shrink_capacity_view) method on
+/// [`Vec`]. See its documentation for more.
+pub struct CapacityView<'a, T> {
+    buffer: &'a mut [MaybeUninit<T>],
+    len: &'a mut usize,
+    /// The vector's injected failures, which also apply to the view.
+    #[cfg(feature = "test-util")]
+    fail_next: &'a mut crate::test_util::FailNext,
+    #[cfg(capacity_stats)]
+    monitor: crate::capacity_stats::Monitor,
 }
 
-impl<T, const N: usize> AsRef<Vec<T, N>> for Vec<T, N> {
-    #[inline]
-    fn as_ref(&self) -> &Self {
-        self
+impl<T> CapacityView<'_, T> {
+    /// Returns a view of the same vector with the same capacity, for a shorter lifetime.
+    pub(crate) fn reborrow(&mut self) -> CapacityView<'_, T> {
+        CapacityView {
+            buffer: self.buffer,
+            len: self.len,
+            #[cfg(feature = "test-util")]
+            fail_next: self.fail_next,
+            #[cfg(capacity_stats)]
+            monitor: self.monitor,
+        }
+    }
+
+    /// Returns the maximum number of elements the view can hold.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns true if the view is full
+    pub fn is_full(&self) -> bool {
+        *self.len == self.capacity()
+    }
+
+    /// Appends an `item` to the back of the collection
+    ///
+    /// Returns back the `item` if the view is full
+    pub fn push(&mut self, item: T) -> Result<(), T> {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(item);
+        }
+
+        match self.buffer.get_mut(*self.len) {
+            Some(slot) => {
+                *slot = MaybeUninit::new(item);
+                *self.len += 1;
+                #[cfg(capacity_stats)]
+                self.monitor.record(*self.len);
+                Ok(())
+            }
+            None => Err(item),
+        }
+    }
+
+    /// Removes the last element and returns it, or `None` if it's empty
+    pub fn pop(&mut self) -> Option<T> {
+        if *self.len == 0 {
+            return None;
+        }
+        *self.len -= 1;
+        // NOTE(unsafe) the element was initialized, and is not part of the vector anymore
+        Some(unsafe { self.buffer[*self.len].as_ptr().read() })
+    }
+
+    /// Clones and appends all elements in a slice to the view.
+    ///
+    /// Returns `Err` without modifying anything if the elements don't fit.
+    #[allow(clippy::result_unit_err)]
+    pub fn extend_from_slice(&mut self, other: &[T]) -> Result<(), ()>
+    where
+        T: Clone,
+    {
+        #[cfg(feature = "test-util")]
+        if self.fail_next.take() {
+            return Err(());
+        }
+
+        if other.len() > self.capacity() - *self.len {
+            return Err(());
+        }
+        for elem in other {
+            self.buffer[*self.len] = MaybeUninit::new(elem.clone());
+            *self.len += 1;
+        }
+        #[cfg(capacity_stats)]
+        self.monitor.record(*self.len);
+        Ok(())
+    }
+
+    /// Shortens the view to `len` elements, dropping the rest.
+    pub fn truncate(&mut self, len: usize) {
+        while *self.len > len {
+            drop(self.pop());
+        }
+    }
+
+    /// Clears the view, removing all values.
+    pub fn clear(&mut self) {
+        self.truncate(0);
     }
 }
 
-impl<T, const N: usize> AsMut<Vec<T, N>> for Vec<T, N> {
-    #[inline]
-    fn as_mut(&mut self) -> &mut Self {
-        self
+impl<T> ops::Deref for CapacityView<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        // NOTE(unsafe) the first `len` slots are initialized
+        unsafe { slice::from_raw_parts(self.buffer.as_ptr() as *const T, *self.len) }
     }
 }
 
-impl<T, const N: usize> AsRef<[T]> for Vec<T, N> {
-    #[inline]
-    fn as_ref(&self) -> &[T] {
-        self
+impl<T> ops::DerefMut for CapacityView<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        // NOTE(unsafe) the first `len` slots are initialized
+        unsafe { slice::from_raw_parts_mut(self.buffer.as_mut_ptr() as *mut T, *self.len) }
     }
 }
 
-impl<T, const N: usize> AsMut<[T]> for Vec<T, N> {
-    #[inline]
-    fn as_mut(&mut self) -> &mut [T] {
-        self
+impl<T> fmt::Debug for CapacityView<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <[T] as fmt::Debug>::fmt(self, f)
     }
 }
 
-impl<T, const N: usize> Clone for Vec<T, N>
+/// Stably partitions `slice` by `pred`, returning the number of elements for which it's `true`.
+fn stable_partition<T, F>(slice: &mut [T], pred: &mut F) -> usize
 where
-    T: Clone,
+    F: FnMut(&T) -> bool,
 {
-    fn clone(&self) -> Self {
-        self.clone()
+    match slice.len() {
+        0 => 0,
+        1 => usize::from(pred(&slice[0])),
+        len => {
+            // partition both halves, then swap the `false` run of the first with the `true` run
+            // of the second
+            let mid = len / 2;
+            let left = stable_partition(&mut slice[..mid], pred);
+            let right = stable_partition(&mut slice[mid..], pred);
+            slice[left..mid + right].rotate_left(mid - left);
+            left + right
+        }
     }
 }
 
+/// Resolves the bounds of `range` against a sequence of length `len`, or returns `None` if they
+/// overflow. The result still needs to be checked against `len`.
+pub(crate) fn checked_slice_range<R>(range: R, len: usize) -> Option<Range<usize>>
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end.checked_add(1)?,
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+    Some(start..end)
+}
+
+/// Resolves `range` against a sequence of length `len`, panicking if it's out of bounds.
+pub(crate) fn slice_range<R>(range: R, len: usize) -> Range<usize>
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(
+        start <= end,
+        "slice index starts at {} but ends at {}",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "range end index {} out of range for slice of length {}",
+        end,
+        len
+    );
+    start..end
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Vec;
@@ -1353,12 +2770,27 @@ mod tests {
     }
 
     #[test]
+    #[cfg(any(debug_assertions, not(feature = "disable-panic-paths")))]
     #[should_panic]
     fn collect_from_iter_overfull() {
         let slice = &[1, 2, 3];
         let _vec = slice.iter().cloned().collect::<Vec<_, 2>>();
     }
 
+    #[test]
+    #[cfg(all(not(debug_assertions), feature = "disable-panic-paths"))]
+    fn overflow_stops_at_capacity() {
+        let vec: Vec<_, 2> = [1, 2, 3].into_iter().collect();
+        assert_eq!(vec, [1, 2]);
+
+        let mut vec: Vec<_, 3> = Vec::new();
+        vec.extend([1, 2, 3, 4]);
+        assert_eq!(vec, [1, 2, 3]);
+
+        let s: crate::String<4> = ["ab", "cd", "ef"].into_iter().collect();
+        assert_eq!(s, "abcd");
+    }
+
     #[test]
     fn iter_move() {
         let mut v: Vec<i32, 4> = Vec::new();
@@ -1376,6 +2808,37 @@ mod tests {
         assert_eq!(items.next(), None);
     }
 
+    #[test]
+    fn iter_move_double_ended() {
+        let v: Vec<i32, 4> = Vec::from_slice(&[0, 1, 2, 3]).unwrap();
+        let mut items = v.clone().into_iter();
+        assert_eq!(items.len(), 4);
+        assert_eq!(items.next_back(), Some(3));
+        assert_eq!(items.next(), Some(0));
+        assert_eq!(items.len(), 2);
+        assert_eq!(items.clone().collect::<Vec<_, 4>>(), [1, 2]);
+        assert_eq!(items.next_back(), Some(2));
+        assert_eq!(items.next_back(), Some(1));
+        assert_eq!(items.next_back(), None);
+        assert_eq!(items.next(), None);
+
+        assert_eq!(v.clone().into_iter().rposition(|x| x < 2), Some(1));
+        assert!(v.into_iter().rev().eq([3, 2, 1, 0]));
+
+        droppable!();
+        {
+            let mut vec: Vec<Droppable, 3> = Vec::new();
+            for _ in 0..3 {
+                vec.push(Droppable::new()).ok().unwrap();
+            }
+            let mut items = vec.into_iter();
+            let _ = items.next_back();
+            let _ = items.next();
+            assert_eq!(Droppable::count(), 1);
+        }
+        assert_eq!(Droppable::count(), 0);
+    }
+
     #[test]
     fn iter_move_drop() {
         droppable!();
@@ -1581,4 +3044,418 @@ mod tests {
         // Validate full
         assert!(v.is_full());
     }
+
+    #[test]
+    fn freeze() {
+        let mut v: Vec<u8, 4> = Vec::new();
+        v.extend_from_slice(&[3, 1, 2]).unwrap();
+        let frozen = v.freeze();
+        let copy = frozen.clone();
+        assert_eq!(frozen, copy);
+        assert_eq!(frozen.as_slice(), [3, 1, 2]);
+        assert_eq!(frozen.iter().max(), Some(&3));
+        assert!((&frozen).into_iter().eq(&[3, 1, 2]));
+    }
+
+    #[test]
+    fn into_chunk_by() {
+        let v: Vec<u8, 6> = Vec::from_slice(&[1, 2, 3, 7, 8, 10]).unwrap();
+        let mut runs = v.into_chunk_by(|a, b| a + 1 == *b);
+        assert_eq!(runs.size_hint(), (1, Some(6)));
+        assert_eq!(runs.next().unwrap(), [1, 2, 3]);
+        assert_eq!(runs.next().unwrap(), [7, 8]);
+        assert_eq!(runs.next().unwrap(), [10]);
+        assert_eq!(runs.size_hint(), (0, Some(0)));
+        assert_eq!(runs.next(), None);
+
+        droppable!();
+        {
+            let mut vec: Vec<Droppable, 4> = Vec::new();
+            for _ in 0..4 {
+                vec.push(Droppable::new()).ok().unwrap();
+            }
+            let mut runs = vec.into_chunk_by(|_, _| false);
+            let first = runs.next().unwrap();
+            assert_eq!(first.len(), 1);
+            // the pending element and the rest are dropped with the iterator
+        }
+        assert_eq!(Droppable::count(), 0);
+    }
+
+    #[test]
+    fn drain_chunks() {
+        droppable!();
+
+        let mut v: Vec<Droppable, 8> = Vec::new();
+        for _ in 0..8 {
+            v.push(Droppable::new()).ok().unwrap();
+        }
+
+        // Unconsumed chunks stay in the vector
+        let mut chunks = v.drain_chunks::<3>();
+        assert_eq!(chunks.len(), 2);
+        let chunk = chunks.next().unwrap();
+        assert_eq!(chunks.len(), 1);
+        core::mem::drop(chunks);
+        assert_eq!(v.len(), 5);
+        assert_eq!(Droppable::count(), 8);
+
+        core::mem::drop(chunk);
+        assert_eq!(Droppable::count(), 5);
+
+        assert_eq!(v.drain_chunks::<2>().count(), 2);
+        assert_eq!(v.len(), 1);
+        assert_eq!(Droppable::count(), 1);
+
+        v.clear();
+        assert_eq!(Droppable::count(), 0);
+
+        let mut v: Vec<u8, 8> = Vec::from_slice(&[1, 2, 3, 4]).unwrap();
+        assert!(v.drain_chunks::<2>().eq([[1, 2], [3, 4]]));
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn move_within() {
+        let mut v: Vec<u8, 8> = Vec::from_slice(&[0, 1, 2, 3, 4, 5]).unwrap();
+
+        v.move_within(1..3, 1);
+        assert_eq!(v, [0, 1, 2, 3, 4, 5]);
+        v.move_within(1..=2, 4);
+        assert_eq!(v, [0, 3, 4, 5, 1, 2]);
+        v.move_within(3.., 0);
+        assert_eq!(v, [5, 1, 2, 0, 3, 4]);
+        v.move_within(2..2, 6);
+        assert_eq!(v, [5, 1, 2, 0, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic(expected = "destination index")]
+    fn move_within_out_of_bounds() {
+        let mut v: Vec<u8, 8> = Vec::from_slice(&[0, 1, 2, 3]).unwrap();
+        v.move_within(1..3, 3);
+    }
+
+    #[test]
+    fn top_k() {
+        let mut v: Vec<u8, 8> = Vec::from_slice(&[5, 1, 5, 0, 7, 2, 6]).unwrap();
+        let top = v.top_k::<4>();
+        top.sort_unstable();
+        assert_eq!(top, [5, 5, 6, 7]);
+        v[4..].sort_unstable();
+        assert_eq!(v[4..], [0, 1, 2]);
+
+        assert!(v.top_k::<0>().is_empty());
+        assert_eq!(v.top_k::<8>().len(), 7);
+
+        let smallest = v.top_k_by::<2, _>(|a, b| b.cmp(a));
+        smallest.sort_unstable();
+        assert_eq!(smallest, [0, 1]);
+    }
+
+    #[test]
+    fn sort_cached_by_key() {
+        let mut v: Vec<(u8, u8), 16> = Vec::new();
+        for i in 0..16 {
+            v.push(((i * 7) % 5, i)).unwrap();
+        }
+        let mut expected = v.clone();
+        expected.sort_by_key(|&(key, _)| key);
+
+        let mut calls = 0;
+        v.sort_cached_by_key(|&(key, _)| {
+            calls += 1;
+            key
+        });
+        assert_eq!(v, expected);
+        assert_eq!(calls, 16);
+
+        let mut v: Vec<u8, 4> = Vec::from_slice(&[3, 2, 1, 0]).unwrap();
+        v.sort_cached_by_key(|&x| x);
+        assert_eq!(v, [0, 1, 2, 3]);
+        let mut v: Vec<u8, 0> = Vec::new();
+        v.sort_cached_by_key(|&x| x);
+    }
+
+    #[test]
+    fn binary_search_first_last() {
+        let v: Vec<u8, 8> = Vec::from_slice(&[1, 1, 3, 3, 3, 4, 6, 6]).unwrap();
+        assert_eq!(v.binary_search_first_of(&1), Ok(0));
+        assert_eq!(v.binary_search_last_of(&1), Ok(1));
+        assert_eq!(v.binary_search_first_of(&3), Ok(2));
+        assert_eq!(v.binary_search_last_of(&3), Ok(4));
+        assert_eq!(v.binary_search_first_of(&4), Ok(5));
+        assert_eq!(v.binary_search_last_of(&4), Ok(5));
+        assert_eq!(v.binary_search_last_of(&6), Ok(7));
+        for (x, index) in [(0, 0), (2, 2), (5, 6), (7, 8)] {
+            assert_eq!(v.binary_search_first_of(&x), Err(index));
+            assert_eq!(v.binary_search_last_of(&x), Err(index));
+        }
+
+        let v: Vec<u8, 0> = Vec::new();
+        assert_eq!(v.binary_search_first_of(&0), Err(0));
+        assert_eq!(v.binary_search_last_of(&0), Err(0));
+    }
+
+    #[test]
+    fn partition_in_place() {
+        let mut v: Vec<u8, 16> = (0..13).collect();
+        let mut visited: Vec<u8, 16> = Vec::new();
+        let split = v.partition_in_place(|&x| {
+            visited.push(x).unwrap();
+            x % 3 == 0
+        });
+        assert_eq!(split, 5);
+        assert_eq!(v, [0, 3, 6, 9, 12, 1, 2, 4, 5, 7, 8, 10, 11]);
+        assert_eq!(visited, (0..13).collect::<Vec<u8, 16>>());
+
+        assert_eq!(v.partition_in_place(|_| true), 13);
+        assert_eq!(v.partition_in_place(|_| false), 0);
+        assert_eq!(v[0], 0);
+
+        let mut v: Vec<u8, 1> = Vec::new();
+        assert_eq!(v.partition_in_place(|_| true), 0);
+    }
+
+    #[test]
+    fn clone_into_capacity() {
+        let v: Vec<u8, 4> = Vec::from_slice(&[1, 2]).unwrap();
+        assert!(v.clone_into_capacity::<1>().is_err());
+        assert_eq!(v.clone_into_capacity::<2>().unwrap(), [1, 2]);
+        assert_eq!(v.clone_into_capacity::<8>().unwrap(), [1, 2]);
+
+        let empty: Vec<u8, 4> = Vec::new();
+        assert!(empty.clone_into_capacity::<0>().unwrap().is_empty());
+    }
+
+    #[test]
+    fn raw_parts() {
+        droppable!();
+
+        let mut v: Vec<Droppable, 4> = Vec::new();
+        v.push(Droppable::new()).ok().unwrap();
+        v.push(Droppable::new()).ok().unwrap();
+        let (buffer, len) = v.into_raw_parts();
+        assert_eq!(len, 2);
+        assert_eq!(Droppable::count(), 2);
+
+        let v = unsafe { Vec::<Droppable, 4>::from_raw_parts(buffer, len) };
+        assert_eq!(v.len(), 2);
+        core::mem::drop(v);
+        assert_eq!(Droppable::count(), 0);
+    }
+
+    #[test]
+    fn layout() {
+        use core::mem::{align_of, offset_of, size_of};
+
+        assert_eq!(offset_of!(Vec<u8, 3>, len), 0);
+        assert_eq!(offset_of!(Vec<u8, 3>, buffer), size_of::<usize>());
+        assert_eq!(offset_of!(Vec<u32, 3>, buffer), size_of::<usize>());
+        assert_eq!(
+            offset_of!(Vec<u128, 3>, buffer),
+            size_of::<usize>().max(align_of::<u128>())
+        );
+        // the offsets above hold with every feature; the testing features add fields after the
+        // buffer
+        #[cfg(not(any(feature = "test-util", capacity_stats)))]
+        assert_eq!(size_of::<Vec<u8, 3>>(), 2 * size_of::<usize>());
+    }
+
+    #[test]
+    fn get_range() {
+        let mut v: Vec<u8, 8> = Vec::from_slice(&[0, 1, 2, 3]).unwrap();
+        assert_eq!(v.get_range(..), Some(&[0, 1, 2, 3][..]));
+        assert_eq!(v.get_range(4..), Some(&[][..]));
+        assert_eq!(v.get_range(5..), None);
+        assert_eq!(v.get_range(..=usize::MAX), None);
+        assert_eq!(
+            v.get_range((
+                core::ops::Bound::Excluded(usize::MAX),
+                core::ops::Bound::Unbounded
+            )),
+            None
+        );
+        let (start, end) = (2, 1);
+        assert_eq!(v.get_range(start..end), None);
+
+        v.get_range_mut(1..=2).unwrap().reverse();
+        assert_eq!(v, [0, 2, 1, 3]);
+        assert_eq!(v.get_range_mut(3..5), None);
+    }
+
+    #[test]
+    fn from_array() {
+        static V: Vec<u8, 4> = Vec::from_array([1, 2, 3]);
+        const _: () = assert!(V.len() == 3 && !V.is_empty() && !V.is_full());
+        assert_eq!(V, [1, 2, 3]);
+
+        droppable!();
+        let v: Vec<Droppable, 3> = Vec::from_array([Droppable::new(), Droppable::new()]);
+        assert_eq!(Droppable::count(), 2);
+        core::mem::drop(v);
+        assert_eq!(Droppable::count(), 0);
+    }
+
+    #[test]
+    fn shrink_capacity_view() {
+        droppable!();
+
+        let mut v: Vec<Droppable, 8> = Vec::new();
+        v.push(Droppable::new()).ok().unwrap();
+        {
+            let mut view = v.shrink_capacity_view(3);
+            assert_eq!(view.capacity(), 3);
+            view.push(Droppable::new()).ok().unwrap();
+            view.push(Droppable::new()).ok().unwrap();
+            assert!(view.is_full());
+            assert!(view.push(Droppable::new()).is_err());
+            assert_eq!(view.len(), 3);
+            view.truncate(1);
+        }
+        assert_eq!(v.len(), 1);
+        assert_eq!(Droppable::count(), 1);
+
+        // never less than the length, never more than `N`
+        v.push(Droppable::new()).ok().unwrap();
+        assert_eq!(v.shrink_capacity_view(0).capacity(), 2);
+        assert_eq!(v.shrink_capacity_view(9).capacity(), 8);
+        v.shrink_capacity_view(4).clear();
+        assert!(v.is_empty());
+        assert_eq!(Droppable::count(), 0);
+
+        let mut v: Vec<u8, 8> = Vec::new();
+        let mut view = v.shrink_capacity_view(4);
+        assert_eq!(view.extend_from_slice(&[1, 2, 3, 4, 5]), Err(()));
+        view.extend_from_slice(&[1, 2, 3]).unwrap();
+        view[0] = 0;
+        assert_eq!(view.pop(), Some(3));
+        assert_eq!(v, [0, 2]);
+    }
+
+    #[test]
+    fn retain_into() {
+        let mut v: Vec<u8, 8> = (0..8).collect();
+        let mut odd: Vec<u8, 8> = Vec::new();
+        v.retain_into(|x| x % 2 == 0, &mut odd).unwrap();
+        assert_eq!(v, [0, 2, 4, 6]);
+        assert_eq!(odd, [1, 3, 5, 7]);
+
+        // the rejected elements that don't fit stay in place
+        let mut v: Vec<u8, 8> = (0..8).collect();
+        let mut small: Vec<u8, 2> = Vec::new();
+        assert!(v.retain_into(|x| *x > 5, &mut small).is_err());
+        assert_eq!(small, [0, 1]);
+        assert_eq!(v, [2, 3, 4, 5, 6, 7]);
+
+        droppable!();
+        let mut v: Vec<Droppable, 4> = Vec::new();
+        for _ in 0..4 {
+            v.push(Droppable::new()).ok().unwrap();
+        }
+        let mut other: Vec<Droppable, 4> = Vec::new();
+        // a panicking predicate leaves both vectors intact
+        let mut calls = 0;
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            v.retain_into(
+                |_| {
+                    calls += 1;
+                    if calls == 3 {
+                        panic!();
+                    }
+                    calls == 2
+                },
+                &mut other,
+            )
+        }));
+        assert!(result.is_err());
+        assert_eq!((v.len(), other.len()), (3, 1));
+        assert_eq!(Droppable::count(), 4);
+        core::mem::drop((v, other));
+        assert_eq!(Droppable::count(), 0);
+    }
+
+    #[test]
+    fn retain_incremental() {
+        use super::RetainProgress;
+
+        let mut v: Vec<u8, 16> = (0..16).collect();
+        let mut progress = RetainProgress::new();
+        assert!(!v.retain_incremental(&mut progress, 10, |x| *x % 4 == 0));
+        // usable in between, with the rejected elements behind the retained ones
+        assert_eq!(v.len(), 16);
+        assert_eq!(v[..3], [0, 4, 8]);
+        assert!(!v.retain_incremental(&mut progress, 0, |_| unreachable!()));
+        assert!(!v.retain_incremental(&mut progress, 10, |x| *x % 4 == 0));
+        assert_eq!(v.len(), 12);
+        assert!(v.retain_incremental(&mut progress, 10, |_| unreachable!()));
+        assert_eq!(v, [0, 4, 8, 12]);
+        assert_eq!(progress, RetainProgress::new());
+
+        // shrinking in between doesn't break anything
+        assert!(!v.retain_incremental(&mut progress, 3, |x| *x != 4));
+        v.truncate(1);
+        assert!(v.retain_incremental(&mut progress, 1, |_| unreachable!()));
+        assert_eq!(v, [0]);
+
+        droppable!();
+        let mut v: Vec<Droppable, 4> = Vec::new();
+        for _ in 0..4 {
+            v.push(Droppable::new()).ok().unwrap();
+        }
+        let mut i = 0;
+        let mut progress = RetainProgress::new();
+        while !v.retain_incremental(&mut progress, 1, |_| {
+            i += 1;
+            i % 2 == 0
+        }) {}
+        assert_eq!(v.len(), 2);
+        assert_eq!(Droppable::count(), 2);
+    }
+
+    #[test]
+    fn init_adapters() {
+        use core::mem::MaybeUninit;
+
+        droppable!();
+
+        let mut v: Vec<MaybeUninit<Droppable>, 4> = Vec::new();
+        for _ in 0..3 {
+            v.push(MaybeUninit::new(Droppable::new())).ok().unwrap();
+        }
+        let mut v = unsafe { v.assume_init() };
+        assert_eq!(Droppable::count(), 3);
+
+        let added = unsafe {
+            v.assume_init_extend(|spare| {
+                assert_eq!(spare.len(), 1);
+                spare[0].write(Droppable::new());
+                1
+            })
+        };
+        assert_eq!(added, 1);
+        assert!(v.is_full());
+        assert_eq!(unsafe { v.assume_init_extend(|_| 0) }, 0);
+        core::mem::drop(v);
+        assert_eq!(Droppable::count(), 0);
+
+        let mut v: Vec<u8, 4> = Vec::new();
+        assert_eq!(v.init_extend(7, |buf| buf.len() - 1), 3);
+        assert_eq!(v, [7, 7, 7]);
+        assert_eq!(
+            v.init_extend(0, |buf| {
+                buf[0] = 1;
+                0
+            }),
+            0
+        );
+        assert_eq!(v, [7, 7, 7]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn init_extend_overreport() {
+        let mut v: Vec<u8, 4> = Vec::new();
+        v.init_extend(0, |_| 5);
+    }
 }
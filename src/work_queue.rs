@@ -0,0 +1,340 @@
+//! A fixed capacity work queue that pairs each submitted job with a completion slot.
+//!
+//! [`WorkQueue`] builds on [`MpMcQueue`] for request/response style offloading, e.g. between the
+//! two cores of a dual-core microcontroller. Submitting a job returns a [`Promise`], and the
+//! context that dequeues the job gets a [`Completer`] that delivers the response back to exactly
+//! that promise. No separate response queue or ID table is needed.
+//!
+//! The queue holds up to `N` jobs in flight; a job stays in flight from submission until its
+//! promise has been resolved (or dropped) *and* its completer has been used (or dropped).
+//!
+//! NOTE: This module requires atomic CAS operations. On targets without them, they are emulated by
+//! the [`portable-atomic`](https://crates.io/crates/portable-atomic) crate.
+//!
+//! # Example
+//!
+//! ```
+//! use heapless::work_queue::WorkQueue;
+//!
+//! static WQ: WorkQueue<u32, u64, 4> = WorkQueue::new();
+//!
+//! // core 0 submits a job
+//! let mut promise = WQ.submit(21).unwrap();
+//! assert_eq!(promise.poll(), None);
+//!
+//! // core 1 processes it
+//! let (x, completer) = WQ.dequeue().unwrap();
+//! completer.complete(u64::from(x) * 2);
+//!
+//! // core 0 picks up the response
+//! assert_eq!(promise.poll(), Some(42));
+//! ```
+
+use core::{cell::UnsafeCell, fmt, mem::MaybeUninit};
+
+#[cfg(not(feature = "portable-atomic"))]
+use core::sync::atomic;
+#[cfg(feature = "portable-atomic")]
+use portable_atomic as atomic;
+
+use atomic::{AtomicU8, Ordering};
+
+use crate::mpmc::MpMcQueue;
+
+// Slot states
+const FREE: u8 = 0;
+const PENDING: u8 = 1;
+const DONE: u8 = 2;
+const CANCELED: u8 = 3;
+const ABANDONED: u8 = 4;
+
+/// A work queue with a capacity for `N` in-flight jobs of type `T` that produce responses of type
+/// `R`.
+///
+/// `N` has the same restrictions as the capacity of [`MpMcQueue`].
+pub struct WorkQueue<T, R, const N: usize> {
+    jobs: MpMcQueue<(usize, T), N>,
+    slots: [Slot<R>; N],
+}
+
+struct Slot<R> {
+    state: AtomicU8,
+    response: UnsafeCell<MaybeUninit<R>>,
+}
+
+impl<R> Slot<R> {
+    const fn new() -> Self {
+        Self {
+            state: AtomicU8::new(FREE),
+            response: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+}
+
+impl<T, R, const N: usize> WorkQueue<T, R, N> {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const SLOT: Slot<R> = Slot::new();
+
+    /// Creates an empty work queue.
+    pub const fn new() -> Self {
+        Self {
+            jobs: MpMcQueue::new(),
+            slots: [Self::SLOT; N],
+        }
+    }
+
+    /// Submits a `job`, returning the [`Promise`] that will receive its response.
+    ///
+    /// Returns back the `job` if `N` jobs are already in flight.
+    pub fn submit(&self, job: T) -> Result<Promise<'_, T, R, N>, T> {
+        let slot = match self.slots.iter().position(|slot| {
+            slot.state
+                .compare_exchange(FREE, PENDING, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        }) {
+            Some(slot) => slot,
+            None => return Err(job),
+        };
+
+        match self.jobs.enqueue((slot, job)) {
+            Ok(()) => Ok(Promise {
+                queue: self,
+                slot,
+                resolved: false,
+            }),
+            Err((_, job)) => {
+                // Unreachable as long as every queued job owns a slot, but don't leak the slot.
+                self.slots[slot].state.store(FREE, Ordering::Release);
+                Err(job)
+            }
+        }
+    }
+
+    /// Takes the oldest submitted job, together with the [`Completer`] for its response.
+    ///
+    /// Returns `None` if there are no jobs waiting to be processed.
+    pub fn dequeue(&self) -> Option<(T, Completer<'_, T, R, N>)> {
+        let (slot, job) = self.jobs.dequeue()?;
+        Some((job, Completer { queue: self, slot }))
+    }
+
+    /// Releases a slot that one side gave up on, unless the other side still holds it.
+    fn release(&self, slot: usize, from: u8, to: u8) {
+        let slot = &self.slots[slot];
+        match slot
+            .state
+            .compare_exchange(from, to, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(_) => {}
+            Err(DONE) => {
+                unsafe { (*slot.response.get()).assume_init_drop() };
+                slot.state.store(FREE, Ordering::Release);
+            }
+            Err(_) => slot.state.store(FREE, Ordering::Release),
+        }
+    }
+}
+
+impl<T, R, const N: usize> Default for WorkQueue<T, R, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, R, const N: usize> Drop for WorkQueue<T, R, N> {
+    fn drop(&mut self) {
+        while self.jobs.dequeue().is_some() {}
+
+        for slot in &mut self.slots {
+            if *slot.state.get_mut() == DONE {
+                unsafe { slot.response.get_mut().assume_init_drop() };
+            }
+        }
+    }
+}
+
+unsafe impl<T, R, const N: usize> Sync for WorkQueue<T, R, N>
+where
+    T: Send,
+    R: Send,
+{
+}
+
+/// The pending response to a job submitted to a [`WorkQueue`].
+///
+/// Dropping the promise before its response arrived abandons the job; the response is discarded
+/// once the job completes.
+pub struct Promise<'a, T, R, const N: usize> {
+    queue: &'a WorkQueue<T, R, N>,
+    slot: usize,
+    resolved: bool,
+}
+
+impl<T, R, const N: usize> Promise<'_, T, R, N> {
+    /// Takes the response if the job has been completed.
+    ///
+    /// Returns `None` if the job is still pending, was canceled, or its response has already
+    /// been taken.
+    pub fn poll(&mut self) -> Option<R> {
+        if self.resolved {
+            return None;
+        }
+
+        let slot = &self.queue.slots[self.slot];
+        if slot.state.load(Ordering::Acquire) != DONE {
+            return None;
+        }
+
+        let response = unsafe { (*slot.response.get()).assume_init_read() };
+        slot.state.store(FREE, Ordering::Release);
+        self.resolved = true;
+        Some(response)
+    }
+
+    /// Returns `true` if the job's [`Completer`] was dropped without completing the job.
+    ///
+    /// A canceled promise will never be resolved.
+    pub fn is_canceled(&self) -> bool {
+        !self.resolved && self.queue.slots[self.slot].state.load(Ordering::Acquire) == CANCELED
+    }
+}
+
+impl<T, R, const N: usize> Drop for Promise<'_, T, R, N> {
+    fn drop(&mut self) {
+        if !self.resolved {
+            self.queue.release(self.slot, PENDING, ABANDONED);
+        }
+    }
+}
+
+impl<T, R, const N: usize> fmt::Debug for Promise<'_, T, R, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Promise")
+            .field("slot", &self.slot)
+            .field("resolved", &self.resolved)
+            .finish()
+    }
+}
+
+/// Delivers the response to a job taken from a [`WorkQueue`] back to its [`Promise`].
+///
+/// Dropping the completer without calling [`complete`](Completer::complete) cancels the job.
+pub struct Completer<'a, T, R, const N: usize> {
+    queue: &'a WorkQueue<T, R, N>,
+    slot: usize,
+}
+
+impl<T, R, const N: usize> Completer<'_, T, R, N> {
+    /// Completes the job with `response`.
+    ///
+    /// If the promise has been dropped in the meantime, `response` is dropped instead.
+    pub fn complete(self, response: R) {
+        let slot = &self.queue.slots[self.slot];
+        unsafe { (*slot.response.get()).write(response) };
+
+        if slot
+            .state
+            .compare_exchange(PENDING, DONE, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            // Abandoned by the promise
+            unsafe { (*slot.response.get()).assume_init_drop() };
+            slot.state.store(FREE, Ordering::Release);
+        }
+
+        core::mem::forget(self);
+    }
+}
+
+impl<T, R, const N: usize> Drop for Completer<'_, T, R, N> {
+    fn drop(&mut self) {
+        self.queue.release(self.slot, PENDING, CANCELED);
+    }
+}
+
+impl<T, R, const N: usize> fmt::Debug for Completer<'_, T, R, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Completer")
+            .field("slot", &self.slot)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkQueue;
+
+    #[test]
+    fn request_response() {
+        let wq: WorkQueue<u8, u16, 2> = WorkQueue::new();
+
+        let mut a = wq.submit(1).unwrap();
+        let mut b = wq.submit(2).unwrap();
+        assert_eq!(wq.submit(3).err(), Some(3));
+
+        let (x, ca) = wq.dequeue().unwrap();
+        let (y, cb) = wq.dequeue().unwrap();
+        assert!(wq.dequeue().is_none());
+
+        // responses are routed to the right promise regardless of completion order
+        cb.complete(u16::from(y) * 100);
+        assert_eq!(a.poll(), None);
+        assert_eq!(b.poll(), Some(200));
+        assert_eq!(b.poll(), None);
+
+        ca.complete(u16::from(x) * 100);
+        assert_eq!(a.poll(), Some(100));
+    }
+
+    #[test]
+    fn slots_are_reused() {
+        let wq: WorkQueue<u8, u8, 2> = WorkQueue::new();
+
+        for i in 0..10 {
+            let mut p = wq.submit(i).unwrap();
+            let (x, c) = wq.dequeue().unwrap();
+            c.complete(x + 1);
+            assert_eq!(p.poll(), Some(i + 1));
+        }
+    }
+
+    #[test]
+    fn cancel() {
+        let wq: WorkQueue<u8, u8, 2> = WorkQueue::new();
+
+        let mut p = wq.submit(0).unwrap();
+        let (_, c) = wq.dequeue().unwrap();
+        assert!(!p.is_canceled());
+        drop(c);
+        assert!(p.is_canceled());
+        assert_eq!(p.poll(), None);
+
+        // the slot is released once the promise goes away as well
+        drop(p);
+        wq.submit(1).unwrap();
+        wq.submit(2).unwrap();
+    }
+
+    #[test]
+    fn abandon() {
+        droppable!();
+
+        let wq: WorkQueue<u8, Droppable, 2> = WorkQueue::new();
+
+        let p = wq.submit(0).unwrap();
+        let (_, c) = wq.dequeue().unwrap();
+        drop(p);
+        c.complete(Droppable::new());
+        assert_eq!(Droppable::count(), 0);
+
+        let p = wq.submit(1).unwrap();
+        let (_, c) = wq.dequeue().unwrap();
+        c.complete(Droppable::new());
+        assert_eq!(Droppable::count(), 1);
+        drop(p);
+        assert_eq!(Droppable::count(), 0);
+
+        wq.submit(2).unwrap();
+        wq.submit(3).unwrap();
+    }
+}
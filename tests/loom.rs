@@ -0,0 +1,172 @@
+//! Model checks of the lock-free queues, run with `RUSTFLAGS="--cfg loom" cargo test --test loom`.
+//!
+//! Each test runs under every interleaving of its threads that loom finds, so the threads only
+//! do a few operations each.
+
+#![cfg(loom)]
+#![deny(rust_2018_compatibility)]
+#![deny(rust_2018_idioms)]
+#![deny(warnings)]
+
+use heapless::{
+    mpmc::{MpMcPool, MpMcQueue},
+    spsc::Queue,
+};
+use loom::{sync::Arc, thread};
+
+#[test]
+fn spsc() {
+    loom::model(|| {
+        // the handles borrow the queue, which has to outlive the producer thread
+        let queue: *mut Queue<u8, 3> = Box::into_raw(Box::new(Queue::new()));
+        let (mut producer, mut consumer) = unsafe { (*queue).split() };
+
+        let thread = thread::spawn(move || {
+            for i in 0..3 {
+                while producer.enqueue(i).is_err() {
+                    thread::yield_now();
+                }
+            }
+        });
+
+        for i in 0..2 {
+            loop {
+                if let Some(item) = consumer.dequeue() {
+                    assert_eq!(item, i);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+
+        thread.join().unwrap();
+        assert_eq!(consumer.dequeue(), Some(2));
+        assert_eq!(consumer.dequeue(), None);
+        drop(unsafe { Box::from_raw(queue) });
+    });
+}
+
+#[test]
+fn spsc_drop_with_items() {
+    loom::model(|| {
+        let queue: *mut Queue<Arc<()>, 3> = Box::into_raw(Box::new(Queue::new()));
+        let (mut producer, mut consumer) = unsafe { (*queue).split() };
+        let item = Arc::new(());
+
+        let thread = {
+            let item = item.clone();
+            thread::spawn(move || {
+                producer.enqueue(item.clone()).unwrap();
+                producer.enqueue(item).unwrap();
+            })
+        };
+        drop(consumer.dequeue());
+
+        thread.join().unwrap();
+        // loom reports the `Arc`s that the queue fails to drop
+        drop(unsafe { Box::from_raw(queue) });
+        assert_eq!(Arc::strong_count(&item), 1);
+    });
+}
+
+#[test]
+fn mpmc_producers() {
+    loom::model(|| {
+        let queue: Arc<MpMcQueue<u8, 2>> = Arc::new(MpMcQueue::new());
+
+        let threads = [1, 2].map(|i| {
+            let queue = queue.clone();
+            thread::spawn(move || queue.enqueue(i).unwrap())
+        });
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut items = [queue.dequeue().unwrap(), queue.dequeue().unwrap()];
+        items.sort();
+        assert_eq!(items, [1, 2]);
+        assert_eq!(queue.dequeue(), None);
+    });
+}
+
+// A producer or consumer that loses the race for a slot must not wait for the winner to fill or
+// empty it: on a single core, the winner may be the context that the losing one preempted. loom
+// fails these with "exceeded maximum number of branches" if the loser spins.
+
+#[test]
+fn mpmc_preempted_producer() {
+    loom::model(|| {
+        let queue: Arc<MpMcQueue<u8, 4>> = Arc::new(MpMcQueue::new());
+
+        let thread = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.enqueue(1).unwrap())
+        };
+        queue.enqueue(2).unwrap();
+        thread.join().unwrap();
+    });
+}
+
+#[test]
+fn mpmc_preempted_consumer() {
+    loom::model(|| {
+        let queue: Arc<MpMcQueue<u8, 4>> = Arc::new(MpMcQueue::new());
+        queue.enqueue(1).unwrap();
+        queue.enqueue(2).unwrap();
+
+        let thread = {
+            let queue = queue.clone();
+            thread::spawn(move || queue.dequeue().unwrap())
+        };
+        let item = queue.dequeue().unwrap();
+        assert_eq!(item + thread.join().unwrap(), 3);
+    });
+}
+
+#[test]
+fn mpmc_producer_consumer() {
+    loom::model(|| {
+        let queue: Arc<MpMcQueue<u8, 2>> = Arc::new(MpMcQueue::new());
+
+        let thread = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                queue.enqueue(1).unwrap();
+                queue.enqueue(2).unwrap();
+            })
+        };
+
+        let first = queue.dequeue();
+        thread.join().unwrap();
+
+        match first {
+            Some(1) => assert_eq!(queue.dequeue(), Some(2)),
+            None => {
+                assert_eq!(queue.dequeue(), Some(1));
+                assert_eq!(queue.dequeue(), Some(2));
+            }
+            _ => panic!("dequeued {:?} first", first),
+        }
+        assert_eq!(queue.dequeue(), None);
+    });
+}
+
+#[test]
+fn mpmc_pool() {
+    loom::model(|| {
+        let pool: Arc<MpMcPool<u8, 2>> = Arc::new(MpMcPool::new());
+        let index = pool.alloc(1).unwrap();
+
+        // the index is handed to another context, which frees the slot for a new allocation
+        let thread = {
+            let pool = pool.clone();
+            thread::spawn(move || assert_eq!(unsafe { pool.free(index) }, 1))
+        };
+        // reuses `index` if it was freed already
+        let other = pool.alloc(2).unwrap();
+
+        thread.join().unwrap();
+        assert_eq!(unsafe { *pool.get(other) }, 2);
+        assert!(pool.alloc(3).is_ok());
+    });
+}
@@ -0,0 +1,121 @@
+//! The panic-free subset of `Vec` and `String` doesn't contain panic branches
+//!
+//! `no-panic` detects panic paths at link time, which requires the functions to be optimized. Run
+//! with `cargo test --release`. The checked functions are kept out of line so that constant
+//! arguments in the tests can't optimize their panic branches away.
+
+#![cfg(not(debug_assertions))]
+
+use heapless::{String, Vec};
+use no_panic::no_panic;
+
+#[no_panic]
+#[inline(never)]
+fn vec_push(v: &mut Vec<u32, 8>, x: u32) -> Result<(), u32> {
+    v.push(x)
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_pop(v: &mut Vec<u32, 8>) -> Option<u32> {
+    v.pop()
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_extend_from_slice(v: &mut Vec<u32, 8>, xs: &[u32]) -> Result<(), ()> {
+    v.extend_from_slice(xs)
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_truncate(v: &mut Vec<u32, 8>, len: usize) {
+    v.truncate(len)
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_resize(v: &mut Vec<u32, 8>, len: usize, x: u32) -> Result<(), ()> {
+    v.resize(len, x)
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_clear(v: &mut Vec<u32, 8>) {
+    v.clear()
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_get(v: &Vec<u32, 8>, index: usize) -> Option<u32> {
+    v.get(index).copied()
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_insert_checked(v: &mut Vec<u32, 8>, index: usize, x: u32) -> Result<(), u32> {
+    v.insert_checked(index, x)
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_remove_checked(v: &mut Vec<u32, 8>, index: usize) -> Option<u32> {
+    v.remove_checked(index)
+}
+
+#[no_panic]
+#[inline(never)]
+fn vec_swap_remove_checked(v: &mut Vec<u32, 8>, index: usize) -> Option<u32> {
+    v.swap_remove_checked(index)
+}
+
+#[no_panic]
+#[inline(never)]
+fn string_push_str(s: &mut String<8>, x: &str) -> Result<(), ()> {
+    s.push_str(x)
+}
+
+#[no_panic]
+#[inline(never)]
+fn string_truncate_checked(s: &mut String<8>, len: usize) -> Result<(), ()> {
+    s.truncate_checked(len)
+}
+
+#[no_panic]
+#[inline(never)]
+fn string_remove_checked(s: &mut String<8>, index: usize) -> Option<char> {
+    s.remove_checked(index)
+}
+
+#[test]
+fn vec() {
+    let mut v = Vec::new();
+    vec_push(&mut v, 1).unwrap();
+    vec_extend_from_slice(&mut v, &[2, 3, 4]).unwrap();
+    assert_eq!(vec_get(&v, 1), Some(2));
+    assert_eq!(vec_get(&v, 8), None);
+    assert_eq!(vec_insert_checked(&mut v, 9, 5), Err(5));
+    vec_insert_checked(&mut v, 0, 0).unwrap();
+    assert_eq!(vec_remove_checked(&mut v, 5), None);
+    assert_eq!(vec_remove_checked(&mut v, 1), Some(1));
+    assert_eq!(vec_swap_remove_checked(&mut v, 0), Some(0));
+    assert_eq!(vec_pop(&mut v), Some(3));
+    vec_truncate(&mut v, 1);
+    assert_eq!(v, [4]);
+    assert_eq!(vec_resize(&mut v, 9, 0), Err(()));
+    vec_resize(&mut v, 3, 5).unwrap();
+    assert_eq!(v, [4, 5, 5]);
+    vec_clear(&mut v);
+    assert!(v.is_empty());
+}
+
+#[test]
+fn string() {
+    let mut s = String::new();
+    string_push_str(&mut s, "héllo").unwrap();
+    assert_eq!(string_remove_checked(&mut s, 2), None);
+    assert_eq!(string_remove_checked(&mut s, 0), Some('h'));
+    assert_eq!(string_truncate_checked(&mut s, 1), Err(()));
+    string_truncate_checked(&mut s, 2).unwrap();
+    assert_eq!(s, "é");
+}